@@ -226,6 +226,9 @@ builtin_macros_test_bad_fn = {$kind} functions cannot be used for tests
 
 builtin_macros_test_case_non_item = `#[test_case]` attribute is only allowed on items
 
+builtin_macros_test_timeout_invalid = `#[test_timeout]` expects a positive number of seconds
+    .note = the test timeout is written as `#[test_timeout = "30"]`
+
 builtin_macros_test_runner_invalid = `test_runner` argument must be a path
 builtin_macros_test_runner_nargs = `#![test_runner(..)]` accepts exactly 1 argument
 
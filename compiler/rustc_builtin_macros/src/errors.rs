@@ -661,6 +661,14 @@ pub(crate) struct TestBadFn {
     pub(crate) kind: &'static str,
 }
 
+#[derive(Diagnostic)]
+#[diag(builtin_macros_test_timeout_invalid)]
+#[note]
+pub(crate) struct TestTimeoutInvalid {
+    #[primary_span]
+    pub(crate) span: Span,
+}
+
 #[derive(Diagnostic)]
 #[diag(builtin_macros_asm_explicit_register_name)]
 pub(crate) struct AsmExplicitRegisterName {
@@ -455,6 +455,14 @@ pub struct BuiltinAttribute {
         test_runner, CrateLevel, template!(List: "path"), ErrorFollowing, custom_test_frameworks,
         "custom test frameworks are an unstable feature",
     ),
+    gated!(
+        test_isolate, Normal, template!(Word), WarnFollowing,
+        @only_local: true, experimental!(test_isolate)
+    ),
+    gated!(
+        test_timeout, Normal, template!(NameValueStr: "seconds"), ErrorFollowing,
+        @only_local: true, experimental!(test_timeout)
+    ),
     // RFC #1268
     gated!(
         marker, Normal, template!(Word), WarnFollowing, @only_local: true,
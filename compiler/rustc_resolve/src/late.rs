@@ -4574,12 +4574,24 @@ fn resolve_doc_links(&mut self, attrs: &[Attribute], maybe_exported: MaybeExport
             | ResolveDocLinks::All => {}
         }
 
-        if !attrs.iter().any(|attr| attr.may_have_doc_links()) {
+        // Rustdoc also resolves the paths in inline code, for its `unresolved_code_paths` lint,
+        // whose level rustdoc checks when reporting. Only the paths of links are needed otherwise,
+        // so they're the only ones resolved when compiling, which saves time and metadata.
+        let code_spans = self.r.tcx.sess.opts.actually_rustdoc
+            && attrs
+                .iter()
+                .any(|attr| attr.doc_str().is_some_and(|doc| doc.as_str().contains('`')));
+        if !code_spans && !attrs.iter().any(|attr| attr.may_have_doc_links()) {
             return;
         }
 
+        let mut paths = rustdoc::attrs_to_preprocessed_links(attrs);
+        if code_spans {
+            paths.extend(rustdoc::attrs_to_code_span_paths(attrs));
+        }
+
         let mut need_traits_in_scope = false;
-        for path_str in rustdoc::attrs_to_preprocessed_links(attrs) {
+        for path_str in paths {
             // Resolve all namespaces due to no disambiguator or for diagnostics.
             let mut any_resolved = false;
             let mut need_assoc = false;
@@ -375,6 +375,22 @@ fn preprocess_link(link: &str) -> Box<str> {
     strip_generics_from_path(link).unwrap_or_else(|_| link.into())
 }
 
+/// Returns the path named by an inline code span of the documentation, such as `` `Vec::new` ``,
+/// without its `()` or `!` suffix, or `None` if the code doesn't look like a path.
+///
+/// Only paths with several segments are considered, as a single word in backticks is more often
+/// the name of a variable or argument than of an item.
+pub fn code_span_path(code: &str) -> Option<&str> {
+    let code = code.trim();
+    let path = code.strip_suffix("()").or_else(|| code.strip_suffix('!')).unwrap_or(code);
+    let is_ident = |segment: &str| {
+        let mut chars = segment.chars();
+        chars.next().is_some_and(|c| c == '_' || c.is_alphabetic())
+            && chars.all(|c| c == '_' || c.is_alphanumeric())
+    };
+    (path.contains("::") && path.split("::").all(is_ident)).then_some(path)
+}
+
 /// Keep inline and reference links `[]`,
 /// but skip autolinks `<>` which we never consider to be intra-doc links.
 pub fn may_be_doc_link(link_type: LinkType) -> bool {
@@ -399,6 +415,16 @@ pub(crate) fn attrs_to_preprocessed_links(attrs: &[ast::Attribute]) -> Vec<Box<s
     parse_links(&doc)
 }
 
+/// Returns the paths named by the inline code spans outside of links in the documentation in
+/// `attrs`, see [`code_span_path`]. They are only resolved when running rustdoc, which checks
+/// them with its `unresolved_code_paths` lint.
+pub(crate) fn attrs_to_code_span_paths(attrs: &[ast::Attribute]) -> Vec<Box<str>> {
+    let (doc_fragments, _) = attrs_to_doc_fragments(attrs.iter().map(|attr| (attr, None)), true);
+    let doc = prepare_to_doc_link_resolution(&doc_fragments).into_values().next().unwrap();
+
+    parse_code_span_paths(&doc)
+}
+
 /// Similiar version of `markdown_links` from rustdoc.
 /// This will collect destination links and display text if exists.
 fn parse_links<'md>(doc: &'md str) -> Vec<Box<str>> {
@@ -435,6 +461,38 @@ fn parse_links<'md>(doc: &'md str) -> Vec<Box<str>> {
     links
 }
 
+/// Similar to `parse_links`, but for the paths of inline code spans.
+fn parse_code_span_paths<'md>(doc: &'md str) -> Vec<Box<str>> {
+    let mut broken_link_callback = |link: BrokenLink<'md>| Some((link.reference, "".into()));
+    let mut event_iter = Parser::new_with_broken_link_callback(
+        doc,
+        main_body_opts(),
+        Some(&mut broken_link_callback),
+    );
+    let mut paths = Vec::new();
+
+    while let Some(event) = event_iter.next() {
+        match event {
+            Event::Code(code) => {
+                if let Some(path) = code_span_path(&code) {
+                    paths.push(path.into());
+                }
+            }
+            // Inline code in links is either resolved as part of an intra-doc link, or not a path.
+            Event::Start(Tag::Link(..)) => {
+                for event in event_iter.by_ref() {
+                    if let Event::End(Tag::Link(..)) = event {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    paths
+}
+
 /// Collects additional data of link.
 fn collect_link_data<'input, 'callback>(
     event_iter: &mut Parser<'input, 'callback>,
@@ -1764,8 +1764,10 @@
         test_2018_feature,
         test_accepted_feature,
         test_case,
+        test_isolate,
         test_removed_feature,
         test_runner,
+        test_timeout,
         test_unstable_lint,
         thread,
         thread_local,
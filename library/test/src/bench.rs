@@ -32,6 +32,7 @@ pub fn black_box<T>(dummy: T) -> T {
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
 }
 
@@ -46,7 +47,9 @@ pub fn iter<T, F>(&mut self, mut inner: F)
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summary, samples) = iter_with_samples(&mut inner);
+        self.summary = Some(summary);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Result<Option<stats::Summary>, String>
@@ -62,6 +65,18 @@ pub fn bench<F>(&mut self, mut f: F) -> Result<Option<stats::Summary>, String>
 pub struct BenchSamples {
     pub ns_iter_summ: stats::Summary,
     pub mb_s: usize,
+    /// Nanoseconds per iteration of each sample the summary was computed from.
+    pub samples: Vec<f64>,
+    pub outliers: stats::Outliers,
+    /// Change compared to the baseline passed with `--baseline`, if it has this benchmark.
+    pub change: Option<stats::Change>,
+}
+
+impl BenchSamples {
+    fn new(ns_iter_summ: stats::Summary, mb_s: usize, samples: Vec<f64>) -> BenchSamples {
+        let outliers = stats::Outliers::new(&samples);
+        BenchSamples { ns_iter_summ, mb_s, samples, outliers, change: None }
+    }
 }
 
 pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
@@ -81,6 +96,27 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
     if bs.mb_s != 0 {
         write!(output, " = {} MB/s", bs.mb_s).unwrap();
     }
+    if let Some(change) = bs.change {
+        let verdict = if change.lower > 0.0 {
+            "regressed"
+        } else if change.upper < 0.0 {
+            "improved"
+        } else {
+            "no significant change"
+        };
+        write!(
+            output,
+            " change: {:+.2}% ({:+.2}% .. {:+.2}%), {verdict}",
+            change.estimate * 100.0,
+            change.lower * 100.0,
+            change.upper * 100.0,
+        )
+        .unwrap();
+        let outliers = bs.outliers.total();
+        if outliers != 0 {
+            write!(output, "; {outliers} of {} samples are outliers", bs.samples.len()).unwrap();
+        }
+    }
     output
 }
 
@@ -120,6 +156,14 @@ fn ns_iter_inner<T, F>(inner: &mut F, k: u64) -> u64
 }
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
+where
+    F: FnMut() -> T,
+{
+    iter_with_samples(inner).0
+}
+
+// Like `iter`, but also returns the samples of the last round, before they were winsorized.
+fn iter_with_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
 where
     F: FnMut() -> T,
 {
@@ -154,6 +198,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
             let ns = ns_iter_inner(inner, 5 * n);
             *p = ns as f64 / (5 * n) as f64;
         }
+        let raw_samples = samples.to_vec();
 
         stats::winsorize(samples, 5.0);
         let summ5 = stats::Summary::new(samples);
@@ -166,13 +211,13 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
             && summ.median_abs_dev_pct < 1.0
             && summ.median - summ5.median < summ5.median_abs_dev
         {
-            return summ5;
+            return (summ5, raw_samples);
         }
 
         total_run += loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, raw_samples);
         }
 
         // If we overflow here just return the results so far. We check a
@@ -182,7 +227,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, raw_samples);
             }
         };
     }
@@ -197,7 +242,7 @@ pub fn benchmark<F>(
 ) where
     F: FnMut(&mut Bencher) -> Result<(), String>,
 {
-    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, samples: Vec::new(), bytes: 0 };
 
     let data = Arc::new(Mutex::new(Vec::new()));
 
@@ -215,14 +260,14 @@ pub fn benchmark<F>(
             let ns_iter = cmp::max(ns_iter_summ.median as u64, 1);
             let mb_s = bs.bytes * 1000 / ns_iter;
 
-            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize };
-            TestResult::TrBench(bs)
+            let samples = std::mem::take(&mut bs.samples);
+            TestResult::TrBench(BenchSamples::new(ns_iter_summ, mb_s as usize, samples))
         }
         Ok(Ok(None)) => {
             // iter not called, so no data.
             // FIXME: error in this case?
             let samples: &mut [f64] = &mut [0.0_f64; 1];
-            let bs = BenchSamples { ns_iter_summ: stats::Summary::new(samples), mb_s: 0 };
+            let bs = BenchSamples::new(stats::Summary::new(samples), 0, Vec::new());
             TestResult::TrBench(bs)
         }
         Err(_) => TestResult::TrFailed,
@@ -238,6 +283,6 @@ pub fn run_once<F>(f: F) -> Result<(), String>
 where
     F: FnMut(&mut Bencher) -> Result<(), String>,
 {
-    let mut bs = Bencher { mode: BenchMode::Single, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Single, summary: None, samples: Vec::new(), bytes: 0 };
     bs.bench(f).map(|_| ())
 }
@@ -2,8 +2,10 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::helpers::failed_tests::FailedTests;
+use super::options::{ColorConfig, Options, OutputFormat, RunIgnored, TestShard};
 use super::time::TestTimeOptions;
 use std::io::{self, IsTerminal};
 
@@ -13,6 +15,8 @@ pub struct TestOpts {
     pub filters: Vec<String>,
     pub filter_exact: bool,
     pub force_run_in_process: bool,
+    /// Run every test in its own process, as if it had the `#[test_isolate]` attribute.
+    pub isolate: bool,
     pub exclude_should_panic: bool,
     pub run_ignored: RunIgnored,
     pub run_tests: bool,
@@ -23,9 +27,28 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub shuffle: bool,
     pub shuffle_seed: Option<u64>,
+    /// Only run (or list) the tests of this shard.
+    pub shard: Option<TestShard>,
+    /// Tests that failed in the previous run. The record is updated with the results of this run
+    /// if it is set.
+    pub failed_tests: Option<FailedTests>,
+    /// Run the tests that failed in the previous run before the others.
+    pub failed_first: bool,
+    /// Only run (or list) the tests that failed in the previous run.
+    pub last_failed: bool,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    /// Hard time limit for tests that don't set their own with `#[test_timeout]`.
+    pub test_timeout: Option<Duration>,
+    /// How often to run a failed test again. Tests that pass when retried are reported as flaky.
+    pub retries: usize,
+    /// Name under which the results of the benchmarks are saved.
+    pub save_baseline: Option<String>,
+    /// Name of previously saved benchmark results to compare the results against.
+    pub baseline: Option<String>,
+    /// Directory the baselines are stored in, instead of one next to the test binary.
+    pub baseline_dir: Option<PathBuf>,
     /// Stop at first failing test.
     /// May run a few more tests due to threading, but will
     /// abort as soon as possible.
@@ -60,6 +83,13 @@ fn optgroups() -> getopts::Options {
     opts.optflag("", "include-ignored", "Run ignored and not ignored tests")
         .optflag("", "ignored", "Run only ignored tests")
         .optflag("", "force-run-in-process", "Forces tests to run in-process when panic=abort")
+        .optflag(
+            "",
+            "isolate",
+            "Run each test in its own process, so that tests can't affect each
+            other through global state like environment variables. Takes
+            precedence over --force-run-in-process",
+        )
         .optflag("", "exclude-should-panic", "Excludes tests marked as should_panic")
         .optflag("", "test", "Run tests and not benchmarks")
         .optflag("", "bench", "Run benchmarks instead of tests")
@@ -109,8 +139,10 @@ fn optgroups() -> getopts::Options {
             pretty = Print verbose output;
             terse  = Display one character per test;
             json   = Output a json document;
-            junit  = Output a JUnit document",
-            "pretty|terse|json|junit",
+            junit  = Output a JUnit document;
+            tap    = Output a TAP version 14 document;
+            github = Report failures as GitHub Actions annotations",
+            "pretty|terse|json|junit|tap|github",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -149,6 +181,70 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests that run for longer than SECS seconds.
+
+            When tests run in subprocesses (panic=abort), the process of a
+            test that times out is killed and the other tests keep running.
+            Otherwise the test cannot be stopped, so no further tests are
+            started and the test binary exits once the running tests have
+            finished or timed out.
+
+            Tests marked with `#[test_timeout = \"SECS\"]` use their own limit.",
+            "SECS",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failed tests again, up to N times.
+
+            Tests that pass when retried are reported as flaky instead of
+            ok. Only tests defined with `#[test]` can be retried, and tests
+            that time out are not retried.",
+            "N",
+        )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the results of the benchmarks under NAME, so that later
+            runs can be compared against them with --baseline",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the results of the benchmarks against those saved
+            under NAME, and report how much they changed",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline-dir",
+            "Store baselines in DIR instead of a directory named
+            libtest-baselines next to the test binary",
+            "DIR",
+        )
+        .optflag(
+            "",
+            "failed-first",
+            "Run the tests that failed in the previous run first, then
+            the others",
+        )
+        .optflag(
+            "",
+            "last-failed",
+            "Only run the tests that failed in the previous run, or all
+            tests if none of them did",
+        )
+        .optopt(
+            "",
+            "failed-tests-file",
+            "Record the tests that failed in PATH, instead of a file in a
+            directory named libtest-failed next to the test binary",
+            "PATH",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
@@ -156,6 +252,21 @@ fn optgroups() -> getopts::Options {
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
         )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests of shard N (counting from 0) out of the
+            number of shards given by --shard-count",
+            "N",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Split the tests into M shards based on a hash of their names,
+            so that adding or removing tests doesn't move the other tests
+            to a different shard. Requires --shard-index",
+            "M",
+        )
         .optopt(
             "",
             "output_postprocess_executable",
@@ -190,6 +301,18 @@ fn usage(binary: &str, options: &getopts::Options) {
 tests in the same order again. Note that --shuffle and --shuffle-seed do not
 affect whether the tests are run in parallel.
 
+The tests can be split across several runs, e.g. on different machines, with
+--shard-count and --shard-index. Each test is assigned to one shard based on
+its name, so the shards together run every test exactly once. Filters are
+applied before sharding, and --list only lists the tests of the given shard.
+
+With --failed-first or --last-failed, the tests that fail are recorded in a
+file, and the next run with one of these flags runs them first or only runs
+them. Tests that weren't run keep their previous state, so that e.g. the
+failures that are left can be run again until all of them pass. Runs without
+these flags don't record their failures, so pass one of them on the failing run
+too.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -207,7 +330,12 @@ fn usage(binary: &str, options: &getopts::Options) {
     `#[ignore]`       - When applied to a function which is already attributed as a
                         test, then the test runner will ignore these tests during
                         normal test runs. Running with --ignored or --include-ignored will run
-                        these tests."#,
+                        these tests.
+    `#[test_timeout = "SECS"]` - The test fails if it runs for longer than SECS seconds.
+                        This overrides --test-timeout and requires
+                        #![feature(test_timeout)].
+    `#[test_isolate]` - The test runs in its own process, like with --isolate.
+                        This requires #![feature(test_isolate)]."#,
         usage = options.usage(&message)
     );
 }
@@ -276,10 +404,18 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
 
     // Unstable flags
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
+    let isolate = unstable_optflag!(matches, allow_unstable, "isolate");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let (save_baseline, baseline, baseline_dir) = get_baselines(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let failed_first = unstable_optflag!(matches, allow_unstable, "failed-first");
+    let last_failed = unstable_optflag!(matches, allow_unstable, "last-failed");
+    let failed_tests = get_failed_tests(&matches, allow_unstable, failed_first || last_failed)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -308,6 +444,7 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         filters,
         filter_exact: exact,
         force_run_in_process,
+        isolate,
         exclude_should_panic,
         run_ignored,
         run_tests,
@@ -318,9 +455,18 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         shuffle,
         shuffle_seed,
+        shard,
+        failed_tests,
+        failed_first,
+        last_failed,
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        retries,
+        save_baseline,
+        baseline,
+        baseline_dir,
         options,
         fail_fast: false,
         output_postprocess_executable,
@@ -360,6 +506,65 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(secs_str) => match secs_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number > 0 \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+type Baselines = (Option<String>, Option<String>, Option<PathBuf>);
+
+fn get_baselines(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Baselines> {
+    let save_baseline = unstable_optopt!(matches, allow_unstable, "save-baseline");
+    let baseline = unstable_optopt!(matches, allow_unstable, "baseline");
+    let baseline_dir = unstable_optopt!(matches, allow_unstable, "baseline-dir").map(PathBuf::from);
+
+    for (option, name) in [("save-baseline", &save_baseline), ("baseline", &baseline)] {
+        let Some(name) = name else { continue };
+        // The name is used as a directory name.
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(format!("argument for --{option} must be a valid directory name"));
+        }
+        if !matches.opt_present("bench") {
+            return Err(format!("the option --{option} requires --bench"));
+        }
+    }
+
+    Ok((save_baseline, baseline, baseline_dir))
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -399,6 +604,50 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_failed_tests(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+    enabled: bool,
+) -> OptPartRes<Option<FailedTests>> {
+    let path = match unstable_optopt!(matches, allow_unstable, "failed-tests-file") {
+        Some(path) => PathBuf::from(path),
+        None if enabled => FailedTests::default_path()
+            .map_err(|e| format!("failed to find the file to record failed tests in: {e}"))?,
+        None => return Ok(None),
+    };
+
+    Ok(Some(FailedTests::new(path)))
+}
+
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<TestShard>> {
+    let parse = |name: &str, value: String| match value.parse::<usize>() {
+        Ok(n) => Ok(n),
+        Err(e) => Err(format!("argument for --{name} must be a number (error: {e})")),
+    };
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+    let shard = match (index, count) {
+        (None, None) => None,
+        (Some(index), Some(count)) => {
+            let index = parse("shard-index", index)?;
+            let count = parse("shard-count", count)?;
+            if count == 0 {
+                return Err("argument for --shard-count must not be 0".to_string());
+            }
+            if index >= count {
+                return Err(format!(
+                    "argument for --shard-index must be less than --shard-count \
+                     (got {index} and {count})"
+                ));
+            }
+            Some(TestShard { index, count })
+        }
+        _ => return Err("the options --shard-index and --shard-count must be used together".into()),
+    };
+
+    Ok(shard)
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -438,9 +687,21 @@ fn get_format(
             }
             OutputFormat::Junit
         }
+        Some("tap") => {
+            if !allow_unstable {
+                return Err("The \"tap\" format is only accepted on the nightly compiler with -Z unstable-options".into());
+            }
+            OutputFormat::Tap
+        }
+        Some("github") => {
+            if !allow_unstable {
+                return Err("The \"github\" format is only accepted on the nightly compiler with -Z unstable-options".into());
+            }
+            OutputFormat::GithubActions
+        }
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, json or junit (was \
+                "argument for --format must be pretty, terse, json, junit, tap or github (was \
                  {v})"
             ));
         }
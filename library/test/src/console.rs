@@ -3,6 +3,7 @@
 use std::fs::File;
 use std::io;
 use std::io::prelude::Write;
+use std::path::Path;
 use std::process::{Command, Stdio};
 use std::time::Instant;
 
@@ -11,10 +12,13 @@
     cli::TestOpts,
     event::{CompletedTest, TestEvent},
     filter_tests,
-    formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
-    helpers::{concurrency::get_concurrency, metrics::MetricMap},
+    formatters::{
+        GithubActionsFormatter, JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter,
+        TapFormatter, TerseFormatter,
+    },
+    helpers::{baseline::Baseline, concurrency::get_concurrency, metrics::MetricMap},
     options::{Options, OutputFormat},
-    run_tests, term,
+    run_tests, stats, term,
     test_result::TestResult,
     time::{TestExecTime, TestSuiteExecTime},
     types::{NamePadding, TestDesc, TestDescAndFn},
@@ -90,12 +94,17 @@ pub struct ConsoleTestState {
     pub ignored: usize,
     pub filtered_out: usize,
     pub measured: usize,
+    /// Number of tests that ran into their hard time limit, which are also counted as failed.
+    pub timed_out: usize,
+    /// Number of tests that failed at first, but passed when retried.
+    pub flaky: usize,
     pub exec_time: Option<TestSuiteExecTime>,
     pub metrics: MetricMap,
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub ignores: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
     pub options: Options,
 }
 
@@ -114,12 +123,15 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             ignored: 0,
             filtered_out: 0,
             measured: 0,
+            timed_out: 0,
+            flaky: 0,
             exec_time: None,
             metrics: MetricMap::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
             ignores: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
             options: opts.options,
         })
     }
@@ -162,6 +174,12 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrTimedOut(timeout) => {
+                        format!("failed (timed out after {}s)", timeout.as_secs())
+                    }
+                    TestResult::TrFlaky(attempts) => {
+                        format!("flaky (passed on attempt {attempts})")
+                    }
                 },
                 name,
             )
@@ -173,7 +191,7 @@ pub fn write_log_result(
     }
 
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured
+        self.passed + self.failed + self.ignored + self.measured + self.flaky
     }
 }
 
@@ -185,7 +203,10 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
     };
 
     let mut out: Box<dyn OutputFormatter> = match opts.format {
-        OutputFormat::Pretty | OutputFormat::Junit => {
+        OutputFormat::Pretty
+        | OutputFormat::Junit
+        | OutputFormat::Tap
+        | OutputFormat::GithubActions => {
             Box::new(PrettyFormatter::new(output, false, 0, false, None))
         }
         OutputFormat::Terse => Box::new(TerseFormatter::new(output, false, 0, false)),
@@ -254,6 +275,19 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrTimedOut(timeout) => {
+            st.failed += 1;
+            st.timed_out += 1;
+            let mut stdout = stdout;
+            stdout.extend_from_slice(
+                format!("note: test timed out after {}s", timeout.as_secs()).as_bytes(),
+            );
+            st.failures.push((test, stdout));
+        }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flaky_tests.push((test, stdout));
+        }
     }
 }
 
@@ -289,11 +323,61 @@ fn on_test_event(
     Ok(())
 }
 
+/// Compares the result of a benchmark against the baseline it was run with, and records it in
+/// the baseline that is going to be saved.
+fn compare_with_baseline(
+    event: &mut TestEvent,
+    baseline: Option<&Baseline>,
+    new_baseline: Option<&mut Baseline>,
+) {
+    let TestEvent::TeResult(CompletedTest { desc, result: TestResult::TrBench(bs), .. }) = event
+    else {
+        return;
+    };
+    if bs.samples.is_empty() {
+        return;
+    }
+    let name = desc.name.as_slice();
+    if let Some(old) = baseline.and_then(|baseline| baseline.get(name)) {
+        if !old.is_empty() && stats::Stats::median(old) > 0.0 {
+            bs.change = Some(stats::Change::bootstrap(old, &bs.samples, 0.95, 10_000));
+        }
+    }
+    if let Some(new_baseline) = new_baseline {
+        new_baseline.insert(name.to_string(), bs.samples.clone());
+    }
+}
+
+fn load_baseline(
+    opts: &TestOpts,
+    name: &str,
+    load: fn(&Path) -> io::Result<Baseline>,
+) -> io::Result<Baseline> {
+    let path = Baseline::path(opts.baseline_dir.as_deref(), name)?;
+    load(&path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("failed to load baseline `{name}` from {}: {e}", path.display()),
+        )
+    })
+}
+
 /// A simple console test runner.
 /// Runs provided tests reporting process and results.
 ///
 /// The results may optionally be piped to the specified postprocessor binary, otherwise written to stdout.
 pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Result<bool> {
+    let baseline =
+        opts.baseline.as_deref().map(|name| load_baseline(opts, name, Baseline::load)).transpose()?;
+    // Only the benchmarks that run are replaced, so that e.g. a filtered run keeps the samples of
+    // the others.
+    let mut new_baseline = opts
+        .save_baseline
+        .as_deref()
+        .map(|name| load_baseline(opts, name, Baseline::load_or_new))
+        .transpose()?;
+    let mut failed_tests = opts.failed_tests.clone();
+
     let mut postprocessor = match &opts.output_postprocess_executable {
         None => None,
         Some(postprocess_executable) => Some(
@@ -350,6 +434,8 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             )),
             OutputFormat::Json => Box::new(JsonFormatter::new(output)),
             OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
+            OutputFormat::Tap => Box::new(TapFormatter::new(output)),
+            OutputFormat::GithubActions => Box::new(GithubActionsFormatter::new(output)),
         };
         let mut st = ConsoleTestState::new(opts)?;
 
@@ -359,7 +445,13 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         let is_instant_supported = !cfg!(target_family = "wasm") && !cfg!(miri);
 
         let start_time = is_instant_supported.then(Instant::now);
-        run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
+        run_tests(opts, tests, |mut x| {
+            compare_with_baseline(&mut x, baseline.as_ref(), new_baseline.as_mut());
+            if let (TestEvent::TeResult(test), Some(failed_tests)) = (&x, failed_tests.as_mut()) {
+                failed_tests.record(test.desc.name.as_slice(), &test.result);
+            }
+            on_test_event(&x, &mut st, &mut *out)
+        })?;
         st.exec_time = start_time.map(|t| TestSuiteExecTime(t.elapsed()));
 
         assert!(opts.fail_fast || st.current_test_count() == st.total);
@@ -372,6 +464,18 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         assert!(status.success());
     }
 
+    if let (Some(name), Some(new_baseline)) = (&opts.save_baseline, new_baseline) {
+        new_baseline.save(&Baseline::path(opts.baseline_dir.as_deref(), name)?)?;
+    }
+    if let Some(failed_tests) = failed_tests {
+        failed_tests.save().map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to record failed tests in {}: {e}", failed_tests.path().display()),
+            )
+        })?;
+    }
+
     write_result
 }
 
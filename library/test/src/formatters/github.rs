@@ -0,0 +1,241 @@
+use std::{io, io::prelude::Write};
+
+use super::OutputFormatter;
+use crate::{
+    bench::fmt_bench_samples,
+    console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation},
+    test_result::TestResult,
+    time,
+    types::TestDesc,
+};
+
+/// Writes one line per test, and reports failures as GitHub Actions workflow commands so that
+/// they are shown as annotations on the failing lines.
+pub(crate) struct GithubActionsFormatter<T> {
+    out: OutputLocation<T>,
+}
+
+impl<T: Write> GithubActionsFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_message(&mut self, s: &str) -> io::Result<()> {
+        // Workflow commands must start at the beginning of a line, so every line is written
+        // with a single `write_all` call.
+        assert_eq!(s.chars().last(), Some('\n'));
+
+        self.out.write_all(s.as_ref())
+    }
+
+    fn write_annotation(
+        &mut self,
+        command: &str,
+        desc: &TestDesc,
+        message: &str,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        let output = String::from_utf8_lossy(stdout);
+        if !output.is_empty() {
+            // Collapse the output of the test in the log.
+            let mut s = format!("::group::{} stdout\n", escape_data(desc.name.as_slice()));
+            s.push_str(&output);
+            if !output.ends_with('\n') {
+                s.push('\n');
+            }
+            s.push_str("::endgroup::\n");
+            self.write_message(&s)?;
+        }
+
+        // Point to where the test panicked, or to the test itself otherwise.
+        let (file, line, col) = match parse_panic_location(&output, desc.name.as_slice()) {
+            Some(location) => (location.file, location.line, location.col),
+            None => (desc.source_file, desc.start_line, desc.start_col),
+        };
+        let mut properties = Vec::new();
+        if !file.is_empty() {
+            properties.push(format!("file={}", escape_property(file)));
+            properties.push(format!("line={line}"));
+            properties.push(format!("col={col}"));
+        }
+        properties.push(format!("title={}", escape_property(&format!("test {}", desc.name))));
+        self.write_message(&format!(
+            "::{command} {}::{}\n",
+            properties.join(","),
+            escape_data(message)
+        ))
+    }
+}
+
+impl<T: Write> OutputFormatter for GithubActionsFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_test_discovered(&mut self, _desc: &TestDesc, _test_type: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_discovery_finish(&mut self, _state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        let noun = if test_count != 1 { "tests" } else { "test" };
+        let shuffle_seed_msg = if let Some(shuffle_seed) = shuffle_seed {
+            format!(" (shuffle seed: {shuffle_seed})")
+        } else {
+            String::new()
+        };
+        self.write_message(&format!("running {test_count} {noun}{shuffle_seed_msg}\n"))
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.write_message(&format!(
+            "test {} has been running for over {} seconds\n",
+            desc.name,
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        _: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        _: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let name = &desc.name;
+        match *result {
+            TestResult::TrOk => self.write_message(&format!("test {name} ... ok\n")),
+            TestResult::TrIgnored => self.write_message(&format!("test {name} ... ignored\n")),
+            TestResult::TrBench(ref bs) => {
+                self.write_message(&format!("test {name} ... bench: {}\n", fmt_bench_samples(bs)))
+            }
+            TestResult::TrFailed => {
+                self.write_message(&format!("test {name} ... FAILED\n"))?;
+                let message = panic_message(&String::from_utf8_lossy(stdout), name.as_slice())
+                    .unwrap_or_else(|| "test failed".to_string());
+                self.write_annotation("error", desc, &message, stdout)
+            }
+            TestResult::TrFailedMsg(ref msg) => {
+                self.write_message(&format!("test {name} ... FAILED\n"))?;
+                self.write_annotation("error", desc, msg, stdout)
+            }
+            TestResult::TrTimedFail => {
+                self.write_message(&format!("test {name} ... FAILED (time limit exceeded)\n"))?;
+                self.write_annotation("error", desc, "time limit exceeded", stdout)
+            }
+            TestResult::TrTimedOut(timeout) => {
+                let message = format!("timed out after {}s", timeout.as_secs());
+                self.write_message(&format!("test {name} ... FAILED ({message})\n"))?;
+                self.write_annotation("error", desc, &message, stdout)
+            }
+            TestResult::TrFlaky(attempts) => {
+                let message = format!("flaky (passed on attempt {attempts})");
+                self.write_message(&format!("test {name} ... {message}\n"))?;
+                self.write_annotation("warning", desc, &message, stdout)
+            }
+        }
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let success = state.failed == 0;
+        let timed_out = if state.timed_out > 0 {
+            format!(" ({} timed out)", state.timed_out)
+        } else {
+            String::new()
+        };
+        let flaky =
+            if state.flaky > 0 { format!("; {} flaky", state.flaky) } else { String::new() };
+        let mut s = format!(
+            "test result: {}. {} passed; {} failed{timed_out}{flaky}; {} ignored; {} measured; \
+             {} filtered out",
+            if success { "ok" } else { "FAILED" },
+            state.passed,
+            state.failed,
+            state.ignored,
+            state.measured,
+            state.filtered_out
+        );
+        if let Some(ref exec_time) = state.exec_time {
+            s.push_str(&format!("; finished in {exec_time}"));
+        }
+        s.push('\n');
+        self.write_message(&s)?;
+
+        Ok(success)
+    }
+}
+
+struct PanicLocation<'a> {
+    file: &'a str,
+    line: usize,
+    col: usize,
+}
+
+/// Finds the panic message of a test in its captured output, which starts with
+/// "thread '<name>' panicked at <file>:<line>:<col>:". Panics of the test's own thread are
+/// preferred over those of other threads it started.
+fn find_panic<'a>(output: &'a str, test_name: &str) -> Option<(&'a str, &'a str)> {
+    let own_thread = format!("thread '{test_name}' panicked at ");
+    let mut panics = output.match_indices("' panicked at ").filter_map(|(i, pattern)| {
+        let line_start = output[..i].rfind('\n').map_or(0, |n| n + 1);
+        let header = &output[line_start..];
+        if !header.starts_with("thread '") {
+            return None;
+        }
+        let rest = &output[i + pattern.len()..];
+        let (location, message) = rest.split_once('\n').unwrap_or((rest, ""));
+        Some((header.starts_with(&own_thread), location.strip_suffix(':')?, message))
+    });
+    let first = panics.next()?;
+    let (_, location, message) =
+        if first.0 { first } else { panics.find(|panic| panic.0).unwrap_or(first) };
+    Some((location, message))
+}
+
+fn parse_panic_location<'a>(output: &'a str, test_name: &str) -> Option<PanicLocation<'a>> {
+    let (location, _) = find_panic(output, test_name)?;
+    // The file name may contain colons itself, e.g. on Windows.
+    let mut parts = location.rsplitn(3, ':');
+    let col = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+    Some(PanicLocation { file, line, col })
+}
+
+/// Returns the panic message of a test, without the hints that follow it.
+fn panic_message(output: &str, test_name: &str) -> Option<String> {
+    let (_, message) = find_panic(output, test_name)?;
+    let message: Vec<&str> = message
+        .lines()
+        .take_while(|line| {
+            !line.starts_with("note: ")
+                && !line.starts_with("stack backtrace:")
+                && !line.starts_with("thread '")
+        })
+        .collect();
+    Some(message.join("\n").trim_end().to_string())
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
@@ -17,6 +17,11 @@ pub fn new(out: OutputLocation<T>) -> Self {
         Self { out }
     }
 
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
     fn writeln_message(&mut self, s: &str) -> io::Result<()> {
         // self.out will take a lock, but that lock is released when write_all returns. This
         // results in a race condition and json output may not end with a new line. We avoid this
@@ -146,6 +151,24 @@ fn write_result(
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrTimedOut(timeout) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""reason": "timed out", "timeout": {}"#, timeout.as_secs_f64())),
+            ),
+
+            TestResult::TrFlaky(attempts) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""attempts": {attempts}"#)),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
@@ -176,12 +199,36 @@ fn write_result(
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
                 let name = EscapedString(desc.name.as_slice());
+                let mean = bs.ns_iter_summ.mean;
+                let std_dev = bs.ns_iter_summ.std_dev;
+                let outliers = bs.outliers;
+                let change = match bs.change {
+                    Some(change) => format!(
+                        ", \"change\": {{ \"percent\": {}, \"lower_percent\": {}, \
+                         \"upper_percent\": {}, \"significant\": {} }}",
+                        change.estimate * 100.0,
+                        change.lower * 100.0,
+                        change.upper * 100.0,
+                        change.is_significant()
+                    ),
+                    None => String::new(),
+                };
 
                 self.writeln_message(&format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{name}\", \
                      \"median\": {median}, \
-                     \"deviation\": {deviation}{mbps} }}\n",
+                     \"deviation\": {deviation}{mbps}, \
+                     \"mean\": {mean}, \
+                     \"std_dev\": {std_dev}, \
+                     \"samples\": {}, \
+                     \"outliers\": {{ \"low_severe\": {}, \"low_mild\": {}, \
+                     \"high_mild\": {}, \"high_severe\": {} }}{change} }}\n",
+                    bs.samples.len(),
+                    outliers.low_severe,
+                    outliers.low_mild,
+                    outliers.high_mild,
+                    outliers.high_severe,
                 ))
             }
         }
@@ -202,6 +249,13 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let ignored = state.ignored;
         let measured = state.measured;
         let filtered_out = state.filtered_out;
+        let timed_out_json = if state.timed_out > 0 {
+            format!(r#", "timed_out": {}"#, state.timed_out)
+        } else {
+            String::new()
+        };
+        let flaky_json =
+            if state.flaky > 0 { format!(r#", "flaky": {}"#, state.flaky) } else { String::new() };
         let exec_time_json = if let Some(ref exec_time) = state.exec_time {
             format!(r#", "exec_time": {}"#, exec_time.0.as_secs_f64())
         } else {
@@ -210,7 +264,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{timed_out_json}{flaky_json}{exec_time_json} }}{newline}"#
         ))?;
 
         Ok(state.failed == 0)
@@ -7,14 +7,18 @@
     types::{TestDesc, TestName},
 };
 
+mod github;
 mod json;
 mod junit;
 mod pretty;
+mod tap;
 mod terse;
 
+pub(crate) use self::github::GithubActionsFormatter;
 pub(crate) use self::json::JsonFormatter;
 pub(crate) use self::junit::JunitFormatter;
 pub(crate) use self::pretty::PrettyFormatter;
+pub(crate) use self::tap::TapFormatter;
 pub(crate) use self::terse::TerseFormatter;
 
 pub(crate) trait OutputFormatter {
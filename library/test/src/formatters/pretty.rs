@@ -1,4 +1,4 @@
-use std::{io, io::prelude::Write};
+use std::{io, io::prelude::Write, time::Duration};
 
 use super::OutputFormatter;
 use crate::{
@@ -57,6 +57,16 @@ pub fn write_time_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub fn write_timed_out(&mut self, timeout: Duration) -> io::Result<()> {
+        let result = format!("FAILED (timed out after {}s)", timeout.as_secs());
+        self.write_short_result(&result, term::color::RED)
+    }
+
+    pub fn write_flaky(&mut self, attempts: usize) -> io::Result<()> {
+        let result = format!("flaky (passed on attempt {attempts})");
+        self.write_short_result(&result, term::color::YELLOW)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -168,6 +178,10 @@ pub fn write_time_failures(&mut self, state: &ConsoleTestState) -> io::Result<()
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flaky_tests, "flaky")
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -251,6 +265,8 @@ fn write_result(
                 self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrTimedOut(timeout) => self.write_timed_out(timeout)?,
+            TestResult::TrFlaky(attempts) => self.write_flaky(attempts)?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -269,6 +285,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
         let success = state.failed == 0;
         if !success {
             if !state.failures.is_empty() {
@@ -289,8 +308,15 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let timed_out = if state.timed_out > 0 {
+            format!(" ({} timed out)", state.timed_out)
+        } else {
+            String::new()
+        };
+        let flaky =
+            if state.flaky > 0 { format!("; {} flaky", state.flaky) } else { String::new() };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            ". {} passed; {} failed{timed_out}{flaky}; {} ignored; {} measured; {} filtered out",
             state.passed, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
@@ -0,0 +1,234 @@
+use std::{io, io::prelude::Write};
+
+use super::OutputFormatter;
+use crate::{
+    bench::fmt_bench_samples,
+    console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation},
+    test_result::TestResult,
+    time,
+    types::TestDesc,
+};
+
+/// Writes the results in version 14 of the Test Anything Protocol, see
+/// <https://testanything.org/tap-version-14-specification.html>.
+pub(crate) struct TapFormatter<T> {
+    out: OutputLocation<T>,
+    /// Number of the last reported test point.
+    test_number: usize,
+}
+
+impl<T: Write> TapFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out, test_number: 0 }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_message(&mut self, s: &str) -> io::Result<()> {
+        // As in the json formatter, every line is written with a single `write_all` call so
+        // that it isn't interleaved with output of tests that run concurrently.
+        assert_eq!(s.chars().last(), Some('\n'));
+
+        self.out.write_all(s.as_ref())
+    }
+
+    fn write_test_point(
+        &mut self,
+        ok: bool,
+        desc: &TestDesc,
+        directive: Option<String>,
+        diagnostics: Option<Diagnostics<'_>>,
+    ) -> io::Result<()> {
+        self.test_number += 1;
+        let mut s = format!(
+            "{}{} - {}",
+            if ok { "ok " } else { "not ok " },
+            self.test_number,
+            escape_description(desc.name.as_slice())
+        );
+        if let Some(directive) = directive {
+            s.push_str(" # ");
+            s.push_str(&directive);
+        }
+        s.push('\n');
+        if let Some(diagnostics) = diagnostics {
+            diagnostics.write_yaml(&mut s);
+        }
+        self.write_message(&s)
+    }
+}
+
+/// The YAML diagnostics block following a test point.
+struct Diagnostics<'a> {
+    message: Option<String>,
+    severity: Option<&'static str>,
+    exec_time: Option<&'a time::TestExecTime>,
+    output: &'a [u8],
+}
+
+impl Diagnostics<'_> {
+    fn write_yaml(&self, s: &mut String) {
+        s.push_str("  ---\n");
+        if let Some(ref message) = self.message {
+            s.push_str(&format!("  message: {}\n", yaml_string(message)));
+        }
+        if let Some(severity) = self.severity {
+            s.push_str(&format!("  severity: {severity}\n"));
+        }
+        if let Some(exec_time) = self.exec_time {
+            s.push_str(&format!("  duration_ms: {}\n", exec_time.0.as_secs_f64() * 1000.0));
+        }
+        if !self.output.is_empty() {
+            // A literal block scalar keeps the output as is, as long as every line is indented.
+            s.push_str("  output: |\n");
+            for line in String::from_utf8_lossy(self.output).lines() {
+                s.push_str("    ");
+                s.push_str(line);
+                s.push('\n');
+            }
+        }
+        s.push_str("  ...\n");
+    }
+}
+
+impl<T: Write> OutputFormatter for TapFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_test_discovered(&mut self, _desc: &TestDesc, _test_type: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_discovery_finish(&mut self, _state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_run_start(&mut self, _test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        self.write_message("TAP version 14\n")?;
+        if let Some(shuffle_seed) = shuffle_seed {
+            self.write_message(&format!("# shuffle seed: {shuffle_seed}\n"))?;
+        }
+        Ok(())
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.write_message(&format!(
+            "# test {} has been running for over {} seconds\n",
+            desc.name,
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let failure = |message: Option<String>| {
+            Some(Diagnostics { message, severity: Some("fail"), exec_time, output: stdout })
+        };
+        match *result {
+            TestResult::TrOk => {
+                let diagnostics = (state.options.display_output && !stdout.is_empty()).then(|| {
+                    Diagnostics { message: None, severity: None, exec_time, output: stdout }
+                });
+                self.write_test_point(true, desc, None, diagnostics)
+            }
+            TestResult::TrFailed => self.write_test_point(false, desc, None, failure(None)),
+            TestResult::TrFailedMsg(ref msg) => {
+                self.write_test_point(false, desc, None, failure(Some(msg.clone())))
+            }
+            TestResult::TrTimedFail => {
+                let message = Some("time limit exceeded".to_string());
+                self.write_test_point(false, desc, None, failure(message))
+            }
+            TestResult::TrTimedOut(timeout) => {
+                let message = Some(format!("timed out after {}s", timeout.as_secs()));
+                self.write_test_point(false, desc, None, failure(message))
+            }
+            TestResult::TrFlaky(attempts) => {
+                let message = Some(format!("flaky, passed on attempt {attempts}"));
+                let diagnostics =
+                    Diagnostics { message, severity: Some("flaky"), exec_time, output: stdout };
+                self.write_test_point(true, desc, None, Some(diagnostics))
+            }
+            TestResult::TrIgnored => {
+                let directive = match desc.ignore_message {
+                    Some(msg) => format!("SKIP {}", escape_description(msg)),
+                    None => "SKIP".to_string(),
+                };
+                self.write_test_point(true, desc, Some(directive), None)
+            }
+            TestResult::TrBench(ref bs) => {
+                self.write_test_point(true, desc, None, None)?;
+                self.write_message(&format!("# bench: {}\n", fmt_bench_samples(bs)))
+            }
+        }
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        // The plan comes last, so that it matches the reported test points even if the run
+        // stopped early, e.g. because of `--fail-fast`.
+        self.write_message(&format!("1..{}\n", self.test_number))?;
+
+        let success = state.failed == 0;
+        let timed_out = if state.timed_out > 0 {
+            format!(" ({} timed out)", state.timed_out)
+        } else {
+            String::new()
+        };
+        let flaky =
+            if state.flaky > 0 { format!("; {} flaky", state.flaky) } else { String::new() };
+        self.write_message(&format!(
+            "# test result: {}. {} passed; {} failed{timed_out}{flaky}; {} ignored; {} measured; \
+             {} filtered out\n",
+            if success { "ok" } else { "FAILED" },
+            state.passed,
+            state.failed,
+            state.ignored,
+            state.measured,
+            state.filtered_out
+        ))?;
+        if let Some(ref exec_time) = state.exec_time {
+            self.write_message(&format!("# finished in {exec_time}\n"))?;
+        }
+
+        Ok(success)
+    }
+}
+
+/// Escapes the characters that would end a test point description or start a directive.
+fn escape_description(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('#', "\\#").replace('\n', " ")
+}
+
+/// Formats a string as a double-quoted YAML scalar.
+fn yaml_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
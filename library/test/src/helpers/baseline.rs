@@ -0,0 +1,98 @@
+//! Saved benchmark results that later runs can be compared against.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The samples of every benchmark of a run, by benchmark name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Baseline {
+    samples: BTreeMap<String, Vec<f64>>,
+}
+
+impl Baseline {
+    pub fn new() -> Baseline {
+        Baseline::default()
+    }
+
+    pub fn insert(&mut self, name: String, samples: Vec<f64>) {
+        self.samples.insert(name, samples);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[f64]> {
+        self.samples.get(name).map(|samples| &samples[..])
+    }
+
+    /// Returns the file the baseline `name` of the current test binary is stored in.
+    ///
+    /// Every test binary has its own file, so that the benchmarks of several crates
+    /// can be saved under the same name.
+    pub fn path(dir: Option<&Path>, name: &str) -> io::Result<PathBuf> {
+        let exe = std::env::current_exe()?;
+        let dir = match dir {
+            Some(dir) => dir.to_path_buf(),
+            None => exe.with_file_name("libtest-baselines"),
+        };
+        let stem = exe.file_stem().and_then(|stem| stem.to_str()).unwrap_or("tests");
+        Ok(dir.join(name).join(strip_hash(stem)))
+    }
+
+    /// Reads a baseline written by `save`.
+    pub fn load(path: &Path) -> io::Result<Baseline> {
+        let contents = fs::read_to_string(path)?;
+        let mut baseline = Baseline::new();
+        for line in contents.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid line in baseline {}: {line:?}", path.display()),
+                )
+            };
+            let (name, samples) = line.rsplit_once('\t').ok_or_else(invalid)?;
+            let samples = samples
+                .split(',')
+                .map(|sample| sample.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid())?;
+            baseline.insert(name.to_string(), samples);
+        }
+        Ok(baseline)
+    }
+
+    /// Reads a baseline written by `save`, or returns an empty one if there is none yet.
+    pub fn load_or_new(path: &Path) -> io::Result<Baseline> {
+        match Baseline::load(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Baseline::new()),
+            result => result,
+        }
+    }
+
+    /// Writes the baseline to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(file, "# libtest benchmark baseline: name, then nanoseconds per iteration")?;
+        for (name, samples) in &self.samples {
+            let samples: Vec<String> = samples.iter().map(|sample| sample.to_string()).collect();
+            writeln!(file, "{name}\t{}", samples.join(","))?;
+        }
+        file.flush()
+    }
+}
+
+// Cargo appends a hash of the build configuration to the names of test binaries,
+// which changes whenever e.g. a dependency is updated.
+pub(crate) fn strip_hash(stem: &str) -> &str {
+    match stem.rsplit_once('-') {
+        Some((name, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            name
+        }
+        _ => stem,
+    }
+}
@@ -0,0 +1,91 @@
+//! Record of the tests that failed in the previous run, for `--failed-first` and
+//! `--last-failed`.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::baseline::strip_hash;
+use crate::test_result::TestResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailedTests {
+    path: PathBuf,
+    /// Read from `path` the first time it is needed, so that parsing the options doesn't
+    /// touch the file system.
+    names: OnceLock<BTreeSet<String>>,
+}
+
+impl FailedTests {
+    /// Returns the file used if no other one is given with `--failed-tests-file`, which is
+    /// named after the test binary and stored next to it.
+    pub fn default_path() -> io::Result<PathBuf> {
+        let exe = std::env::current_exe()?;
+        let stem = exe.file_stem().and_then(|stem| stem.to_str()).unwrap_or("tests");
+        Ok(exe.with_file_name("libtest-failed").join(strip_hash(stem)))
+    }
+
+    /// Creates the record kept in `path`. The file is only read when the record is first used.
+    pub fn new(path: PathBuf) -> FailedTests {
+        FailedTests { path, names: OnceLock::new() }
+    }
+
+    /// The record is only a cache, so a file that doesn't exist or can't be read is treated
+    /// as if no tests failed.
+    fn names(&self) -> &BTreeSet<String> {
+        self.names.get_or_init(|| match fs::read_to_string(&self.path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect(),
+            Err(_) => BTreeSet::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names().contains(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names().is_empty()
+    }
+
+    /// Updates the record with the result of a test. Ignored tests keep their previous state,
+    /// just like the tests that weren't run at all.
+    pub fn record(&mut self, name: &str, result: &TestResult) {
+        self.names();
+        let names = self.names.get_mut().unwrap();
+        match *result {
+            TestResult::TrFailed
+            | TestResult::TrFailedMsg(_)
+            | TestResult::TrTimedFail
+            | TestResult::TrTimedOut(_) => {
+                names.insert(name.to_string());
+            }
+            TestResult::TrOk | TestResult::TrFlaky(_) | TestResult::TrBench(_) => {
+                names.remove(name);
+            }
+            TestResult::TrIgnored => {}
+        }
+    }
+
+    /// Writes the record back to its file, creating its directory if needed.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = io::BufWriter::new(fs::File::create(&self.path)?);
+        writeln!(file, "# libtest: tests that failed in the last run")?;
+        for name in self.names() {
+            writeln!(file, "{name}")?;
+        }
+        file.flush()
+    }
+}
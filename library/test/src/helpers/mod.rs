@@ -1,6 +1,9 @@
 //! Module with common helpers not directly related to tests
 //! but used in `libtest`.
 
+pub mod baseline;
 pub mod concurrency;
+pub mod failed_tests;
 pub mod metrics;
+pub mod shard;
 pub mod shuffle;
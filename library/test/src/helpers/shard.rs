@@ -0,0 +1,21 @@
+//! Helper module for splitting the tests into shards, so that they can be run
+//! on several machines.
+
+use crate::options::TestShard;
+
+/// Returns whether the test with the given name belongs to `shard`.
+///
+/// The shard is chosen by hashing the test name, so adding or removing a test
+/// doesn't move any of the other tests to a different shard.
+pub fn is_in_shard(test_name: &str, shard: TestShard) -> bool {
+    name_hash(test_name) % shard.count as u64 == shard.index as u64
+}
+
+// The hash has to be the same on every machine running a shard, no matter how
+// the test binary was built, so don't rely on `DefaultHasher` here. This is
+// 64-bit FNV-1a.
+fn name_hash(test_name: &str) -> u64 {
+    test_name
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3))
+}
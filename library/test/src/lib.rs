@@ -29,7 +29,7 @@
 // Public reexports
 pub use self::bench::{black_box, Bencher};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic, TestShard};
 pub use self::types::TestName::*;
 pub use self::types::*;
 pub use self::ColorConfig::*;
@@ -57,7 +57,7 @@ pub mod test {
 use std::{
     collections::VecDeque,
     env, io,
-    io::prelude::Write,
+    io::prelude::{Read, Write},
     mem::ManuallyDrop,
     panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo},
     process::{self, Command, Termination},
@@ -86,6 +86,7 @@ pub mod test {
 use core::any::Any;
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
+use helpers::shard::is_in_shard;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::RunStrategy;
 use test_result::*;
@@ -109,7 +110,13 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
         None => return,
     };
     if let Some(options) = options {
-        opts.options = options;
+        // Keep `--show-output`, which is the only one of these options set on the command line.
+        let display_output = options.display_output || opts.options.display_output;
+        opts.options = options.display_output(display_output);
+    }
+    if opts.isolate && !opts.options.supports_isolation {
+        eprintln!("error: --isolate is not supported by this test harness");
+        process::exit(ERROR_EXIT_CODE);
     }
     if opts.list {
         if let Err(e) = console::list_tests_console(&opts, tests) {
@@ -157,9 +164,12 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=unwind.
 pub fn test_main_static(tests: &[&TestDescAndFn]) {
+    // If we're being run in SpawnedSecondary mode to isolate a test, run the test here.
+    run_spawned_secondary_test(tests, false);
+
     let args = env::args().collect::<Vec<_>>();
     let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
-    test_main(&args, owned_tests, None)
+    test_main(&args, owned_tests, Some(Options::new().supports_isolation(true)))
 }
 
 /// A variant optimized for invocation with a static test vector.
@@ -171,8 +181,18 @@ pub fn test_main_static(tests: &[&TestDescAndFn]) {
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=abort.
 pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
-    // If we're being run in SpawnedSecondary mode, run the test here. run_test
-    // will then exit the process.
+    // If we're being run in SpawnedSecondary mode, run the test here.
+    run_spawned_secondary_test(tests, true);
+
+    let args = env::args().collect::<Vec<_>>();
+    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
+    let options = Options::new().panic_abort(true).supports_isolation(true);
+    test_main(&args, owned_tests, Some(options))
+}
+
+/// Runs the test named by `SECONDARY_TEST_INVOKER_VAR` and exits the process, if the variable
+/// is set. Returns otherwise.
+fn run_spawned_secondary_test(tests: &[&TestDescAndFn], panic_abort: bool) {
     if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
         env::remove_var(SECONDARY_TEST_INVOKER_VAR);
 
@@ -194,17 +214,13 @@ pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
                 if runnable_test.is_dynamic() {
                     panic!("only static tests are supported");
                 }
-                run_test_in_spawned_subprocess(desc, runnable_test);
+                run_test_in_spawned_subprocess(desc, runnable_test, panic_abort);
             }
             Runnable::Bench(_) => {
                 panic!("benchmarks should not be executed into child processes")
             }
         }
     }
-
-    let args = env::args().collect::<Vec<_>>();
-    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
-    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
 }
 
 /// Clones static values for putting into a dynamic vector, which test_main()
@@ -270,6 +286,90 @@ pub fn run_tests<F>(
 
     struct RunningTest {
         join_handle: Option<thread::JoinHandle<()>>,
+        hard_timeout: Option<HardTimeout>,
+        retry: Option<Retry>,
+    }
+
+    // The hard time limit of a test that runs in a thread of this process. Tests that
+    // run in a subprocess are killed by `spawn_test_subprocess` instead.
+    struct HardTimeout {
+        desc: TestDesc,
+        timeout: Duration,
+        deadline: Instant,
+    }
+
+    impl HardTimeout {
+        fn new(opts: &TestOpts, desc: &TestDesc, strategy: RunStrategy) -> Option<HardTimeout> {
+            match strategy {
+                RunStrategy::InProcess => {}
+                RunStrategy::SpawnPrimary => return None,
+            }
+            let timeout = time::get_test_timeout(desc, opts.test_timeout)?;
+            Some(HardTimeout { desc: desc.clone(), timeout, deadline: Instant::now() + timeout })
+        }
+
+        fn into_completed_test(self, id: TestId, opts: &TestOpts) -> CompletedTest {
+            let exec_time = opts.time_options.map(|_| TestExecTime(self.timeout));
+            CompletedTest::new(id, self.desc, TrTimedOut(self.timeout), exec_time, Vec::new())
+        }
+    }
+
+    // The state of a test that may be run again when it fails, see `--retries`.
+    struct Retry {
+        desc: TestDesc,
+        testfn: TestFn,
+        retries_left: usize,
+        attempts: usize,
+        // Output of the earlier attempts, reported together with the final result.
+        output: Vec<u8>,
+    }
+
+    impl Retry {
+        fn new(opts: &TestOpts, test: &TestDescAndFn) -> Option<Retry> {
+            if opts.retries == 0 {
+                return None;
+            }
+            Some(Retry {
+                desc: test.desc.clone(),
+                testfn: test.testfn.try_clone()?,
+                retries_left: opts.retries,
+                attempts: 1,
+                output: Vec::new(),
+            })
+        }
+
+        // Returns the test to run next if the attempt failed and retries are left.
+        // Otherwise, turns the attempt into the result of the test as a whole.
+        fn next_attempt(&mut self, completed_test: &mut CompletedTest) -> Option<TestDescAndFn> {
+            let failed = match completed_test.result {
+                TrFailed | TrFailedMsg(_) | TrTimedFail => true,
+                TrIgnored | TrOk | TrBench(_) | TrTimedOut(_) | TrFlaky(_) => false,
+            };
+            if failed && self.retries_left > 0 {
+                self.retries_left -= 1;
+                self.record_attempt(completed_test);
+                if let TrFailedMsg(ref msg) = completed_test.result {
+                    self.output.extend_from_slice(format!("note: {msg}\n").as_bytes());
+                }
+                self.attempts += 1;
+                let testfn = self.testfn.try_clone().unwrap();
+                return Some(TestDescAndFn { desc: self.desc.clone(), testfn });
+            }
+            if self.attempts > 1 {
+                self.record_attempt(completed_test);
+                completed_test.stdout = std::mem::take(&mut self.output);
+                if let TrOk = completed_test.result {
+                    completed_test.result = TrFlaky(self.attempts);
+                }
+            }
+            None
+        }
+
+        fn record_attempt(&mut self, completed_test: &CompletedTest) {
+            let header = format!("---- attempt {} ----\n", self.attempts);
+            self.output.extend_from_slice(header.as_bytes());
+            self.output.extend_from_slice(&completed_test.stdout);
+        }
     }
 
     impl RunningTest {
@@ -332,6 +432,10 @@ struct TimeoutEntry {
     if let Some(shuffle_seed) = shuffle_seed {
         shuffle_tests(shuffle_seed, &mut remaining);
     }
+    if let (true, Some(failed_tests)) = (opts.failed_first, &opts.failed_tests) {
+        // The sort is stable, so both groups keep their (possibly shuffled) order.
+        remaining.sort_by_key(|(_, test)| !failed_tests.contains(test.desc.name.as_slice()));
+    }
     // Store the tests in a VecDeque so we can efficiently remove the first element to run the
     // tests in the order they were passed (unless shuffled).
     let mut remaining = VecDeque::from(remaining);
@@ -343,6 +447,14 @@ struct TimeoutEntry {
     } else {
         RunStrategy::InProcess
     };
+    // Isolated tests run in a subprocess of their own, whatever the panic strategy.
+    let test_run_strategy = |desc: &TestDesc| {
+        if (opts.isolate || desc.isolate) && opts.options.supports_isolation {
+            RunStrategy::SpawnPrimary
+        } else {
+            run_strategy
+        }
+    };
 
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
@@ -372,19 +484,92 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         })
     }
 
+    fn get_hard_timed_out_tests(running_tests: &mut TestMap) -> Vec<(TestId, HardTimeout)> {
+        let now = Instant::now();
+        let mut timed_out: Vec<TestId> = running_tests
+            .iter()
+            .filter(|(_, test)| test.hard_timeout.as_ref().is_some_and(|t| t.deadline <= now))
+            .map(|(&id, _)| id)
+            .collect();
+        timed_out.sort_by_key(|id| id.0);
+        // Dropping the join handle detaches the thread, as there is no way to stop it.
+        timed_out
+            .into_iter()
+            .map(|id| (id, running_tests.remove(&id).unwrap().hard_timeout.unwrap()))
+            .collect()
+    }
+
+    fn calc_hard_timeout(running_tests: &TestMap) -> Option<Duration> {
+        let now = Instant::now();
+        running_tests
+            .values()
+            .filter_map(|test| test.hard_timeout.as_ref())
+            .map(|t| t.deadline.saturating_duration_since(now))
+            .min()
+    }
+
+    // Set once a test thread has been abandoned after running into its hard time limit.
+    let mut timed_out = false;
+
+    // Dynamic tests, such as doctests, can't be copied to run them again. The first of them that
+    // fails says so in its output, so that `--retries` doesn't silently do nothing.
+    let mut noted_not_retried = opts.retries == 0;
+    let mut note_not_retried = |completed_test: &mut CompletedTest| {
+        if !noted_not_retried
+            && matches!(completed_test.result, TrFailed | TrFailedMsg(_) | TrTimedFail)
+        {
+            noted_not_retried = true;
+            completed_test.stdout.extend_from_slice(
+                b"note: not retried, --retries does not run dynamic tests (e.g. doctests) again\n",
+            );
+        }
+    };
+
     if concurrency == 1 {
-        while !remaining.is_empty() {
-            let (id, test) = remaining.pop_front().unwrap();
+        'tests: while !remaining.is_empty() {
+            let (id, mut test) = remaining.pop_front().unwrap();
             let event = TestEvent::TeWait(test.desc.clone());
             notify_about_test_event(event)?;
-            let join_handle = run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
-            // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
-            RunningTest { join_handle }.join(&mut completed_test);
+            let mut retry = Retry::new(opts, &test);
+            let mut completed_test = loop {
+                let strategy = test_run_strategy(&test.desc);
+                let hard_timeout = HardTimeout::new(opts, &test.desc, strategy);
+                let join_handle = run_test(opts, !opts.run_tests, id, test, strategy, tx.clone());
+                // Wait for the test to complete.
+                let mut completed_test = match hard_timeout {
+                    Some(hard_timeout) => {
+                        let wait = hard_timeout.deadline.saturating_duration_since(Instant::now());
+                        match rx.recv_timeout(wait) {
+                            Ok(completed_test) => completed_test,
+                            Err(_) => {
+                                let completed_test = hard_timeout.into_completed_test(id, opts);
+                                let event = TestEvent::TeResult(completed_test);
+                                notify_about_test_event(event)?;
+                                if opts.fail_fast {
+                                    std::mem::forget(rx);
+                                    return Ok(());
+                                }
+                                timed_out = true;
+                                break 'tests;
+                            }
+                        }
+                    }
+                    None => rx.recv().unwrap(),
+                };
+                RunningTest { join_handle, hard_timeout: None, retry: None }
+                    .join(&mut completed_test);
+                match retry.as_mut().and_then(|retry| retry.next_attempt(&mut completed_test)) {
+                    Some(next_attempt) => test = next_attempt,
+                    None => break completed_test,
+                }
+            };
+            if retry.is_none() {
+                note_not_retried(&mut completed_test);
+            }
 
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut(_) => opts.fail_fast,
             };
 
             let event = TestEvent::TeResult(completed_test);
@@ -395,36 +580,54 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             }
         }
     } else {
-        while pending > 0 || !remaining.is_empty() {
-            while pending < concurrency && !remaining.is_empty() {
+        while pending > 0 || (!remaining.is_empty() && !timed_out) {
+            while pending < concurrency && !remaining.is_empty() && !timed_out {
                 let (id, test) = remaining.pop_front().unwrap();
                 let timeout = time::get_default_test_timeout();
                 let desc = test.desc.clone();
 
                 let event = TestEvent::TeWait(desc.clone());
                 notify_about_test_event(event)?; //here no pad
-                let join_handle =
-                    run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
-                running_tests.insert(id, RunningTest { join_handle });
+                let strategy = test_run_strategy(&desc);
+                let hard_timeout = HardTimeout::new(opts, &desc, strategy);
+                let retry = Retry::new(opts, &test);
+                let join_handle = run_test(opts, !opts.run_tests, id, test, strategy, tx.clone());
+                running_tests.insert(id, RunningTest { join_handle, hard_timeout, retry });
                 timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
                 pending += 1;
             }
 
             let mut res;
             loop {
-                if let Some(timeout) = calc_timeout(&timeout_queue) {
+                let timeout =
+                    match (calc_timeout(&timeout_queue), calc_hard_timeout(&running_tests)) {
+                        (Some(warn), Some(hard)) => Some(warn.min(hard)),
+                        (warn, hard) => warn.or(hard),
+                    };
+                if let Some(timeout) = timeout {
                     res = rx.recv_timeout(timeout);
                     for test in get_timed_out_tests(&running_tests, &mut timeout_queue) {
                         let event = TestEvent::TeTimeout(test);
                         notify_about_test_event(event)?;
                     }
+                    for (id, hard_timeout) in get_hard_timed_out_tests(&mut running_tests) {
+                        let event = TestEvent::TeResult(hard_timeout.into_completed_test(id, opts));
+                        notify_about_test_event(event)?;
+                        pending -= 1;
+                        timed_out = true;
+                    }
+                    if timed_out && opts.fail_fast {
+                        std::mem::forget(rx);
+                        return Ok(());
+                    }
 
                     match res {
-                        Err(RecvTimeoutError::Timeout) => {
+                        Err(RecvTimeoutError::Timeout) if pending > 0 => {
                             // Result is not yet ready, continue waiting.
                         }
                         _ => {
-                            // We've got a result, stop the loop.
+                            // We've got a result, or all remaining tests timed out,
+                            // stop the loop.
                             break;
                         }
                     }
@@ -434,13 +637,42 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 }
             }
 
-            let mut completed_test = res.unwrap();
-            let running_test = running_tests.remove(&completed_test.id).unwrap();
+            let mut completed_test = match res {
+                Ok(completed_test) => completed_test,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => panic!("test result channel disconnected"),
+            };
+            // A test that was already reported as timed out may still finish eventually.
+            let Some(mut running_test) = running_tests.remove(&completed_test.id) else {
+                continue;
+            };
+            let retry = running_test.retry.take();
             running_test.join(&mut completed_test);
 
+            if let Some(mut retry) = retry {
+                if let Some(test) = retry.next_attempt(&mut completed_test) {
+                    // Run the test again in place of the failed attempt, without reporting it.
+                    let id = completed_test.id;
+                    let timeout = time::get_default_test_timeout();
+                    let desc = test.desc.clone();
+                    let strategy = test_run_strategy(&desc);
+                    let hard_timeout = HardTimeout::new(opts, &desc, strategy);
+                    let join_handle =
+                        run_test(opts, !opts.run_tests, id, test, strategy, tx.clone());
+                    let retry = Some(retry);
+                    running_tests.insert(id, RunningTest { join_handle, hard_timeout, retry });
+                    // The time limit of the failed attempt no longer applies.
+                    timeout_queue.retain(|entry| entry.id != id);
+                    timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
+                    continue;
+                }
+            } else {
+                note_not_retried(&mut completed_test);
+            }
+
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut(_) => opts.fail_fast,
             };
 
             let event = TestEvent::TeResult(completed_test);
@@ -455,6 +687,23 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         }
     }
 
+    if timed_out {
+        // The test thread that was abandoned can't be stopped, so no more tests are started.
+        // Report the tests that were never run as ignored, so that the totals still add up.
+        for (id, test) in remaining.into_iter().chain(filtered.benches) {
+            let mut desc = test.desc;
+            desc.ignore = true;
+            desc.ignore_message = Some("not run, an earlier test was abandoned after timing out");
+            let event = TestEvent::TeWait(desc.clone());
+            notify_about_test_event(event)?;
+            let completed_test = CompletedTest::new(id, desc, TrIgnored, None, Vec::new());
+            notify_about_test_event(TestEvent::TeResult(completed_test))?;
+        }
+        // Prevent abandoned test threads from panicking if they finish after all.
+        std::mem::forget(rx);
+        return Ok(());
+    }
+
     if opts.bench_benchmarks {
         // All benchmarks run at the end, in serial.
         for (id, b) in filtered.benches {
@@ -463,7 +712,7 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let join_handle = run_test(opts, false, id, b, run_strategy, tx.clone());
             // Wait for the test to complete.
             let mut completed_test = rx.recv().unwrap();
-            RunningTest { join_handle }.join(&mut completed_test);
+            RunningTest { join_handle, hard_timeout: None, retry: None }.join(&mut completed_test);
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
@@ -498,6 +747,19 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         filtered.retain(|test| test.desc.should_panic == ShouldPanic::No);
     }
 
+    // Only keep the tests of the requested shard
+    if let Some(shard) = opts.shard {
+        filtered.retain(|test| is_in_shard(test.desc.name.as_slice(), shard));
+    }
+
+    // Only keep the tests that failed last time, unless none of them did
+    if let (true, Some(failed_tests)) = (opts.last_failed, &opts.failed_tests) {
+        let failed = |test: &TestDescAndFn| failed_tests.contains(test.desc.name.as_slice());
+        if filtered.iter().any(failed) {
+            filtered.retain(failed);
+        }
+    }
+
     // maybe unignore tests
     match opts.run_ignored {
         RunIgnored::Yes => {
@@ -562,6 +824,7 @@ pub fn run_test(
             let nocapture = opts.nocapture;
             let time_options = opts.time_options;
             let bench_benchmarks = opts.bench_benchmarks;
+            let timeout = time::get_test_timeout(&desc, opts.test_timeout);
 
             let runtest = move || match strategy {
                 RunStrategy::InProcess => run_test_in_process(
@@ -581,6 +844,7 @@ pub fn run_test(
                     monitor_ch,
                     time_options,
                     bench_benchmarks,
+                    timeout,
                 ),
             };
 
@@ -679,6 +943,7 @@ fn spawn_test_subprocess(
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
     bench_benchmarks: bool,
+    timeout: Option<Duration>,
 ) {
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
@@ -692,10 +957,17 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match timeout {
+            Some(timeout) => output_with_timeout(&mut command, timeout),
+            None => command.output().map(|out| (out, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -712,7 +984,10 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
-        let result = get_result_from_exit_code(&desc, status, &time_opts, &exec_time);
+        let result = match timeout {
+            Some(timeout) if timed_out => TrTimedOut(timeout),
+            _ => get_result_from_exit_code(&desc, status, &time_opts, &exec_time),
+        };
         (result, test_output, exec_time)
     })();
 
@@ -720,7 +995,71 @@ fn spawn_test_subprocess(
     monitor_ch.send(message).unwrap();
 }
 
-fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -> ! {
+/// Like `Command::output`, but kills the child process if it runs for longer than `timeout`.
+/// Also returns whether the child was killed.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> io::Result<(process::Output, bool)> {
+    let deadline = Instant::now() + timeout;
+    let mut child = command.stdin(process::Stdio::null()).spawn()?;
+
+    // Drain the pipes while waiting, so the child doesn't block on a full pipe.
+    fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> Option<thread::JoinHandle<Vec<u8>>> {
+        pipe.map(|mut pipe| {
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = pipe.read_to_end(&mut buf);
+                buf
+            })
+        })
+    }
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            // The child may have exited in the meantime, in which case this fails harmlessly.
+            let _ = child.kill();
+            timed_out = true;
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let join = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+        reader.map(|reader| reader.join().unwrap_or_default()).unwrap_or_default()
+    };
+    let output = process::Output { status, stdout: join(stdout), stderr: join(stderr) };
+    Ok((output, timed_out))
+}
+
+fn run_test_in_spawned_subprocess(
+    desc: TestDesc,
+    runnable_test: RunnableTest,
+    panic_abort: bool,
+) -> ! {
+    if !panic_abort {
+        // Catch the panic like a test thread would, so that panics the test catches itself
+        // (e.g. in other threads) don't end the test.
+        let result = fold_err(catch_unwind(AssertUnwindSafe(|| runnable_test.run())));
+        let test_result = match result {
+            Ok(()) => calc_result(&desc, Ok(()), &None, &None),
+            Err(e) => calc_result(&desc, Err(e.as_ref()), &None, &None),
+        };
+        if let TrFailedMsg(msg) = &test_result {
+            eprintln!("{msg}");
+        }
+        match test_result {
+            TrOk => process::exit(test_result::TR_OK),
+            _ => process::exit(test_result::TR_FAILED),
+        }
+    }
+
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicInfo<'_>>| {
         let test_result = match panic_info {
@@ -36,6 +36,10 @@ pub enum OutputFormat {
     Json,
     /// JUnit output
     Junit,
+    /// TAP version 14 output
+    Tap,
+    /// GitHub Actions workflow commands that annotate failures
+    GithubActions,
 }
 
 /// Whether ignored test should be run or not
@@ -47,6 +51,15 @@ pub enum RunIgnored {
     Only,
 }
 
+/// Which part of the tests to run, when they are split across several runs
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TestShard {
+    /// Index of this shard, in `0..count`
+    pub index: usize,
+    /// Total number of shards
+    pub count: usize,
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
@@ -65,11 +78,14 @@ pub enum RunStrategy {
 pub struct Options {
     pub display_output: bool,
     pub panic_abort: bool,
+    /// Whether the test binary can run a single test in a subprocess, which is needed to
+    /// isolate tests when panic=unwind.
+    pub supports_isolation: bool,
 }
 
 impl Options {
     pub fn new() -> Options {
-        Options { display_output: false, panic_abort: false }
+        Options { display_output: false, panic_abort: false, supports_isolation: false }
     }
 
     pub fn display_output(mut self, display_output: bool) -> Options {
@@ -81,4 +97,9 @@ pub fn panic_abort(mut self, panic_abort: bool) -> Options {
         self.panic_abort = panic_abort;
         self
     }
+
+    pub fn supports_isolation(mut self, supports_isolation: bool) -> Options {
+        self.supports_isolation = supports_isolation;
+        self
+    }
 }
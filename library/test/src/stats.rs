@@ -300,3 +300,125 @@ pub fn winsorize(samples: &mut [f64], pct: f64) {
         }
     }
 }
+
+/// Numbers of samples outside of the Tukey fences of a sample set: more than 1.5 (mild) or 3
+/// (severe) interquartile ranges below the first or above the third quartile.
+///
+/// See: <https://en.wikipedia.org/wiki/Outlier#Tukey's_fences>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Outliers {
+    pub low_severe: usize,
+    pub low_mild: usize,
+    pub high_mild: usize,
+    pub high_severe: usize,
+}
+
+impl Outliers {
+    /// Classify the outliers of a sample set.
+    pub fn new(samples: &[f64]) -> Outliers {
+        let mut outliers = Outliers::default();
+        if samples.is_empty() {
+            return outliers;
+        }
+        let (q1, _, q3) = samples.quartiles();
+        let iqr = q3 - q1;
+        for &samp in samples {
+            if samp < q1 - 3.0 * iqr {
+                outliers.low_severe += 1;
+            } else if samp < q1 - 1.5 * iqr {
+                outliers.low_mild += 1;
+            } else if samp > q3 + 3.0 * iqr {
+                outliers.high_severe += 1;
+            } else if samp > q3 + 1.5 * iqr {
+                outliers.high_mild += 1;
+            }
+        }
+        outliers
+    }
+
+    pub fn total(&self) -> usize {
+        self.low_severe + self.low_mild + self.high_mild + self.high_severe
+    }
+}
+
+/// Relative change of the median between two sample sets, with a confidence interval estimated
+/// by resampling both sets with replacement.
+///
+/// See: <https://en.wikipedia.org/wiki/Bootstrapping_(statistics)>
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Change {
+    /// Relative change of the median, e.g. `0.05` if it grew by 5%.
+    pub estimate: f64,
+    /// Lower bound of the confidence interval of the change.
+    pub lower: f64,
+    /// Upper bound of the confidence interval of the change.
+    pub upper: f64,
+}
+
+impl Change {
+    /// Estimate the change from the `old` to the `new` samples, with a confidence interval of the
+    /// given level (e.g. `0.95`) computed from `resamples` bootstrap resamples.
+    ///
+    /// The resamples are drawn from a fixed seed, so the result only depends on the samples. The
+    /// median of `old` must be positive; resamples of it whose median is zero, which happens when
+    /// many samples are zero, are skipped since they have no relative change.
+    pub fn bootstrap(old: &[f64], new: &[f64], confidence: f64, resamples: usize) -> Change {
+        assert!(!old.is_empty() && !new.is_empty());
+        assert!(old.median() > 0.0);
+        assert!(0.0 < confidence && confidence < 1.0);
+        assert!(resamples > 0);
+
+        let mut rng = SplitMix64(0x2545_F491_4F6C_DD1D);
+        let mut tmp = Vec::new();
+        let mut resampled_median = |samples: &[f64], rng: &mut SplitMix64| {
+            tmp.clear();
+            tmp.extend((0..samples.len()).map(|_| samples[rng.below(samples.len())]));
+            tmp.median()
+        };
+        let mut changes: Vec<f64> = (0..resamples)
+            .filter_map(|_| {
+                let old = resampled_median(old, &mut rng);
+                let new = resampled_median(new, &mut rng);
+                if old > 0.0 { Some(new / old - 1.0) } else { None }
+            })
+            .collect();
+        local_sort(&mut changes);
+
+        let estimate = new.median() / old.median() - 1.0;
+        if changes.is_empty() {
+            // Every resample was skipped, so nothing is known about the change.
+            return Change { estimate, lower: f64::NEG_INFINITY, upper: f64::INFINITY };
+        }
+        let tail = (1.0 - confidence) / 2.0 * 100.0;
+        Change {
+            estimate,
+            lower: percentile_of_sorted(&changes, tail),
+            upper: percentile_of_sorted(&changes, 100.0 - tail),
+        }
+    }
+
+    /// Whether the confidence interval excludes "no change".
+    pub fn is_significant(&self) -> bool {
+        self.lower > 0.0 || self.upper < 0.0
+    }
+}
+
+// A small, deterministic pseudorandom number generator for resampling.
+//
+// See: <https://prng.di.unimi.it/splitmix64.c>
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Returns a number in `0..n`. The modulo bias is negligible for the small `n` used here.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
@@ -571,6 +571,46 @@ fn test_sum_f64_between_ints_that_sum_to_0() {
     assert_eq!([1e30f64, 1.2f64, -1e30f64].sum(), 1.2);
 }
 
+#[test]
+fn test_outliers() {
+    let samples = [1.0, 10.0, 11.0, 12.0, 12.0, 13.0, 14.0, 19.0, 30.0];
+    let outliers = Outliers::new(&samples);
+    assert_eq!(outliers, Outliers { low_severe: 1, low_mild: 0, high_mild: 1, high_severe: 1 });
+    assert_eq!(outliers.total(), 3);
+    assert_eq!(Outliers::new(&[5.0, 5.0, 5.0]).total(), 0);
+    assert_eq!(Outliers::new(&[]).total(), 0);
+}
+
+#[test]
+fn test_change_bootstrap() {
+    let old: Vec<f64> = (0..50).map(|i| 100.0 + (i % 7) as f64).collect();
+
+    let same = Change::bootstrap(&old, &old, 0.95, 1000);
+    assert_eq!(same.estimate, 0.0);
+    assert!(same.lower <= 0.0 && same.upper >= 0.0);
+    assert!(!same.is_significant());
+
+    let slower: Vec<f64> = old.iter().map(|x| x * 1.5).collect();
+    let change = Change::bootstrap(&old, &slower, 0.95, 1000);
+    assert_approx_eq!(change.estimate, 0.5);
+    assert!(change.lower > 0.4 && change.upper < 0.6);
+    assert!(change.is_significant());
+
+    // The resamples are deterministic.
+    assert_eq!(Change::bootstrap(&old, &slower, 0.95, 1000), change);
+}
+
+#[test]
+fn test_change_bootstrap_zero_baseline() {
+    // Many resamples of a baseline that is mostly zeros have a median of zero.
+    let old: Vec<f64> = (0..51).map(|i| if i < 25 { 0.0 } else { 10.0 }).collect();
+    let new: Vec<f64> = (0..51).map(|i| if i < 25 { 0.0 } else { 20.0 }).collect();
+    let change = Change::bootstrap(&old, &new, 0.95, 1000);
+    assert_approx_eq!(change.estimate, 1.0);
+    assert!(change.lower.is_finite() && change.upper.is_finite());
+    assert!(change.lower <= change.estimate && change.estimate <= change.upper);
+}
+
 #[bench]
 pub fn sum_three_items(b: &mut Bencher) {
     b.iter(|| {
@@ -1,5 +1,6 @@
 use std::any::Any;
 use std::process::ExitStatus;
+use std::time::Duration;
 
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
@@ -15,6 +16,8 @@
 // Start somewhere other than 0 so we know the return code means what we think
 // it means.
 pub const TR_OK: i32 = 50;
+// Return code for a failed test in a secondary process that caught the panic itself.
+pub const TR_FAILED: i32 = 51;
 
 // On Windows we use __fastfail to abort, which is documented to use this
 // exception code.
@@ -29,6 +32,10 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test was stopped (or abandoned) after running into its hard time limit.
+    TrTimedOut(Duration),
+    /// The test failed, but passed when it was retried. Holds the number of attempts it took.
+    TrFlaky(usize),
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
@@ -95,6 +102,7 @@ pub fn get_result_from_exit_code(
 ) -> TestResult {
     let result = match status.code() {
         Some(TR_OK) => TestResult::TrOk,
+        Some(TR_FAILED) => TestResult::TrFailed,
         #[cfg(windows)]
         Some(STATUS_ABORTED) => TestResult::TrFailed,
         #[cfg(unix)]
@@ -6,7 +6,7 @@
 
 use crate::{
     console::OutputLocation,
-    formatters::PrettyFormatter,
+    formatters::{GithubActionsFormatter, OutputFormatter, PrettyFormatter, TapFormatter},
     test::{
         parse_opts,
         MetricMap,
@@ -24,6 +24,7 @@ fn new() -> TestOpts {
             filters: vec![],
             filter_exact: false,
             force_run_in_process: false,
+            isolate: false,
             exclude_should_panic: false,
             run_ignored: RunIgnored::No,
             run_tests: false,
@@ -34,9 +35,18 @@ fn new() -> TestOpts {
             format: OutputFormat::Pretty,
             shuffle: false,
             shuffle_seed: None,
+            shard: None,
+            failed_tests: None,
+            failed_first: false,
+            last_failed: false,
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            retries: 0,
+            save_baseline: None,
+            baseline: None,
+            baseline_dir: None,
             options: Options::new(),
             fail_fast: false,
             output_postprocess_executable: None,
@@ -91,6 +101,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout_secs: None,
+                isolate: false,
             },
             testfn: DynTestFn(Box::new(move || Ok(()))),
         },
@@ -108,6 +120,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout_secs: None,
+                isolate: false,
             },
             testfn: DynTestFn(Box::new(move || Ok(()))),
         },
@@ -133,6 +147,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -161,6 +177,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -192,6 +210,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -223,6 +243,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -259,6 +281,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -299,6 +323,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -333,6 +359,8 @@ fn f() -> Result<(), String> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout_secs: None,
+                isolate: false,
             },
             testfn: DynTestFn(Box::new(f)),
         };
@@ -366,6 +394,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -408,6 +438,8 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -452,9 +484,16 @@ fn typed_test_desc(test_type: TestType) -> TestDesc {
         compile_fail: false,
         no_run: false,
         test_type,
+        timeout_secs: None,
+        isolate: false,
     }
 }
 
+/// A test named `name` that runs `testfn`, with the defaults of `typed_test_desc` otherwise.
+fn named_test(name: TestName, testfn: TestFn) -> TestDescAndFn {
+    TestDescAndFn { desc: TestDesc { name, ..typed_test_desc(TestType::Unknown) }, testfn }
+}
+
 fn test_exec_time(millis: u64) -> TestExecTime {
     TestExecTime(Duration::from_millis(millis))
 }
@@ -533,6 +572,437 @@ fn parse_output_postprocess() {
     );
 }
 
+#[test]
+fn parse_test_timeout() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout".to_string(),
+        "5".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(5)));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout=0".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn test_timeout_attribute_overrides_flag() {
+    let mut desc = typed_test_desc(TestType::Unknown);
+    let default = Some(Duration::from_secs(10));
+    assert_eq!(time::get_test_timeout(&desc, None), None);
+    assert_eq!(time::get_test_timeout(&desc, default), default);
+
+    desc.timeout_secs = Some(3);
+    assert_eq!(time::get_test_timeout(&desc, None), Some(Duration::from_secs(3)));
+    assert_eq!(time::get_test_timeout(&desc, default), Some(Duration::from_secs(3)));
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_hard_timeout_stops_test_run() {
+    fn hangs() -> Result<(), String> {
+        loop {
+            thread::park();
+        }
+    }
+    fn passes() -> Result<(), String> {
+        Ok(())
+    }
+
+    for test_threads in [1, 2] {
+        let tests = vec![
+            named_test(StaticTestName("a"), StaticTestFn(passes)),
+            named_test(StaticTestName("b"), StaticTestFn(hangs)),
+            named_test(StaticTestName("c"), StaticTestFn(passes)),
+        ];
+        let (tx, rx) = channel();
+        let notify = move |event: TestEvent| {
+            if let TestEvent::TeResult(result) = event {
+                tx.send((result.desc.name.to_string(), result.result)).unwrap();
+            }
+            Ok(())
+        };
+        let timeout = Duration::from_millis(100);
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            test_timeout: Some(timeout),
+            ..TestOpts::new()
+        };
+        run_tests(&opts, tests, notify).unwrap();
+        let mut results = rx.iter().collect::<Vec<_>>();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // The hanging test is reported as timed out, and the tests that are not started after
+        // that are reported as ignored.
+        let expected = [
+            ("a".to_string(), TrOk),
+            ("b".to_string(), TrTimedOut(timeout)),
+            ("c".to_string(), TrIgnored),
+        ];
+        if test_threads == 1 {
+            assert_eq!(results, expected);
+        } else {
+            // "c" may have been started alongside "b".
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[..2], expected[..2]);
+            assert!(results[2] == expected[2] || results[2] == ("c".to_string(), TrOk));
+        }
+    }
+}
+
+#[test]
+fn parse_isolate_flag() {
+    let args =
+        vec!["progname".to_string(), "-Zunstable-options".to_string(), "--isolate".to_string()];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert!(opts.isolate);
+
+    let args = vec!["progname".to_string(), "--isolate".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn parse_retries() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--retries".to_string(),
+        "3".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.retries, 3);
+
+    let args = vec!["progname".to_string(), "--retries=3".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn parse_baselines() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--bench".to_string(),
+        "--save-baseline".to_string(),
+        "new".to_string(),
+        "--baseline=main".to_string(),
+        "--baseline-dir=target/baselines".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.save_baseline.as_deref(), Some("new"));
+    assert_eq!(opts.baseline.as_deref(), Some("main"));
+    assert_eq!(opts.baseline_dir, Some(PathBuf::from("target/baselines")));
+
+    for args in [
+        &["--bench", "--baseline=main"][..],
+        &["-Zunstable-options", "--baseline=main"],
+        &["-Zunstable-options", "--bench", "--save-baseline=../main"],
+        &["-Zunstable-options", "--bench", "--baseline=.."],
+    ] {
+        let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        args.insert(0, "progname".to_string());
+        assert!(parse_opts(&args).unwrap().is_err(), "{args:?}");
+    }
+}
+
+#[test]
+fn baseline_round_trip() {
+    use crate::helpers::baseline::Baseline;
+
+    let dir = tmpdir();
+    let path = dir.join("main/tests");
+    let mut baseline = Baseline::new();
+    baseline.insert("bench::a".to_string(), vec![1.5, 2.0, 1e9]);
+    baseline.insert("bench with spaces\tab".to_string(), vec![0.25]);
+    baseline.save(&path).unwrap();
+
+    let loaded = Baseline::load(&path).unwrap();
+    assert_eq!(loaded, baseline);
+    assert_eq!(loaded.get("bench::a"), Some(&[1.5, 2.0, 1e9][..]));
+    assert_eq!(loaded.get("bench::b"), None);
+
+    fs::write(&path, "bench::a\t1.5,x\n").unwrap();
+    assert_eq!(Baseline::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn save_baseline_keeps_benchmarks_that_did_not_run() {
+    use crate::helpers::baseline::Baseline;
+
+    fn benches() -> Vec<TestDescAndFn> {
+        ["bench_a", "bench_b"]
+            .into_iter()
+            .map(|name| {
+                let f = |b: &mut Bencher| {
+                    b.iter(|| black_box(1));
+                    Ok(())
+                };
+                named_test(StaticTestName(name), DynBenchFn(Box::new(f)))
+            })
+            .collect()
+    }
+
+    let dir = tmpdir();
+    let opts = |filter: &str| TestOpts {
+        bench_benchmarks: true,
+        filters: vec![filter.to_string()],
+        save_baseline: Some("main".to_string()),
+        baseline_dir: Some(dir.join("baselines")),
+        ..TestOpts::new()
+    };
+    let path = Baseline::path(Some(&dir.join("baselines")), "main").unwrap();
+
+    run_tests_console(&opts("bench_a"), benches()).unwrap();
+    let first = Baseline::load(&path).unwrap();
+    assert!(first.get("bench_a").is_some());
+    assert!(first.get("bench_b").is_none());
+
+    run_tests_console(&opts("bench_b"), benches()).unwrap();
+    let second = Baseline::load(&path).unwrap();
+    assert_eq!(second.get("bench_a"), first.get("bench_a"));
+    assert!(second.get("bench_b").is_some());
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn retry_failed_tests() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+    static FAILING_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    fn flaky() -> Result<(), String> {
+        match FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) {
+            0 => Err("failed on the first run".to_string()),
+            _ => Ok(()),
+        }
+    }
+    fn fails() -> Result<(), String> {
+        FAILING_RUNS.fetch_add(1, Ordering::SeqCst);
+        Err("always fails".to_string())
+    }
+
+    for test_threads in [1, 2] {
+        FLAKY_RUNS.store(0, Ordering::SeqCst);
+        FAILING_RUNS.store(0, Ordering::SeqCst);
+
+        let tests = vec![
+            named_test(StaticTestName("fails"), StaticTestFn(fails)),
+            named_test(StaticTestName("flaky"), StaticTestFn(flaky)),
+        ];
+        let (tx, rx) = channel();
+        let notify = move |event: TestEvent| {
+            if let TestEvent::TeResult(result) = event {
+                tx.send(result).unwrap();
+            }
+            Ok(())
+        };
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            retries: 2,
+            ..TestOpts::new()
+        };
+        run_tests(&opts, tests, notify).unwrap();
+        let mut results = rx.iter().collect::<Vec<_>>();
+        results.sort_by_key(|result| result.desc.name.to_string());
+
+        // Each test is reported once, after its last attempt.
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].result, TrFailed);
+        assert_eq!(FAILING_RUNS.load(Ordering::SeqCst), 3);
+        assert_eq!(results[1].result, TrFlaky(2));
+        assert_eq!(FLAKY_RUNS.load(Ordering::SeqCst), 2);
+
+        let stdout = String::from_utf8_lossy(&results[1].stdout);
+        assert!(stdout.contains("---- attempt 1 ----"));
+        assert!(stdout.contains("---- attempt 2 ----"));
+    }
+}
+
+#[test]
+fn retry_notes_dynamic_tests() {
+    for test_threads in [1, 2] {
+        let tests = (0..2)
+            .map(|i| {
+                let testfn = || Err("always fails".to_string());
+                named_test(DynTestName(format!("dynamic_{i}")), DynTestFn(Box::new(testfn)))
+            })
+            .collect();
+        let (tx, rx) = channel();
+        let notify = move |event: TestEvent| {
+            if let TestEvent::TeResult(result) = event {
+                tx.send(result).unwrap();
+            }
+            Ok(())
+        };
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            retries: 2,
+            ..TestOpts::new()
+        };
+        run_tests(&opts, tests, notify).unwrap();
+        let results = rx.iter().collect::<Vec<_>>();
+
+        // Dynamic tests can't run again, which only the first failure notes.
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|result| result.result == TrFailed));
+        let noted = results
+            .iter()
+            .filter(|result| String::from_utf8_lossy(&result.stdout).contains("not retried"))
+            .count();
+        assert_eq!(noted, 1);
+    }
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = |args: &[&str]| {
+        let mut v = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        v.extend(args.iter().map(|s| s.to_string()));
+        v
+    };
+    let opts = parse_opts(&args(&["--shard-index=2", "--shard-count=8"])).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(TestShard { index: 2, count: 8 }));
+
+    assert!(parse_opts(&args(&["--shard-index=1"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-count=4"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index=4", "--shard-count=4"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index=0", "--shard-count=0"])).unwrap().is_err());
+}
+
+#[test]
+fn filter_shards() {
+    fn tests(count: usize) -> Vec<TestDescAndFn> {
+        (0..count)
+            .map(|i| {
+                named_test(
+                    DynTestName(format!("module::test_{i}")),
+                    DynTestFn(Box::new(move || Ok(()))),
+                )
+            })
+            .collect()
+    }
+    fn shard(index: usize, test_count: usize) -> Vec<String> {
+        let opts = TestOpts { shard: Some(TestShard { index, count: 4 }), ..TestOpts::new() };
+        filter_tests(&opts, tests(test_count)).iter().map(|t| t.desc.name.to_string()).collect()
+    }
+
+    // Every test runs in exactly one shard.
+    let shards: Vec<_> = (0..4).map(|index| shard(index, 100)).collect();
+    let mut all: Vec<_> = shards.concat();
+    all.sort();
+    all.dedup();
+    assert_eq!(all.len(), 100);
+    assert!(shards.iter().all(|shard| !shard.is_empty()));
+
+    // Adding tests doesn't move the existing ones to another shard.
+    for (index, tests) in shards.iter().enumerate() {
+        let grown = shard(index, 150);
+        assert!(tests.iter().all(|test| grown.contains(test)));
+    }
+}
+
+#[test]
+fn parse_failed_tests_options() {
+    let dir = tmpdir();
+    let path = dir.join("failed");
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--last-failed".to_string(),
+        format!("--failed-tests-file={}", path.display()),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert!(opts.last_failed);
+    assert!(!opts.failed_first);
+    // The record is only read when it is used, not while parsing the options.
+    fs::write(&path, "# comment\nmodule::test_1\n").unwrap();
+    let failed_tests = opts.failed_tests.unwrap();
+    assert_eq!(failed_tests.path(), path);
+    assert!(failed_tests.contains("module::test_1"));
+    assert!(!failed_tests.contains("# comment"));
+
+    // A missing record means that no tests failed.
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        format!("--failed-tests-file={}", dir.join("missing").display()),
+    ];
+    assert!(parse_opts(&args).unwrap().unwrap().failed_tests.unwrap().is_empty());
+
+    let args = vec!["progname".to_string(), "--failed-first".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+fn run_last_failed_tests() {
+    use crate::helpers::failed_tests::FailedTests;
+
+    fn tests() -> Vec<TestDescAndFn> {
+        (0..4)
+            .map(|i| {
+                let testfn = move || if i == 2 { Err("failed".to_string()) } else { Ok(()) };
+                named_test(DynTestName(format!("module::test_{i}")), DynTestFn(Box::new(testfn)))
+            })
+            .collect()
+    }
+    fn run(opts: &TestOpts) -> (Vec<String>, FailedTests) {
+        let mut failed_tests = opts.failed_tests.clone().unwrap();
+        let mut names = Vec::new();
+        run_tests(opts, tests(), |event| {
+            if let TestEvent::TeResult(test) = event {
+                names.push(test.desc.name.to_string());
+                failed_tests.record(test.desc.name.as_slice(), &test.result);
+            }
+            Ok(())
+        })
+        .unwrap();
+        (names, failed_tests)
+    }
+
+    let dir = tmpdir();
+    let path = dir.join("libtest-failed/tests");
+    let opts = |failed_first, last_failed| TestOpts {
+        run_tests: true,
+        test_threads: Some(1),
+        failed_tests: Some(FailedTests::new(path.clone())),
+        failed_first,
+        last_failed,
+        ..TestOpts::new()
+    };
+
+    // Nothing failed yet, so all tests run in their usual order.
+    let (names, failed_tests) = run(&opts(true, true));
+    assert_eq!(names, ["module::test_0", "module::test_1", "module::test_2", "module::test_3"]);
+    assert!(failed_tests.contains("module::test_2"));
+    failed_tests.save().unwrap();
+
+    let (names, _) = run(&opts(true, false));
+    assert_eq!(names, ["module::test_2", "module::test_0", "module::test_1", "module::test_3"]);
+    let (names, _) = run(&opts(false, true));
+    assert_eq!(names, ["module::test_2"]);
+
+    // Tests that didn't run keep their state, and tests that pass again are removed.
+    let mut failed_tests = FailedTests::new(path.clone());
+    failed_tests.record("module::test_2", &TrOk);
+    failed_tests.record("module::test_3", &TrFailed);
+    failed_tests.record("module::test_0", &TrIgnored);
+    failed_tests.save().unwrap();
+    let failed_tests = FailedTests::new(path.clone());
+    assert!(!failed_tests.contains("module::test_2"));
+    assert!(failed_tests.contains("module::test_3"));
+    assert!(!failed_tests.contains("module::test_0"));
+}
+
 #[test]
 pub fn filter_for_ignored_option() {
     // When we run ignored tests the test filter should filter out all the
@@ -588,6 +1058,8 @@ pub fn exclude_should_panic_option() {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(move || Ok(()))),
     });
@@ -617,6 +1089,8 @@ fn tests() -> Vec<TestDescAndFn> {
                     compile_fail: false,
                     no_run: false,
                     test_type: TestType::Unknown,
+                    timeout_secs: None,
+                    isolate: false,
                 },
                 testfn: DynTestFn(Box::new(move || Ok(()))),
             })
@@ -713,6 +1187,8 @@ fn testfn() -> Result<(), String> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout_secs: None,
+                isolate: false,
             },
             testfn: DynTestFn(Box::new(testfn)),
         };
@@ -847,6 +1323,8 @@ fn f(_: &mut Bencher) -> Result<(), String> {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout_secs: None,
+        isolate: false,
     };
 
     crate::bench::benchmark(TestId(0), desc, tx, true, f);
@@ -875,6 +1353,8 @@ fn f(b: &mut Bencher) -> Result<(), String> {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout_secs: None,
+        isolate: false,
     };
 
     crate::bench::benchmark(TestId(0), desc, tx, true, f);
@@ -896,6 +1376,8 @@ fn should_sort_failures_before_printing_them() {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout_secs: None,
+        isolate: false,
     };
 
     let test_b = TestDesc {
@@ -911,6 +1393,8 @@ fn should_sort_failures_before_printing_them() {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout_secs: None,
+        isolate: false,
     };
 
     let mut out = PrettyFormatter::new(OutputLocation::Raw(Vec::new()), false, 10, false, None);
@@ -923,6 +1407,8 @@ fn should_sort_failures_before_printing_them() {
         ignored: 0,
         filtered_out: 0,
         measured: 0,
+        timed_out: 0,
+        flaky: 0,
         exec_time: None,
         metrics: MetricMap::new(),
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
@@ -930,6 +1416,7 @@ fn should_sort_failures_before_printing_them() {
         not_failures: Vec::new(),
         ignores: Vec::new(),
         time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
@@ -943,6 +1430,129 @@ fn should_sort_failures_before_printing_them() {
     assert!(apos < bpos);
 }
 
+/// Reports a passing, a panicking, an ignored and a failing test to `out`, as the console does.
+fn write_formatter_test_results(out: &mut dyn OutputFormatter) -> bool {
+    let desc =
+        |name| TestDesc { name: StaticTestName(name), ..typed_test_desc(TestType::UnitTest) };
+    let panics = TestDesc {
+        source_file: "src/lib.rs",
+        start_line: 10,
+        start_col: 4,
+        ..desc("module::panics")
+    };
+    let ignored = TestDesc { ignore: true, ignore_message: Some("needs #net"), ..desc("ignored") };
+    let fails =
+        TestDesc { source_file: "src/lib.rs", start_line: 20, start_col: 4, ..desc("fails") };
+    let panic_output = [
+        "thread 'module::panics' panicked at src/lib.rs:12:5:",
+        "x is not 1",
+        "note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace",
+        "",
+    ];
+    let results = [
+        (desc("passes"), TrOk, Vec::new()),
+        (panics, TrFailed, panic_output.join("\n").into_bytes()),
+        (ignored, TrIgnored, Vec::new()),
+        (fails, TrFailedMsg("expected \"a\"\ngot b".to_string()), Vec::new()),
+    ];
+
+    let mut st = console::ConsoleTestState::new(&TestOpts::new()).unwrap();
+    st.passed = 1;
+    st.failed = 2;
+    st.ignored = 1;
+    out.write_run_start(results.len(), None).unwrap();
+    for (desc, result, stdout) in &results {
+        out.write_result(desc, result, None, stdout, &st).unwrap();
+    }
+    out.write_run_finish(&st).unwrap()
+}
+
+#[test]
+fn tap_formatter_output() {
+    let mut out = TapFormatter::new(OutputLocation::Raw(Vec::new()));
+    assert!(!write_formatter_test_results(&mut out));
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+
+    let expected_lines = vec![
+        "TAP version 14",
+        "ok 1 - passes",
+        "not ok 2 - module::panics",
+        "  ---",
+        "  severity: fail",
+        "  output: |",
+        "    thread 'module::panics' panicked at src/lib.rs:12:5:",
+        "    x is not 1",
+        "    note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace",
+        "  ...",
+        r"ok 3 - ignored # SKIP needs \#net",
+        "not ok 4 - fails",
+        "  ---",
+        r#"  message: "expected \"a\"\ngot b""#,
+        "  severity: fail",
+        "  ...",
+        "1..4",
+        "# test result: FAILED. 1 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out",
+    ];
+    assert_eq!(s.lines().collect::<Vec<_>>(), expected_lines);
+}
+
+#[test]
+fn summary_counts_timed_out_tests() {
+    use crate::formatters::JsonFormatter;
+
+    let mut st = console::ConsoleTestState::new(&TestOpts::new()).unwrap();
+    st.passed = 1;
+    st.failed = 2;
+    st.timed_out = 1;
+
+    let mut out = PrettyFormatter::new(OutputLocation::Raw(Vec::new()), false, 0, false, None);
+    assert!(!out.write_run_finish(&st).unwrap());
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    assert!(s.contains("test result: FAILED. 1 passed; 2 failed (1 timed out); 0 ignored;"));
+
+    let mut out = JsonFormatter::new(OutputLocation::Raw(Vec::new()));
+    assert!(!out.write_run_finish(&st).unwrap());
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    assert!(s.contains(r#""filtered_out": 0, "timed_out": 1 }"#));
+}
+
+#[test]
+fn github_actions_formatter_output() {
+    let mut out = GithubActionsFormatter::new(OutputLocation::Raw(Vec::new()));
+    assert!(!write_formatter_test_results(&mut out));
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+
+    // The annotation of a panicking test points to where it panicked, not to the test itself.
+    let expected_lines = vec![
+        "running 4 tests",
+        "test passes ... ok",
+        "test module::panics ... FAILED",
+        "::group::module::panics stdout",
+        "thread 'module::panics' panicked at src/lib.rs:12:5:",
+        "x is not 1",
+        "note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace",
+        "::endgroup::",
+        "::error file=src/lib.rs,line=12,col=5,title=test module%3A%3Apanics::x is not 1",
+        "test ignored ... ignored",
+        "test fails ... FAILED",
+        r#"::error file=src/lib.rs,line=20,col=4,title=test fails::expected "a"%0Agot b"#,
+        "test result: FAILED. 1 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out",
+    ];
+    assert_eq!(s.lines().collect::<Vec<_>>(), expected_lines);
+}
+
 #[test]
 #[cfg(not(target_os = "emscripten"))]
 fn test_dyn_bench_returning_err_fails_when_run_as_test() {
@@ -963,6 +1573,8 @@ fn f(_: &mut Bencher) -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynBenchFn(Box::new(f)),
     };
@@ -994,6 +1606,8 @@ fn test_output_postprocessing() {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout_secs: None,
+            isolate: false,
         },
         testfn: DynTestFn(Box::new(move || Ok(()))),
     };
@@ -2,6 +2,7 @@
 //! execution.
 //! The purposes of this module:
 //! - Check whether test is timed out.
+//! - Determine the hard time limit of a test.
 //! - Provide helpers for `report-time` and `measure-time` options.
 //! - Provide newtypes for executions times.
 
@@ -61,6 +62,12 @@ pub fn get_default_test_timeout() -> Instant {
     Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S)
 }
 
+/// Returns the hard time limit of the test, after which it is reported as timed out.
+/// A limit set with `#[test_timeout]` takes precedence over the `--test-timeout` one.
+pub fn get_test_timeout(desc: &TestDesc, default: Option<Duration>) -> Option<Duration> {
+    desc.timeout_secs.map(Duration::from_secs).or(default)
+}
+
 /// The measured execution time of a unit test.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TestExecTime(pub Duration);
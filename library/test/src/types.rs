@@ -103,6 +103,17 @@ pub fn padding(&self) -> NamePadding {
         }
     }
 
+    /// Returns a copy of a static test function, e.g. to run a failed test again.
+    /// Dynamic test functions can only be run once, so they can't be copied.
+    pub(crate) fn try_clone(&self) -> Option<TestFn> {
+        match *self {
+            StaticTestFn(f) => Some(StaticTestFn(f)),
+            StaticBenchFn(f) => Some(StaticBenchFn(f)),
+            StaticBenchAsTestFn(f) => Some(StaticBenchAsTestFn(f)),
+            DynTestFn(..) | DynBenchFn(..) | DynBenchAsTestFn(..) => None,
+        }
+    }
+
     pub(crate) fn into_runnable(self) -> Runnable {
         match self {
             StaticTestFn(f) => Runnable::Test(RunnableTest::Static(f)),
@@ -208,6 +219,8 @@ pub struct TestDesc {
     pub compile_fail: bool,
     pub no_run: bool,
     pub test_type: TestType,
+    pub timeout_secs: Option<u64>,
+    pub isolate: bool,
 }
 
 impl TestDesc {
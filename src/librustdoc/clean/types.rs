@@ -164,6 +164,7 @@ pub(crate) fn location(
         &self,
         extern_url: Option<&str>,
         extern_url_takes_precedence: bool,
+        assume_local: bool,
         dst: &std::path::Path,
         tcx: TyCtxt<'_>,
     ) -> ExternalLocation {
@@ -199,7 +200,9 @@ fn to_remote(url: impl ToString) -> ExternalLocation {
             .map(to_remote)
             .next()
             .or_else(|| extern_url.map(to_remote)) // NOTE: only matters if `extern_url_takes_precedence` is false
-            .unwrap_or(Unknown) // Well, at least we tried.
+            // With `--assume-local-extern-docs`, the documentation will be merged into the local
+            // directory later.
+            .unwrap_or(if assume_local { Local } else { Unknown }) // Well, at least we tried.
     }
 
     pub(crate) fn keywords(&self, tcx: TyCtxt<'_>) -> ThinVec<(DefId, Symbol)> {
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use rustc_data_structures::fx::FxHashMap;
@@ -35,6 +35,9 @@ pub(crate) enum OutputFormat {
     Json,
     #[default]
     Html,
+    Markdown,
+    /// Only used with `--show-coverage`.
+    Lcov,
 }
 
 impl OutputFormat {
@@ -50,6 +53,8 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
+            "markdown" => Ok(OutputFormat::Markdown),
+            "lcov" => Ok(OutputFormat::Lcov),
             _ => Err(format!("unknown output format `{value}`")),
         }
     }
@@ -125,6 +130,11 @@ pub(crate) struct Options {
     pub(crate) enable_per_target_ignores: bool,
     /// Do not run doctests, compile them if should_test is active.
     pub(crate) no_run: bool,
+    /// Whether to compile the doctests that allow it together, as the tests of a single harness,
+    /// instead of compiling each doctest into its own executable.
+    pub(crate) merge_doctests: bool,
+    /// Whether to check the standard output of the doctests followed by an `output` block.
+    pub(crate) check_doctest_output: bool,
 
     /// The path to a rustc-like binary to build tests with. If not set, we
     /// default to loading from `$sysroot/bin/rustc`.
@@ -204,6 +214,8 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("enable-per-target-ignores", &self.enable_per_target_ignores)
             .field("run_check", &self.run_check)
             .field("no_run", &self.no_run)
+            .field("merge_doctests", &self.merge_doctests)
+            .field("check_doctest_output", &self.check_doctest_output)
             .field("nocapture", &self.nocapture)
             .field("scrape_examples_options", &self.scrape_examples_options)
             .field("unstable_features", &self.unstable_features)
@@ -237,6 +249,9 @@ pub(crate) struct RenderOptions {
     pub(crate) extern_html_root_urls: BTreeMap<String, String>,
     /// Whether to give precedence to `html_root_url` or `--extern-html-root-url`.
     pub(crate) extern_html_root_takes_precedence: bool,
+    /// Whether to link to crates whose documentation location is unknown as if it was in the
+    /// output directory, because it will be merged there with `--merge-doc-dir`.
+    pub(crate) assume_local_extern_docs: bool,
     /// A map of the default settings (values are as for DOM storage API). Keys should lack the
     /// `rustdoc-` prefix.
     pub(crate) default_settings: FxHashMap<String, String>,
@@ -281,6 +296,14 @@ pub(crate) struct RenderOptions {
     pub(crate) no_emit_shared: bool,
     /// If `true`, HTML source code pages won't be generated.
     pub(crate) html_no_source: bool,
+    /// If `true`, the words of the documentation are added to the search index.
+    pub(crate) full_text_search: bool,
+    /// With `--show-coverage`, whether to list each item that was counted.
+    pub(crate) coverage_items: bool,
+    /// With `--show-coverage`, the minimum percentage of documented items.
+    pub(crate) min_coverage: Option<f64>,
+    /// With `--show-coverage`, the minimum percentage of items with examples.
+    pub(crate) min_example_coverage: Option<f64>,
     /// Whether `-Zforce-unstable-if-unmarked` unstable option is set
     pub(crate) force_unstable_if_unmarked: bool,
 }
@@ -444,6 +467,55 @@ fn println_condition(condition: Condition) {
             return None;
         }
 
+        if let Some(old) = matches.opt_str("api-diff") {
+            let new = match &matches.free[..] {
+                [new] => new,
+                [] => dcx.fatal("missing file operand"),
+                _ => dcx.fatal("too many file operands"),
+            };
+            let json = match matches.opt_str("output-format").as_deref() {
+                None => false,
+                Some("json") => true,
+                Some(s) => {
+                    dcx.fatal(format!("{s} output format isn't supported for the --api-diff option"))
+                }
+            };
+            if let Err(e) = crate::json::diff::run(Path::new(&old), Path::new(new), json) {
+                dcx.fatal(e);
+            }
+            return None;
+        }
+
+        let out_dir = matches.opt_str("out-dir").map(|s| PathBuf::from(&s));
+        let output = matches.opt_str("output").map(|s| PathBuf::from(&s));
+        let output = match (out_dir, output) {
+            (Some(_), Some(_)) => {
+                dcx.fatal("cannot use both 'out-dir' and 'output' at once");
+            }
+            (Some(out_dir), None) => out_dir,
+            (None, Some(output)) => output,
+            (None, None) => PathBuf::from("doc"),
+        };
+
+        let merge_doc_dirs = matches.opt_strs("merge-doc-dir");
+        if !merge_doc_dirs.is_empty() {
+            if !matches.free.is_empty() {
+                dcx.fatal("--merge-doc-dir doesn't take any file operand");
+            }
+            let inputs = merge_doc_dirs.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+            let resource_suffix = matches.opt_str("resource-suffix").unwrap_or_default();
+            let enable_index_page = matches.opt_present("enable-index-page");
+            if let Err(e) = crate::html::render::merge_doc_dirs(
+                &inputs,
+                &output,
+                &resource_suffix,
+                enable_index_page,
+            ) {
+                dcx.fatal(e.to_string());
+            }
+            return None;
+        }
+
         let (lint_opts, describe_lints, lint_cap) = get_cmd_lint_options(early_dcx, matches);
 
         let input = PathBuf::from(if describe_lints {
@@ -516,22 +588,13 @@ fn println_condition(condition: Condition) {
 
         let should_test = matches.opt_present("test");
         let no_run = matches.opt_present("no-run");
+        let merge_doctests = matches.opt_present("merge-doctests");
+        let check_doctest_output = matches.opt_present("check-doctest-output");
 
         if !should_test && no_run {
             dcx.fatal("the `--test` flag must be passed to enable `--no-run`");
         }
 
-        let out_dir = matches.opt_str("out-dir").map(|s| PathBuf::from(&s));
-        let output = matches.opt_str("output").map(|s| PathBuf::from(&s));
-        let output = match (out_dir, output) {
-            (Some(_), Some(_)) => {
-                dcx.fatal("cannot use both 'out-dir' and 'output' at once");
-            }
-            (Some(out_dir), None) => out_dir,
-            (None, Some(output)) => output,
-            (None, None) => PathBuf::from("doc"),
-        };
-
         let cfgs = matches.opt_strs("cfg");
         let check_cfgs = matches.opt_strs("check-cfg");
 
@@ -621,6 +684,14 @@ fn println_condition(condition: Condition) {
         let target = parse_target_triple(early_dcx, matches);
 
         let show_coverage = matches.opt_present("show-coverage");
+        for name in ["coverage-items", "min-coverage", "min-example-coverage"] {
+            if matches.opt_present(name) && !show_coverage {
+                dcx.fatal(format!("--{name} can only be used with --show-coverage"));
+            }
+        }
+        let coverage_items = matches.opt_present("coverage-items");
+        let min_coverage = parse_percentage(matches, "min-coverage", &dcx);
+        let min_example_coverage = parse_percentage(matches, "min-example-coverage", &dcx);
 
         let crate_types = match parse_crate_types_from_list(matches.opt_strs("crate-type")) {
             Ok(types) => types,
@@ -632,10 +703,14 @@ fn println_condition(condition: Condition) {
         let output_format = match matches.opt_str("output-format") {
             Some(s) => match OutputFormat::try_from(s.as_str()) {
                 Ok(out_fmt) => {
-                    if !out_fmt.is_json() && show_coverage {
-                        dcx.fatal(
-                            "html output format isn't supported for the --show-coverage option",
-                        );
+                    if !matches!(out_fmt, OutputFormat::Json | OutputFormat::Lcov) && show_coverage
+                    {
+                        dcx.fatal(format!(
+                            "{s} output format isn't supported for the --show-coverage option"
+                        ));
+                    }
+                    if out_fmt == OutputFormat::Lcov && !show_coverage {
+                        dcx.fatal("lcov output format can only be used with --show-coverage");
                     }
                     out_fmt
                 }
@@ -679,7 +754,9 @@ fn println_condition(condition: Condition) {
         let generate_link_to_definition = matches.opt_present("generate-link-to-definition");
         let extern_html_root_takes_precedence =
             matches.opt_present("extern-html-root-takes-precedence");
+        let assume_local_extern_docs = matches.opt_present("assume-local-extern-docs");
         let html_no_source = matches.opt_present("html-no-source");
+        let full_text_search = matches.opt_present("full-text-search");
 
         if generate_link_to_definition && (show_coverage || output_format != OutputFormat::Html) {
             dcx.fatal(
@@ -727,6 +804,8 @@ fn println_condition(condition: Condition) {
             test_builder,
             run_check,
             no_run,
+            merge_doctests,
+            check_doctest_output,
             nocapture,
             crate_name,
             output_format,
@@ -745,6 +824,7 @@ fn println_condition(condition: Condition) {
             extension_css,
             extern_html_root_urls,
             extern_html_root_takes_precedence,
+            assume_local_extern_docs,
             default_settings,
             resource_suffix,
             enable_index_page,
@@ -763,6 +843,10 @@ fn println_condition(condition: Condition) {
             call_locations,
             no_emit_shared: false,
             html_no_source,
+            full_text_search,
+            coverage_items,
+            min_coverage,
+            min_example_coverage,
             force_unstable_if_unmarked,
         };
         Some((options, render_options))
@@ -774,6 +858,19 @@ pub(crate) fn markdown_input(&self) -> bool {
     }
 }
 
+/// Parses the value of the `name` option, a percentage.
+fn parse_percentage(
+    matches: &getopts::Matches,
+    name: &str,
+    dcx: &rustc_errors::DiagCtxt,
+) -> Option<f64> {
+    let s = matches.opt_str(name)?;
+    match s.parse::<f64>() {
+        Ok(percentage) if (0.0..=100.0).contains(&percentage) => Some(percentage),
+        _ => dcx.fatal(format!("--{name} expects a percentage between 0 and 100, found `{s}`")),
+    }
+}
+
 /// Prints deprecation warnings for deprecated options
 fn check_deprecated_options(matches: &getopts::Matches, dcx: &rustc_errors::DiagCtxt) {
     let deprecated_flags = [];
@@ -25,7 +25,7 @@
 use std::env;
 use std::io::{self, Write};
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
 use std::str;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -36,6 +36,8 @@
 use crate::html::markdown::{self, ErrorCodes, Ignore, LangString};
 use crate::lint::init_lints;
 
+mod merged;
+
 /// Options that apply to all doctests in a crate or Markdown file (for `rustdoc foo.md`).
 #[derive(Clone, Default)]
 pub(crate) struct GlobalTestOptions {
@@ -268,6 +270,8 @@ enum TestFailure {
     ExecutionFailure(process::Output),
     /// The test is marked `should_panic` but the test binary executed successfully.
     UnexpectedRunPass,
+    /// The test binary printed something else than the `output` block following the test.
+    UnexpectedOutput { expected: String, found: String },
 }
 
 enum DirState {
@@ -306,30 +310,15 @@ fn add_exe_suffix(input: String, target: &TargetTriple) -> String {
     input + &exe_suffix
 }
 
-fn run_test(
-    test: &str,
-    crate_name: &str,
-    line: usize,
-    rustdoc_options: RustdocOptions,
-    mut lang_string: LangString,
-    no_run: bool,
-    runtool: Option<String>,
-    runtool_args: Vec<String>,
-    target: TargetTriple,
-    opts: &GlobalTestOptions,
+/// Creates the command compiling a doctest (or several, see [`merged`]) into `output_file`,
+/// with the options given to rustdoc. The caller adds the input.
+fn rustc_command(
+    rustdoc_options: &RustdocOptions,
+    target: &TargetTriple,
     edition: Edition,
-    outdir: DirState,
-    path: PathBuf,
-    test_id: &str,
-    report_unused_externs: impl Fn(UnusedExterns),
-) -> Result<(), TestFailure> {
-    let (test, line_offset, supports_color) =
-        make_test(test, Some(crate_name), lang_string.test_harness, opts, edition, Some(test_id));
-
-    // Make sure we emit well-formed executable names for our target.
-    let rust_out = add_exe_suffix("rust_out".to_owned(), &target);
-    let output_file = outdir.path().join(rust_out);
-
+    output_file: &Path,
+    supports_color: bool,
+) -> Command {
     let rustc_binary = rustdoc_options
         .test_builder
         .as_deref()
@@ -345,22 +334,11 @@ fn run_test(
             compiler.arg("--check-cfg").arg(&check_cfg);
         }
     }
-    if let Some(sysroot) = rustdoc_options.maybe_sysroot {
+    if let Some(sysroot) = &rustdoc_options.maybe_sysroot {
         compiler.arg("--sysroot").arg(sysroot);
     }
     compiler.arg("--edition").arg(&edition.to_string());
-    compiler.env("UNSTABLE_RUSTDOC_TEST_PATH", path);
-    compiler.env("UNSTABLE_RUSTDOC_TEST_LINE", format!("{}", line as isize - line_offset as isize));
-    compiler.arg("-o").arg(&output_file);
-    if lang_string.test_harness {
-        compiler.arg("--test");
-    }
-    if rustdoc_options.json_unused_externs.is_enabled() && !lang_string.compile_fail {
-        compiler.arg("--error-format=json");
-        compiler.arg("--json").arg("unused-externs");
-        compiler.arg("-Z").arg("unstable-options");
-        compiler.arg("-W").arg("unused_crate_dependencies");
-    }
+    compiler.arg("-o").arg(output_file);
     for lib_str in &rustdoc_options.lib_strs {
         compiler.arg("-L").arg(&lib_str);
     }
@@ -374,11 +352,8 @@ fn run_test(
     for unstable_option_str in &rustdoc_options.unstable_opts_strs {
         compiler.arg("-Z").arg(&unstable_option_str);
     }
-    if no_run && !lang_string.compile_fail && rustdoc_options.persist_doctests.is_none() {
-        compiler.arg("--emit=metadata");
-    }
     compiler.arg("--target").arg(match target {
-        TargetTriple::TargetTriple(s) => s,
+        TargetTriple::TargetTriple(s) => s.clone(),
         TargetTriple::TargetJson { path_for_rustdoc, .. } => {
             path_for_rustdoc.to_str().expect("target path must be valid unicode").to_string()
         }
@@ -402,6 +377,49 @@ fn run_test(
             }
         }
     }
+    compiler
+}
+
+fn run_test(
+    test: &str,
+    crate_name: &str,
+    line: usize,
+    rustdoc_options: RustdocOptions,
+    mut lang_string: LangString,
+    no_run: bool,
+    runtool: Option<String>,
+    runtool_args: Vec<String>,
+    target: TargetTriple,
+    opts: &GlobalTestOptions,
+    edition: Edition,
+    outdir: DirState,
+    path: PathBuf,
+    test_id: &str,
+    report_unused_externs: impl Fn(UnusedExterns),
+) -> Result<(), TestFailure> {
+    let (test, line_offset, supports_color) =
+        make_test(test, Some(crate_name), lang_string.test_harness, opts, edition, Some(test_id));
+
+    // Make sure we emit well-formed executable names for our target.
+    let rust_out = add_exe_suffix("rust_out".to_owned(), &target);
+    let output_file = outdir.path().join(rust_out);
+
+    let mut compiler =
+        rustc_command(&rustdoc_options, &target, edition, &output_file, supports_color);
+    compiler.env("UNSTABLE_RUSTDOC_TEST_PATH", path);
+    compiler.env("UNSTABLE_RUSTDOC_TEST_LINE", format!("{}", line as isize - line_offset as isize));
+    if lang_string.test_harness {
+        compiler.arg("--test");
+    }
+    if rustdoc_options.json_unused_externs.is_enabled() && !lang_string.compile_fail {
+        compiler.arg("--error-format=json");
+        compiler.arg("--json").arg("unused-externs");
+        compiler.arg("-Z").arg("unstable-options");
+        compiler.arg("-W").arg("unused_crate_dependencies");
+    }
+    if no_run && !lang_string.compile_fail && rustdoc_options.persist_doctests.is_none() {
+        compiler.arg("--emit=metadata");
+    }
 
     compiler.arg("-");
     compiler.stdin(Stdio::piped());
@@ -502,12 +520,28 @@ fn drop(&mut self) {
             } else if !lang_string.should_panic && !out.status.success() {
                 return Err(TestFailure::ExecutionFailure(out));
             }
+            // The output isn't captured with `--nocapture`.
+            if let Some(expected) = lang_string.expected_output.take()
+                && !rustdoc_options.nocapture
+            {
+                let found = String::from_utf8_lossy(&out.stdout);
+                if normalize_output(&expected) != normalize_output(&found) {
+                    let found = found.into_owned();
+                    return Err(TestFailure::UnexpectedOutput { expected, found });
+                }
+            }
         }
     }
 
     Ok(())
 }
 
+/// Normalizes the output of a doctest before comparing it with its expected output, ignoring
+/// trailing whitespace.
+fn normalize_output(output: &str) -> String {
+    output.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim_end().to_owned()
+}
+
 /// Converts a path intended to use as a command to absolute if it is
 /// relative, and not a single component.
 ///
@@ -929,6 +963,8 @@ pub(crate) struct Collector {
     visited_tests: FxHashMap<(String, usize), usize>,
     unused_extern_reports: Arc<Mutex<Vec<UnusedExterns>>>,
     compiling_test_count: AtomicUsize,
+    /// With `--merge-doctests`, the doctests compiled together, by edition.
+    merged: FxHashMap<Edition, Arc<merged::MergedDoctests>>,
 }
 
 impl Collector {
@@ -955,6 +991,7 @@ pub(crate) fn new(
             visited_tests: FxHashMap::default(),
             unused_extern_reports: Default::default(),
             compiling_test_count: AtomicUsize::new(0),
+            merged: FxHashMap::default(),
         }
     }
 
@@ -991,7 +1028,10 @@ fn get_filename(&self) -> FileName {
 }
 
 impl Tester for Collector {
-    fn add_test(&mut self, test: String, config: LangString, line: usize) {
+    fn add_test(&mut self, test: String, mut config: LangString, line: usize) {
+        if !self.rustdoc_options.check_doctest_output {
+            config.expected_output = None;
+        }
         let filename = self.get_filename();
         let name = self.generate_name(line, &filename);
         let crate_name = self.crate_name.clone();
@@ -1055,6 +1095,27 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
             )
         };
 
+        // The unused externs are reported by each compilation, so merged doctests would hide
+        // the externs that they use.
+        let merged = if self.rustdoc_options.merge_doctests
+            && !self.rustdoc_options.json_unused_externs.is_enabled()
+            && merged::is_mergeable(&test, &config, edition)
+        {
+            let harness = self.merged.entry(edition).or_insert_with(|| {
+                Arc::new(merged::MergedDoctests::new(
+                    crate_name.clone(),
+                    edition,
+                    opts.clone(),
+                    rustdoc_options.clone(),
+                ))
+            });
+            let test_fn = format!("_doctest_{test_id}");
+            harness.add_test(&test_fn, &test, &config, &path, line);
+            Some((Arc::clone(harness), test_fn))
+        } else {
+            None
+        };
+
         debug!("creating test {name}: {test}");
         self.tests.push(test::TestDescAndFn {
             desc: test::TestDesc {
@@ -1075,28 +1136,37 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
                 compile_fail: config.compile_fail,
                 no_run,
                 test_type: test::TestType::DocTest,
+                timeout_secs: None,
+                isolate: false,
             },
             testfn: test::DynTestFn(Box::new(move || {
                 let report_unused_externs = |uext| {
                     unused_externs.lock().unwrap().push(uext);
                 };
-                let res = run_test(
-                    &test,
-                    &crate_name,
-                    line,
-                    rustdoc_options,
-                    config,
-                    no_run,
-                    runtool,
-                    runtool_args,
-                    target,
-                    &opts,
-                    edition,
-                    outdir,
-                    path,
-                    &test_id,
-                    report_unused_externs,
-                );
+                let merged_res = merged.and_then(|(harness, test_fn)| {
+                    harness.run_test(&test_fn, no_run, runtool.clone(), runtool_args.clone())
+                });
+                let res = if let Some(res) = merged_res {
+                    res
+                } else {
+                    run_test(
+                        &test,
+                        &crate_name,
+                        line,
+                        rustdoc_options,
+                        config,
+                        no_run,
+                        runtool,
+                        runtool_args,
+                        target,
+                        &opts,
+                        edition,
+                        outdir,
+                        path,
+                        &test_id,
+                        report_unused_externs,
+                    )
+                };
 
                 if let Err(err) = res {
                     match err {
@@ -1112,6 +1182,12 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
                         TestFailure::MissingErrorCodes(codes) => {
                             eprint!("Some expected error codes were not found: {codes:?}");
                         }
+                        TestFailure::UnexpectedOutput { expected, found } => {
+                            eprintln!("Test executable printed unexpected output.");
+                            eprintln!();
+                            eprintln!("expected:\n{expected}");
+                            eprintln!("found:\n{found}");
+                        }
                         TestFailure::ExecutionError(err) => {
                             eprint!("Couldn't run the test: {err}");
                             if err.kind() == io::ErrorKind::PermissionDenied {
@@ -0,0 +1,320 @@
+//! Doctests compiled together, as the tests of a single harness (`--merge-doctests`).
+//!
+//! Compiling and linking an executable for each doctest takes most of the time spent running
+//! them. When they're merged, each doctest becomes a `#[test]` function of a harness compiled
+//! once per edition, which is then run once for each doctest, with the name of its function as
+//! filter. Doctests that can't be turned into a function (because they need their own crate
+//! attributes, or are expected to fail to compile, for example) are compiled on their own as
+//! before, and so are all the doctests of a harness that fails to compile.
+//!
+//! The locations in the harness found in the output of a doctest, like the one of a panic, are
+//! replaced with the locations in the documentation the doctest comes from.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+use rustc_span::edition::Edition;
+use tempfile::Builder as TempFileBuilder;
+
+use super::{
+    add_exe_suffix, make_maybe_absolute_path, partition_source, rustc_command, DirState,
+    GlobalTestOptions, TestFailure,
+};
+use crate::config::Options as RustdocOptions;
+use crate::html::markdown::{Ignore, LangString};
+
+/// Whether a doctest can be compiled as a function of the merged harness.
+pub(super) fn is_mergeable(test: &str, config: &LangString, edition: Edition) -> bool {
+    // The output of the harness mixes its own messages with the one of the doctest.
+    if config.compile_fail
+        || config.test_harness
+        || config.ignore != Ignore::None
+        || config.expected_output.is_some()
+    {
+        return false;
+    }
+    let (crate_attrs, everything_else, crates) = partition_source(test, edition);
+    // `#[should_panic]` tests must return `()`.
+    let returns_result = everything_else.trim_end().ends_with("(())");
+    crates.trim().is_empty()
+        && !crate_attrs.contains("#![")
+        && !everything_else.contains("fn main")
+        && !(config.should_panic && returns_result)
+}
+
+#[derive(Default)]
+struct Source {
+    /// The test functions, one for each doctest.
+    tests: String,
+    /// The number of lines of `tests`.
+    lines: usize,
+    /// Where the doctests come from, in the order of their functions in `tests`.
+    locations: Vec<DoctestLocation>,
+    /// Whether one of the doctests mentions the documented crate.
+    uses_crate: bool,
+}
+
+/// The lines of `Source::tests` holding a doctest, and where the doctest comes from.
+struct DoctestLocation {
+    /// The first line of the doctest in `Source::tests`, counting from 0.
+    start: usize,
+    len: usize,
+    path: PathBuf,
+    /// The line of `path` the doctest starts at.
+    line: usize,
+}
+
+/// A compiled harness.
+struct Harness {
+    exe: PathBuf,
+    /// The number of lines of the harness before the test functions.
+    prelude_lines: usize,
+}
+
+/// The doctests of one edition that are compiled together.
+pub(super) struct MergedDoctests {
+    crate_name: String,
+    edition: Edition,
+    opts: GlobalTestOptions,
+    rustdoc_options: RustdocOptions,
+    source: Mutex<Source>,
+    /// The compiled harness, or `None` if it failed to compile. The harness is compiled when the
+    /// first of its tests runs, once all the doctests have been added.
+    harness: OnceLock<Option<Harness>>,
+    outdir: DirState,
+    /// The source file of the harness, which the locations in its output refer to.
+    src_file: PathBuf,
+}
+
+impl MergedDoctests {
+    pub(super) fn new(
+        crate_name: String,
+        edition: Edition,
+        opts: GlobalTestOptions,
+        rustdoc_options: RustdocOptions,
+    ) -> MergedDoctests {
+        let outdir = if let Some(mut path) = rustdoc_options.persist_doctests.clone() {
+            path.push(format!("merged_doctests_{edition}"));
+
+            if let Err(err) = std::fs::create_dir_all(&path) {
+                eprintln!("Couldn't create directory for doctest executables: {err}");
+                std::panic::resume_unwind(Box::new(()));
+            }
+
+            DirState::Perm(path)
+        } else {
+            DirState::Temp(
+                TempFileBuilder::new()
+                    .prefix("rustdoctest")
+                    .tempdir()
+                    .expect("rustdoc needs a tempdir"),
+            )
+        };
+        let src_file = outdir.path().join("merged_doctests.rs");
+        MergedDoctests {
+            crate_name,
+            edition,
+            opts,
+            rustdoc_options,
+            source: Mutex::default(),
+            harness: OnceLock::new(),
+            outdir,
+            src_file,
+        }
+    }
+
+    /// Adds a doctest accepted by [`is_mergeable`] to the harness, as the function `test_fn`.
+    /// The doctest comes from the code block starting at `line` of `path`.
+    pub(super) fn add_test(
+        &self,
+        test_fn: &str,
+        test: &str,
+        config: &LangString,
+        path: &Path,
+        line: usize,
+    ) {
+        // A doctest without crate attributes or `extern crate` items is a valid function body
+        // as is, which keeps its lines in the same order as in the documentation.
+        let test = test.trim_end();
+        let mut source = self.source.lock().unwrap();
+        source.uses_crate |= test.contains(self.crate_name.as_str());
+
+        let mut function = String::from("#[test]\n");
+        if config.should_panic {
+            function.push_str("#[should_panic]\n");
+        }
+        let output =
+            if test.ends_with("(())") { " -> Result<(), impl core::fmt::Debug>" } else { "" };
+        function.push_str("#[allow(non_snake_case)]\n");
+        function.push_str(&format!("fn {test_fn}(){output} {{\n"));
+        let start = source.lines + function.lines().count();
+        function.push_str(&format!("{test}\n}}\n"));
+
+        // The code starts on the line after the one of the code block.
+        source.locations.push(DoctestLocation {
+            start,
+            len: test.lines().count(),
+            path: path.to_owned(),
+            line: line + 1,
+        });
+        source.lines += function.lines().count();
+        source.tests.push_str(&function);
+    }
+
+    /// Returns the source of the harness, and the number of lines before the test functions.
+    fn source(&self) -> (String, usize) {
+        let mut prog = String::new();
+        if self.opts.attrs.is_empty() {
+            // See `make_test`.
+            prog.push_str("#![allow(unused)]\n");
+        }
+        for attr in &self.opts.attrs {
+            prog.push_str(&format!("#![{attr}]\n"));
+        }
+
+        let source = self.source.lock().unwrap();
+        if source.uses_crate && !self.opts.no_crate_inject && self.crate_name != "std" {
+            prog.push_str("#[allow(unused_extern_crates)]\n");
+            prog.push_str(&format!("extern crate r#{};\n", self.crate_name));
+        }
+        let prelude_lines = prog.lines().count();
+        prog.push_str(&source.tests);
+        (prog, prelude_lines)
+    }
+
+    fn compile(&self) -> Option<Harness> {
+        let target = &self.rustdoc_options.target;
+        let output_file = self.outdir.path().join(add_exe_suffix("rust_out".to_owned(), target));
+        let (source, prelude_lines) = self.source();
+        debug!("merged doctests:\n{source}");
+
+        // The harness is written to a file rather than piped to rustc, so that it can be looked
+        // at with `--persist-doctests` if it fails to compile.
+        if let Err(err) = fs::write(&self.src_file, &source) {
+            self.report_failure(&format!("couldn't be written: {err}\n"));
+            return None;
+        }
+
+        let mut compiler =
+            rustc_command(&self.rustdoc_options, target, self.edition, &output_file, false);
+        compiler.arg("--test");
+        if self.rustdoc_options.no_run && self.rustdoc_options.persist_doctests.is_none() {
+            compiler.arg("--emit=metadata");
+        }
+        compiler.arg(&self.src_file);
+        compiler.stdout(Stdio::null());
+
+        debug!("compiler invocation for merged doctests: {compiler:?}");
+
+        let output = compiler.output().expect("Failed to spawn rustc process");
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            self.report_failure(&format!("failed to compile:\n{stderr}"));
+            return None;
+        }
+        Some(Harness { exe: output_file, prelude_lines })
+    }
+
+    /// Reports why the harness can't be used, in which case its doctests are compiled on their
+    /// own.
+    fn report_failure(&self, reason: &str) {
+        // Not `eprint!`, which libtest captures and only shows if the doctest fails, while the
+        // doctests usually pass once they're compiled on their own.
+        let _ = write!(
+            io::stderr(),
+            "warning: the merged doctests of edition {} are compiled on their own, as their \
+             harness {reason}",
+            self.edition,
+        );
+    }
+
+    /// Replaces the locations in the harness found in `output` with the locations of the
+    /// doctests they are in.
+    fn map_locations(&self, harness: &Harness, output: Vec<u8>) -> Vec<u8> {
+        let output = match String::from_utf8(output) {
+            Ok(output) => output,
+            Err(err) => return err.into_bytes(),
+        };
+        let prefix = format!("{}:", self.src_file.display());
+        let source = self.source.lock().unwrap();
+        let mut mapped = String::with_capacity(output.len());
+        let mut rest = &output[..];
+        while let Some(pos) = rest.find(&prefix) {
+            mapped.push_str(&rest[..pos]);
+            rest = &rest[pos + prefix.len()..];
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let location = rest[..digits].parse::<usize>().ok().and_then(|line| {
+                // The lines of the harness count from 1.
+                let line = line.checked_sub(harness.prelude_lines + 1)?;
+                let i = source.locations.partition_point(|location| location.start <= line);
+                let location = &source.locations[i.checked_sub(1)?];
+                (line < location.start + location.len).then(|| {
+                    format!("{}:{}", location.path.display(), location.line + line - location.start)
+                })
+            });
+            if let Some(location) = location {
+                mapped.push_str(&location);
+                rest = &rest[digits..];
+            } else {
+                mapped.push_str(&prefix);
+            }
+        }
+        mapped.push_str(rest);
+        mapped.into_bytes()
+    }
+
+    /// Runs the test function `test_fn` of the harness, compiling it first if needed. Returns
+    /// `None` if the harness failed to compile, in which case the doctest must be compiled on its
+    /// own.
+    pub(super) fn run_test(
+        &self,
+        test_fn: &str,
+        no_run: bool,
+        runtool: Option<String>,
+        runtool_args: Vec<String>,
+    ) -> Option<Result<(), TestFailure>> {
+        let harness = self.harness.get_or_init(|| self.compile()).as_ref()?;
+        if no_run {
+            return Some(Ok(()));
+        }
+
+        let mut cmd;
+        let exe = make_maybe_absolute_path(harness.exe.clone());
+        if let Some(tool) = runtool {
+            let tool = make_maybe_absolute_path(tool.into());
+            cmd = Command::new(tool);
+            cmd.args(runtool_args);
+            cmd.arg(exe);
+        } else {
+            cmd = Command::new(exe);
+        }
+        cmd.arg(test_fn).arg("--exact");
+        if let Some(run_directory) = &self.rustdoc_options.test_run_directory {
+            cmd.current_dir(run_directory);
+        }
+
+        let nocapture = self.rustdoc_options.nocapture;
+        if nocapture {
+            cmd.arg("--nocapture");
+        }
+        let result = cmd.output().map(|mut out| {
+            out.stdout = self.map_locations(harness, out.stdout);
+            out.stderr = self.map_locations(harness, out.stderr);
+            if nocapture {
+                // The output is only shown once the doctest is done, as its locations have to
+                // be mapped first.
+                let _ = io::stdout().write_all(&std::mem::take(&mut out.stdout));
+                let _ = io::stderr().write_all(&std::mem::take(&mut out.stderr));
+            }
+            out
+        });
+        Some(match result {
+            Err(e) => Err(TestFailure::ExecutionError(e)),
+            Ok(out) if !out.status.success() => Err(TestFailure::ExecutionFailure(out)),
+            Ok(_) => Ok(()),
+        })
+    }
+}
@@ -1,4 +1,5 @@
-use super::{make_test, GlobalTestOptions};
+use super::{make_test, merged, GlobalTestOptions};
+use crate::html::markdown::{Ignore, LangString};
 use rustc_span::edition::DEFAULT_EDITION;
 
 #[test]
@@ -344,3 +345,25 @@ fn main() {
     let (output, len, _) = make_test(input, None, false, &opts, DEFAULT_EDITION, None);
     assert_eq!((output, len), (expected, 1));
 }
+
+#[test]
+fn merged_doctests_mergeable() {
+    let mergeable =
+        |input: &str, config: LangString| merged::is_mergeable(input, &config, DEFAULT_EDITION);
+    let default = LangString::default;
+
+    assert!(mergeable("assert_eq!(2+2, 4);", default()));
+    assert!(mergeable("use asdf::qwop;\nqwop()?;\nOk::<(), ()>(())", default()));
+    assert!(mergeable("panic!()", LangString { should_panic: true, ..default() }));
+    assert!(mergeable("loop {}", LangString { no_run: true, ..default() }));
+
+    // These doctests need a crate of their own.
+    assert!(!mergeable("#![feature(sanitize)]\nassert!(true);", default()));
+    assert!(!mergeable("extern crate asdf;\nassert!(true);", default()));
+    assert!(!mergeable("fn main() {\n    assert!(true);\n}", default()));
+    assert!(!mergeable("let x: u8 = 1000;", LangString { compile_fail: true, ..default() }));
+    assert!(!mergeable("#[test]\nfn f() {}", LangString { test_harness: true, ..default() }));
+    assert!(!mergeable("assert!(true);", LangString { ignore: Ignore::All, ..default() }));
+    let should_panic = LangString { should_panic: true, ..default() };
+    assert!(!mergeable("Err::<(), ()>(())?;\nOk::<(), ()>(())", should_panic));
+}
@@ -12,7 +12,7 @@
 use crate::formats::Impl;
 use crate::html::format::join_with_double_colon;
 use crate::html::markdown::short_markdown_summary;
-use crate::html::render::search_index::get_function_type_for_search;
+use crate::html::render::search_index::{get_function_type_for_search, prose_terms};
 use crate::html::render::IndexItem;
 use crate::visit_lib::RustdocEffectiveVisibilities;
 
@@ -89,6 +89,9 @@ pub(crate) struct Cache {
     /// Whether to document hidden items.
     /// This is stored in `Cache` so it doesn't need to be passed through all rustdoc functions.
     pub(crate) document_hidden: bool,
+    /// Whether the words of the documentation of items go into the search index, set by
+    /// `--full-text-search`.
+    pub(crate) full_text_search: bool,
 
     /// Crates marked with [`#[doc(masked)]`][doc_masked].
     ///
@@ -140,8 +143,12 @@ struct CacheBuilder<'a, 'tcx> {
 }
 
 impl Cache {
-    pub(crate) fn new(document_private: bool, document_hidden: bool) -> Self {
-        Cache { document_private, document_hidden, ..Cache::default() }
+    pub(crate) fn new(
+        document_private: bool,
+        document_hidden: bool,
+        full_text_search: bool,
+    ) -> Self {
+        Cache { document_private, document_hidden, full_text_search, ..Cache::default() }
     }
 
     /// Populates the `Cache` with more data. The returned `Crate` will be missing some data that was
@@ -162,8 +169,10 @@ pub(crate) fn populate(cx: &mut DocContext<'_>, mut krate: clean::Crate) -> clea
             let render_options = &cx.render_options;
             let extern_url = render_options.extern_html_root_urls.get(name.as_str()).map(|u| &**u);
             let extern_url_takes_precedence = render_options.extern_html_root_takes_precedence;
+            let assume_local = render_options.assume_local_extern_docs;
             let dst = &render_options.output;
-            let location = e.location(extern_url, extern_url_takes_precedence, dst, tcx);
+            let location =
+                e.location(extern_url, extern_url_takes_precedence, assume_local, dst, tcx);
             cx.cache.extern_locations.insert(e.crate_num, location);
             cx.cache.external_paths.insert(e.def_id(), (vec![name], ItemType::Module));
         }
@@ -346,8 +355,13 @@ fn is_from_private_dep(tcx: TyCtxt<'_>, cache: &Cache, def_id: DefId) -> bool {
                         && (ty != ItemType::StructField
                             || u16::from_str_radix(s.as_str(), 10).is_err())
                     {
-                        let desc =
-                            short_markdown_summary(&item.doc_value(), &item.link_names(self.cache));
+                        let doc = item.doc_value();
+                        let desc = short_markdown_summary(&doc, &item.link_names(self.cache));
+                        let prose_terms = if self.cache.full_text_search {
+                            prose_terms(&doc)
+                        } else {
+                            Box::default()
+                        };
                         // In case this is a field from a tuple struct, we don't add it into
                         // the search index because its name is something like "0", which is
                         // not useful for rustdoc search.
@@ -374,6 +388,7 @@ fn is_from_private_dep(tcx: TyCtxt<'_>, cache: &Cache, def_id: DefId) -> bool {
                             ),
                             aliases: item.attrs.get_doc_aliases(),
                             deprecation: item.deprecation(self.tcx),
+                            prose_terms,
                         });
                     }
                 }
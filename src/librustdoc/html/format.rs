@@ -3,9 +3,9 @@
 //! This module contains a large number of `Display` implementations for
 //! various types in `rustdoc::clean`.
 //!
-//! These implementations all emit HTML. As an internal implementation detail,
-//! some of them support an alternate format that emits text, but that should
-//! not be used external to this module.
+//! These implementations all emit HTML. Most of them also support an alternate
+//! format (`{:#}`) that emits text, which is how the Markdown backend prints
+//! declarations; see [`FormatContext`].
 
 use std::borrow::Cow;
 use std::cell::Cell;
@@ -165,9 +165,39 @@ pub(crate) fn comma_sep<T: Display>(
     })
 }
 
+/// What the printers of this module need from the renderer they print for.
+///
+/// Only the HTML renderer links paths and primitives to their pages. Other renderers print with
+/// the alternate format (`{:#}`), which emits no markup and so never asks for an `href`.
+pub(crate) trait FormatContext<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx>;
+    fn cache(&self) -> &Cache;
+    /// The path of the module being rendered, starting with the crate name.
+    fn current(&self) -> &[Symbol];
+    fn href(&self, did: DefId) -> Result<(String, ItemType, Vec<Symbol>), HrefError>;
+}
+
+impl<'tcx> FormatContext<'tcx> for Context<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        Context::tcx(self)
+    }
+
+    fn cache(&self) -> &Cache {
+        Context::cache(self)
+    }
+
+    fn current(&self) -> &[Symbol] {
+        &self.current
+    }
+
+    fn href(&self, did: DefId) -> Result<(String, ItemType, Vec<Symbol>), HrefError> {
+        href(did, self)
+    }
+}
+
 pub(crate) fn print_generic_bounds<'a, 'tcx: 'a>(
     bounds: &'a [clean::GenericBound],
-    cx: &'a Context<'tcx>,
+    cx: &'a impl FormatContext<'tcx>,
 ) -> impl Display + 'a + Captures<'tcx> {
     display_fn(move |f| {
         let mut bounds_dup = FxHashSet::default();
@@ -185,7 +215,7 @@ pub(crate) fn print_generic_bounds<'a, 'tcx: 'a>(
 impl clean::GenericParamDef {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| match &self.kind {
             clean::GenericParamDefKind::Lifetime { outlives } => {
@@ -240,7 +270,7 @@ pub(crate) fn print<'a, 'tcx: 'a>(
 impl clean::Generics {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| {
             let mut real_params = self.params.iter().filter(|p| !p.is_synthetic_param()).peekable();
@@ -268,7 +298,7 @@ pub(crate) enum Ending {
 /// * Whether the where-clause needs to add a comma and newline after the last bound.
 pub(crate) fn print_where_clause<'a, 'tcx: 'a>(
     gens: &'a clean::Generics,
-    cx: &'a Context<'tcx>,
+    cx: &'a impl FormatContext<'tcx>,
     indent: usize,
     ending: Ending,
 ) -> impl Display + 'a + Captures<'tcx> {
@@ -388,7 +418,10 @@ pub(crate) fn print(&self, tcx: TyCtxt<'_>) -> impl Display + '_ {
 }
 
 impl clean::PolyTrait {
-    fn print<'a, 'tcx: 'a>(&'a self, cx: &'a Context<'tcx>) -> impl Display + 'a + Captures<'tcx> {
+    fn print<'a, 'tcx: 'a>(
+        &'a self,
+        cx: &'a impl FormatContext<'tcx>,
+    ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| {
             print_higher_ranked_params_with_space(&self.generic_params, cx).fmt(f)?;
             self.trait_.print(cx).fmt(f)
@@ -399,7 +432,7 @@ fn print<'a, 'tcx: 'a>(&'a self, cx: &'a Context<'tcx>) -> impl Display + 'a + C
 impl clean::GenericBound {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| match self {
             clean::GenericBound::Outlives(lt) => write!(f, "{}", lt.print()),
@@ -418,7 +451,10 @@ pub(crate) fn print<'a, 'tcx: 'a>(
 }
 
 impl clean::GenericArgs {
-    fn print<'a, 'tcx: 'a>(&'a self, cx: &'a Context<'tcx>) -> impl Display + 'a + Captures<'tcx> {
+    fn print<'a, 'tcx: 'a>(
+        &'a self,
+        cx: &'a impl FormatContext<'tcx>,
+    ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| {
             match self {
                 clean::GenericArgs::AngleBracketed { args, bindings } => {
@@ -633,7 +669,7 @@ fn generate_item_def_id_path(
     Ok((format!("{url_parts}#{kind}.{}", tcx.item_name(original_def_id)), shortty, fqp))
 }
 
-fn to_module_fqp(shortty: ItemType, fqp: &[Symbol]) -> &[Symbol] {
+pub(crate) fn to_module_fqp(shortty: ItemType, fqp: &[Symbol]) -> &[Symbol] {
     if shortty == ItemType::Module { fqp } else { &fqp[..fqp.len() - 1] }
 }
 
@@ -821,13 +857,13 @@ pub(crate) fn link_tooltip(did: DefId, fragment: &Option<UrlFragment>, cx: &Cont
 }
 
 /// Used to render a [`clean::Path`].
-fn resolved_path<'cx>(
+fn resolved_path<'tcx>(
     w: &mut fmt::Formatter<'_>,
     did: DefId,
     path: &clean::Path,
     print_all: bool,
     use_absolute: bool,
-    cx: &'cx Context<'_>,
+    cx: &impl FormatContext<'tcx>,
 ) -> fmt::Result {
     let last = path.segments.last().unwrap();
 
@@ -840,7 +876,7 @@ fn resolved_path<'cx>(
         write!(w, "{}{:#}", &last.name, last.args.print(cx))?;
     } else {
         let path = if use_absolute {
-            if let Ok((_, _, fqp)) = href(did, cx) {
+            if let Ok((_, _, fqp)) = cx.href(did) {
                 format!(
                     "{path}::{anchor}",
                     path = join_with_double_colon(&fqp[..fqp.len() - 1]),
@@ -857,28 +893,28 @@ fn resolved_path<'cx>(
     Ok(())
 }
 
-fn primitive_link(
+fn primitive_link<'tcx>(
     f: &mut fmt::Formatter<'_>,
     prim: clean::PrimitiveType,
     name: fmt::Arguments<'_>,
-    cx: &Context<'_>,
+    cx: &impl FormatContext<'tcx>,
 ) -> fmt::Result {
     primitive_link_fragment(f, prim, name, "", cx)
 }
 
-fn primitive_link_fragment(
+fn primitive_link_fragment<'tcx>(
     f: &mut fmt::Formatter<'_>,
     prim: clean::PrimitiveType,
     name: fmt::Arguments<'_>,
     fragment: &str,
-    cx: &Context<'_>,
+    cx: &impl FormatContext<'tcx>,
 ) -> fmt::Result {
     let m = &cx.cache();
     let mut needs_termination = false;
     if !f.alternate() {
         match m.primitive_locations.get(&prim) {
             Some(&def_id) if def_id.is_local() => {
-                let len = cx.current.len();
+                let len = cx.current().len();
                 let path = if len == 0 {
                     let cname_sym = ExternalCrate { crate_num: def_id.krate }.name(cx.tcx());
                     format!("{cname_sym}/")
@@ -905,11 +941,11 @@ fn primitive_link_fragment(
                     }
                     ExternalLocation::Local => {
                         let cname_sym = ExternalCrate { crate_num: def_id.krate }.name(cx.tcx());
-                        Some(if cx.current.first() == Some(&cname_sym) {
-                            iter::repeat(sym::dotdot).take(cx.current.len() - 1).collect()
+                        Some(if cx.current().first() == Some(&cname_sym) {
+                            iter::repeat(sym::dotdot).take(cx.current().len() - 1).collect()
                         } else {
                             iter::repeat(sym::dotdot)
-                                .take(cx.current.len())
+                                .take(cx.current().len())
                                 .chain(iter::once(cname_sym))
                                 .collect()
                         })
@@ -935,7 +971,7 @@ fn primitive_link_fragment(
 fn tybounds<'a, 'tcx: 'a>(
     bounds: &'a [clean::PolyTrait],
     lt: &'a Option<clean::Lifetime>,
-    cx: &'a Context<'tcx>,
+    cx: &'a impl FormatContext<'tcx>,
 ) -> impl Display + 'a + Captures<'tcx> {
     display_fn(move |f| {
         for (i, bound) in bounds.iter().enumerate() {
@@ -955,7 +991,7 @@ fn tybounds<'a, 'tcx: 'a>(
 
 fn print_higher_ranked_params_with_space<'a, 'tcx: 'a>(
     params: &'a [clean::GenericParamDef],
-    cx: &'a Context<'tcx>,
+    cx: &'a impl FormatContext<'tcx>,
 ) -> impl Display + 'a + Captures<'tcx> {
     display_fn(move |f| {
         if !params.is_empty() {
@@ -967,12 +1003,12 @@ fn print_higher_ranked_params_with_space<'a, 'tcx: 'a>(
     })
 }
 
-pub(crate) fn anchor<'a, 'cx: 'a>(
+pub(crate) fn anchor<'a, 'tcx>(
     did: DefId,
     text: Symbol,
-    cx: &'cx Context<'_>,
+    cx: &impl FormatContext<'tcx>,
 ) -> impl Display + 'a {
-    let parts = href(did, cx);
+    let parts = cx.href(did);
     display_fn(move |f| {
         if let Ok((url, short_ty, fqp)) = parts {
             write!(
@@ -986,11 +1022,11 @@ pub(crate) fn anchor<'a, 'cx: 'a>(
     })
 }
 
-fn fmt_type<'cx>(
+fn fmt_type<'tcx>(
     t: &clean::Type,
     f: &mut fmt::Formatter<'_>,
     use_absolute: bool,
-    cx: &'cx Context<'_>,
+    cx: &impl FormatContext<'tcx>,
 ) -> fmt::Result {
     trace!("fmt_type(t = {t:?})");
 
@@ -1215,8 +1251,8 @@ fn fmt_type<'cx>(
                 // I don't feel like that right now :cold_sweat:.
 
                 let parent_href = match trait_ {
-                    Some(trait_) => href(trait_.def_id(), cx).ok(),
-                    None => self_type.def_id(cx.cache()).and_then(|did| href(did, cx).ok()),
+                    Some(trait_) => cx.href(trait_.def_id()).ok(),
+                    None => self_type.def_id(cx.cache()).and_then(|did| cx.href(did).ok()),
                 };
 
                 if let Some((url, _, path)) = parent_href {
@@ -1243,7 +1279,7 @@ fn fmt_type<'cx>(
 impl clean::Type {
     pub(crate) fn print<'b, 'a: 'b, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'b + Captures<'tcx> {
         display_fn(move |f| fmt_type(self, f, false, cx))
     }
@@ -1252,7 +1288,7 @@ pub(crate) fn print<'b, 'a: 'b, 'tcx: 'a>(
 impl clean::Path {
     pub(crate) fn print<'b, 'a: 'b, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'b + Captures<'tcx> {
         display_fn(move |f| resolved_path(f, self.def_id(), self, false, false, cx))
     }
@@ -1262,7 +1298,7 @@ impl clean::Impl {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
         use_absolute: bool,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| {
             f.write_str("impl")?;
@@ -1329,7 +1365,7 @@ pub(crate) fn print<'a, 'tcx: 'a>(
 impl clean::Arguments {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| {
             for (i, input) in self.values.iter().enumerate() {
@@ -1369,7 +1405,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 impl clean::FnDecl {
     pub(crate) fn print<'b, 'a: 'b, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'b + Captures<'tcx> {
         display_fn(move |f| {
             let ellipsis = if self.c_variadic { ", ..." } else { "" };
@@ -1403,7 +1439,7 @@ pub(crate) fn full_print<'a, 'tcx: 'a>(
         &'a self,
         header_len: usize,
         indent: usize,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| {
             // First, generate the text form of the declaration, with no line wrapping, and count the bytes.
@@ -1413,19 +1449,19 @@ pub(crate) fn full_print<'a, 'tcx: 'a>(
             // If the text form was over 80 characters wide, we will line-wrap our output.
             let line_wrapping_indent =
                 if header_len + counter.0 > 80 { Some(indent) } else { None };
-            // Generate the final output. This happens to accept `{:#}` formatting to get textual
-            // output but in practice it is only formatted with `{}` to get HTML output.
+            // Generate the final output. This is formatted with `{}` to get HTML output, and with
+            // `{:#}` to get the text of the Markdown output.
             self.inner_full_print(line_wrapping_indent, f, cx)
         })
     }
 
-    fn inner_full_print(
+    fn inner_full_print<'tcx>(
         &self,
         // For None, the declaration will not be line-wrapped. For Some(n),
         // the declaration will be line-wrapped, with an indent of n spaces.
         line_wrapping_indent: Option<usize>,
         f: &mut fmt::Formatter<'_>,
-        cx: &Context<'_>,
+        cx: &impl FormatContext<'tcx>,
     ) -> fmt::Result {
         let amp = if f.alternate() { "&" } else { "&amp;" };
 
@@ -1493,7 +1529,7 @@ fn inner_full_print(
 
     fn print_output<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| match &self.output {
             clean::Tuple(tys) if tys.is_empty() => Ok(()),
@@ -1508,7 +1544,7 @@ fn print_output<'a, 'tcx: 'a>(
 pub(crate) fn visibility_print_with_space<'a, 'tcx: 'a>(
     visibility: Option<ty::Visibility<DefId>>,
     item_did: ItemId,
-    cx: &'a Context<'tcx>,
+    cx: &'a impl FormatContext<'tcx>,
 ) -> impl Display + 'a + Captures<'tcx> {
     use std::fmt::Write as _;
 
@@ -1635,7 +1671,7 @@ pub(crate) fn print_constness_with_space(
 impl clean::Import {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| match self.kind {
             clean::ImportKind::Simple(name) => {
@@ -1659,7 +1695,7 @@ pub(crate) fn print<'a, 'tcx: 'a>(
 impl clean::ImportSource {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| match self.did {
             Some(did) => resolved_path(f, did, &self.path, true, false, cx),
@@ -1687,7 +1723,7 @@ pub(crate) fn print<'a, 'tcx: 'a>(
 impl clean::TypeBinding {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| {
             f.write_str(self.assoc.name.as_str())?;
@@ -1726,7 +1762,7 @@ pub(crate) fn print_default_space<'a>(v: bool) -> &'a str {
 impl clean::GenericArg {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| match self {
             clean::GenericArg::Lifetime(lt) => lt.print().fmt(f),
@@ -1740,7 +1776,7 @@ pub(crate) fn print<'a, 'tcx: 'a>(
 impl clean::Term {
     pub(crate) fn print<'a, 'tcx: 'a>(
         &'a self,
-        cx: &'a Context<'tcx>,
+        cx: &'a impl FormatContext<'tcx>,
     ) -> impl Display + 'a + Captures<'tcx> {
         display_fn(move |f| match self {
             clean::Term::Type(ty) => ty.print(cx).fmt(f),
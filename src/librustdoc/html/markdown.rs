@@ -261,15 +261,18 @@ fn next(&mut self) -> Option<Self::Item> {
                     );
                     if !parse_result.rust {
                         let added_classes = parse_result.added_classes;
-                        let lang_string = if let Some(lang) = parse_result.unknown.first() {
+                        let lang_string = if parse_result.output {
+                            "language-output".to_owned()
+                        } else if let Some(lang) = parse_result.unknown.first() {
                             format!("language-{}", lang)
                         } else {
                             String::new()
                         };
+                        let wrap_class = if parse_result.output { " example-output" } else { "" };
                         let whitespace = if added_classes.is_empty() { "" } else { " " };
                         return Some(Event::Html(
                             format!(
-                                "<div class=\"example-wrap\">\
+                                "<div class=\"example-wrap{wrap_class}\">\
                                  <pre class=\"{lang_string}{whitespace}{added_classes}\">\
                                      <code>{text}</code>\
                                  </pre>\
@@ -755,14 +758,14 @@ pub(crate) fn find_codes<T: doctest::Tester>(
     include_non_rust: bool,
     custom_code_classes_in_docs: bool,
 ) {
-    let mut parser = Parser::new(doc).into_offset_iter();
+    let mut parser = Parser::new(doc).into_offset_iter().peekable();
     let mut prev_offset = 0;
     let mut nb_lines = 0;
     let mut register_header = None;
     while let Some((event, offset)) = parser.next() {
         match event {
             Event::Start(Tag::CodeBlock(kind)) => {
-                let block_info = match kind {
+                let mut block_info = match kind {
                     CodeBlockKind::Fenced(ref lang) => {
                         if lang.is_empty() {
                             Default::default()
@@ -793,6 +796,27 @@ pub(crate) fn find_codes<T: doctest::Tester>(
                     .collect::<Vec<Cow<'_, str>>>()
                     .join("\n");
 
+                // An `output` block right after a doctest holds its expected output.
+                if !include_non_rust
+                    && let Some((Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang))), _)) =
+                        parser.peek()
+                {
+                    let output_info = LangString::parse_without_check(
+                        lang,
+                        error_codes,
+                        false,
+                        custom_code_classes_in_docs,
+                    );
+                    if output_info.output && !output_info.rust {
+                        parser.next();
+                        let mut expected_output = String::new();
+                        while let Some((Event::Text(s), _)) = parser.next() {
+                            expected_output.push_str(&s);
+                        }
+                        block_info.expected_output = Some(expected_output);
+                    }
+                }
+
                 nb_lines += doc[prev_offset..offset.start].lines().count();
                 // If there are characters between the preceding line ending and
                 // this code block, `str::lines` will return an additional line,
@@ -870,6 +894,10 @@ pub(crate) struct LangString {
     pub(crate) edition: Option<Edition>,
     pub(crate) added_classes: Vec<String>,
     pub(crate) unknown: Vec<String>,
+    /// Whether this is an `output` block, which holds the expected output of the doctest before it.
+    pub(crate) output: bool,
+    /// The expected standard output of the doctest, from the `output` block following it.
+    pub(crate) expected_output: Option<String>,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -1204,6 +1232,8 @@ fn default() -> Self {
             edition: None,
             added_classes: Vec::new(),
             unknown: Vec::new(),
+            output: false,
+            expected_output: None,
         }
     }
 }
@@ -1272,6 +1302,10 @@ fn parse(
                             seen_other_tags = true;
                         }
                     }
+                    LangStringToken::LangToken("output") => {
+                        data.output = true;
+                        seen_other_tags = true;
+                    }
                     LangStringToken::LangToken("test_harness") => {
                         data.test_harness = true;
                         seen_rust_tags = !seen_other_tags || seen_rust_tags;
@@ -1660,6 +1694,164 @@ pub(crate) fn plain_text_summary(md: &str, link_names: &[RenderedLink]) -> Strin
     s
 }
 
+/// Returns the lowercased words of the prose of a doc comment, for the full-text search index.
+///
+/// Words are split on whitespace and ASCII punctuation. Code blocks and HTML are ignored, but
+/// inline code is split like the rest of the text, so `retry_with_backoff` gives `retry`, `with`
+/// and `backoff`.
+pub(crate) fn prose_words(md: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut in_code_block = false;
+    for event in Parser::new_ext(md, main_body_opts()) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Text(text) | Event::Code(text) if !in_code_block => {
+                words.extend(
+                    text.split(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+                        .filter(|word| !word.is_empty())
+                        .map(|word| word.to_lowercase()),
+                );
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Rewrites a doc comment for rustdoc's Markdown output, keeping everything else as written:
+///
+/// - Intra-doc links become inline links to their `href`, without disambiguators.
+/// - Headings are moved down by `heading_offset` levels.
+/// - Hidden lines are removed from Rust code blocks, whose language is set to `rust` so that
+///   Markdown viewers highlight them.
+pub(crate) fn rewrite_doc_markdown(
+    md: &str,
+    links: &[RenderedLink],
+    heading_offset: HeadingOffset,
+    custom_code_classes_in_docs: bool,
+) -> String {
+    if md.is_empty() {
+        return String::new();
+    }
+
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        links
+            .iter()
+            .find(|link| &*link.original_text == &*broken_link.reference)
+            .map(|link| (link.href.as_str().into(), link.tooltip.as_str().into()))
+    };
+    let mut events =
+        Parser::new_with_broken_link_callback(md, main_body_opts(), Some(&mut replacer))
+            .into_offset_iter();
+
+    // Replacements of ranges of `md`; they never overlap.
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut in_rust_code_block = false;
+    while let Some((event, range)) = events.next() {
+        match event {
+            Event::Start(Tag::Link(link_type, dest, _)) => {
+                // Shortcut links were resolved by the broken link callback, so their destination
+                // already is the href. The others still have the path that was written.
+                let link = match link_type {
+                    LinkType::ShortcutUnknown
+                    | LinkType::CollapsedUnknown
+                    | LinkType::ReferenceUnknown => links.iter().find(|l| *l.href == *dest),
+                    _ => links.iter().find(|l| *l.original_text == *dest),
+                };
+                let mut text: Option<Range<usize>> = None;
+                for (event, inner) in &mut events {
+                    if let Event::End(Tag::Link(..)) = event {
+                        break;
+                    }
+                    text = Some(match text {
+                        Some(text) => text.start.min(inner.start)..text.end.max(inner.end),
+                        None => inner,
+                    });
+                }
+                let (Some(link), Some(text)) = (link, text) else { continue };
+                let text = &md[text];
+                let replacement = if text != &*link.original_text {
+                    format!("[{text}]({})", link.href)
+                } else if text.starts_with('`') {
+                    format!("[`{}`]({})", link.new_text, link.href)
+                } else {
+                    format!("[{}]({})", link.new_text, link.href)
+                };
+                edits.push((range, replacement));
+            }
+            Event::Start(Tag::Heading(level, _, _)) => {
+                let level = level as u32;
+                let new_level = std::cmp::min(level + heading_offset as u32, MAX_HEADER_LEVEL);
+                let heading = md[range.clone()].trim_end();
+                let start = range.start + heading.len() - heading.trim_start().len();
+                if heading.trim_start().starts_with('#') {
+                    let extra = "#".repeat((new_level - level) as usize);
+                    edits.push((start..start, extra));
+                } else if let Some(underline) = heading.rfind('\n') {
+                    // A setext heading: turn it into an ATX one, since those can have any level.
+                    edits.push((start..start, format!("{} ", "#".repeat(new_level as usize))));
+                    let underline = range.start + underline;
+                    edits.push((underline..range.start + heading.len(), String::new()));
+                }
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_rust_code_block = match kind {
+                    CodeBlockKind::Fenced(ref info) => {
+                        let lang = LangString::parse_without_check(
+                            info,
+                            ErrorCodes::Yes,
+                            false,
+                            custom_code_classes_in_docs,
+                        );
+                        if lang.rust && &**info != "rust" {
+                            // Replace the info string after the fence, which other Markdown
+                            // renderers wouldn't understand.
+                            let fence = md[range.clone()].lines().next().unwrap_or("").trim_end();
+                            let info_start = fence.len()
+                                - fence.trim_start().trim_start_matches(['`', '~']).len();
+                            edits.push((
+                                range.start + info_start..range.start + fence.len(),
+                                "rust".to_string(),
+                            ));
+                        }
+                        lang.rust
+                    }
+                    CodeBlockKind::Indented => true,
+                };
+            }
+            Event::End(Tag::CodeBlock(_)) => in_rust_code_block = false,
+            // Inside of block quotes and lists, the text of a code block may not be a slice of
+            // `md`; such text is kept as is.
+            Event::Text(text) if in_rust_code_block && md.get(range.clone()) == Some(&*text) => {
+                let mut start = range.start;
+                for line in text.split_inclusive('\n') {
+                    let end = start + line.len();
+                    match map_line(line) {
+                        Line::Hidden(_) => edits.push((start..end, String::new())),
+                        Line::Shown(Cow::Owned(line)) => edits.push((start..end, line)),
+                        Line::Shown(Cow::Borrowed(_)) => {}
+                    }
+                    start = end;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Insertions at the start of a heading come before the edits of its content.
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut s = String::with_capacity(md.len());
+    let mut pos = 0;
+    for (range, replacement) in edits {
+        s.push_str(&md[pos..range.start]);
+        s.push_str(&replacement);
+        pos = range.end;
+    }
+    s.push_str(&md[pos..]);
+    s
+}
+
 #[derive(Debug)]
 pub(crate) struct MarkdownLink {
     pub kind: LinkType,
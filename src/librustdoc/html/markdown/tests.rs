@@ -1,8 +1,12 @@
-use super::{find_testable_code, plain_text_summary, short_markdown_summary};
 use super::{
     ErrorCodes, HeadingOffset, IdMap, Ignore, LangString, LangStringToken, Markdown,
     MarkdownItemInfo, TagIterator,
 };
+use super::{
+    find_testable_code, plain_text_summary, prose_words, rewrite_doc_markdown,
+    short_markdown_summary,
+};
+use crate::clean::RenderedLink;
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 
 #[test]
@@ -54,6 +58,7 @@ fn t(lg: LangString) {
 
     t(Default::default());
     t(LangString { original: "rust".into(), ..Default::default() });
+    t(LangString { original: "output".into(), rust: false, output: true, ..Default::default() });
     t(LangString {
         original: "rusta".into(),
         rust: false,
@@ -464,6 +469,20 @@ fn t(input: &str, expect: &str) {
     );
 }
 
+#[test]
+fn test_prose_words() {
+    fn t(input: &str, expect: &[&str]) {
+        let output = prose_words(input);
+        assert_eq!(output, expect, "original: {}", input);
+    }
+
+    t("", &[]);
+    t("Hello, [World](https://www.rust-lang.org)!", &["hello", "world"]);
+    t("Calls `Vec::new` first.", &["calls", "vec", "new", "first"]);
+    t("# Header\n\nText", &["header", "text"]);
+    t("Before\n\n```\nlet skipped = 1;\n```\n\nafter", &["before", "after"]);
+}
+
 #[test]
 fn test_markdown_html_escape() {
     fn t(input: &str, expect: &str) {
@@ -494,6 +513,29 @@ fn t(input: &str, expect: &[usize]) {
     t("```rust\n```\n ```rust\n```", &[1, 3]);
 }
 
+#[test]
+fn test_find_testable_code_expected_output() {
+    struct ExpectedOutputs(Vec<Option<String>>);
+
+    impl crate::doctest::Tester for ExpectedOutputs {
+        fn add_test(&mut self, _: String, config: LangString, _: usize) {
+            self.0.push(config.expected_output);
+        }
+    }
+
+    fn t(input: &str, expect: &[Option<&str>]) {
+        let mut outputs = ExpectedOutputs(Vec::new());
+        find_testable_code(input, &mut outputs, ErrorCodes::No, false, None, true);
+        let expect = expect.iter().map(|output| output.map(str::to_owned)).collect::<Vec<_>>();
+        assert_eq!(outputs.0, expect);
+    }
+
+    t("```\nprintln!(\"hi\");\n```\n```output\nhi\n```", &[Some("hi\n")]);
+    t("```\n```\n```text,output\nhi\n```\n```\n```", &[Some("hi\n"), None]);
+    t("```\n```\nSome text.\n```output\nhi\n```", &[None]);
+    t("```text\n```\n```output\nhi\n```", &[]);
+}
+
 #[test]
 fn test_ascii_with_prepending_hashtag() {
     fn t(input: &str, expect: &str) {
@@ -539,3 +581,58 @@ fn t(input: &str, expect: &str) {
 </code></pre></div>",
     );
 }
+
+#[test]
+fn test_output_code_block() {
+    let mut map = IdMap::new();
+    let output = Markdown {
+        content: "```output\nhi\n```",
+        links: &[],
+        ids: &mut map,
+        error_codes: ErrorCodes::Yes,
+        edition: DEFAULT_EDITION,
+        playground: &None,
+        heading_offset: HeadingOffset::H2,
+        custom_code_classes_in_docs: true,
+    }
+    .into_string();
+    assert_eq!(
+        output,
+        "<div class=\"example-wrap example-output\"><pre class=\"language-output\"><code>hi\n\
+         </code></pre></div>",
+    );
+}
+
+#[test]
+fn test_rewrite_doc_markdown() {
+    fn t(input: &str, expect: &str) {
+        let link = |original_text: &str, new_text: &str, href: &str| RenderedLink {
+            original_text: original_text.into(),
+            new_text: new_text.into(),
+            href: href.to_string(),
+            tooltip: String::new(),
+        };
+        let links = [
+            link("Foo", "Foo", "struct.Foo.md"),
+            link("`fn@bar`", "bar", "fn.bar.md"),
+            link("crate::Foo", "crate::Foo", "../struct.Foo.md#method.new"),
+        ];
+        let output = rewrite_doc_markdown(input, &links, HeadingOffset::H2, false);
+        assert_eq!(output, expect, "original: {}", input);
+    }
+
+    t("", "");
+    t("see [Foo] and [`fn@bar`]", "see [Foo](struct.Foo.md) and [`bar`](fn.bar.md)");
+    t("see [the struct][Foo]", "see [the struct](struct.Foo.md)");
+    t("see [the struct](crate::Foo)", "see [the struct](../struct.Foo.md#method.new)");
+    t("[Rust](https://www.rust-lang.org) and [Baz]", "[Rust](https://www.rust-lang.org) and [Baz]");
+    t("# Examples\n\ntext", "### Examples\n\ntext");
+    t("##### Deep", "###### Deep");
+    t("Title\n=====\n\ntext", "### Title\n\ntext");
+    t(
+        "```\n# fn main() {\nlet x = 1;\n## not hidden\n# }\n```",
+        "```rust\nlet x = 1;\n# not hidden\n```",
+    );
+    t("```should_panic\npanic!();\n```", "```rust\npanic!();\n```");
+    t("```text\n# not rust\n```", "```text\n# not rust\n```");
+}
@@ -13,7 +13,7 @@
 use crate::formats::cache::{Cache, OrphanImplItem};
 use crate::formats::item_type::ItemType;
 use crate::html::format::join_with_double_colon;
-use crate::html::markdown::short_markdown_summary;
+use crate::html::markdown::{prose_words, short_markdown_summary};
 use crate::html::render::{self, IndexItem, IndexItemFunctionType, RenderType, RenderTypeId};
 
 /// Builds the search index from the collected metadata
@@ -32,7 +32,10 @@ pub(crate) fn build_index<'tcx>(
     for &OrphanImplItem { impl_id, parent, ref item, ref impl_generics } in &cache.orphan_impl_items
     {
         if let Some((fqp, _)) = cache.paths.get(&parent) {
-            let desc = short_markdown_summary(&item.doc_value(), &item.link_names(cache));
+            let doc = item.doc_value();
+            let desc = short_markdown_summary(&doc, &item.link_names(cache));
+            let prose_terms =
+                if cache.full_text_search { prose_terms(&doc) } else { Box::default() };
             cache.search_index.push(IndexItem {
                 ty: item.type_(),
                 name: item.name.unwrap(),
@@ -50,6 +53,7 @@ pub(crate) fn build_index<'tcx>(
                 ),
                 aliases: item.attrs.get_doc_aliases(),
                 deprecation: item.deprecation(tcx),
+                prose_terms,
             });
         }
     }
@@ -316,6 +320,14 @@ fn convert_render_type(
         })
         .collect::<Vec<_>>();
 
+    // The full-text index, from each term to the items whose documentation contains it.
+    let mut prose: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (index, item) in crate_items.iter().enumerate() {
+        for term in &item.prose_terms[..] {
+            prose.entry(term).or_default().push(index);
+        }
+    }
+
     struct CrateData<'a> {
         doc: String,
         items: Vec<&'a IndexItem>,
@@ -326,6 +338,10 @@ struct CrateData<'a> {
         aliases: &'a BTreeMap<String, Vec<usize>>,
         // Used when a type has more than one impl with an associated item with the same name.
         associated_item_disambiguators: &'a Vec<(usize, String)>,
+        // Empty unless `--full-text-search` is passed.
+        //
+        // To be noted: the `usize` elements are indexes to `items`, in increasing order.
+        prose: &'a BTreeMap<&'a str, Vec<usize>>,
     }
 
     struct Paths {
@@ -451,8 +467,11 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             }
 
             let has_aliases = !self.aliases.is_empty();
-            let mut crate_data =
-                serializer.serialize_struct("CrateData", if has_aliases { 9 } else { 8 })?;
+            let has_prose = !self.prose.is_empty();
+            let mut crate_data = serializer.serialize_struct(
+                "CrateData",
+                8 + usize::from(has_aliases) + usize::from(has_prose),
+            )?;
             crate_data.serialize_field("doc", &self.doc)?;
             crate_data.serialize_field("t", &types)?;
             crate_data.serialize_field("n", &names)?;
@@ -467,6 +486,26 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             if has_aliases {
                 crate_data.serialize_field("a", &self.aliases)?;
             }
+            if has_prose {
+                // The lists of items are delta-encoded: the gaps between the indexes are
+                // smaller numbers than the indexes themselves, so they take fewer digits.
+                let prose: BTreeMap<&str, Vec<usize>> = self
+                    .prose
+                    .iter()
+                    .map(|(&term, items)| {
+                        let deltas = items
+                            .iter()
+                            .scan(0, |last, &index| {
+                                let delta = index - *last;
+                                *last = index;
+                                Some(delta)
+                            })
+                            .collect();
+                        (term, deltas)
+                    })
+                    .collect();
+                crate_data.serialize_field("w", &prose)?;
+            }
             crate_data.end()
         }
     }
@@ -481,6 +520,7 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             paths: crate_paths,
             aliases: &aliases,
             associated_item_disambiguators: &associated_item_disambiguators,
+            prose: &prose,
         })
         .expect("failed serde conversion")
         // All these `replace` calls are because we have to go through JS string for JSON content.
@@ -491,6 +531,83 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     )
 }
 
+/// Words too common in documentation to be worth indexing.
+///
+/// Must be kept in sync with `PROSE_STOP_WORDS` in `search.js`.
+const PROSE_STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "for", "from", "if", "in", "is", "it",
+    "its", "not", "of", "on", "or", "that", "the", "this", "to", "was", "when", "which", "will",
+    "with",
+];
+
+/// Returns the terms under which full-text search finds an item documented by `doc`: the stems
+/// of its words, without duplicates.
+///
+/// `search.js` turns the words of a query into terms in the same way.
+pub(crate) fn prose_terms(doc: &str) -> Box<[String]> {
+    let mut terms: Vec<String> = prose_words(doc)
+        .iter()
+        .filter(|word| {
+            word.chars().count() > 1
+                && !word.chars().all(|c| c.is_ascii_digit())
+                && !PROSE_STOP_WORDS.contains(&&word[..])
+        })
+        .map(|word| stem(word))
+        .collect();
+    terms.sort_unstable();
+    terms.dedup();
+    terms.into()
+}
+
+/// Strips the common suffixes of English words, so that "retries", "retried" and "retrying" are
+/// all found by searching for "retry".
+///
+/// This is a much cruder version of the Porter stemmer: it only has to map the forms of a word to
+/// the same term, not to produce an actual word. Must be kept in sync with `stemProseWord` in
+/// `search.js`.
+fn stem(word: &str) -> String {
+    const SUFFIXES: &[(&str, &str)] = &[
+        ("sses", "ss"),
+        ("ies", "y"),
+        ("ied", "y"),
+        ("xes", "x"),
+        ("ches", "ch"),
+        ("shes", "sh"),
+        ("ing", ""),
+        ("ed", ""),
+        ("s", ""),
+    ];
+
+    let mut stem = word.to_string();
+    for &(suffix, replacement) in SUFFIXES {
+        let Some(root) = word.strip_suffix(suffix) else { continue };
+        // Keep "bus" and "class", or "sing" and "bed".
+        if root.chars().count() < 3
+            || (suffix == "s" && (root.ends_with('s') || root.ends_with('u')))
+        {
+            continue;
+        }
+        stem = format!("{root}{replacement}");
+        // "stopped" and "running" become "stop" and "run".
+        if suffix == "ing" || suffix == "ed" {
+            let mut chars = stem.chars().rev();
+            if let (Some(last), Some(before)) = (chars.next(), chars.next())
+                && last == before
+                && last.is_ascii_lowercase()
+                && !"aeioulsz".contains(last)
+            {
+                stem.pop();
+            }
+        }
+        break;
+    }
+    // "parse" and "parsed" become the same term.
+    if stem.chars().count() > 4 && stem.ends_with('e') {
+        stem.pop();
+    }
+    stem
+}
+
 pub(crate) fn get_function_type_for_search<'tcx>(
     item: &clean::Item,
     tcx: TyCtxt<'tcx>,
@@ -1,8 +1,10 @@
 use std::cell::RefCell;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::{self, BufReader};
-use std::path::{Component, Path};
+use std::path::{Component, Path, PathBuf};
 use std::rc::{Rc, Weak};
 
 use indexmap::IndexMap;
@@ -15,11 +17,12 @@
 use serde::ser::SerializeSeq;
 use serde::{Serialize, Serializer};
 
-use super::{collect_paths_for_type, ensure_trailing_slash, Context, RenderMode};
+use super::{collect_paths_for_type, ensure_trailing_slash, Context, RenderMode, StylePath};
 use crate::clean::{Crate, Item, ItemId, ItemKind};
 use crate::config::{EmitType, RenderOptions};
 use crate::docfs::PathError;
 use crate::error::Error;
+use crate::externalfiles::ExternalHtml;
 use crate::formats::cache::Cache;
 use crate::formats::item_type::ItemType;
 use crate::formats::Impl;
@@ -103,97 +106,6 @@ pub(super) fn write_shared(
         })?;
     }
 
-    /// Read a file and return all lines that match the `"{crate}":{data},` format,
-    /// and return a tuple `(Vec<DataString>, Vec<CrateNameString>)`.
-    ///
-    /// This forms the payload of files that look like this:
-    ///
-    /// ```javascript
-    /// var data = {
-    /// "{crate1}":{data},
-    /// "{crate2}":{data}
-    /// };
-    /// use_data(data);
-    /// ```
-    ///
-    /// The file needs to be formatted so that *only crate data lines start with `"`*.
-    fn collect(path: &Path, krate: &str) -> io::Result<(Vec<String>, Vec<String>)> {
-        let mut ret = Vec::new();
-        let mut krates = Vec::new();
-
-        if path.exists() {
-            let prefix = format!("\"{krate}\"");
-            for line in BufReader::new(File::open(path)?).lines() {
-                let line = line?;
-                if !line.starts_with('"') {
-                    continue;
-                }
-                if line.starts_with(&prefix) {
-                    continue;
-                }
-                if line.ends_with(',') {
-                    ret.push(line[..line.len() - 1].to_string());
-                } else {
-                    // No comma (it's the case for the last added crate line)
-                    ret.push(line.to_string());
-                }
-                krates.push(
-                    line.split('"')
-                        .find(|s| !s.is_empty())
-                        .map(|s| s.to_owned())
-                        .unwrap_or_else(String::new),
-                );
-            }
-        }
-        Ok((ret, krates))
-    }
-
-    /// Read a file and return all lines that match the <code>"{crate}":{data},\ </code> format,
-    /// and return a tuple `(Vec<DataString>, Vec<CrateNameString>)`.
-    ///
-    /// This forms the payload of files that look like this:
-    ///
-    /// ```javascript
-    /// var data = JSON.parse('{\
-    /// "{crate1}":{data},\
-    /// "{crate2}":{data}\
-    /// }');
-    /// use_data(data);
-    /// ```
-    ///
-    /// The file needs to be formatted so that *only crate data lines start with `"`*.
-    fn collect_json(path: &Path, krate: &str) -> io::Result<(Vec<String>, Vec<String>)> {
-        let mut ret = Vec::new();
-        let mut krates = Vec::new();
-
-        if path.exists() {
-            let prefix = format!("[\"{krate}\"");
-            for line in BufReader::new(File::open(path)?).lines() {
-                let line = line?;
-                if !line.starts_with("[\"") {
-                    continue;
-                }
-                if line.starts_with(&prefix) {
-                    continue;
-                }
-                if line.ends_with("],\\") {
-                    ret.push(line[..line.len() - 2].to_string());
-                } else {
-                    // Ends with "\\" (it's the case for the last added crate line)
-                    ret.push(line[..line.len() - 1].to_string());
-                }
-                krates.push(
-                    line[1..] // We skip the `[` parent at the beginning of the line.
-                        .split('"')
-                        .find(|s| !s.is_empty())
-                        .map(|s| s.to_owned())
-                        .unwrap_or_else(String::new),
-                );
-            }
-        }
-        Ok((ret, krates))
-    }
-
     use std::ffi::OsString;
 
     #[derive(Debug, Default)]
@@ -297,13 +209,7 @@ fn add_path(self: &Rc<Self>, path: &Path) {
                     .replace("\\\"", "\\\\\"")
             ));
             all_sources.sort();
-            // This needs to be `var`, not `const`.
-            // This variable needs declared in the current global scope so that if
-            // src-script.js loads first, it can pick it up.
-            let mut v = String::from("var srcIndex = new Map(JSON.parse('[\\\n");
-            v.push_str(&all_sources.join(",\\\n"));
-            v.push_str("\\\n]'));\ncreateSrcSidebar();\n");
-            Ok(v.into_bytes())
+            Ok(src_files_js(&all_sources).into_bytes())
         };
         write_invocation_specific("src-files.js", &make_sources)?;
     }
@@ -320,25 +226,10 @@ fn add_path(self: &Rc<Self>, path: &Path) {
     // with rustdoc running in parallel.
     all_indexes.sort();
     write_invocation_specific("search-index.js", &|| {
-        // This needs to be `var`, not `const`.
-        // This variable needs declared in the current global scope so that if
-        // search.js loads first, it can pick it up.
-        let mut v = String::from("var searchIndex = new Map(JSON.parse('[\\\n");
-        v.push_str(&all_indexes.join(",\\\n"));
-        v.push_str(
-            r#"\
-]'));
-if (typeof exports !== 'undefined') exports.searchIndex = searchIndex;
-else if (window.initSearch) window.initSearch(searchIndex);
-"#,
-        );
-        Ok(v.into_bytes())
+        Ok(search_index_js(&all_indexes).into_bytes())
     })?;
 
-    write_invocation_specific("crates.js", &|| {
-        let krates = krates.iter().map(|k| format!("\"{k}\"")).join(",");
-        Ok(format!("window.ALL_CRATES = [{krates}];").into_bytes())
-    })?;
+    write_invocation_specific("crates.js", &|| Ok(crates_js(&krates).into_bytes()))?;
 
     if options.enable_index_page {
         if let Some(index_page) = options.index_page.clone() {
@@ -351,26 +242,13 @@ fn add_path(self: &Rc<Self>, path: &Path) {
         } else {
             let shared = Rc::clone(&cx.shared);
             let dst = cx.dst.join("index.html");
-            let page = layout::Page {
-                title: "Index of crates",
-                css_class: "mod sys",
-                root_path: "./",
-                static_root_path: shared.static_root_path.as_deref(),
-                description: "List of crates",
-                resource_suffix: &shared.resource_suffix,
-                rust_logo: true,
-            };
-
-            let content = format!(
-                "<h1>List of all crates</h1><ul class=\"all-items\">{}</ul>",
-                krates.iter().format_with("", |k, f| {
-                    f(&format_args!(
-                        "<li><a href=\"{trailing_slash}index.html\">{k}</a></li>",
-                        trailing_slash = ensure_trailing_slash(k),
-                    ))
-                })
+            let v = crate_list_page(
+                &shared.layout,
+                shared.static_root_path.as_deref(),
+                &shared.resource_suffix,
+                &shared.style_files,
+                &krates,
             );
-            let v = layout::render(&shared.layout, &page, "", content, &shared.style_files);
             shared.fs.write(dst, v)?;
         }
     }
@@ -622,18 +500,7 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         // identically even with rustdoc running in parallel.
         all_impls.sort();
 
-        let mut v = String::from("(function() {var type_impls = {\n");
-        v.push_str(&all_impls.join(",\n"));
-        v.push_str("\n};");
-        v.push_str(
-            "if (window.register_type_impls) {\
-                 window.register_type_impls(type_impls);\
-             } else {\
-                 window.pending_type_impls = type_impls;\
-             }",
-        );
-        v.push_str("})()");
-        cx.shared.fs.write(mydst, v)?;
+        cx.shared.fs.write(mydst, type_impls_js(&all_impls))?;
     }
 
     // Update the list of all implementors for traits
@@ -733,18 +600,319 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         // identically even with rustdoc running in parallel.
         all_implementors.sort();
 
-        let mut v = String::from("(function() {var implementors = {\n");
-        v.push_str(&all_implementors.join(",\n"));
-        v.push_str("\n};");
-        v.push_str(
-            "if (window.register_implementors) {\
-                 window.register_implementors(implementors);\
-             } else {\
-                 window.pending_implementors = implementors;\
-             }",
-        );
-        v.push_str("})()");
-        cx.shared.fs.write(mydst, v)?;
+        cx.shared.fs.write(mydst, implementors_js(&all_implementors))?;
     }
     Ok(())
 }
+
+/// Read a file and return all lines that match the `"{crate}":{data},` format,
+/// and return a tuple `(Vec<DataString>, Vec<CrateNameString>)`.
+///
+/// This forms the payload of files that look like this:
+///
+/// ```javascript
+/// var data = {
+/// "{crate1}":{data},
+/// "{crate2}":{data}
+/// };
+/// use_data(data);
+/// ```
+///
+/// The file needs to be formatted so that *only crate data lines start with `"`*.
+fn collect(path: &Path, krate: &str) -> io::Result<(Vec<String>, Vec<String>)> {
+    let mut ret = Vec::new();
+    let mut krates = Vec::new();
+
+    if path.exists() {
+        let prefix = format!("\"{krate}\"");
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if !line.starts_with('"') {
+                continue;
+            }
+            if line.starts_with(&prefix) {
+                continue;
+            }
+            if line.ends_with(',') {
+                ret.push(line[..line.len() - 1].to_string());
+            } else {
+                // No comma (it's the case for the last added crate line)
+                ret.push(line.to_string());
+            }
+            krates.push(
+                line.split('"')
+                    .find(|s| !s.is_empty())
+                    .map(|s| s.to_owned())
+                    .unwrap_or_else(String::new),
+            );
+        }
+    }
+    Ok((ret, krates))
+}
+
+/// Read a file and return all lines that match the <code>"{crate}":{data},\ </code> format,
+/// and return a tuple `(Vec<DataString>, Vec<CrateNameString>)`.
+///
+/// This forms the payload of files that look like this:
+///
+/// ```javascript
+/// var data = JSON.parse('{\
+/// "{crate1}":{data},\
+/// "{crate2}":{data}\
+/// }');
+/// use_data(data);
+/// ```
+///
+/// The file needs to be formatted so that *only crate data lines start with `"`*.
+fn collect_json(path: &Path, krate: &str) -> io::Result<(Vec<String>, Vec<String>)> {
+    let mut ret = Vec::new();
+    let mut krates = Vec::new();
+
+    if path.exists() {
+        let prefix = format!("[\"{krate}\"");
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if !line.starts_with("[\"") {
+                continue;
+            }
+            if line.starts_with(&prefix) {
+                continue;
+            }
+            if line.ends_with("],\\") {
+                ret.push(line[..line.len() - 2].to_string());
+            } else {
+                // Ends with "\\" (it's the case for the last added crate line)
+                ret.push(line[..line.len() - 1].to_string());
+            }
+            krates.push(
+                line[1..] // We skip the `[` parent at the beginning of the line.
+                    .split('"')
+                    .find(|s| !s.is_empty())
+                    .map(|s| s.to_owned())
+                    .unwrap_or_else(String::new),
+            );
+        }
+    }
+    Ok((ret, krates))
+}
+
+/// Lists the files under `dir`, relative to it.
+fn relative_files(dir: &Path, rel: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let path = dir.join(rel);
+    for entry in try_err!(fs::read_dir(&path), &path) {
+        let entry = try_err!(entry, &path);
+        let rel = rel.join(entry.file_name());
+        if try_err!(entry.file_type(), &entry.path()).is_dir() {
+            relative_files(dir, &rel, files)?;
+        } else {
+            files.push(rel);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the data of each crate in a file read by [`collect`] (or [`collect_json`] if `json`
+/// is `true`), by crate name.
+fn crate_data(path: &Path, json: bool) -> Result<BTreeMap<String, String>, Error> {
+    let (data, krates) =
+        try_err!(if json { collect_json(path, "") } else { collect(path, "") }, path);
+    Ok(krates.into_iter().zip(data).collect())
+}
+
+/// Merges the documentation that rustdoc wrote to each of `inputs` into `dst`, so that crates
+/// can be documented separately (in parallel, or only when they change) and still end up in a
+/// single site.
+///
+/// Most files are copied as they are: each crate has its own directory, and the static files
+/// are the same in every output. The files listing the data of all crates (the search index,
+/// the list of sources, and the implementors of traits and aliased types) are combined instead,
+/// as if the crates had been documented into `dst` one after the other. Crates that were already
+/// documented in `dst` are kept, unless one of the inputs documents them again.
+///
+/// `dst` can't be one of the inputs or be nested with one of them: the files would be read while
+/// they are being written.
+pub(crate) fn merge_doc_dirs(
+    inputs: &[PathBuf],
+    dst: &Path,
+    resource_suffix: &str,
+    enable_index_page: bool,
+) -> Result<(), Error> {
+    try_err!(fs::create_dir_all(dst), dst);
+    let canonical_dst = try_err!(dst.canonicalize(), dst);
+    for input in inputs {
+        let canonical_input = try_err!(input.canonicalize(), input);
+        // `starts_with` compares whole components, and is also true for equal paths.
+        if canonical_input.starts_with(&canonical_dst)
+            || canonical_dst.starts_with(&canonical_input)
+        {
+            let error = format!("overlaps with the output directory \"{}\"", dst.display());
+            return Err(Error::new(error, input));
+        }
+    }
+    let lock_file = dst.join(".lock");
+    let _lock = try_err!(flock::Lock::new(&lock_file, true, true, true), &lock_file);
+
+    let search_index = static_files::suffix_path("search-index.js", resource_suffix);
+    let src_files = static_files::suffix_path("src-files.js", resource_suffix);
+    let crates = static_files::suffix_path("crates.js", resource_suffix);
+    let is_impls = |rel: &Path| rel.starts_with("trait.impl") || rel.starts_with("type.impl");
+
+    let mut merged: BTreeMap<PathBuf, BTreeMap<String, String>> = BTreeMap::new();
+    for input in inputs {
+        let mut files = Vec::new();
+        relative_files(input, Path::new(""), &mut files)?;
+        for rel in files {
+            let src = input.join(&rel);
+            let out = dst.join(&rel);
+            if let Some(parent) = out.parent() {
+                try_err!(fs::create_dir_all(parent), parent);
+            }
+            let json = rel == search_index || rel == src_files;
+            if json || is_impls(&rel) {
+                let data = match merged.entry(rel) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(crate_data(&out, json)?),
+                };
+                data.extend(crate_data(&src, json)?);
+            } else if rel == crates
+                || rel == Path::new(".lock")
+                || (enable_index_page && rel == Path::new("index.html"))
+            {
+                // These are generated from the merged search index below.
+                continue;
+            } else {
+                try_err!(fs::copy(&src, &out), &src);
+            }
+        }
+    }
+
+    let mut krates = Vec::new();
+    for (rel, data) in &merged {
+        let lines = data.values().cloned().collect::<Vec<_>>();
+        let contents = if *rel == search_index {
+            krates = data.keys().cloned().collect();
+            search_index_js(&lines)
+        } else if *rel == src_files {
+            src_files_js(&lines)
+        } else if rel.starts_with("trait.impl") {
+            implementors_js(&lines)
+        } else {
+            type_impls_js(&lines)
+        };
+        let path = dst.join(rel);
+        try_err!(fs::write(&path, contents), &path);
+    }
+    if krates.is_empty() {
+        return Ok(());
+    }
+
+    let path = dst.join(&crates);
+    try_err!(fs::write(&path, crates_js(&krates)), &path);
+    if enable_index_page {
+        let layout = layout::Layout {
+            logo: String::new(),
+            favicon: String::new(),
+            external_html: ExternalHtml::default(),
+            default_settings: Default::default(),
+            krate: String::new(),
+            krate_version: String::new(),
+            css_file_extension: None,
+            scrape_examples_extension: false,
+        };
+        let path = dst.join("index.html");
+        let v = crate_list_page(&layout, None, resource_suffix, &[], &krates);
+        try_err!(fs::write(&path, v), &path);
+    }
+    Ok(())
+}
+
+/// Renders the `index.html` page listing all the documented crates.
+fn crate_list_page(
+    layout: &layout::Layout,
+    static_root_path: Option<&str>,
+    resource_suffix: &str,
+    style_files: &[StylePath],
+    krates: &[String],
+) -> String {
+    let page = layout::Page {
+        title: "Index of crates",
+        css_class: "mod sys",
+        root_path: "./",
+        static_root_path,
+        description: "List of crates",
+        resource_suffix,
+        rust_logo: true,
+    };
+
+    let content = format!(
+        "<h1>List of all crates</h1><ul class=\"all-items\">{}</ul>",
+        krates.iter().format_with("", |k, f| {
+            f(&format_args!(
+                "<li><a href=\"{trailing_slash}index.html\">{k}</a></li>",
+                trailing_slash = ensure_trailing_slash(k),
+            ))
+        })
+    );
+    layout::render(layout, &page, "", content, style_files)
+}
+
+fn search_index_js(all_indexes: &[String]) -> String {
+    // This needs to be `var`, not `const`.
+    // This variable needs declared in the current global scope so that if
+    // search.js loads first, it can pick it up.
+    let mut v = String::from("var searchIndex = new Map(JSON.parse('[\\\n");
+    v.push_str(&all_indexes.join(",\\\n"));
+    v.push_str(
+        r#"\
+]'));
+if (typeof exports !== 'undefined') exports.searchIndex = searchIndex;
+else if (window.initSearch) window.initSearch(searchIndex);
+"#,
+    );
+    v
+}
+
+fn src_files_js(all_sources: &[String]) -> String {
+    // This needs to be `var`, not `const`.
+    // This variable needs declared in the current global scope so that if
+    // src-script.js loads first, it can pick it up.
+    let mut v = String::from("var srcIndex = new Map(JSON.parse('[\\\n");
+    v.push_str(&all_sources.join(",\\\n"));
+    v.push_str("\\\n]'));\ncreateSrcSidebar();\n");
+    v
+}
+
+fn crates_js(krates: &[String]) -> String {
+    let krates = krates.iter().map(|k| format!("\"{k}\"")).join(",");
+    format!("window.ALL_CRATES = [{krates}];")
+}
+
+fn type_impls_js(all_impls: &[String]) -> String {
+    let mut v = String::from("(function() {var type_impls = {\n");
+    v.push_str(&all_impls.join(",\n"));
+    v.push_str("\n};");
+    v.push_str(
+        "if (window.register_type_impls) {\
+             window.register_type_impls(type_impls);\
+         } else {\
+             window.pending_type_impls = type_impls;\
+         }",
+    );
+    v.push_str("})()");
+    v
+}
+
+fn implementors_js(all_implementors: &[String]) -> String {
+    let mut v = String::from("(function() {var implementors = {\n");
+    v.push_str(&all_implementors.join(",\n"));
+    v.push_str("\n};");
+    v.push_str(
+        "if (window.register_implementors) {\
+             window.register_implementors(implementors);\
+         } else {\
+             window.pending_implementors = implementors;\
+         }",
+    );
+    v.push_str("})()");
+    v
+}
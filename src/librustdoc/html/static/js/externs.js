@@ -241,6 +241,11 @@ let FunctionType;
  * `p` is a list of path/type pairs. It is used for parents and function parameters.
  *
  * `c` is an array of item indices that are deprecated.
+ *
+ * `w` is only present with `--full-text-search`. It maps the stems of the words of the
+ * documentation to the items whose documentation contains them, as an array of offsets
+ * into the n/t/d/q/i/f arrays. The first offset is absolute, and each of the next ones is
+ * relative to the previous one.
  * @typedef {{
  *   doc: string,
  *   a: Object,
@@ -252,7 +257,8 @@ let FunctionType;
  *   f: string,
  *   p: Array<Object>,
  *   b: Array<[Number, String]>,
- *   c: Array<Number>
+ *   c: Array<Number>,
+ *   w: Object
  * }}
  */
 let RawSearchIndexCrate;
@@ -227,6 +227,79 @@ function editDistance(a, b, limit) {
     return editDistanceState.calculate(a, b, limit);
 }
 
+// Must be kept in sync with `PROSE_STOP_WORDS` in `search_index.rs`.
+const PROSE_STOP_WORDS = new Set([
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "for", "from", "if", "in", "is", "it",
+    "its", "not", "of", "on", "or", "that", "the", "this", "to", "was", "when", "which", "will",
+    "with",
+]);
+
+/**
+ * Returns the terms of the full-text index that a query looks up, in the same way as
+ * `prose_terms` in `search_index.rs` does for the documentation of items.
+ *
+ * @param {string} text
+ * @returns {Array<string>}
+ */
+function proseTerms(text) {
+    const terms = new Set();
+    for (const word of text.toLowerCase().split(/[\s!-\/:-@\[-`{-~]+/)) {
+        if ([...word].length <= 1 || /^[0-9]+$/.test(word) || PROSE_STOP_WORDS.has(word)) {
+            continue;
+        }
+        terms.add(stemProseWord(word));
+    }
+    return [...terms];
+}
+
+/**
+ * Strips the common suffixes of English words. Must be kept in sync with `stem` in
+ * `search_index.rs`.
+ *
+ * @param {string} word
+ * @returns {string}
+ */
+function stemProseWord(word) {
+    const suffixes = [
+        ["sses", "ss"],
+        ["ies", "y"],
+        ["ied", "y"],
+        ["xes", "x"],
+        ["ches", "ch"],
+        ["shes", "sh"],
+        ["ing", ""],
+        ["ed", ""],
+        ["s", ""],
+    ];
+    let stem = word;
+    for (const [suffix, replacement] of suffixes) {
+        if (!word.endsWith(suffix)) {
+            continue;
+        }
+        const root = word.slice(0, word.length - suffix.length);
+        // Keep "bus" and "class", or "sing" and "bed".
+        if ([...root].length < 3 ||
+            (suffix === "s" && (root.endsWith("s") || root.endsWith("u")))) {
+            continue;
+        }
+        stem = root + replacement;
+        // "stopped" and "running" become "stop" and "run".
+        if (suffix === "ing" || suffix === "ed") {
+            const last = stem[stem.length - 1];
+            if (last === stem[stem.length - 2] && /^[a-z]$/.test(last) &&
+                !"aeioulsz".includes(last)) {
+                stem = stem.slice(0, -1);
+            }
+        }
+        break;
+    }
+    // "parse" and "parsed" become the same term.
+    if ([...stem].length > 4 && stem.endsWith("e")) {
+        stem = stem.slice(0, -1);
+    }
+    return stem;
+}
+
 function initSearch(rawSearchIndex) {
     const MAX_RESULTS = 200;
     const NO_TYPE_FILTER = -1;
@@ -247,6 +320,14 @@ function initSearch(rawSearchIndex) {
      */
     let typeNameIdMap;
     const ALIASES = new Map();
+    /**
+     * Full-text index of the documentation of each crate, from the stems of the words of the
+     * documentation to the positions in `searchIndex` of the items whose documentation
+     * contains them. Empty unless the docs were generated with `--full-text-search`.
+     *
+     * @type {Map<string, Map<string, Array<integer>>>}
+     */
+    const PROSE_INDEX = new Map();
 
     /**
      * Special type name IDs for searching by array.
@@ -1974,6 +2055,74 @@ if (parserState.userQuery[parserState.pos] === "[") {
             crateAliases.forEach(pushFunc);
         }
 
+        /**
+         * Adds the items whose documentation contains every word of the query to the "others"
+         * results, after the items that were found by name.
+         *
+         * @param {ResultsTable} ret
+         * @param {string} query
+         * @param {string|null} filterCrates
+         * @param {string} currentCrate
+         */
+        function handleProse(ret, query, filterCrates, currentCrate) {
+            const terms = proseTerms(query);
+            if (terms.length === 0 || ret.others.length >= MAX_RESULTS) {
+                return;
+            }
+            const found = [];
+            for (const [crate, crateProse] of PROSE_INDEX) {
+                if (filterCrates !== null && crate !== filterCrates) {
+                    continue;
+                }
+                // Start from the rarest term, so that there are few items left to check.
+                const postings = terms.map(term => crateProse.get(term) || []);
+                postings.sort((a, b) => a.length - b.length);
+                const others = postings.slice(1).map(items => new Set(items));
+                for (const id of postings[0]) {
+                    if (others.every(items => items.has(id))) {
+                        found.push(searchIndex[id]);
+                    }
+                }
+            }
+
+            found.sort((aaa, bbb) => {
+                // sort by crate (current crate comes first)
+                let a = (aaa.crate !== currentCrate);
+                let b = (bbb.crate !== currentCrate);
+                if (a !== b) {
+                    return a - b;
+                }
+
+                // sort deprecated items later
+                a = aaa.deprecated;
+                b = bbb.deprecated;
+                if (a !== b) {
+                    return a - b;
+                }
+
+                // sort by path, then by name
+                a = aaa.path + "::" + aaa.name;
+                b = bbb.path + "::" + bbb.name;
+                return a < b ? -1 : (a > b ? 1 : 0);
+            });
+
+            const alreadyFound = new Set(ret.others.map(item => item.fullPath));
+            for (const item of found) {
+                const res = buildHrefAndPath(item);
+                item.displayPath = pathSplitter(res[0]);
+                item.fullPath = item.displayPath + item.name + "|" + item.ty;
+                item.href = res[1];
+                if (alreadyFound.has(item.fullPath)) {
+                    continue;
+                }
+                alreadyFound.add(item.fullPath);
+                ret.others.push(item);
+                if (ret.others.length >= MAX_RESULTS) {
+                    break;
+                }
+            }
+        }
+
         /**
          * This function adds the given result into the provided `results` map if it matches the
          * following condition:
@@ -2325,8 +2474,10 @@ if (parserState.userQuery[parserState.pos] === "[") {
             sortResults(results_others, false, currentCrate),
             parsedQuery);
         handleAliases(ret, parsedQuery.original.replace(/"/g, ""), filterCrates, currentCrate);
+        handleProse(ret, parsedQuery.original, filterCrates, currentCrate);
         if (parsedQuery.error !== null && ret.others.length !== 0) {
-            // It means some doc aliases were found so let's "remove" the error!
+            // It means some doc aliases or documentation matching the query were found so let's
+            // "remove" the error!
             ret.query.error = null;
         }
         return ret;
@@ -3170,6 +3321,9 @@ ${item.displayPath}<span class="${type}">${name}</span>\
             // an array of [(String) alias name
             //             [Number] index to items]
             const aliases = crateCorpus.a;
+            // an object mapping (String) stems of words to [Number] delta-encoded
+            // indexes to items
+            const prose = crateCorpus.w;
 
             // an array of [{name: String, ty: Number}]
             const lowercasePaths = [];
@@ -3266,6 +3420,18 @@ ${item.displayPath}<span class="${type}">${name}</span>\
                     }
                 }
             }
+
+            if (prose) {
+                const currentCrateProse = new Map();
+                PROSE_INDEX.set(crate, currentCrateProse);
+                for (const term of Object.keys(prose)) {
+                    let index = currentIndex;
+                    currentCrateProse.set(term, prose[term].map(delta => {
+                        index += delta;
+                        return index;
+                    }));
+                }
+            }
             currentIndex += itemTypes.length;
         }
         // Drop the (rather large) hash table used for reusing function items
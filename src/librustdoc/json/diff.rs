@@ -0,0 +1,798 @@
+//! Comparison of the public API of two versions of a crate.
+//!
+//! This is what `--api-diff` uses: it reads two blobs written by the JSON backend, collects every
+//! item that is reachable from the root of each crate together with a plain-text rendering of its
+//! declaration, and reports which items were added, removed or changed between them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use rustdoc_json_types::{
+    Abi, Crate, FnDecl, Function, GenericArg, GenericArgs, GenericBound, GenericParamDef,
+    GenericParamDefKind, Generics, Header, Id, Impl, Import, Item, ItemEnum, MacroKind, Module,
+    PolyTrait, StructKind, Term, TraitBoundModifier, Type, TypeBinding, TypeBindingKind,
+    VariantKind, Visibility, WherePredicate, FORMAT_VERSION,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod tests;
+
+/// A public item of a crate, as far as its API is concerned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ApiItem {
+    /// The declaration of the item, without its body or documentation.
+    signature: String,
+    /// The bounds the item puts on its generic parameters, one per entry, e.g. `T: Clone`.
+    bounds: Vec<String>,
+}
+
+/// The changes to the public API of a crate.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct ApiDiff {
+    pub(crate) added: Vec<Change>,
+    pub(crate) removed: Vec<Change>,
+    pub(crate) changed: Vec<Change>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct Change {
+    /// The path the item can be reached by, e.g. `std::vec::Vec::push`.
+    pub(crate) path: String,
+    pub(crate) kind: &'static str,
+    /// The declaration of the item in the old version, if it exists there.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) old: Option<String>,
+    /// The declaration of the item in the new version, if it exists there.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) new: Option<String>,
+    /// The bounds of a changed item that the old version didn't have.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) added_bounds: Vec<String>,
+}
+
+/// Compares the JSON documentation of two versions of a crate and prints the changes to stdout,
+/// either as text meant for humans or as JSON if `json` is set.
+pub(crate) fn run(old: &Path, new: &Path, json: bool) -> Result<(), String> {
+    let old = load(old)?;
+    let new = load(new)?;
+    let diff = diff(&old, &new);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())?);
+    } else {
+        print!("{}", diff.to_text());
+    }
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Crate, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read `{}`: {e}", path.display()))?;
+
+    // Check the version on its own first: a blob written by another version of rustdoc most
+    // likely fails to deserialize, and "missing field" isn't a helpful error for that.
+    #[derive(Deserialize)]
+    struct Version {
+        format_version: u32,
+    }
+    let Version { format_version } = serde_json::from_str(&contents)
+        .map_err(|e| format!("`{}` isn't rustdoc JSON output: {e}", path.display()))?;
+    if format_version != FORMAT_VERSION {
+        return Err(format!(
+            "`{}` has format version {format_version}, but this rustdoc only supports version \
+             {FORMAT_VERSION}; regenerate it with this rustdoc",
+            path.display(),
+        ));
+    }
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("`{}` isn't rustdoc JSON output: {e}", path.display()))
+}
+
+pub(crate) fn diff(old: &Crate, new: &Crate) -> ApiDiff {
+    let old = collect(old);
+    let new = collect(new);
+    let mut diff = ApiDiff::default();
+    for ((path, kind), new_item) in &new {
+        let change = |old: Option<&ApiItem>, added_bounds| Change {
+            path: path.clone(),
+            kind: *kind,
+            old: old.map(|old| old.signature.clone()),
+            new: Some(new_item.signature.clone()),
+            added_bounds,
+        };
+        match old.get(&(path.clone(), *kind)) {
+            None => diff.added.push(change(None, Vec::new())),
+            Some(old_item) if old_item != new_item => {
+                let added_bounds = new_item
+                    .bounds
+                    .iter()
+                    .filter(|bound| !old_item.bounds.contains(bound))
+                    .cloned()
+                    .collect();
+                diff.changed.push(change(Some(old_item), added_bounds));
+            }
+            Some(_) => {}
+        }
+    }
+    for ((path, kind), old_item) in &old {
+        if !new.contains_key(&(path.clone(), *kind)) {
+            diff.removed.push(Change {
+                path: path.clone(),
+                kind: *kind,
+                old: Some(old_item.signature.clone()),
+                new: None,
+                added_bounds: Vec::new(),
+            });
+        }
+    }
+    diff
+}
+
+impl ApiDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        if self.is_empty() {
+            return "No changes to the public API.\n".to_string();
+        }
+        let mut out = format!(
+            "{} removed, {} added, {} changed\n",
+            self.removed.len(),
+            self.added.len(),
+            self.changed.len(),
+        );
+        for (title, changes) in
+            [("Removed", &self.removed), ("Added", &self.added), ("Changed", &self.changed)]
+        {
+            if changes.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{title}:\n"));
+            for change in changes {
+                out.push_str(&format!("    {} {}\n", change.kind, change.path));
+                match (&change.old, &change.new) {
+                    (Some(old), Some(new)) => {
+                        out.push_str(&format!("        old: {old}\n        new: {new}\n"));
+                    }
+                    (Some(signature), None) | (None, Some(signature)) => {
+                        out.push_str(&format!("        {signature}\n"));
+                    }
+                    (None, None) => {}
+                }
+                for bound in &change.added_bounds {
+                    out.push_str(&format!("        added bound: {bound}\n"));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Returns the public items of `krate` by path and kind.
+///
+/// The kind is part of the key because items of different namespaces can share a path, like a
+/// function and the macro of the same name.
+fn collect(krate: &Crate) -> BTreeMap<(String, &'static str), ApiItem> {
+    let mut collector = Collector { krate, items: BTreeMap::new(), modules: Vec::new() };
+    let root = &krate.index[&krate.root];
+    if let ItemEnum::Module(ref module) = root.inner {
+        collector.module(root.name.as_deref().unwrap_or_default(), &root.id, module);
+    }
+    collector.items
+}
+
+struct Collector<'a> {
+    krate: &'a Crate,
+    items: BTreeMap<(String, &'static str), ApiItem>,
+    /// The modules being walked, so that a module re-exporting one of its parents doesn't make
+    /// the walk loop forever.
+    modules: Vec<&'a Id>,
+}
+
+impl<'a> Collector<'a> {
+    fn insert(&mut self, path: String, kind: &'static str, signature: String, bounds: Vec<String>) {
+        self.items.insert((path, kind), ApiItem { signature, bounds });
+    }
+
+    fn module(&mut self, path: &str, id: &'a Id, module: &'a Module) {
+        if self.modules.contains(&id) {
+            return;
+        }
+        self.modules.push(id);
+        let krate = self.krate;
+        for item in module.items.iter().filter_map(|id| public_item(krate, id)) {
+            match item.inner {
+                ItemEnum::Import(ref import) => self.import(path, import),
+                ItemEnum::ExternCrate { .. } | ItemEnum::Impl(_) => {}
+                _ => {
+                    if let Some(ref name) = item.name {
+                        self.item(format!("{path}::{name}"), name, item);
+                    }
+                }
+            }
+        }
+        self.modules.pop();
+    }
+
+    fn import(&mut self, path: &str, import: &'a Import) {
+        let target = import.id.as_ref().and_then(|id| self.krate.index.get(id));
+        match (target, import.glob) {
+            (Some(target), true) => match target.inner {
+                ItemEnum::Module(ref module) => self.module(path, &target.id, module),
+                ItemEnum::Enum(ref e) => {
+                    let krate = self.krate;
+                    for variant in e.variants.iter().filter_map(|id| public_item(krate, id)) {
+                        if let Some(ref name) = variant.name {
+                            self.item(format!("{path}::{name}"), name, variant);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            (Some(target), false) => {
+                self.item(format!("{path}::{}", import.name), &import.name, target)
+            }
+            // The target isn't documented in this crate, so all we know about it is its path.
+            (None, true) => {
+                let signature = format!("pub use {}::*;", import.source);
+                self.insert(format!("{path}::{}::*", import.source), "use", signature, Vec::new());
+            }
+            (None, false) => {
+                let signature = if import.source.rsplit("::").next() == Some(&import.name[..]) {
+                    format!("pub use {};", import.source)
+                } else {
+                    format!("pub use {} as {};", import.source, import.name)
+                };
+                self.insert(format!("{path}::{}", import.name), "use", signature, Vec::new());
+            }
+        }
+    }
+
+    /// Adds `item`, which is reachable by `path` under `name`, and the items inside of it.
+    fn item(&mut self, path: String, name: &str, item: &'a Item) {
+        let vis = if item.visibility == Visibility::Public { "pub " } else { "" };
+        match item.inner {
+            ItemEnum::Module(ref module) => {
+                // The items of stripped modules are only reachable through re-exports.
+                if !module.is_stripped {
+                    self.insert(path.clone(), "mod", format!("{vis}mod {name};"), Vec::new());
+                    self.module(&path, &item.id, module);
+                }
+            }
+            ItemEnum::Struct(ref s) => {
+                let body = match s.kind {
+                    StructKind::Unit => ";".to_string(),
+                    StructKind::Tuple(ref fields) => {
+                        format!("({}){};", self.tuple_fields(fields), where_clause(&s.generics))
+                    }
+                    StructKind::Plain { ref fields, fields_stripped } => {
+                        self.fields(&path, fields);
+                        format!(
+                            "{} {{ {} }}",
+                            where_clause(&s.generics),
+                            fields_body(fields_stripped)
+                        )
+                    }
+                };
+                let signature = format!("{vis}struct {name}{}{body}", generics(&s.generics));
+                self.insert(path.clone(), "struct", signature, generic_bounds(&s.generics));
+                self.impls(&path, &s.impls);
+            }
+            ItemEnum::Union(ref u) => {
+                self.fields(&path, &u.fields);
+                let signature = format!(
+                    "{vis}union {name}{}{} {{ {} }}",
+                    generics(&u.generics),
+                    where_clause(&u.generics),
+                    fields_body(u.fields_stripped),
+                );
+                self.insert(path.clone(), "union", signature, generic_bounds(&u.generics));
+                self.impls(&path, &u.impls);
+            }
+            ItemEnum::Enum(ref e) => {
+                let krate = self.krate;
+                for variant in e.variants.iter().filter_map(|id| public_item(krate, id)) {
+                    if let Some(ref variant_name) = variant.name {
+                        self.item(format!("{path}::{variant_name}"), variant_name, variant);
+                    }
+                }
+                let signature = format!(
+                    "{vis}enum {name}{}{}",
+                    generics(&e.generics),
+                    where_clause(&e.generics),
+                );
+                self.insert(path.clone(), "enum", signature, generic_bounds(&e.generics));
+                self.impls(&path, &e.impls);
+            }
+            ItemEnum::Variant(ref v) => {
+                let mut signature = match v.kind {
+                    VariantKind::Plain => name.to_string(),
+                    VariantKind::Tuple(ref fields) => {
+                        format!("{name}({})", self.tuple_fields(fields))
+                    }
+                    VariantKind::Struct { ref fields, fields_stripped } => {
+                        self.fields(&path, fields);
+                        format!("{name} {{ {} }}", fields_body(fields_stripped))
+                    }
+                };
+                if let Some(ref discriminant) = v.discriminant {
+                    signature.push_str(&format!(" = {}", discriminant.expr));
+                }
+                self.insert(path, "variant", signature, Vec::new());
+            }
+            ItemEnum::StructField(ref ty) => {
+                self.insert(path, "field", format!("{vis}{name}: {}", type_(ty)), Vec::new());
+            }
+            ItemEnum::Function(ref f) => {
+                let signature = function(vis, name, f);
+                self.insert(path, "fn", signature, generic_bounds(&f.generics));
+            }
+            ItemEnum::Trait(ref t) => {
+                let krate = self.krate;
+                for trait_item in t.items.iter().filter_map(|id| public_item(krate, id)) {
+                    if let Some(ref item_name) = trait_item.name {
+                        self.assoc_item(format!("{path}::{item_name}"), item_name, trait_item);
+                    }
+                }
+                let supertraits = if t.bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", bounds(&t.bounds))
+                };
+                let signature = format!(
+                    "{vis}{}{}trait {name}{}{supertraits}{}",
+                    if t.is_unsafe { "unsafe " } else { "" },
+                    if t.is_auto { "auto " } else { "" },
+                    generics(&t.generics),
+                    where_clause(&t.generics),
+                );
+                let mut item_bounds = generic_bounds(&t.generics);
+                item_bounds.extend(t.bounds.iter().map(|b| format!("Self: {}", bound(b))));
+                self.insert(path, "trait", signature, item_bounds);
+            }
+            ItemEnum::TraitAlias(ref t) => {
+                let signature = format!(
+                    "{vis}trait {name}{} = {}{};",
+                    generics(&t.generics),
+                    bounds(&t.params),
+                    where_clause(&t.generics),
+                );
+                self.insert(path, "trait alias", signature, generic_bounds(&t.generics));
+            }
+            ItemEnum::TypeAlias(ref t) => {
+                let signature = format!(
+                    "{vis}type {name}{}{} = {};",
+                    generics(&t.generics),
+                    where_clause(&t.generics),
+                    type_(&t.type_),
+                );
+                self.insert(path, "type", signature, generic_bounds(&t.generics));
+            }
+            ItemEnum::OpaqueTy(ref t) => {
+                let signature = format!(
+                    "{vis}type {name}{}{} = impl {};",
+                    generics(&t.generics),
+                    where_clause(&t.generics),
+                    bounds(&t.bounds),
+                );
+                self.insert(path, "type", signature, generic_bounds(&t.generics));
+            }
+            ItemEnum::Constant(ref c) => {
+                let signature = format!("{vis}const {name}: {};", type_(&c.type_));
+                self.insert(path, "const", signature, Vec::new());
+            }
+            ItemEnum::Static(ref s) => {
+                let signature = format!(
+                    "{vis}static {}{name}: {};",
+                    if s.mutable { "mut " } else { "" },
+                    type_(&s.type_),
+                );
+                self.insert(path, "static", signature, Vec::new());
+            }
+            ItemEnum::ForeignType => {
+                self.insert(path, "extern type", format!("{vis}type {name};"), Vec::new());
+            }
+            ItemEnum::Macro(ref source) => {
+                self.insert(path, "macro", source.clone(), Vec::new());
+            }
+            ItemEnum::ProcMacro(ref m) => {
+                let signature = match m.kind {
+                    MacroKind::Bang => format!("{name}!() {{ /* proc-macro */ }}"),
+                    MacroKind::Attr => format!("#[{name}]"),
+                    MacroKind::Derive if m.helpers.is_empty() => format!("#[derive({name})]"),
+                    MacroKind::Derive => {
+                        format!("#[derive({name})] // helpers: {}", m.helpers.join(", "))
+                    }
+                };
+                self.insert(path, "macro", signature, Vec::new());
+            }
+            ItemEnum::AssocConst { .. } | ItemEnum::AssocType { .. } => {
+                self.assoc_item(path, name, item)
+            }
+            ItemEnum::Import(_)
+            | ItemEnum::ExternCrate { .. }
+            | ItemEnum::Impl(_)
+            | ItemEnum::Primitive(_) => {}
+        }
+    }
+
+    /// Adds an item of a trait or an inherent impl.
+    fn assoc_item(&mut self, path: String, name: &str, item: &'a Item) {
+        let vis = if item.visibility == Visibility::Public { "pub " } else { "" };
+        match item.inner {
+            ItemEnum::Function(ref f) => {
+                // Whether a trait method has a default matters to implementors.
+                let body = if f.has_body { " { ... }" } else { ";" };
+                let signature = format!("{}{body}", function(vis, name, f));
+                self.insert(path, "fn", signature, generic_bounds(&f.generics));
+            }
+            ItemEnum::AssocConst { type_: ref ty, ref default } => {
+                let default = default.as_ref().map(|d| format!(" = {d}")).unwrap_or_default();
+                let signature = format!("{vis}const {name}: {}{default};", type_(ty));
+                self.insert(path, "assoc const", signature, Vec::new());
+            }
+            ItemEnum::AssocType { generics: ref g, bounds: ref b, ref default } => {
+                let item_bounds =
+                    if b.is_empty() { String::new() } else { format!(": {}", bounds(b)) };
+                let default =
+                    default.as_ref().map(|d| format!(" = {}", type_(d))).unwrap_or_default();
+                let signature = format!(
+                    "{vis}type {name}{}{item_bounds}{}{default};",
+                    generics(g),
+                    where_clause(g)
+                );
+                let mut all_bounds = generic_bounds(g);
+                all_bounds.extend(b.iter().map(|b| format!("Self::{name}: {}", bound(b))));
+                self.insert(path, "assoc type", signature, all_bounds);
+            }
+            _ => {}
+        }
+    }
+
+    fn fields(&mut self, path: &str, fields: &'a [Id]) {
+        let krate = self.krate;
+        for field in fields.iter().filter_map(|id| public_item(krate, id)) {
+            if let Some(ref name) = field.name {
+                self.item(format!("{path}::{name}"), name, field);
+            }
+        }
+    }
+
+    fn tuple_fields(&self, fields: &[Option<Id>]) -> String {
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|id| match id.as_ref().and_then(|id| public_item(self.krate, id)) {
+                Some(Item { visibility, inner: ItemEnum::StructField(ty), .. }) => {
+                    let vis = if *visibility == Visibility::Public { "pub " } else { "" };
+                    format!("{vis}{}", type_(ty))
+                }
+                _ => "_".to_string(),
+            })
+            .collect();
+        fields.join(", ")
+    }
+
+    /// Adds the methods of the inherent impls and the headers of the trait impls of the type at
+    /// `path`.
+    fn impls(&mut self, path: &str, impls: &'a [Id]) {
+        let krate = self.krate;
+        for item in impls.iter().filter_map(|id| krate.index.get(id)) {
+            let ItemEnum::Impl(ref impl_) = item.inner else { continue };
+            // Blanket impls come from the traits' crates, not from this one.
+            if impl_.blanket_impl.is_some() {
+                continue;
+            }
+            match impl_.trait_ {
+                None => {
+                    for impl_item in impl_.items.iter().filter_map(|id| public_item(krate, id)) {
+                        if let Some(ref name) = impl_item.name {
+                            self.assoc_item(format!("{path}::{name}"), name, impl_item);
+                        }
+                    }
+                }
+                Some(ref trait_) => {
+                    let key = format!(
+                        "impl {}{} for {}",
+                        if impl_.negative { "!" } else { "" },
+                        self::path(trait_),
+                        type_(&impl_.for_),
+                    );
+                    self.insert(key, "impl", impl_header(impl_), generic_bounds(&impl_.generics));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the item `id` of `krate`, unless it's private.
+///
+/// Items of traits and enum variants have the visibility of their parent, which is `Default`.
+fn public_item<'a>(krate: &'a Crate, id: &Id) -> Option<&'a Item> {
+    krate
+        .index
+        .get(id)
+        .filter(|item| matches!(item.visibility, Visibility::Public | Visibility::Default))
+}
+
+fn fields_body(fields_stripped: bool) -> &'static str {
+    if fields_stripped { "/* private fields */" } else { ".." }
+}
+
+fn impl_header(impl_: &Impl) -> String {
+    let trait_ = impl_.trait_.as_ref().map(path).unwrap_or_default();
+    format!(
+        "{}impl{} {}{trait_} for {}{}",
+        if impl_.is_unsafe { "unsafe " } else { "" },
+        generics(&impl_.generics),
+        if impl_.negative { "!" } else { "" },
+        type_(&impl_.for_),
+        where_clause(&impl_.generics),
+    )
+}
+
+fn function(vis: &str, name: &str, f: &Function) -> String {
+    format!(
+        "{vis}{}fn {name}{}{}{}",
+        header(&f.header),
+        generics(&f.generics),
+        fn_decl(&f.decl),
+        where_clause(&f.generics),
+    )
+}
+
+fn header(header: &Header) -> String {
+    format!(
+        "{}{}{}{}",
+        if header.const_ { "const " } else { "" },
+        if header.async_ { "async " } else { "" },
+        if header.unsafe_ { "unsafe " } else { "" },
+        abi(&header.abi),
+    )
+}
+
+fn abi(abi: &Abi) -> String {
+    let (name, unwind) = match *abi {
+        Abi::Rust => return String::new(),
+        Abi::C { unwind } => ("C", unwind),
+        Abi::Cdecl { unwind } => ("cdecl", unwind),
+        Abi::Stdcall { unwind } => ("stdcall", unwind),
+        Abi::Fastcall { unwind } => ("fastcall", unwind),
+        Abi::Aapcs { unwind } => ("aapcs", unwind),
+        Abi::Win64 { unwind } => ("win64", unwind),
+        Abi::SysV64 { unwind } => ("sysv64", unwind),
+        Abi::System { unwind } => ("system", unwind),
+        Abi::Other(ref name) => return format!("extern \"{name}\" "),
+    };
+    if unwind { format!("extern \"{name}-unwind\" ") } else { format!("extern \"{name}\" ") }
+}
+
+fn fn_decl(decl: &FnDecl) -> String {
+    let mut inputs: Vec<_> = decl
+        .inputs
+        .iter()
+        .map(|(name, ty)| match (&name[..], ty) {
+            ("self", Type::Generic(self_)) if self_ == "Self" => "self".to_string(),
+            ("self", Type::BorrowedRef { lifetime, mutable, type_: self_ })
+                if **self_ == Type::Generic("Self".to_string()) =>
+            {
+                let lifetime = lifetime.as_ref().map(|l| format!("{l} ")).unwrap_or_default();
+                format!("&{lifetime}{}self", if *mutable { "mut " } else { "" })
+            }
+            ("", ty) => type_(ty),
+            (name, ty) => format!("{name}: {}", type_(ty)),
+        })
+        .collect();
+    if decl.c_variadic {
+        inputs.push("...".to_string());
+    }
+    let output = decl.output.as_ref().map(|ty| format!(" -> {}", type_(ty))).unwrap_or_default();
+    format!("({}){output}", inputs.join(", "))
+}
+
+fn generics(generics: &Generics) -> String {
+    let params: Vec<_> = generics
+        .params
+        .iter()
+        // `impl Trait` arguments are already shown in the argument list.
+        .filter(|param| !matches!(param.kind, GenericParamDefKind::Type { synthetic: true, .. }))
+        .map(generic_param)
+        .collect();
+    if params.is_empty() { String::new() } else { format!("<{}>", params.join(", ")) }
+}
+
+fn generic_param(param: &GenericParamDef) -> String {
+    let name = &param.name;
+    match param.kind {
+        GenericParamDefKind::Lifetime { ref outlives } if outlives.is_empty() => name.clone(),
+        GenericParamDefKind::Lifetime { ref outlives } => {
+            format!("{name}: {}", outlives.join(" + "))
+        }
+        GenericParamDefKind::Type { bounds: ref b, ref default, .. } => {
+            let mut param = name.clone();
+            if !b.is_empty() {
+                param.push_str(&format!(": {}", bounds(b)));
+            }
+            if let Some(default) = default {
+                param.push_str(&format!(" = {}", type_(default)));
+            }
+            param
+        }
+        GenericParamDefKind::Const { type_: ref ty, ref default } => {
+            let default = default.as_ref().map(|d| format!(" = {d}")).unwrap_or_default();
+            format!("const {name}: {}{default}", type_(ty))
+        }
+    }
+}
+
+fn where_clause(generics: &Generics) -> String {
+    if generics.where_predicates.is_empty() {
+        return String::new();
+    }
+    let predicates: Vec<_> = generics.where_predicates.iter().map(where_predicate).collect();
+    format!(" where {}", predicates.join(", "))
+}
+
+fn where_predicate(predicate: &WherePredicate) -> String {
+    match predicate {
+        WherePredicate::BoundPredicate { type_: ty, bounds: b, generic_params } => {
+            format!("{}{}: {}", higher_ranked(generic_params), type_(ty), bounds(b))
+        }
+        WherePredicate::RegionPredicate { lifetime, bounds: b } => {
+            format!("{lifetime}: {}", bounds(b))
+        }
+        WherePredicate::EqPredicate { lhs, rhs } => format!("{} = {}", type_(lhs), term(rhs)),
+    }
+}
+
+/// Returns every bound of `generics` on its own, whether it is declared on the parameter or in
+/// the where clause, so that moving a bound between the two doesn't count as adding one.
+fn generic_bounds(generics: &Generics) -> Vec<String> {
+    let mut out = Vec::new();
+    for param in &generics.params {
+        let name = &param.name;
+        match param.kind {
+            GenericParamDefKind::Lifetime { ref outlives } => {
+                out.extend(outlives.iter().map(|lifetime| format!("{name}: {lifetime}")));
+            }
+            GenericParamDefKind::Type { bounds: ref b, .. } => {
+                out.extend(b.iter().map(|b| format!("{name}: {}", bound(b))));
+            }
+            GenericParamDefKind::Const { .. } => {}
+        }
+    }
+    for predicate in &generics.where_predicates {
+        match predicate {
+            WherePredicate::BoundPredicate { type_: ty, bounds: b, generic_params } => {
+                let ty = format!("{}{}", higher_ranked(generic_params), type_(ty));
+                out.extend(b.iter().map(|b| format!("{ty}: {}", bound(b))));
+            }
+            WherePredicate::RegionPredicate { lifetime, bounds: b } => {
+                out.extend(b.iter().map(|b| format!("{lifetime}: {}", bound(b))));
+            }
+            WherePredicate::EqPredicate { .. } => out.push(where_predicate(predicate)),
+        }
+    }
+    out
+}
+
+fn higher_ranked(params: &[GenericParamDef]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let params: Vec<_> = params.iter().map(generic_param).collect();
+    format!("for<{}> ", params.join(", "))
+}
+
+fn bounds(bounds: &[GenericBound]) -> String {
+    let bounds: Vec<_> = bounds.iter().map(bound).collect();
+    bounds.join(" + ")
+}
+
+fn bound(bound: &GenericBound) -> String {
+    match bound {
+        GenericBound::TraitBound { trait_, generic_params, modifier } => {
+            let modifier = match modifier {
+                TraitBoundModifier::None => "",
+                TraitBoundModifier::Maybe => "?",
+                TraitBoundModifier::MaybeConst => "~const ",
+            };
+            format!("{modifier}{}{}", higher_ranked(generic_params), path(trait_))
+        }
+        GenericBound::Outlives(lifetime) => lifetime.clone(),
+    }
+}
+
+fn poly_trait(poly_trait: &PolyTrait) -> String {
+    format!("{}{}", higher_ranked(&poly_trait.generic_params), path(&poly_trait.trait_))
+}
+
+fn path(path: &rustdoc_json_types::Path) -> String {
+    format!("{}{}", path.name, path.args.as_deref().map(generic_args).unwrap_or_default())
+}
+
+fn generic_args(args: &GenericArgs) -> String {
+    match args {
+        GenericArgs::AngleBracketed { args, bindings } => {
+            if args.is_empty() && bindings.is_empty() {
+                return String::new();
+            }
+            let mut parts: Vec<_> = args.iter().map(generic_arg).collect();
+            parts.extend(bindings.iter().map(type_binding));
+            format!("<{}>", parts.join(", "))
+        }
+        GenericArgs::Parenthesized { inputs, output } => {
+            let inputs: Vec<_> = inputs.iter().map(type_).collect();
+            let output = output.as_ref().map(|ty| format!(" -> {}", type_(ty))).unwrap_or_default();
+            format!("({}){output}", inputs.join(", "))
+        }
+    }
+}
+
+fn generic_arg(arg: &GenericArg) -> String {
+    match arg {
+        GenericArg::Lifetime(lifetime) => lifetime.clone(),
+        GenericArg::Type(ty) => type_(ty),
+        GenericArg::Const(c) => c.expr.clone(),
+        GenericArg::Infer => "_".to_string(),
+    }
+}
+
+fn type_binding(binding: &TypeBinding) -> String {
+    let name = format!("{}{}", binding.name, generic_args(&binding.args));
+    match binding.binding {
+        TypeBindingKind::Equality(ref t) => format!("{name} = {}", term(t)),
+        TypeBindingKind::Constraint(ref b) => format!("{name}: {}", bounds(b)),
+    }
+}
+
+fn term(term: &Term) -> String {
+    match term {
+        Term::Type(ty) => type_(ty),
+        Term::Constant(c) => c.expr.clone(),
+    }
+}
+
+fn type_(ty: &Type) -> String {
+    match ty {
+        Type::ResolvedPath(p) => path(p),
+        Type::DynTrait(dyn_trait) => {
+            let mut bounds: Vec<_> = dyn_trait.traits.iter().map(poly_trait).collect();
+            bounds.extend(dyn_trait.lifetime.clone());
+            format!("dyn {}", bounds.join(" + "))
+        }
+        Type::Generic(name) | Type::Primitive(name) => name.clone(),
+        Type::FunctionPointer(f) => {
+            format!(
+                "{}{}fn{}",
+                higher_ranked(&f.generic_params),
+                header(&f.header),
+                fn_decl(&f.decl)
+            )
+        }
+        Type::Tuple(types) if types.len() == 1 => format!("({},)", type_(&types[0])),
+        Type::Tuple(types) => {
+            let types: Vec<_> = types.iter().map(type_).collect();
+            format!("({})", types.join(", "))
+        }
+        Type::Slice(ty) => format!("[{}]", type_(ty)),
+        Type::Array { type_: ty, len } => format!("[{}; {len}]", type_(ty)),
+        Type::ImplTrait(b) => format!("impl {}", bounds(b)),
+        Type::Infer => "_".to_string(),
+        Type::RawPointer { mutable, type_: ty } => {
+            format!("*{} {}", if *mutable { "mut" } else { "const" }, type_(ty))
+        }
+        Type::BorrowedRef { lifetime, mutable, type_: ty } => {
+            let lifetime = lifetime.as_ref().map(|l| format!("{l} ")).unwrap_or_default();
+            format!("&{lifetime}{}{}", if *mutable { "mut " } else { "" }, type_(ty))
+        }
+        Type::QualifiedPath { name, args, self_type, trait_ } => match trait_ {
+            Some(trait_) => {
+                format!("<{} as {}>::{name}{}", type_(self_type), path(trait_), generic_args(args))
+            }
+            None => format!("{}::{name}{}", type_(self_type), generic_args(args)),
+        },
+    }
+}
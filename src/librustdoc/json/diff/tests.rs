@@ -0,0 +1,211 @@
+use rustdoc_json_types::{
+    Crate, FnDecl, Function, GenericBound, GenericParamDef, GenericParamDefKind, Generics, Header,
+    Id, Item, ItemEnum, Module, Path, TraitBoundModifier, Type, Visibility, WherePredicate,
+    FORMAT_VERSION,
+};
+
+use super::{diff, ApiDiff, Change};
+
+fn item(id: &str, name: &str, inner: ItemEnum) -> Item {
+    Item {
+        id: Id(id.to_string()),
+        crate_id: 0,
+        name: Some(name.to_string()),
+        span: None,
+        visibility: Visibility::Public,
+        docs: None,
+        links: Default::default(),
+        attrs: Vec::new(),
+        deprecation: None,
+        inner,
+    }
+}
+
+fn krate(items: Vec<Item>) -> Crate {
+    let root = item(
+        "0:0",
+        "krate",
+        ItemEnum::Module(Module {
+            is_crate: true,
+            items: items.iter().map(|item| item.id.clone()).collect(),
+            is_stripped: false,
+        }),
+    );
+    let mut krate = Crate {
+        root: root.id.clone(),
+        crate_version: None,
+        includes_private: false,
+        index: Default::default(),
+        paths: Default::default(),
+        external_crates: Default::default(),
+        format_version: FORMAT_VERSION,
+    };
+    krate.index.insert(root.id.clone(), root);
+    krate.index.extend(items.into_iter().map(|item| (item.id.clone(), item)));
+    krate
+}
+
+fn function(id: &str, name: &str, inputs: &[(&str, Type)], generics: Generics) -> Item {
+    item(
+        id,
+        name,
+        ItemEnum::Function(Function {
+            decl: FnDecl {
+                inputs: inputs.iter().map(|(name, ty)| (name.to_string(), ty.clone())).collect(),
+                output: None,
+                c_variadic: false,
+            },
+            generics,
+            header: Header {
+                const_: false,
+                unsafe_: false,
+                async_: false,
+                abi: rustdoc_json_types::Abi::Rust,
+            },
+            has_body: true,
+        }),
+    )
+}
+
+fn no_generics() -> Generics {
+    Generics { params: Vec::new(), where_predicates: Vec::new() }
+}
+
+fn type_param(name: &str, bounds: Vec<GenericBound>) -> GenericParamDef {
+    GenericParamDef {
+        name: name.to_string(),
+        kind: GenericParamDefKind::Type { bounds, default: None, synthetic: false },
+    }
+}
+
+fn trait_bound(name: &str) -> GenericBound {
+    GenericBound::TraitBound {
+        trait_: Path { name: name.to_string(), id: Id(format!("1:{name}")), args: None },
+        generic_params: Vec::new(),
+        modifier: TraitBoundModifier::None,
+    }
+}
+
+fn u32() -> Type {
+    Type::Primitive("u32".to_string())
+}
+
+#[test]
+fn test_added_removed_changed() {
+    let old = krate(vec![
+        function("0:1", "same", &[], no_generics()),
+        function("0:2", "gone", &[], no_generics()),
+        function("0:3", "changed", &[("x", u32())], no_generics()),
+    ]);
+    let new = krate(vec![
+        function("0:4", "same", &[], no_generics()),
+        function("0:5", "changed", &[("x", u32()), ("y", u32())], no_generics()),
+        function("0:6", "added", &[], no_generics()),
+    ]);
+    let change = |path: &str, old: Option<&str>, new: Option<&str>| Change {
+        path: path.to_string(),
+        kind: "fn",
+        old: old.map(str::to_string),
+        new: new.map(str::to_string),
+        added_bounds: Vec::new(),
+    };
+    assert_eq!(
+        diff(&old, &new),
+        ApiDiff {
+            added: vec![change("krate::added", None, Some("pub fn added()"))],
+            removed: vec![change("krate::gone", Some("pub fn gone()"), None)],
+            changed: vec![change(
+                "krate::changed",
+                Some("pub fn changed(x: u32)"),
+                Some("pub fn changed(x: u32, y: u32)"),
+            )],
+        },
+    );
+    assert_eq!(diff(&old, &old), ApiDiff::default());
+}
+
+#[test]
+fn test_added_bounds() {
+    let generic = Type::Generic("T".to_string());
+    let old = krate(vec![
+        function(
+            "0:1",
+            "f",
+            &[("t", generic.clone())],
+            Generics { params: vec![type_param("T", vec![])], where_predicates: vec![] },
+        ),
+        function(
+            "0:2",
+            "g",
+            &[("t", generic.clone())],
+            Generics {
+                params: vec![type_param("T", vec![trait_bound("Clone")])],
+                where_predicates: vec![],
+            },
+        ),
+    ]);
+    let new = krate(vec![
+        function(
+            "0:1",
+            "f",
+            &[("t", generic.clone())],
+            Generics {
+                params: vec![type_param("T", vec![trait_bound("Clone")])],
+                where_predicates: vec![WherePredicate::BoundPredicate {
+                    type_: generic.clone(),
+                    bounds: vec![trait_bound("Send")],
+                    generic_params: vec![],
+                }],
+            },
+        ),
+        // Moving a bound to the where clause changes the signature, but adds no bound.
+        function(
+            "0:2",
+            "g",
+            &[("t", generic.clone())],
+            Generics {
+                params: vec![type_param("T", vec![])],
+                where_predicates: vec![WherePredicate::BoundPredicate {
+                    type_: generic.clone(),
+                    bounds: vec![trait_bound("Clone")],
+                    generic_params: vec![],
+                }],
+            },
+        ),
+    ]);
+    let diff = diff(&old, &new);
+    assert_eq!(
+        diff.changed,
+        vec![
+            Change {
+                path: "krate::f".to_string(),
+                kind: "fn",
+                old: Some("pub fn f<T>(t: T)".to_string()),
+                new: Some("pub fn f<T: Clone>(t: T) where T: Send".to_string()),
+                added_bounds: vec!["T: Clone".to_string(), "T: Send".to_string()],
+            },
+            Change {
+                path: "krate::g".to_string(),
+                kind: "fn",
+                old: Some("pub fn g<T: Clone>(t: T)".to_string()),
+                new: Some("pub fn g<T>(t: T) where T: Clone".to_string()),
+                added_bounds: vec![],
+            },
+        ],
+    );
+    assert_eq!(
+        diff.to_text(),
+        "0 removed, 0 added, 2 changed
+
+Changed:
+    fn krate::f
+        old: pub fn f<T>(t: T)
+        new: pub fn f<T: Clone>(t: T) where T: Send
+        added bound: T: Clone
+        added bound: T: Send
+    fn krate::g
+        old: pub fn g<T: Clone>(t: T)
+        new: pub fn g<T>(t: T) where T: Clone
+",
+    );
+}
@@ -5,6 +5,7 @@
 //! docs for usage and details.
 
 mod conversions;
+pub(crate) mod diff;
 mod import_finder;
 
 use std::cell::RefCell;
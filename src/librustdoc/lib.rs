@@ -118,6 +118,7 @@ macro_rules! map {
 mod json;
 pub(crate) mod lint;
 mod markdown;
+mod md;
 mod passes;
 mod scrape_examples;
 mod theme;
@@ -511,6 +512,27 @@ fn opts() -> Vec<RustcOptGroup> {
                 "calculate percentage of public items with documentation",
             )
         }),
+        unstable("coverage-items", |o| {
+            o.optflagmulti("", "coverage-items", "list each item counted by --show-coverage")
+        }),
+        unstable("min-coverage", |o| {
+            o.optopt(
+                "",
+                "min-coverage",
+                "fail if fewer items than this percentage are documented, \
+                 with --show-coverage",
+                "PERCENT",
+            )
+        }),
+        unstable("min-example-coverage", |o| {
+            o.optopt(
+                "",
+                "min-example-coverage",
+                "fail if fewer items than this percentage have examples, \
+                 with --show-coverage",
+                "PERCENT",
+            )
+        }),
         unstable("enable-per-target-ignores", |o| {
             o.optflagmulti(
                 "",
@@ -556,6 +578,20 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("no-run", |o| {
             o.optflagmulti("", "no-run", "Compile doctests without running them")
         }),
+        unstable("merge-doctests", |o| {
+            o.optflagmulti(
+                "",
+                "merge-doctests",
+                "Compile the doctests that allow it together, into a single test harness",
+            )
+        }),
+        unstable("check-doctest-output", |o| {
+            o.optflagmulti(
+                "",
+                "check-doctest-output",
+                "Check the standard output of doctests against the `output` blocks following them",
+            )
+        }),
         unstable("show-type-layout", |o| {
             o.optflagmulti("", "show-type-layout", "Include the memory layout of types in the docs")
         }),
@@ -596,6 +632,15 @@ fn opts() -> Vec<RustcOptGroup> {
                 "path to function call information (for displaying examples in the documentation)",
             )
         }),
+        unstable("api-diff", |o| {
+            o.optopt(
+                "",
+                "api-diff",
+                "compare the public API documented in the given JSON output with the one of the \
+                 JSON output given as input, and print the differences",
+                "OLD.json",
+            )
+        }),
         // deprecated / removed options
         unstable("disable-minification", |o| o.optflagmulti("", "disable-minification", "removed")),
         stable("plugin-path", |o| {
@@ -645,6 +690,30 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("html-no-source", |o| {
             o.optflag("", "html-no-source", "Disable HTML source code pages generation")
         }),
+        unstable("full-text-search", |o| {
+            o.optflag(
+                "",
+                "full-text-search",
+                "Add the words of the documentation to the search index, not only item names",
+            )
+        }),
+        unstable("merge-doc-dir", |o| {
+            o.optmulti(
+                "",
+                "merge-doc-dir",
+                "Merge the documentation that rustdoc wrote to PATH into the output directory, \
+                 instead of documenting a crate",
+                "PATH",
+            )
+        }),
+        unstable("assume-local-extern-docs", |o| {
+            o.optflag(
+                "",
+                "assume-local-extern-docs",
+                "Link to dependencies without a known documentation location as if they were \
+                 documented in the output directory",
+            )
+        }),
     ]
 }
 
@@ -819,6 +888,12 @@ fn main_args(
                     config::OutputFormat::Json => sess.time("render_json", || {
                         run_renderer::<json::JsonRenderer<'_>>(krate, render_opts, cache, tcx)
                     }),
+                    config::OutputFormat::Markdown => sess.time("render_markdown", || {
+                        run_renderer::<md::MarkdownRenderer<'_>>(krate, render_opts, cache, tcx)
+                    }),
+                    config::OutputFormat::Lcov => {
+                        unreachable!("lcov output format is only used with --show-coverage")
+                    }
                 }
             })
         })
@@ -196,6 +196,18 @@ macro_rules! declare_rustdoc_lint {
     "detects redundant explicit links in doc comments"
 }
 
+declare_rustdoc_lint! {
+    /// This lint is **allowed by default**. It detects inline code in documentation that looks
+    /// like a path, such as `` `Foo::bar` ``, but doesn't resolve. This usually means that the item
+    /// was renamed or removed. This is a `rustdoc` only lint, see the documentation in the
+    /// [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#unresolved_code_paths
+    UNRESOLVED_CODE_PATHS,
+    Allow,
+    "detects inline code that looks like a path but does not resolve"
+}
+
 pub(crate) static RUSTDOC_LINTS: Lazy<Vec<&'static Lint>> = Lazy::new(|| {
     vec![
         BROKEN_INTRA_DOC_LINKS,
@@ -209,6 +221,7 @@ macro_rules! declare_rustdoc_lint {
         MISSING_CRATE_LEVEL_DOCS,
         UNESCAPED_BACKTICKS,
         REDUNDANT_EXPLICIT_LINKS,
+        UNRESOLVED_CODE_PATHS,
     ]
 });
 
@@ -0,0 +1,461 @@
+//! Rustdoc's Markdown backend
+//!
+//! This module renders a crate as one Markdown file per module and item, laid out like the HTML
+//! output: `krate/index.md`, `krate/struct.Foo.md`, `krate/foo/index.md` and so on. Declarations
+//! are shown in Rust code blocks, and intra-doc links are rewritten to relative links between
+//! these files, so that the output can be browsed in any Markdown viewer.
+
+mod print;
+
+use std::fs::{self, create_dir_all};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::def_id::LOCAL_CRATE;
+use rustc_span::hygiene::MacroKind;
+use rustc_span::Symbol;
+
+use crate::clean::types::ExternalLocation;
+use crate::clean::{self, ItemKind, ItemLink, RenderedLink};
+use crate::config::RenderOptions;
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::formats::{FormatRenderer, Impl};
+use crate::html::format::{
+    href_relative_parts, join_with_double_colon, to_module_fqp, FormatContext, HrefError,
+};
+use crate::html::markdown::{plain_text_summary, rewrite_doc_markdown, HeadingOffset};
+use crate::html::render::{item_ty_to_section, ItemSection};
+use crate::md::print::Printer;
+use crate::try_err;
+
+#[derive(Clone)]
+pub(crate) struct MarkdownRenderer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    cache: Rc<Cache>,
+    /// The path of the module being rendered, starting with the crate name.
+    current: Vec<Symbol>,
+    /// The directory the pages of the module being rendered are written to.
+    dst: PathBuf,
+    /// Whether the module being rendered is stripped. Its items can still be reached through
+    /// re-exports, which are rendered where they are re-exported, so it gets no pages.
+    in_stripped_module: bool,
+}
+
+impl<'tcx> MarkdownRenderer<'tcx> {
+    fn write(&self, file_name: &str, contents: String) -> Result<(), Error> {
+        try_err!(create_dir_all(&self.dst), &self.dst);
+        let path = self.dst.join(file_name);
+        try_err!(fs::write(&path, contents), &path);
+        Ok(())
+    }
+
+    /// Returns the relative link from the pages of the current module to the page of `did`,
+    /// like `html::format::href` does for the HTML output.
+    ///
+    /// The pages of other crates are assumed to be Markdown as well if they are documented
+    /// locally, and HTML if they are documented at a remote URL.
+    fn href(&self, did: DefId) -> Option<String> {
+        let tcx = self.tcx;
+        let did = match tcx.def_kind(did) {
+            // Link to the struct or variant instead of its constructor.
+            DefKind::Ctor(..) => tcx.parent(did),
+            // Link to the crate itself, not the `extern crate` item.
+            DefKind::ExternCrate => match did.as_local() {
+                Some(local_did) => {
+                    tcx.extern_mod_stmt_cnum(local_did).unwrap_or(LOCAL_CRATE).as_def_id()
+                }
+                None => did,
+            },
+            _ => did,
+        };
+        let cache = &self.cache;
+        let (fqp, shortty, mut parts) = if let Some(&(ref fqp, shortty)) = cache.paths.get(&did) {
+            let module_fqp = to_module_fqp(shortty, fqp);
+            let parts: Vec<_> =
+                href_relative_parts(module_fqp, &self.current).map(|s| s.to_string()).collect();
+            (fqp, shortty, parts)
+        } else if let Some(&(ref fqp, shortty)) = cache.external_paths.get(&did) {
+            let module_fqp = to_module_fqp(shortty, fqp);
+            match cache.extern_locations[&did.krate] {
+                ExternalLocation::Remote(ref url) => {
+                    let mut parts = vec![url.trim_end_matches('/').to_string()];
+                    parts.extend(module_fqp.iter().map(|s| s.to_string()));
+                    parts.push(match shortty {
+                        ItemType::Module => "index.html".to_string(),
+                        _ => format!("{shortty}.{}.html", fqp.last().unwrap()),
+                    });
+                    return Some(parts.join("/"));
+                }
+                ExternalLocation::Local => {
+                    let parts: Vec<_> = href_relative_parts(module_fqp, &self.current)
+                        .map(|s| s.to_string())
+                        .collect();
+                    (fqp, shortty, parts)
+                }
+                ExternalLocation::Unknown => return None,
+            }
+        } else {
+            return None;
+        };
+        parts.push(match shortty {
+            ItemType::Module => "index.md".to_string(),
+            _ => format!("{shortty}.{}.md", fqp.last().unwrap()),
+        });
+        Some(parts.join("/"))
+    }
+
+    fn links(&self, item: &clean::Item) -> Vec<RenderedLink> {
+        let Some(links) = self.cache.intra_doc_links.get(&item.item_id) else { return vec![] };
+        links
+            .iter()
+            .filter_map(|ItemLink { link, link_text, page_id, fragment }| {
+                let mut href = self.href(*page_id)?;
+                if let Some(fragment) = fragment {
+                    fragment.render(&mut href, self.tcx);
+                }
+                Some(RenderedLink {
+                    original_text: link.clone(),
+                    new_text: link_text.clone(),
+                    href,
+                    tooltip: String::new(),
+                })
+            })
+            .collect()
+    }
+
+    /// Appends the documentation of `item`, followed by a blank line, to `page`.
+    fn document(&self, page: &mut String, item: &clean::Item, heading_offset: HeadingOffset) {
+        if let Some(doc) = item.opt_doc_value() {
+            let custom_code_classes_in_docs = self.tcx.features().custom_code_classes_in_docs;
+            let doc = rewrite_doc_markdown(
+                &doc,
+                &self.links(item),
+                heading_offset,
+                custom_code_classes_in_docs,
+            );
+            page.push_str(doc.trim_end());
+            page.push_str("\n\n");
+        }
+    }
+
+    fn module_page(&self, item: &clean::Item) -> String {
+        let title = if item.is_crate() { "Crate" } else { "Module" };
+        let mut page = format!("# {title} `{}`\n\n", join_with_double_colon(&self.current));
+        self.document(&mut page, item, HeadingOffset::H2);
+
+        let (ItemKind::StrippedItem(box ItemKind::ModuleItem(ref module))
+        | ItemKind::ModuleItem(ref module)) = *item.kind
+        else {
+            unreachable!()
+        };
+        let mut items: Vec<_> = module
+            .items
+            .iter()
+            .filter(|i| !i.is_stripped() && (i.name.is_some() || i.is_import()))
+            .collect();
+        // The same order as in the HTML output: by section, then by name.
+        let section_index = |i: &clean::Item| {
+            let section = item_ty_to_section(i.type_());
+            ItemSection::ALL.iter().position(|&s| s == section)
+        };
+        items.sort_by_cached_key(|i| (section_index(i), i.name.map(|name| name.to_string())));
+
+        let mut last_section = None;
+        for i in items {
+            let line = match *i.kind {
+                ItemKind::ImportItem(ref import) => {
+                    let mut printer = Printer::new(self);
+                    printer.import(i, import);
+                    let import_code = format!("`{}`", printer.finish());
+                    match import.source.did.and_then(|did| self.href(did)) {
+                        Some(href) => format!("[{import_code}]({href})"),
+                        None => import_code,
+                    }
+                }
+                ItemKind::ExternCrateItem { ref src } => match *src {
+                    Some(src) => format!("`extern crate {src} as {};`", i.name.unwrap()),
+                    None => format!("`extern crate {};`", i.name.unwrap()),
+                },
+                _ => {
+                    let name = i.name.unwrap();
+                    let href = match i.type_() {
+                        ItemType::Module => format!("{name}/index.md"),
+                        ty => format!("{ty}.{name}.md"),
+                    };
+                    let summary = plain_text_summary(&i.doc_value(), &i.link_names(&self.cache));
+                    if summary.is_empty() {
+                        format!("[`{name}`]({href})")
+                    } else {
+                        format!("[`{name}`]({href}): {summary}")
+                    }
+                }
+            };
+            let section = item_ty_to_section(i.type_());
+            if last_section != Some(section) {
+                if last_section.is_some() {
+                    page.push('\n');
+                }
+                page.push_str(&format!("## {}\n\n", section.name()));
+                last_section = Some(section);
+            }
+            page.push_str(&format!("- {line}\n"));
+        }
+        page
+    }
+
+    fn item_page(&self, item: &clean::Item) -> String {
+        let name = item.name.unwrap();
+        let title = item_type_title(item);
+        let mut page = match item.type_() {
+            ItemType::Primitive | ItemType::Keyword => format!("# {title} `{name}`\n\n"),
+            _ => format!("# {title} `{}::{name}`\n\n", join_with_double_colon(&self.current)),
+        };
+
+        let mut printer = Printer::new(self);
+        printer.item_decl(item);
+        let decl = printer.finish();
+        if !decl.is_empty() {
+            page.push_str(&format!("```rust\n{decl}\n```\n\n"));
+        }
+        self.document(&mut page, item, HeadingOffset::H2);
+
+        match *item.kind {
+            ItemKind::StructItem(clean::Struct { ref fields, .. })
+            | ItemKind::UnionItem(clean::Union { ref fields, .. }) => {
+                self.members(&mut page, "Fields", fields.iter());
+                self.impls(&mut page, item);
+            }
+            ItemKind::EnumItem(ref e) => {
+                self.members(&mut page, "Variants", e.variants.iter());
+                self.impls(&mut page, item);
+            }
+            ItemKind::TraitItem(ref t) => {
+                let items = |f: fn(&clean::Item) -> bool| t.items.iter().filter(move |i| f(i));
+                let sections = [
+                    ("Required Associated Types", items(clean::Item::is_ty_associated_type)),
+                    ("Provided Associated Types", items(clean::Item::is_associated_type)),
+                    ("Required Associated Constants", items(clean::Item::is_ty_associated_const)),
+                    ("Provided Associated Constants", items(clean::Item::is_associated_const)),
+                    ("Required Methods", items(clean::Item::is_ty_method)),
+                    ("Provided Methods", items(clean::Item::is_method)),
+                ];
+                for (title, items) in sections {
+                    self.members(&mut page, title, items);
+                }
+                if let Some(implementors) = self.cache.implementors.get(&t.def_id) {
+                    self.impl_list(&mut page, "Implementors", implementors.iter());
+                }
+            }
+            ItemKind::ForeignTypeItem | ItemKind::PrimitiveItem(_) => self.impls(&mut page, item),
+            _ => {}
+        }
+        page.truncate(page.trim_end().len());
+        page.push('\n');
+        page
+    }
+
+    /// Appends a section for the fields, variants or associated items of an item. Each of them
+    /// gets an anchor named like in the HTML output, so that links to them work the same way.
+    fn members<'a>(
+        &self,
+        page: &mut String,
+        title: &str,
+        items: impl Iterator<Item = &'a clean::Item>,
+    ) {
+        let mut items = items.filter(|i| !i.is_stripped() && i.name.is_some()).peekable();
+        if items.peek().is_none() {
+            return;
+        }
+        page.push_str(&format!("## {title}\n\n"));
+        for i in items {
+            self.member(page, i, "###");
+        }
+    }
+
+    fn member(&self, page: &mut String, item: &clean::Item, heading: &str) {
+        let mut printer = Printer::new(self);
+        printer.item_decl(item);
+        let decl = one_line(&printer.finish());
+        let anchor = format!("{}.{}", item.type_(), item.name.unwrap());
+        page.push_str(&format!("<a id=\"{anchor}\"></a>\n\n{heading} `{decl}`\n\n"));
+        self.document(page, item, HeadingOffset::H4);
+    }
+
+    /// Appends the implementations of the type `item`: the inherent ones with their items, and
+    /// the headers of the trait implementations.
+    fn impls(&self, page: &mut String, item: &clean::Item) {
+        let Some(impls) = item.item_id.as_def_id().and_then(|did| self.cache.impls.get(&did))
+        else {
+            return;
+        };
+        let (inherent, trait_impls): (Vec<_>, Vec<_>) = impls
+            .iter()
+            .filter(|i| !i.impl_item.is_stripped())
+            .partition(|i| i.inner_impl().trait_.is_none());
+
+        if !inherent.is_empty() {
+            page.push_str("## Implementations\n\n");
+            for i in inherent {
+                let mut printer = Printer::new(self);
+                printer.impl_header(i.inner_impl());
+                page.push_str(&format!("### `{}`\n\n", one_line(&printer.finish())));
+                self.document(page, &i.impl_item, HeadingOffset::H4);
+                for assoc in &i.inner_impl().items {
+                    if !assoc.is_stripped() && assoc.name.is_some() {
+                        self.member(page, assoc, "####");
+                    }
+                }
+            }
+        }
+
+        let (synthetic, trait_impls): (Vec<_>, Vec<_>) =
+            trait_impls.into_iter().partition(|i| i.inner_impl().kind.is_auto());
+        let (blanket, trait_impls): (Vec<_>, Vec<_>) =
+            trait_impls.into_iter().partition(|i| i.inner_impl().kind.is_blanket());
+        self.impl_list(page, "Trait Implementations", trait_impls.into_iter());
+        self.impl_list(page, "Auto Trait Implementations", synthetic.into_iter());
+        self.impl_list(page, "Blanket Implementations", blanket.into_iter());
+    }
+
+    fn impl_list<'a>(&self, page: &mut String, title: &str, impls: impl Iterator<Item = &'a Impl>) {
+        let mut impls = impls.peekable();
+        if impls.peek().is_none() {
+            return;
+        }
+        page.push_str(&format!("## {title}\n\n"));
+        for i in impls {
+            let mut printer = Printer::new(self);
+            printer.impl_header(i.inner_impl());
+            page.push_str(&format!("- `{}`\n", one_line(&printer.finish())));
+        }
+        page.push('\n');
+    }
+}
+
+/// Returns the kind of item shown in the title of its page, like the HTML output does.
+fn item_type_title(item: &clean::Item) -> &'static str {
+    match *item.kind {
+        ItemKind::ModuleItem(..) => {
+            if item.is_crate() {
+                "Crate"
+            } else {
+                "Module"
+            }
+        }
+        ItemKind::FunctionItem(..) | ItemKind::ForeignFunctionItem(..) => "Function",
+        ItemKind::TraitItem(..) => "Trait",
+        ItemKind::StructItem(..) => "Struct",
+        ItemKind::UnionItem(..) => "Union",
+        ItemKind::EnumItem(..) => "Enum",
+        ItemKind::TypeAliasItem(..) => "Type Alias",
+        ItemKind::MacroItem(..) => "Macro",
+        ItemKind::ProcMacroItem(ref mac) => match mac.kind {
+            MacroKind::Bang => "Macro",
+            MacroKind::Attr => "Attribute Macro",
+            MacroKind::Derive => "Derive Macro",
+        },
+        ItemKind::PrimitiveItem(..) => "Primitive Type",
+        ItemKind::StaticItem(..) | ItemKind::ForeignStaticItem(..) => "Static",
+        ItemKind::ConstantItem(..) => "Constant",
+        ItemKind::ForeignTypeItem => "Foreign Type",
+        ItemKind::KeywordItem => "Keyword",
+        ItemKind::OpaqueTyItem(..) => "Opaque Type",
+        ItemKind::TraitAliasItem(..) => "Trait Alias",
+        _ => "Item",
+    }
+}
+
+/// Joins the lines of a declaration, for headings and list items.
+fn one_line(decl: &str) -> String {
+    decl.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+impl<'tcx> FormatContext<'tcx> for MarkdownRenderer<'tcx> {
+    fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
+    fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    fn current(&self) -> &[Symbol] {
+        &self.current
+    }
+
+    fn href(&self, _did: DefId) -> Result<(String, ItemType, Vec<Symbol>), HrefError> {
+        // Declarations are printed in the alternate format, which doesn't link to anything.
+        Err(HrefError::DocumentationNotBuilt)
+    }
+}
+
+impl<'tcx> FormatRenderer<'tcx> for MarkdownRenderer<'tcx> {
+    fn descr() -> &'static str {
+        "markdown"
+    }
+
+    const RUN_ON_MODULE: bool = true;
+
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        debug!("Initializing markdown renderer");
+
+        let dst = options.output;
+        try_err!(create_dir_all(&dst), &dst);
+        Ok((
+            MarkdownRenderer {
+                tcx,
+                cache: Rc::new(cache),
+                current: Vec::new(),
+                dst,
+                in_stripped_module: false,
+            },
+            krate,
+        ))
+    }
+
+    fn make_child_renderer(&self) -> Self {
+        self.clone()
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
+        if !self.in_stripped_module {
+            self.in_stripped_module = item.is_stripped();
+        }
+        let item_name = item.name.unwrap();
+        self.dst.push(item_name.as_str());
+        self.current.push(item_name);
+
+        if !self.in_stripped_module {
+            let page = self.module_page(item);
+            self.write("index.md", page)?;
+        }
+        Ok(())
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        if self.in_stripped_module || item.is_stripped() || item.is_import() {
+            return Ok(());
+        }
+        let page = self.item_page(&item);
+        self.write(&format!("{}.{}.md", item.type_(), item.name.unwrap()), page)
+    }
+
+    fn after_krate(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn cache(&self) -> &Cache {
+        &self.cache
+    }
+}
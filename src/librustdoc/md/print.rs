@@ -0,0 +1,384 @@
+//! Plain-text rendering of item declarations, for the code blocks of the Markdown pages.
+//!
+//! Types, generics, where clauses and signatures are printed by the printers of `html::format`,
+//! in their alternate (`{:#}`) format which emits text without links. This module only lays out
+//! the items around them.
+
+use std::fmt::{Display, Write};
+
+use rustc_hir as hir;
+use rustc_span::hygiene::MacroKind;
+
+use crate::clean::{self, ItemKind};
+use crate::html::format::{
+    print_abi_with_space, print_generic_bounds, print_where_clause, visibility_to_src_with_space,
+    Ending, PrintWithSpace,
+};
+use crate::md::MarkdownRenderer;
+
+pub(super) struct Printer<'a, 'tcx> {
+    cx: &'a MarkdownRenderer<'tcx>,
+    s: String,
+}
+
+impl<'a, 'tcx> Printer<'a, 'tcx> {
+    pub(super) fn new(cx: &'a MarkdownRenderer<'tcx>) -> Self {
+        Printer { cx, s: String::new() }
+    }
+
+    pub(super) fn finish(self) -> String {
+        self.s
+    }
+
+    fn push(&mut self, s: &str) {
+        self.s.push_str(s);
+    }
+
+    /// Appends the text form of one of the `html::format` printers.
+    fn text(&mut self, d: impl Display) {
+        write!(self.s, "{d:#}").unwrap();
+    }
+
+    fn sep<T>(&mut self, items: impl IntoIterator<Item = T>, sep: &str, f: impl Fn(&mut Self, T)) {
+        for (i, item) in items.into_iter().enumerate() {
+            if i != 0 {
+                self.push(sep);
+            }
+            f(self, item);
+        }
+    }
+
+    fn visibility(&mut self, item: &clean::Item) {
+        if let Some(def_id) = item.item_id.as_def_id() {
+            let tcx = self.cx.tcx;
+            self.text(visibility_to_src_with_space(item.visibility(tcx), tcx, def_id));
+        }
+    }
+
+    fn where_clause(&mut self, generics: &clean::Generics) {
+        self.text(print_where_clause(generics, self.cx, 0, Ending::NoNewline));
+    }
+
+    fn function(&mut self, item: &clean::Item, f: &clean::Function) {
+        if let Some(header) = item.fn_header(self.cx.tcx) {
+            if header.constness == hir::Constness::Const {
+                self.push("const ");
+            }
+            self.push(header.asyncness.print_with_space());
+            self.push(header.unsafety.print_with_space());
+            self.text(print_abi_with_space(header.abi));
+        }
+        self.push("fn ");
+        self.push(item.name.unwrap().as_str());
+        self.text(f.generics.print(self.cx));
+        // Wrap the arguments like the HTML output does, counting from the start of the line.
+        let header_len = self.s.len() - self.s.rfind('\n').map_or(0, |i| i + 1);
+        self.text(f.decl.full_print(header_len, 0, self.cx));
+        self.where_clause(&f.generics);
+    }
+
+    /// Prints the header of an impl block, e.g. `impl<T: Clone> Clone for Wrapper<T>`.
+    pub(super) fn impl_header(&mut self, i: &clean::Impl) {
+        self.push(i.unsafety.print_with_space());
+        self.text(i.print(false, self.cx));
+    }
+
+    /// Prints a re-export, e.g. `pub use foo::Bar as Baz;`.
+    pub(super) fn import(&mut self, item: &clean::Item, import: &clean::Import) {
+        self.visibility(item);
+        self.text(import.print(self.cx));
+    }
+
+    fn fields(&mut self, fields: &[clean::Item], indent: &str, omitted: &str) {
+        let mut has_stripped = false;
+        for field in fields {
+            match *field.kind {
+                ItemKind::StructFieldItem(ref ty) => {
+                    self.push("\n");
+                    self.push(indent);
+                    self.push("    ");
+                    self.visibility(field);
+                    self.push(field.name.unwrap().as_str());
+                    self.push(": ");
+                    self.text(ty.print(self.cx));
+                    self.push(",");
+                }
+                _ => has_stripped = true,
+            }
+        }
+        if has_stripped {
+            self.push("\n");
+            self.push(indent);
+            self.push("    // some ");
+            self.push(omitted);
+            self.push(" omitted");
+        }
+        self.push("\n");
+        self.push(indent);
+    }
+
+    fn tuple_fields(&mut self, fields: &[clean::Item]) {
+        self.push("(");
+        self.sep(fields, ", ", |this, field| match *field.kind {
+            ItemKind::StructFieldItem(ref ty) => {
+                this.visibility(field);
+                this.text(ty.print(this.cx));
+            }
+            _ => this.push("_"),
+        });
+        self.push(")");
+    }
+
+    fn variant(&mut self, variant: &clean::Item) {
+        let ItemKind::VariantItem(ref v) = *variant.kind else { return };
+        self.push(variant.name.unwrap().as_str());
+        match v.kind {
+            clean::VariantKind::CLike => {
+                if let Some(ref discriminant) = v.discriminant {
+                    self.push(" = ");
+                    let value = discriminant.value(self.cx.tcx, true);
+                    self.push(&value);
+                }
+            }
+            clean::VariantKind::Tuple(ref fields) => self.tuple_fields(fields),
+            clean::VariantKind::Struct(ref s) => {
+                self.push(" {");
+                self.fields(&s.fields, "    ", "fields");
+                self.push("}");
+            }
+        }
+    }
+
+    /// Prints an associated item as it's declared in a trait or an impl block.
+    fn assoc_item(&mut self, item: &clean::Item) {
+        let name = item.name.unwrap();
+        match *item.kind {
+            ItemKind::TyMethodItem(ref f) => {
+                self.function(item, f);
+                self.push(";");
+            }
+            ItemKind::MethodItem(ref f, _) => {
+                self.visibility(item);
+                self.function(item, f);
+            }
+            ItemKind::TyAssocConstItem(ref generics, ref ty) => {
+                self.push(&format!("const {name}"));
+                self.text(generics.print(self.cx));
+                self.push(": ");
+                self.text(ty.print(self.cx));
+                self.where_clause(generics);
+                self.push(";");
+            }
+            ItemKind::AssocConstItem(ref generics, ref ty, ref kind) => {
+                self.visibility(item);
+                self.push(&format!("const {name}"));
+                self.text(generics.print(self.cx));
+                self.push(": ");
+                self.text(ty.print(self.cx));
+                self.push(" = ");
+                let expr = kind.expr(self.cx.tcx);
+                self.push(&expr);
+                self.where_clause(generics);
+                self.push(";");
+            }
+            ItemKind::TyAssocTypeItem(ref generics, ref bounds) => {
+                self.push(&format!("type {name}"));
+                self.text(generics.print(self.cx));
+                if !bounds.is_empty() {
+                    self.push(": ");
+                    self.text(print_generic_bounds(bounds, self.cx));
+                }
+                self.where_clause(generics);
+                self.push(";");
+            }
+            ItemKind::AssocTypeItem(ref alias, _) => {
+                self.push(&format!("type {name}"));
+                self.text(alias.generics.print(self.cx));
+                self.push(" = ");
+                self.text(alias.type_.print(self.cx));
+                self.where_clause(&alias.generics);
+                self.push(";");
+            }
+            _ => {}
+        }
+    }
+
+    /// Prints the declaration of `item`, or nothing for items that have none, like modules.
+    pub(super) fn item_decl(&mut self, item: &clean::Item) {
+        let name = item.name.unwrap();
+        let tcx = self.cx.tcx;
+        match *item.kind {
+            ItemKind::StructItem(ref s) => {
+                self.visibility(item);
+                self.push(&format!("struct {name}"));
+                self.text(s.generics.print(self.cx));
+                match s.ctor_kind {
+                    Some(hir::def::CtorKind::Fn) => {
+                        self.tuple_fields(&s.fields);
+                        self.where_clause(&s.generics);
+                        self.push(";");
+                    }
+                    Some(hir::def::CtorKind::Const) => {
+                        self.where_clause(&s.generics);
+                        self.push(";");
+                    }
+                    None => {
+                        self.where_clause(&s.generics);
+                        self.push(" {");
+                        self.fields(&s.fields, "", "fields");
+                        self.push("}");
+                    }
+                }
+            }
+            ItemKind::UnionItem(ref u) => {
+                self.visibility(item);
+                self.push(&format!("union {name}"));
+                self.text(u.generics.print(self.cx));
+                self.where_clause(&u.generics);
+                self.push(" {");
+                self.fields(&u.fields, "", "fields");
+                self.push("}");
+            }
+            ItemKind::EnumItem(ref e) => {
+                self.visibility(item);
+                self.push(&format!("enum {name}"));
+                self.text(e.generics.print(self.cx));
+                self.where_clause(&e.generics);
+                self.push(" {");
+                let mut has_stripped = false;
+                for variant in &e.variants {
+                    if variant.is_stripped() {
+                        has_stripped = true;
+                        continue;
+                    }
+                    self.push("\n    ");
+                    self.variant(variant);
+                    self.push(",");
+                }
+                if has_stripped {
+                    self.push("\n    // some variants omitted");
+                }
+                self.push("\n}");
+            }
+            ItemKind::FunctionItem(ref f) | ItemKind::ForeignFunctionItem(ref f) => {
+                self.visibility(item);
+                self.function(item, f);
+            }
+            ItemKind::TypeAliasItem(ref t) => {
+                self.visibility(item);
+                self.push(&format!("type {name}"));
+                self.text(t.generics.print(self.cx));
+                self.where_clause(&t.generics);
+                self.push(" = ");
+                self.text(t.type_.print(self.cx));
+                self.push(";");
+            }
+            ItemKind::OpaqueTyItem(ref t) => {
+                self.push(&format!("type {name}"));
+                self.text(t.generics.print(self.cx));
+                self.push(" = impl ");
+                self.text(print_generic_bounds(&t.bounds, self.cx));
+                self.push(";");
+            }
+            ItemKind::StaticItem(ref s) | ItemKind::ForeignStaticItem(ref s) => {
+                self.visibility(item);
+                self.push("static ");
+                self.push(s.mutability.print_with_space());
+                self.push(&format!("{name}: "));
+                self.text(s.type_.print(self.cx));
+                self.push(";");
+            }
+            ItemKind::ConstantItem(ref c) => {
+                self.visibility(item);
+                self.push(&format!("const {name}"));
+                self.text(c.generics.print(self.cx));
+                self.push(": ");
+                self.text(c.type_.print(self.cx));
+                self.push(" = ");
+                let expr = c.expr(tcx);
+                self.push(&expr);
+                self.where_clause(&c.generics);
+                self.push(";");
+            }
+            ItemKind::TraitItem(ref t) => {
+                self.visibility(item);
+                self.push(t.unsafety(tcx).print_with_space());
+                if t.is_auto(tcx) {
+                    self.push("auto ");
+                }
+                self.push(&format!("trait {name}"));
+                self.text(t.generics.print(self.cx));
+                if !t.bounds.is_empty() {
+                    self.push(": ");
+                    self.text(print_generic_bounds(&t.bounds, self.cx));
+                }
+                self.where_clause(&t.generics);
+                self.push(" {");
+                let mut has_stripped = false;
+                for trait_item in &t.items {
+                    if trait_item.is_stripped() {
+                        has_stripped = true;
+                        continue;
+                    }
+                    self.push("\n    ");
+                    let start = self.s.len();
+                    self.assoc_item(trait_item);
+                    if matches!(*trait_item.kind, ItemKind::MethodItem(..)) {
+                        self.push(" { ... }");
+                    }
+                    let indented = self.s[start..].replace('\n', "\n    ");
+                    self.s.replace_range(start.., &indented);
+                }
+                if has_stripped {
+                    self.push("\n    // some items omitted");
+                }
+                self.push("\n}");
+            }
+            ItemKind::TraitAliasItem(ref t) => {
+                self.push(&format!("trait {name}"));
+                self.text(t.generics.print(self.cx));
+                self.push(" = ");
+                self.text(print_generic_bounds(&t.bounds, self.cx));
+                self.where_clause(&t.generics);
+                self.push(";");
+            }
+            ItemKind::ForeignTypeItem => {
+                self.visibility(item);
+                self.push(&format!("type {name};"));
+            }
+            ItemKind::MacroItem(ref m) => self.push(&m.source),
+            ItemKind::ProcMacroItem(ref m) => match m.kind {
+                MacroKind::Bang => self.push(&format!("{name}!() {{ ... }}")),
+                MacroKind::Attr => self.push(&format!("#[{name}]")),
+                MacroKind::Derive => {
+                    self.push(&format!("#[derive({name})]"));
+                    if !m.helpers.is_empty() {
+                        self.push("\n\n// Attributes available to this derive:");
+                        for attr in &m.helpers {
+                            self.push(&format!("\n#[{attr}]"));
+                        }
+                    }
+                }
+            },
+            ItemKind::TyMethodItem(_)
+            | ItemKind::MethodItem(..)
+            | ItemKind::TyAssocConstItem(..)
+            | ItemKind::AssocConstItem(..)
+            | ItemKind::TyAssocTypeItem(..)
+            | ItemKind::AssocTypeItem(..) => self.assoc_item(item),
+            ItemKind::StructFieldItem(ref ty) => {
+                self.visibility(item);
+                self.push(&format!("{name}: "));
+                self.text(ty.print(self.cx));
+            }
+            ItemKind::VariantItem(_) => self.variant(item),
+            ItemKind::ExternCrateItem { .. }
+            | ItemKind::ImportItem(_)
+            | ItemKind::ModuleItem(_)
+            | ItemKind::ImplItem(_)
+            | ItemKind::PrimitiveItem(_)
+            | ItemKind::KeywordItem
+            | ItemKind::StrippedItem(_) => {}
+        }
+    }
+}
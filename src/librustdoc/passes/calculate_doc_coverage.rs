@@ -1,11 +1,13 @@
 //! Calculates information used for the --show-coverage flag.
 use crate::clean;
+use crate::config::OutputFormat;
 use crate::core::DocContext;
 use crate::html::markdown::{find_testable_code, ErrorCodes};
 use crate::passes::check_doc_test_visibility::{should_have_doc_example, Tests};
 use crate::passes::Pass;
 use crate::visit::DocVisitor;
 use rustc_hir as hir;
+use rustc_hir::def_id::LOCAL_CRATE;
 use rustc_lint::builtin::MISSING_DOCS;
 use rustc_middle::lint::LintLevelSource;
 use rustc_session::lint;
@@ -22,7 +24,7 @@
 };
 
 fn calculate_doc_coverage(krate: clean::Crate, ctx: &mut DocContext<'_>) -> clean::Crate {
-    let mut calc = CoverageCalculator { items: Default::default(), ctx };
+    let mut calc = CoverageCalculator { items: Default::default(), records: Vec::new(), ctx };
     calc.visit_crate(&krate);
 
     calc.print_results();
@@ -100,8 +102,21 @@ fn add_assign(&mut self, rhs: Self) {
     }
 }
 
+/// An item counted in the coverage report, listed with `--coverage-items`.
+#[derive(Serialize, Debug)]
+struct ItemRecord {
+    path: String,
+    kind: &'static str,
+    file: String,
+    line: usize,
+    column: usize,
+    has_docs: bool,
+    has_examples: bool,
+}
+
 struct CoverageCalculator<'a, 'b> {
     items: BTreeMap<FileName, ItemCount>,
+    records: Vec<ItemRecord>,
     ctx: &'a mut DocContext<'b>,
 }
 
@@ -117,23 +132,86 @@ fn limit_filename_len(filename: String) -> String {
 
 impl<'a, 'b> CoverageCalculator<'a, 'b> {
     fn to_json(&self) -> String {
-        serde_json::to_string(
-            &self
-                .items
-                .iter()
-                .map(|(k, v)| (k.prefer_local().to_string(), v))
-                .collect::<BTreeMap<String, &ItemCount>>(),
-        )
-        .expect("failed to convert JSON data to string")
+        let files = self
+            .items
+            .iter()
+            .map(|(k, v)| (k.prefer_local().to_string(), v))
+            .collect::<BTreeMap<String, &ItemCount>>();
+        let json = if self.ctx.render_options.coverage_items {
+            serde_json::to_string(&serde_json::json!({ "files": files, "items": self.records }))
+        } else {
+            serde_json::to_string(&files)
+        };
+        json.expect("failed to convert JSON data to string")
     }
 
-    fn print_results(&self) {
-        let output_format = self.ctx.output_format;
-        if output_format.is_json() {
-            println!("{}", self.to_json());
-            return;
+    /// Formats the report as LCOV tracefile records, where each line declaring an item is an
+    /// instrumented line, hit if all the items declared on it are documented.
+    fn to_lcov(&self) -> String {
+        let mut files: BTreeMap<&str, BTreeMap<usize, bool>> = BTreeMap::new();
+        for record in &self.records {
+            let documented =
+                files.entry(record.file.as_str()).or_default().entry(record.line).or_insert(true);
+            *documented &= record.has_docs;
+        }
+
+        let mut lcov = String::new();
+        for (file, lines) in files {
+            lcov.push_str(&format!("TN:\nSF:{file}\n"));
+            for (line, &documented) in &lines {
+                lcov.push_str(&format!("DA:{line},{}\n", documented as u8));
+            }
+            let hit = lines.values().filter(|&&documented| documented).count();
+            lcov.push_str(&format!("LF:{}\nLH:{hit}\nend_of_record\n", lines.len()));
         }
+        lcov
+    }
+
+    fn total(&self) -> ItemCount {
         let mut total = ItemCount::default();
+        for count in self.items.values() {
+            if count.percentage().is_some() {
+                total += *count;
+            }
+        }
+        total
+    }
+
+    fn print_results(&self) {
+        self.print_report();
+        self.check_thresholds();
+    }
+
+    /// Emits an error if the coverage of the crate is below the minimum asked for with
+    /// `--min-coverage` or `--min-example-coverage`.
+    fn check_thresholds(&self) {
+        let total = self.total();
+        let thresholds = [
+            ("documentation", self.ctx.render_options.min_coverage, total.percentage()),
+            ("example", self.ctx.render_options.min_example_coverage, total.examples_percentage()),
+        ];
+        for (what, minimum, percentage) in thresholds {
+            let (Some(minimum), Some(percentage)) = (minimum, percentage) else { continue };
+            if percentage < minimum {
+                self.ctx.tcx.dcx().err(format!(
+                    "{what} coverage of {percentage:.1}% is below the minimum of {minimum}%"
+                ));
+            }
+        }
+    }
+
+    fn print_report(&self) {
+        match self.ctx.output_format {
+            OutputFormat::Json => {
+                println!("{}", self.to_json());
+                return;
+            }
+            OutputFormat::Lcov => {
+                print!("{}", self.to_lcov());
+                return;
+            }
+            OutputFormat::Html | OutputFormat::Markdown => {}
+        }
 
         fn print_table_line() {
             println!("+-{0:->35}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+-{0:->10}-+", "");
@@ -168,11 +246,10 @@ fn print_table_record(
                     percentage,
                     count.examples_percentage().unwrap_or(0.),
                 );
-
-                total += count;
             }
         }
 
+        let total = self.total();
         print_table_line();
         print_table_record(
             "Total",
@@ -181,6 +258,21 @@ fn print_table_record(
             total.examples_percentage().unwrap_or(0.0),
         );
         print_table_line();
+
+        if self.ctx.render_options.coverage_items {
+            for record in &self.records {
+                println!(
+                    "{}:{}:{}: {} {}: {}, {}",
+                    record.file,
+                    record.line,
+                    record.column,
+                    record.kind,
+                    record.path,
+                    if record.has_docs { "documented" } else { "undocumented" },
+                    if record.has_examples { "has examples" } else { "no examples" },
+                );
+            }
+        }
     }
 }
 
@@ -259,6 +351,24 @@ fn visit_item(&mut self, i: &clean::Item) {
                 if let Some(span) = i.span(self.ctx.tcx) {
                     let filename = span.filename(self.ctx.sess());
                     debug!("counting {:?} {:?} in {filename:?}", i.type_(), i.name);
+                    if has_docs || should_have_docs {
+                        let tcx = self.ctx.tcx;
+                        let loc = span.lo(self.ctx.sess());
+                        let mut path = tcx.crate_name(LOCAL_CRATE).to_string();
+                        let def_id = i.item_id.as_def_id();
+                        if let Some(def_id) = def_id.filter(|def_id| !def_id.is_crate_root()) {
+                            path = format!("{path}::{}", tcx.def_path_str(def_id));
+                        }
+                        self.records.push(ItemRecord {
+                            path,
+                            kind: i.type_().as_str(),
+                            file: filename.prefer_local().to_string(),
+                            line: loc.line,
+                            column: loc.col_display + 1,
+                            has_docs,
+                            has_examples: has_doc_example,
+                        });
+                    }
                     self.items.entry(filename).or_default().count_item(
                         has_docs,
                         has_doc_example,
@@ -50,6 +50,23 @@ fn collect_intra_doc_links(krate: Crate, cx: &mut DocContext<'_>) -> Crate {
     krate
 }
 
+/// Whether `path_str` resolves in any namespace, like the destination of an intra-doc link in the
+/// documentation of `item_id`. Used by the `unresolved_code_paths` lint.
+///
+/// `rustc_resolve` must have resolved `path_str` in `module_id`, which it does for the paths
+/// returned by [`rustc_resolve::rustdoc::code_span_path`] when running rustdoc.
+pub(crate) fn path_resolves(
+    cx: &mut DocContext<'_>,
+    path_str: &str,
+    item_id: DefId,
+    module_id: DefId,
+) -> bool {
+    let mut collector = LinkCollector { cx, visited_links: FxHashMap::default() };
+    [TypeNS, ValueNS, MacroNS]
+        .into_iter()
+        .any(|ns| collector.resolve(path_str, ns, item_id, module_id).is_ok())
+}
+
 fn filter_assoc_items_by_name_and_namespace<'a>(
     tcx: TyCtxt<'a>,
     assoc_items_of: DefId,
@@ -6,6 +6,7 @@
 mod html_tags;
 mod redundant_explicit_links;
 mod unescaped_backticks;
+mod unresolved_code_paths;
 
 use super::Pass;
 use crate::clean::*;
@@ -31,6 +32,7 @@ fn visit_item(&mut self, item: &Item) {
         html_tags::visit_item(self.cx, item);
         unescaped_backticks::visit_item(self.cx, item);
         redundant_explicit_links::visit_item(self.cx, item);
+        unresolved_code_paths::visit_item(self.cx, item);
 
         self.visit_item_recur(item)
     }
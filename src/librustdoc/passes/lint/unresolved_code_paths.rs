@@ -0,0 +1,115 @@
+//! Detects inline code that looks like a path but doesn't resolve, e.g., `` `Foo::bar` `` in the
+//! documentation after `Foo` was renamed. Once fixed, such paths are better written as intra-doc
+//! links, which are checked by rustdoc.
+
+use std::ops::Range;
+
+use pulldown_cmark::{BrokenLink, Event, Parser, Tag};
+use rustc_hir::def::DefKind;
+use rustc_hir::def::Namespace::TypeNS;
+use rustc_hir::HirId;
+use rustc_resolve::rustdoc::{
+    code_span_path, has_primitive_or_keyword_docs, prepare_to_doc_link_resolution,
+    source_span_for_markdown_range,
+};
+use rustc_span::def_id::DefId;
+use rustc_span::Symbol;
+
+use crate::clean::utils::find_nearest_parent_module;
+use crate::clean::Item;
+use crate::core::DocContext;
+use crate::html::markdown::main_body_opts;
+use crate::passes::collect_intra_doc_links::path_resolves;
+
+pub(super) fn visit_item(cx: &mut DocContext<'_>, item: &Item) {
+    let Some(hir_id) = DocContext::as_local_hir_id(cx.tcx, item.item_id) else {
+        // If non-local, no need to check anything.
+        return;
+    };
+    let def_id = item.item_id.expect_def_id();
+    if !cx.render_options.document_private
+        && !cx.tcx.effective_visibilities(()).is_exported(def_id.expect_local())
+        && !has_primitive_or_keyword_docs(&item.attrs.other_attrs)
+    {
+        // `rustc_resolve` doesn't resolve the paths in the documentation of non-exported items.
+        return;
+    }
+
+    // Like intra-doc links, paths are resolved in the module of the attribute they come from.
+    for (item_id, doc) in prepare_to_doc_link_resolution(&item.attrs.doc_strings) {
+        let item_id = item_id.unwrap_or(def_id);
+        let module_id = match cx.tcx.def_kind(item_id) {
+            DefKind::Mod if item.inner_docs(cx.tcx) => item_id,
+            _ => find_nearest_parent_module(cx.tcx, item_id).unwrap(),
+        };
+        check_code_paths(cx, item, hir_id, item_id, module_id, &doc);
+    }
+}
+
+fn check_code_paths<'md>(
+    cx: &mut DocContext<'_>,
+    item: &Item,
+    hir_id: HirId,
+    item_id: DefId,
+    module_id: DefId,
+    doc: &'md str,
+) {
+    let tcx = cx.tcx;
+    let Some(resolutions) = module_id
+        .as_local()
+        .and_then(|module_id| tcx.resolutions(()).doc_link_resolutions.get(&module_id))
+    else {
+        return;
+    };
+
+    let mut broken_link_callback = |link: BrokenLink<'md>| Some((link.reference, "".into()));
+    let mut p = Parser::new_with_broken_link_callback(
+        doc,
+        main_body_opts(),
+        Some(&mut broken_link_callback),
+    )
+    .into_offset_iter();
+
+    while let Some((event, range)) = p.next() {
+        match event {
+            Event::Code(code) => {
+                let Some(path) = code_span_path(&code) else { continue };
+                // Paths that `rustc_resolve` didn't see can't be resolved here.
+                if !resolutions.contains_key(&(Symbol::intern(path), TypeNS))
+                    || path_resolves(cx, path, item_id, module_id)
+                {
+                    continue;
+                }
+                report(cx, item, hir_id, doc, &code, range);
+            }
+            // Inline code in links is either checked as an intra-doc link or links elsewhere.
+            Event::Start(Tag::Link(..)) => {
+                while let Some((event, _)) = p.next() {
+                    if let Event::End(Tag::Link(..)) = event {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn report(
+    cx: &DocContext<'_>,
+    item: &Item,
+    hir_id: HirId,
+    doc: &str,
+    code: &str,
+    range: Range<usize>,
+) {
+    let sp = source_span_for_markdown_range(cx.tcx, doc, &range, &item.attrs.doc_strings)
+        .unwrap_or_else(|| item.attr_span(cx.tcx));
+    let msg = format!("`{code}` looks like a path, but it does not resolve");
+    cx.tcx.node_span_lint(crate::lint::UNRESOLVED_CODE_PATHS, hir_id, sp, msg, |lint| {
+        lint.note("it may name an item that was renamed or removed").help(format!(
+            "once the path is fixed, write it as an intra-doc link, like [`{code}`], \
+             so that rustdoc checks it"
+        ));
+    });
+}
@@ -1325,6 +1325,8 @@ macro_rules! decision {
         compile_fail: false,
         no_run: false,
         test_type: test::TestType::Unknown,
+        timeout_secs: None,
+        isolate: false,
     }
 }
 
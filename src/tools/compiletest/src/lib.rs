@@ -537,12 +537,22 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         color: config.color,
         shuffle: false,
         shuffle_seed: None,
+        shard: None,
+        failed_tests: None,
+        failed_first: false,
+        last_failed: false,
         test_threads: None,
         skip: config.skip.clone(),
         list: false,
         options: test::Options::new(),
         time_options: None,
+        test_timeout: None,
+        retries: 0,
+        save_baseline: None,
+        baseline: None,
+        baseline_dir: None,
         force_run_in_process: false,
+        isolate: false,
         fail_fast: std::env::var_os("RUSTC_TEST_FAIL_FAST").is_some(),
         output_postprocess_executable: None,
         output_postprocess_args: vec![],
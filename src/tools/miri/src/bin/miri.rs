@@ -40,7 +40,7 @@
 use rustc_session::search_paths::PathKind;
 use rustc_session::{CtfeBacktrace, EarlyDiagCtxt};
 
-use miri::{BacktraceStyle, BorrowTrackerMethod, ProvenanceMode, RetagFields};
+use miri::{BacktraceStyle, BorrowTrackerMethod, ProvenanceMode, RetagFields, ScheduleLogMode};
 
 struct MiriCompilerCalls {
     miri_config: miri::MiriConfig,
@@ -452,6 +452,10 @@ fn main() {
                         "-Zmiri-isolation-error must be `abort`, `hide`, `warn`, or `warn-nobacktrace`"
                     ),
             };
+        } else if arg == "-Zmiri-leak-summary" {
+            miri_config.leak_summary = true;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-leak-report-json=") {
+            miri_config.leak_report_json = Some(param.into());
         } else if arg == "-Zmiri-ignore-leaks" {
             miri_config.ignore_leaks = true;
             miri_config.collect_leak_backtraces = false;
@@ -561,6 +565,31 @@ fn main() {
                     ),
             };
             miri_config.preemption_rate = rate;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-record-schedule=") {
+            if miri_config.schedule_log.is_some() {
+                show_error!(
+                    "-Zmiri-record-schedule and -Zmiri-replay-schedule can only be specified once"
+                );
+            }
+            miri_config.schedule_log = Some(ScheduleLogMode::Record(param.into()));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-replay-schedule=") {
+            if miri_config.schedule_log.is_some() {
+                show_error!(
+                    "-Zmiri-record-schedule and -Zmiri-replay-schedule can only be specified once"
+                );
+            }
+            if !std::path::Path::new(param).exists() {
+                show_error!("-Zmiri-replay-schedule `{}` does not exist", param);
+            }
+            miri_config.schedule_log = Some(ScheduleLogMode::Replay(param.into()));
+        } else if arg == "-Zmiri-explore-schedules" {
+            miri_config.explore_schedules = Some(2);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-explore-schedules=") {
+            let bound = match param.parse::<u32>() {
+                Ok(i) => i,
+                Err(err) => show_error!("-Zmiri-explore-schedules requires a `u32`: {}", err),
+            };
+            miri_config.explore_schedules = Some(bound);
         } else if arg == "-Zmiri-report-progress" {
             // This makes it take a few seconds between progress reports on my laptop.
             miri_config.report_progress = Some(1_000_000);
@@ -578,6 +607,12 @@ fn main() {
             miri_config.gc_interval = interval;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-measureme=") {
             miri_config.measureme_out = Some(param.to_string());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-coverage=") {
+            miri_config.coverage_out = Some(param.to_string());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-instruction-profile=") {
+            miri_config.instruction_profile_out = Some(param.to_string());
+        } else if arg == "-Zmiri-instruction-profile-stacks" {
+            miri_config.instruction_profile_stacks = true;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-backtrace=") {
             miri_config.backtrace_style = match param {
                 "0" => BacktraceStyle::Off,
@@ -631,6 +666,13 @@ fn main() {
         );
     }
 
+    // Replaying a schedule forces all decisions, so there is nothing left to explore.
+    if miri_config.explore_schedules.is_some()
+        && matches!(miri_config.schedule_log, Some(ScheduleLogMode::Replay(_)))
+    {
+        show_error!("-Zmiri-explore-schedules cannot be combined with -Zmiri-replay-schedule");
+    }
+
     debug!("rustc arguments: {:?}", rustc_args);
     debug!("crate arguments: {:?}", miri_config.args);
     run_compiler(
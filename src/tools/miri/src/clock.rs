@@ -1,4 +1,5 @@
 use std::cell::Cell;
+use std::rc::Rc;
 use std::time::{Duration, Instant as StdInstant};
 
 /// When using a virtual clock, this defines how many nanoseconds we pretend are passing for each
@@ -8,12 +9,12 @@
 /// (See `tests/pass/shims/time-with-isolation*.rs`.)
 const NANOSECONDS_PER_BASIC_BLOCK: u64 = 5000;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Instant {
     kind: InstantKind,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum InstantKind {
     Host(StdInstant),
     Virtual { nanoseconds: u64 },
@@ -46,12 +47,13 @@ pub fn duration_since(&self, earlier: Instant) -> Duration {
 }
 
 /// A monotone clock used for `Instant` simulation.
-#[derive(Debug)]
+/// Clones share the same time, so that e.g. timer file descriptors can keep the clock around.
+#[derive(Debug, Clone)]
 pub struct Clock {
     kind: ClockKind,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum ClockKind {
     Host {
         /// The "time anchor" for this machine's monotone clock.
@@ -59,7 +61,7 @@ enum ClockKind {
     },
     Virtual {
         /// The "current virtual time".
-        nanoseconds: Cell<u64>,
+        nanoseconds: Rc<Cell<u64>>,
     },
 }
 
@@ -69,7 +71,7 @@ pub fn new(communicate: bool) -> Self {
         let kind = if communicate {
             ClockKind::Host { time_anchor: StdInstant::now() }
         } else {
-            ClockKind::Virtual { nanoseconds: 0.into() }
+            ClockKind::Virtual { nanoseconds: Rc::new(Cell::new(0)) }
         };
 
         Self { kind }
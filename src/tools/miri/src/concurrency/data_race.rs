@@ -737,6 +737,7 @@ fn atomic_compare_exchange_scalar(
     /// Update the data-race detector for an atomic fence on the current thread.
     fn atomic_fence(&mut self, atomic: AtomicFenceOrd) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
+        this.visible_operation();
         let current_span = this.machine.current_span();
         if let Some(data_race) = &mut this.machine.data_race {
             data_race.maybe_perform_sync_operation(
@@ -1173,6 +1174,8 @@ fn atomic_access_check(
                 }
             }
         }
+        // Other threads can observe this access, so it is worth preempting the thread after it.
+        this.visible_operation();
         Ok(())
     }
 
@@ -1,5 +1,6 @@
 pub mod data_race;
 mod range_object_map;
+pub mod schedule_log;
 #[macro_use]
 pub mod sync;
 pub mod init_once;
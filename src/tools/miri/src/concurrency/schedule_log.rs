@@ -0,0 +1,455 @@
+//! Recording, replaying, and systematically exploring scheduling decisions.
+//!
+//! With `-Zmiri-record-schedule=<file>`, every decision that determines how threads interleave is
+//! written to `<file>`: which thread runs next whenever the scheduler has to pick one, at which
+//! preemption points the active thread got preempted, and which store an atomic load observed
+//! when weak memory emulation offered more than one candidate. With
+//! `-Zmiri-replay-schedule=<file>`, those decisions are read back and forced, so that an
+//! interleaving that was found with some seed can be reproduced after editing the program. When
+//! the program asks for a decision that does not match the next recorded one, execution stops with
+//! an error.
+//!
+//! The file is plain text with one entry per line:
+//!
+//! ```text
+//! seed 42
+//! switch 1
+//! preempt 1234
+//! load 0 3
+//! ```
+//!
+//! `seed` is the RNG seed of the recording run; it is used for the replay unless `-Zmiri-seed` is
+//! given explicitly. `switch <thread>` means the scheduler picked that thread to run next.
+//! `preempt <n>` means the active thread was preempted at the `n`-th preemption point (counting
+//! from 0). `load <index> <candidates>` means a weak memory load picked the `index`-th of
+//! `candidates` stores, where index 0 is the latest store. Empty lines and lines starting with `#`
+//! are ignored.
+//!
+//! With `-Zmiri-explore-schedules`, the program is instead executed once for every schedule that
+//! needs at most a given number of preemptions, in the style of stateless model checkers such as
+//! Loom. Each execution makes the same decisions as the previous one up to the last decision that
+//! still has unexplored alternatives (see [`SchedulePrefix`]), takes the next alternative there,
+//! and from then on sticks to the default decisions. To keep the number of schedules manageable,
+//! the active thread is only preempted right after it performed an operation that other threads
+//! can observe, like an atomic access or releasing a lock. All other interleavings are
+//! indistinguishable for a program without data races, and data races are reported by the data
+//! race detector anyway. Reading an outdated value from a weak memory store buffer counts against
+//! the preemption bound as well.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::*;
+
+/// Whether to record, replay, or explore the schedule.
+#[derive(Clone, Debug)]
+pub enum ScheduleLogMode {
+    /// Write all scheduling decisions to the given file.
+    Record(PathBuf),
+    /// Force the scheduling decisions that were recorded in the given file.
+    Replay(PathBuf),
+    /// Run one execution of a systematic exploration of the schedules.
+    Explore { prefix: SchedulePrefix, preemption_bound: u32 },
+}
+
+/// A single scheduling decision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Decision {
+    /// The scheduler picked this thread to run next.
+    Switch(ThreadId),
+    /// The active thread was preempted at this preemption point.
+    Preempt(u64),
+    /// A weak memory load picked the store at `index` out of `candidates` stores.
+    Load { index: usize, candidates: usize },
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Decision::Switch(thread) => write!(f, "switch {}", thread.to_u32()),
+            Decision::Preempt(point) => write!(f, "preempt {point}"),
+            Decision::Load { index, candidates } => write!(f, "load {index} {candidates}"),
+        }
+    }
+}
+
+impl Decision {
+    fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let decision = match words.next()? {
+            "switch" => Decision::Switch(ThreadId::from(words.next()?.parse::<u32>().ok()?)),
+            "preempt" => Decision::Preempt(words.next()?.parse().ok()?),
+            "load" => {
+                let index = words.next()?.parse().ok()?;
+                let candidates = words.next()?.parse().ok()?;
+                if index >= candidates {
+                    return None;
+                }
+                Decision::Load { index, candidates }
+            }
+            _ => return None,
+        };
+        words.next().is_none().then_some(decision)
+    }
+}
+
+/// A point of an execution at which exploration could have taken a different decision.
+#[derive(Clone, Copy, Debug)]
+struct Branch {
+    /// The index of the option that was taken. Option 0 is the default decision.
+    chosen: usize,
+    /// The number of available options.
+    options: usize,
+    /// Whether taking any but the default option counts against the preemption bound.
+    preempts: bool,
+}
+
+/// The decisions with which an execution starts when exploring schedules systematically.
+#[derive(Clone, Debug, Default)]
+pub struct SchedulePrefix(Vec<Branch>);
+
+/// The state of one execution while exploring schedules systematically.
+#[derive(Debug)]
+struct Exploration {
+    /// The decisions to take at the first branches of this execution.
+    prefix: Vec<Branch>,
+    /// The branches this execution went through so far.
+    branches: Vec<Branch>,
+    /// The maximal number of preemptions in one execution.
+    preemption_bound: u32,
+    /// Whether an operation that other threads can observe happened since the last preemption
+    /// point.
+    visible_operation: bool,
+    /// The decisions of this execution, in the format of a recorded schedule.
+    decisions: Vec<Decision>,
+}
+
+impl Exploration {
+    /// Pick one of `options` options. Option 0 is the default decision.
+    fn branch<'tcx>(&mut self, options: usize, preempts: bool) -> InterpResult<'tcx, usize> {
+        if options <= 1 {
+            // Nothing to explore here.
+            return Ok(0);
+        }
+        let chosen = match self.prefix.get(self.branches.len()) {
+            None => 0,
+            Some(branch) if branch.options == options && branch.preempts == preempts =>
+                branch.chosen,
+            Some(_) =>
+                throw_machine_stop!(TerminationInfo::ScheduleDiverged(
+                    "the program made different decisions than in the previous execution, \
+                    so its schedules cannot be explored systematically"
+                        .to_owned()
+                )),
+        };
+        self.branches.push(Branch { chosen, options, preempts });
+        Ok(chosen)
+    }
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record {
+        out: BufWriter<File>,
+        path: PathBuf,
+    },
+    Replay {
+        /// The decisions that still have to be replayed, with the line they were read from.
+        decisions: VecDeque<(usize, Decision)>,
+        path: PathBuf,
+    },
+    Explore(Exploration),
+}
+
+/// The schedule that is being recorded, replayed, or explored.
+#[derive(Debug)]
+pub struct ScheduleLog {
+    mode: Mode,
+    /// The seed that was used when the schedule was recorded.
+    seed: Option<u64>,
+    /// The number of preemption points that were passed so far.
+    preemption_points: u64,
+}
+
+impl ScheduleLog {
+    /// Start recording the schedule of a program that is run with the given seed.
+    pub fn create(path: &Path, seed: u64) -> Result<Self, String> {
+        let mut out = File::create(path)
+            .map(BufWriter::new)
+            .map_err(|err| format!("failed to create schedule file `{}`: {err}", path.display()))?;
+        writeln!(out, "seed {seed}")
+            .map_err(|err| format!("failed to write schedule file `{}`: {err}", path.display()))?;
+        Ok(ScheduleLog {
+            mode: Mode::Record { out, path: path.to_owned() },
+            seed: Some(seed),
+            preemption_points: 0,
+        })
+    }
+
+    /// Load a previously recorded schedule for replaying it.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read schedule file `{}`: {err}", path.display()))?;
+        let mut seed = None;
+        let mut decisions = VecDeque::new();
+        for (line_number, line) in (1..).zip(contents.lines()) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid =
+                || format!("invalid entry `{line}` at line {line_number} of `{}`", path.display());
+            if let Some(recorded_seed) = line.strip_prefix("seed ") {
+                seed = Some(recorded_seed.trim().parse::<u64>().map_err(|_| invalid())?);
+                continue;
+            }
+            decisions.push_back((line_number, Decision::parse(line).ok_or_else(invalid)?));
+        }
+        Ok(ScheduleLog {
+            mode: Mode::Replay { decisions, path: path.to_owned() },
+            seed,
+            preemption_points: 0,
+        })
+    }
+
+    /// Start one execution of a systematic exploration, which begins with the decisions in
+    /// `prefix` and may preempt threads at most `preemption_bound` times.
+    pub fn explore(prefix: SchedulePrefix, preemption_bound: u32, seed: u64) -> Self {
+        ScheduleLog {
+            mode: Mode::Explore(Exploration {
+                prefix: prefix.0,
+                branches: Vec::new(),
+                preemption_bound,
+                visible_operation: false,
+                decisions: Vec::new(),
+            }),
+            seed: Some(seed),
+            preemption_points: 0,
+        }
+    }
+
+    /// The seed that was used when the schedule was recorded, if known.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The number of recorded decisions that have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        match &self.mode {
+            Mode::Replay { decisions, .. } => decisions.len(),
+            Mode::Record { .. } | Mode::Explore(_) => 0,
+        }
+    }
+
+    /// The file the schedule is recorded to or replayed from.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.mode {
+            Mode::Record { path, .. } | Mode::Replay { path, .. } => Some(path),
+            Mode::Explore(_) => None,
+        }
+    }
+
+    /// Make sure that everything recorded so far has been written to the file.
+    pub fn flush(&mut self) -> Result<(), String> {
+        if let Mode::Record { out, path } = &mut self.mode {
+            out.flush().map_err(|err| {
+                format!("failed to write schedule file `{}`: {err}", path.display())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// After an execution of a systematic exploration, determine the decisions that the next
+    /// execution has to start with. Returns `None` if all schedules have been explored.
+    pub fn next_schedule(&self) -> Option<SchedulePrefix> {
+        let Mode::Explore(exploration) = &self.mode else { bug!("not exploring schedules") };
+        let branches = &exploration.branches;
+        // The number of preemptions that happened before each branch.
+        let preemptions: Vec<u32> = branches
+            .iter()
+            .scan(0u32, |preemptions, branch| {
+                let before = *preemptions;
+                if branch.preempts && branch.chosen > 0 {
+                    *preemptions = preemptions.checked_add(1).unwrap();
+                }
+                Some(before)
+            })
+            .collect();
+        // Take the next option at the last branch that still has one, if we can afford it.
+        let last = branches.iter().zip(preemptions).rposition(|(branch, preemptions)| {
+            branch.chosen.checked_add(1).unwrap() < branch.options
+                && (!branch.preempts || preemptions < exploration.preemption_bound)
+        })?;
+        let mut prefix = branches[..=last].to_vec();
+        prefix[last].chosen = prefix[last].chosen.checked_add(1).unwrap();
+        Some(SchedulePrefix(prefix))
+    }
+
+    /// Write the decisions of an execution of a systematic exploration to `path`, in a format that
+    /// can be replayed.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let Mode::Explore(exploration) = &self.mode else { bug!("not exploring schedules") };
+        let mut contents = format!("seed {}\n", self.seed.unwrap_or(0));
+        for decision in &exploration.decisions {
+            contents.push_str(&format!("{decision}\n"));
+        }
+        fs::write(path, contents)
+            .map_err(|err| format!("failed to write schedule file `{}`: {err}", path.display()))
+    }
+
+    /// Called for operations that other threads can observe, such as atomic accesses and releasing
+    /// locks.
+    pub fn visible_operation(&mut self) {
+        if let Mode::Explore(exploration) = &mut self.mode {
+            exploration.visible_operation = true;
+        }
+    }
+
+    /// Called whenever the scheduler has to pick the next thread to run. `candidates` lists the
+    /// threads that can run, the default choice first. When replaying, the recorded thread is
+    /// returned instead, and when exploring, the thread the current schedule asks for.
+    pub fn thread_switch<'tcx>(&mut self, candidates: &[ThreadId]) -> InterpResult<'tcx, ThreadId> {
+        let decisions = match &mut self.mode {
+            Mode::Record { .. } => {
+                self.write(Decision::Switch(candidates[0]))?;
+                return Ok(candidates[0]);
+            }
+            Mode::Explore(exploration) => {
+                let thread = candidates[exploration.branch(candidates.len(), false)?];
+                exploration.decisions.push(Decision::Switch(thread));
+                return Ok(thread);
+            }
+            Mode::Replay { decisions, .. } => decisions,
+        };
+        let Some((line, decision)) = decisions.pop_front() else {
+            return self.exhausted("a thread switch");
+        };
+        match decision {
+            Decision::Switch(recorded) if candidates.contains(&recorded) => Ok(recorded),
+            Decision::Switch(recorded) =>
+                self.diverged(format!(
+                    "{} switches to thread {}, but that thread cannot run",
+                    self.location(line),
+                    recorded.to_u32()
+                )),
+            _ => self.unexpected(line, decision, "a thread switch"),
+        }
+    }
+
+    /// Called at every preemption point with the random decision whether the active thread should
+    /// be preempted, and whether any other thread could run. When replaying, the recorded decision
+    /// is returned instead, and when exploring, the decision the current schedule asks for.
+    pub fn preemption_point<'tcx>(
+        &mut self,
+        preempt: bool,
+        can_switch: bool,
+    ) -> InterpResult<'tcx, bool> {
+        let point = self.preemption_points;
+        self.preemption_points = point.checked_add(1).unwrap();
+        let decisions = match &mut self.mode {
+            Mode::Record { .. } => {
+                if preempt {
+                    self.write(Decision::Preempt(point))?;
+                }
+                return Ok(preempt);
+            }
+            Mode::Explore(exploration) => {
+                let visible_operation = std::mem::take(&mut exploration.visible_operation);
+                if !visible_operation || !can_switch {
+                    return Ok(false);
+                }
+                let preempt = exploration.branch(2, true)? == 1;
+                if preempt {
+                    exploration.decisions.push(Decision::Preempt(point));
+                }
+                return Ok(preempt);
+            }
+            Mode::Replay { decisions, .. } => decisions,
+        };
+        match decisions.front().copied() {
+            Some((_, Decision::Preempt(recorded))) if recorded == point => {
+                decisions.pop_front();
+                Ok(true)
+            }
+            Some((line, decision @ Decision::Preempt(recorded))) if recorded < point =>
+                self.diverged(format!(
+                    "{} records `{decision}`, but the program is already at preemption point {point}",
+                    self.location(line)
+                )),
+            _ => Ok(false),
+        }
+    }
+
+    /// Called when a weak memory load can pick from `candidates` stores, with `index` being the
+    /// randomly picked one. When replaying, the recorded index is returned instead, and when
+    /// exploring, the index the current schedule asks for.
+    pub fn load<'tcx>(&mut self, index: usize, candidates: usize) -> InterpResult<'tcx, usize> {
+        let decisions = match &mut self.mode {
+            Mode::Record { .. } => {
+                self.write(Decision::Load { index, candidates })?;
+                return Ok(index);
+            }
+            Mode::Explore(exploration) => {
+                let index = exploration.branch(candidates, true)?;
+                exploration.decisions.push(Decision::Load { index, candidates });
+                return Ok(index);
+            }
+            Mode::Replay { decisions, .. } => decisions,
+        };
+        let Some((line, decision)) = decisions.pop_front() else {
+            return self.exhausted("a weak memory load");
+        };
+        match decision {
+            Decision::Load { index, candidates: recorded } if recorded == candidates => Ok(index),
+            Decision::Load { .. } =>
+                self.diverged(format!(
+                    "{} records `{decision}`, but the load has {candidates} candidate stores",
+                    self.location(line)
+                )),
+            _ => self.unexpected(line, decision, "a weak memory load"),
+        }
+    }
+
+    fn write<'tcx>(&mut self, decision: Decision) -> InterpResult<'tcx> {
+        let Mode::Record { out, path } = &mut self.mode else {
+            bug!("writing to a schedule that is not being recorded")
+        };
+        if let Err(err) = writeln!(out, "{decision}") {
+            throw_machine_stop!(TerminationInfo::Abort(format!(
+                "failed to write schedule file `{}`: {err}",
+                path.display()
+            )));
+        }
+        Ok(())
+    }
+
+    fn location(&self, line: usize) -> String {
+        format!("line {line} of `{}`", self.path().unwrap().display())
+    }
+
+    fn exhausted<'tcx, T>(&self, expected: &str) -> InterpResult<'tcx, T> {
+        self.diverged(format!(
+            "`{}` has no more entries, but the program needs {expected}",
+            self.path().unwrap().display()
+        ))
+    }
+
+    fn unexpected<'tcx, T>(
+        &self,
+        line: usize,
+        decision: Decision,
+        expected: &str,
+    ) -> InterpResult<'tcx, T> {
+        self.diverged(format!(
+            "{} records `{decision}`, but the program needs {expected}",
+            self.location(line)
+        ))
+    }
+
+    fn diverged<'tcx, T>(&self, msg: String) -> InterpResult<'tcx, T> {
+        throw_machine_stop!(TerminationInfo::ScheduleDiverged(msg))
+    }
+}
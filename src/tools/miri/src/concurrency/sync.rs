@@ -368,6 +368,7 @@ fn mutex_lock(&mut self, id: MutexId, thread: ThreadId) {
     /// return `None`.
     fn mutex_unlock(&mut self, id: MutexId, expected_owner: ThreadId) -> Option<usize> {
         let this = self.eval_context_mut();
+        this.visible_operation();
         let current_span = this.machine.current_span();
         let mutex = &mut this.machine.threads.sync.mutexes[id];
         if let Some(current_owner) = mutex.owner {
@@ -468,6 +469,7 @@ fn rwlock_reader_lock(&mut self, id: RwLockId, reader: ThreadId) {
     /// Returns `true` if succeeded, `false` if this `reader` did not hold the lock.
     fn rwlock_reader_unlock(&mut self, id: RwLockId, reader: ThreadId) -> bool {
         let this = self.eval_context_mut();
+        this.visible_operation();
         let current_span = this.machine.current_span();
         let rwlock = &mut this.machine.threads.sync.rwlocks[id];
         match rwlock.readers.entry(reader) {
@@ -531,6 +533,7 @@ fn rwlock_writer_lock(&mut self, id: RwLockId, writer: ThreadId) {
     #[inline]
     fn rwlock_writer_unlock(&mut self, id: RwLockId, expected_writer: ThreadId) -> bool {
         let this = self.eval_context_mut();
+        this.visible_operation();
         let current_span = this.machine.current_span();
         let rwlock = &mut this.machine.threads.sync.rwlocks[id];
         if let Some(current_writer) = rwlock.writer {
@@ -616,6 +619,7 @@ fn condvar_wait(&mut self, id: CondvarId, thread: ThreadId, lock: CondvarLock) {
     /// variable.
     fn condvar_signal(&mut self, id: CondvarId) -> Option<(ThreadId, CondvarLock)> {
         let this = self.eval_context_mut();
+        this.visible_operation();
         let current_thread = this.get_active_thread();
         let current_span = this.machine.current_span();
         let condvar = &mut this.machine.threads.sync.condvars[id];
@@ -650,6 +654,7 @@ fn futex_wait(&mut self, addr: u64, thread: ThreadId, bitset: u32) {
 
     fn futex_wake(&mut self, addr: u64, bitset: u32) -> Option<ThreadId> {
         let this = self.eval_context_mut();
+        this.visible_operation();
         let current_thread = this.get_active_thread();
         let current_span = this.machine.current_span();
         let futex = &mut this.machine.threads.sync.futexes.get_mut(&addr)?;
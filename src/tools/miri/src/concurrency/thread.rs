@@ -2,8 +2,10 @@
 
 use std::cell::RefCell;
 use std::collections::hash_map::Entry;
+use std::iter;
 use std::num::TryFromIntError;
 use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::sync::Once;
 use std::task::Poll;
 use std::time::{Duration, SystemTime};
 
@@ -18,6 +20,7 @@
 use rustc_target::spec::abi::Abi;
 
 use crate::concurrency::data_race;
+use crate::concurrency::schedule_log::ScheduleLog;
 use crate::concurrency::sync::SynchronizationState;
 use crate::shims::tls;
 use crate::*;
@@ -477,6 +480,13 @@ fn have_all_terminated(&self) -> bool {
         self.threads.iter().all(|thread| thread.state == ThreadState::Terminated)
     }
 
+    /// Is any thread other than the active one enabled?
+    fn other_threads_enabled(&self) -> bool {
+        self.threads
+            .iter_enumerated()
+            .any(|(id, thread)| id != self.active_thread && thread.state == ThreadState::Enabled)
+    }
+
     /// Enable the thread for execution. The thread must be terminated.
     fn enable_thread(&mut self, thread_id: ThreadId) {
         assert!(self.has_terminated(thread_id));
@@ -700,7 +710,14 @@ fn thread_terminated(
     /// used in stateless model checkers such as Loom: run the active thread as
     /// long as we can and switch only when we have to (the active thread was
     /// blocked, terminated, or has explicitly asked to be preempted).
-    fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
+    ///
+    /// If a `schedule_log` is given, the choice of the next thread is recorded in it, or, when
+    /// replaying or exploring schedules, taken from it.
+    fn schedule(
+        &mut self,
+        clock: &Clock,
+        schedule_log: Option<&RefCell<ScheduleLog>>,
+    ) -> InterpResult<'tcx, SchedulingAction> {
         // This thread and the program can keep going.
         if self.threads[self.active_thread].state == ThreadState::Enabled
             && !self.yield_active_thread
@@ -727,20 +744,23 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
         // `skip(N)` means we start iterating at thread N, so we skip 1 more to start just *after*
         // the active thread. Then after that we look at `take(N)`, i.e., the threads *before* the
         // active thread.
-        let threads = self
+        let mut candidates = self
             .threads
             .iter_enumerated()
             .skip(self.active_thread.index() + 1)
-            .chain(self.threads.iter_enumerated().take(self.active_thread.index()));
-        for (id, thread) in threads {
-            debug_assert_ne!(self.active_thread, id);
-            if thread.state == ThreadState::Enabled {
-                self.active_thread = id;
-                break;
-            }
-        }
+            .chain(self.threads.iter_enumerated().take(self.active_thread.index()))
+            .filter_map(|(id, thread)| (thread.state == ThreadState::Enabled).then_some(id));
+        let mut next_thread = candidates.next().unwrap_or(self.active_thread);
         self.yield_active_thread = false;
-        if self.threads[self.active_thread].state == ThreadState::Enabled {
+        if self.threads[next_thread].state == ThreadState::Enabled {
+            // When replaying or exploring, this may pick a different thread. We only switch
+            // afterwards so that a divergence is reported on the thread that gave up control.
+            if let Some(schedule_log) = schedule_log {
+                // The thread we would have picked anyway is the default choice.
+                let candidates: Vec<ThreadId> = iter::once(next_thread).chain(candidates).collect();
+                next_thread = schedule_log.borrow_mut().thread_switch(&candidates)?;
+            }
+            self.active_thread = next_thread;
             return Ok(SchedulingAction::ExecuteStep);
         }
         // We have not found a thread to execute.
@@ -1005,13 +1025,29 @@ fn yield_active_thread(&mut self) {
     }
 
     #[inline]
-    fn maybe_preempt_active_thread(&mut self) {
+    fn maybe_preempt_active_thread(&mut self) -> InterpResult<'tcx> {
         use rand::Rng as _;
 
         let this = self.eval_context_mut();
-        if this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate) {
+        let mut preempt = this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate);
+        if let Some(schedule_log) = &this.machine.schedule_log {
+            let can_switch = this.machine.threads.other_threads_enabled();
+            preempt = schedule_log.borrow_mut().preemption_point(preempt, can_switch)?;
+        }
+        if preempt {
             this.yield_active_thread();
         }
+        Ok(())
+    }
+
+    /// Called for operations that other threads can observe. When exploring schedules, the active
+    /// thread is only preempted after such operations.
+    #[inline]
+    fn visible_operation(&self) {
+        let this = self.eval_context_ref();
+        if let Some(schedule_log) = &this.machine.schedule_log {
+            schedule_log.borrow_mut().visible_operation();
+        }
     }
 
     #[inline]
@@ -1038,24 +1074,32 @@ fn unregister_timeout_callback_if_exists(&mut self, thread: ThreadId) {
     /// termination).
     fn run_threads(&mut self) -> InterpResult<'tcx, !> {
         static SIGNALED: AtomicBool = AtomicBool::new(false);
-        ctrlc::set_handler(move || {
-            // Indicate that we have ben signaled to stop. If we were already signaled, exit
-            // immediately. In our interpreter loop we try to consult this value often, but if for
-            // whatever reason we don't get to that check or the cleanup we do upon finding that
-            // this bool has become true takes a long time, the exit here will promptly exit the
-            // process on the second Ctrl-C.
-            if SIGNALED.swap(true, Relaxed) {
-                std::process::exit(1);
-            }
-        })
-        .unwrap();
+        // When exploring schedules, the program runs several times in the same process.
+        static SET_HANDLER: Once = Once::new();
+        SET_HANDLER.call_once(|| {
+            ctrlc::set_handler(move || {
+                // Indicate that we have ben signaled to stop. If we were already signaled, exit
+                // immediately. In our interpreter loop we try to consult this value often, but if
+                // for whatever reason we don't get to that check or the cleanup we do upon finding
+                // that this bool has become true takes a long time, the exit here will promptly
+                // exit the process on the second Ctrl-C.
+                if SIGNALED.swap(true, Relaxed) {
+                    std::process::exit(1);
+                }
+            })
+            .unwrap();
+        });
         let this = self.eval_context_mut();
         loop {
             if SIGNALED.load(Relaxed) {
                 this.machine.handle_abnormal_termination();
                 std::process::exit(1);
             }
-            match this.machine.threads.schedule(&this.machine.clock)? {
+            match this
+                .machine
+                .threads
+                .schedule(&this.machine.clock, this.machine.schedule_log.as_ref())?
+            {
                 SchedulingAction::ExecuteStep => {
                     if !this.step()? {
                         // See if this thread can do something else.
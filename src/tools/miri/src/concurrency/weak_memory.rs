@@ -89,6 +89,7 @@
 use super::{
     data_race::{GlobalState as DataRaceState, ThreadClockSet},
     range_object_map::{AccessType, RangeObjectMap},
+    schedule_log::ScheduleLog,
     vector_clock::{VClock, VTimestamp, VectorIdx},
 };
 
@@ -281,6 +282,7 @@ fn buffered_read(
         thread_mgr: &ThreadManager<'_, '_>,
         is_seqcst: bool,
         rng: &mut (impl rand::Rng + ?Sized),
+        schedule_log: Option<&RefCell<ScheduleLog>>,
         validate: impl FnOnce() -> InterpResult<'tcx>,
     ) -> InterpResult<'tcx, (Scalar<Provenance>, LoadRecency)> {
         // Having a live borrow to store_buffer while calling validate_atomic_load is fine
@@ -291,7 +293,7 @@ fn buffered_read(
             // as the race detector will update it
             let (.., clocks) = global.current_thread_state(thread_mgr);
             // Load from a valid entry in the store buffer
-            self.fetch_store(is_seqcst, &clocks, &mut *rng)
+            self.fetch_store(is_seqcst, &clocks, &mut *rng, schedule_log)?
         };
 
         // Unlike in buffered_atomic_write, thread clock updates have to be done
@@ -325,83 +327,99 @@ fn fetch_store<R: rand::Rng + ?Sized>(
         is_seqcst: bool,
         clocks: &ThreadClockSet,
         rng: &mut R,
-    ) -> (&StoreElement, LoadRecency) {
+        schedule_log: Option<&RefCell<ScheduleLog>>,
+    ) -> InterpResult<'tcx, (&StoreElement, LoadRecency)> {
         use rand::seq::IteratorRandom;
-        let mut found_sc = false;
-        // FIXME: we want an inclusive take_while (stops after a false predicate, but
-        // includes the element that gave the false), but such function doesn't yet
-        // exist in the standard library https://github.com/rust-lang/rust/issues/62208
-        // so we have to hack around it with keep_searching
-        let mut keep_searching = true;
-        let candidates = self
-            .buffer
-            .iter()
-            .rev()
-            .take_while(move |&store_elem| {
-                if !keep_searching {
-                    return false;
-                }
+        // The candidates are produced by a function so that they can be walked again
+        // to count them and to find the one picked by a schedule.
+        let candidates = move || {
+            let mut found_sc = false;
+            // FIXME: we want an inclusive take_while (stops after a false predicate, but
+            // includes the element that gave the false), but such function doesn't yet
+            // exist in the standard library https://github.com/rust-lang/rust/issues/62208
+            // so we have to hack around it with keep_searching
+            let mut keep_searching = true;
+            self.buffer
+                .iter()
+                .rev()
+                .take_while(move |&store_elem| {
+                    if !keep_searching {
+                        return false;
+                    }
+
+                    keep_searching = if store_elem.timestamp <= clocks.clock[store_elem.store_index]
+                    {
+                        // CoWR: if a store happens-before the current load,
+                        // then we can't read-from anything earlier in modification order.
+                        // C++20 §6.9.2.2 [intro.races] paragraph 18
+                        false
+                    } else if store_elem.load_info.borrow().timestamps.iter().any(
+                        |(&load_index, &load_timestamp)| load_timestamp <= clocks.clock[load_index],
+                    ) {
+                        // CoRR: if there was a load from this store which happened-before the current load,
+                        // then we cannot read-from anything earlier in modification order.
+                        // C++20 §6.9.2.2 [intro.races] paragraph 16
+                        false
+                    } else if store_elem.timestamp <= clocks.fence_seqcst[store_elem.store_index] {
+                        // The current load, which may be sequenced-after an SC fence, cannot read-before
+                        // the last store sequenced-before an SC fence in another thread.
+                        // C++17 §32.4 [atomics.order] paragraph 6
+                        false
+                    } else if store_elem.timestamp <= clocks.write_seqcst[store_elem.store_index]
+                        && store_elem.is_seqcst
+                    {
+                        // The current non-SC load, which may be sequenced-after an SC fence,
+                        // cannot read-before the last SC store executed before the fence.
+                        // C++17 §32.4 [atomics.order] paragraph 4
+                        false
+                    } else if is_seqcst
+                        && store_elem.timestamp <= clocks.read_seqcst[store_elem.store_index]
+                    {
+                        // The current SC load cannot read-before the last store sequenced-before
+                        // the last SC fence.
+                        // C++17 §32.4 [atomics.order] paragraph 5
+                        false
+                    } else if is_seqcst && store_elem.load_info.borrow().sc_loaded {
+                        // The current SC load cannot read-before a store that an earlier SC load has observed.
+                        // See https://github.com/rust-lang/miri/issues/2301#issuecomment-1222720427
+                        // Consequences of C++20 §31.4 [atomics.order] paragraph 3.1, 3.3 (coherence-ordered before)
+                        // and 4.1 (coherence-ordered before between SC makes global total order S)
+                        false
+                    } else {
+                        true
+                    };
 
-                keep_searching = if store_elem.timestamp <= clocks.clock[store_elem.store_index] {
-                    // CoWR: if a store happens-before the current load,
-                    // then we can't read-from anything earlier in modification order.
-                    // C++20 §6.9.2.2 [intro.races] paragraph 18
-                    false
-                } else if store_elem.load_info.borrow().timestamps.iter().any(
-                    |(&load_index, &load_timestamp)| load_timestamp <= clocks.clock[load_index],
-                ) {
-                    // CoRR: if there was a load from this store which happened-before the current load,
-                    // then we cannot read-from anything earlier in modification order.
-                    // C++20 §6.9.2.2 [intro.races] paragraph 16
-                    false
-                } else if store_elem.timestamp <= clocks.fence_seqcst[store_elem.store_index] {
-                    // The current load, which may be sequenced-after an SC fence, cannot read-before
-                    // the last store sequenced-before an SC fence in another thread.
-                    // C++17 §32.4 [atomics.order] paragraph 6
-                    false
-                } else if store_elem.timestamp <= clocks.write_seqcst[store_elem.store_index]
-                    && store_elem.is_seqcst
-                {
-                    // The current non-SC load, which may be sequenced-after an SC fence,
-                    // cannot read-before the last SC store executed before the fence.
-                    // C++17 §32.4 [atomics.order] paragraph 4
-                    false
-                } else if is_seqcst
-                    && store_elem.timestamp <= clocks.read_seqcst[store_elem.store_index]
-                {
-                    // The current SC load cannot read-before the last store sequenced-before
-                    // the last SC fence.
-                    // C++17 §32.4 [atomics.order] paragraph 5
-                    false
-                } else if is_seqcst && store_elem.load_info.borrow().sc_loaded {
-                    // The current SC load cannot read-before a store that an earlier SC load has observed.
-                    // See https://github.com/rust-lang/miri/issues/2301#issuecomment-1222720427
-                    // Consequences of C++20 §31.4 [atomics.order] paragraph 3.1, 3.3 (coherence-ordered before)
-                    // and 4.1 (coherence-ordered before between SC makes global total order S)
-                    false
-                } else {
-                    true
-                };
-
-                true
-            })
-            .filter(|&store_elem| {
-                if is_seqcst && store_elem.is_seqcst {
-                    // An SC load needs to ignore all but last store maked SC (stores not marked SC are not
-                    // affected)
-                    let include = !found_sc;
-                    found_sc = true;
-                    include
-                } else {
                     true
-                }
-            });
+                })
+                .filter(move |&store_elem| {
+                    if is_seqcst && store_elem.is_seqcst {
+                        // An SC load needs to ignore all but last store maked SC (stores not marked SC are not
+                        // affected)
+                        let include = !found_sc;
+                        found_sc = true;
+                        include
+                    } else {
+                        true
+                    }
+                })
+        };
 
-        let chosen = candidates.choose(rng).expect("store buffer cannot be empty");
+        // Pick the same way whether or not a schedule is logged, so that recording does not
+        // change the RNG draws and a `-Zmiri-seed` run reproduces the same execution.
+        let (mut index, mut chosen) =
+            candidates().enumerate().choose(rng).expect("store buffer cannot be empty");
+        if let Some(schedule_log) = schedule_log {
+            let count = candidates().count();
+            // Only loads that actually have a choice are part of the schedule.
+            if count > 1 {
+                index = schedule_log.borrow_mut().load(index, count)?;
+                chosen = candidates().nth(index).expect("schedule picked a missing store");
+            }
+        }
         if std::ptr::eq(chosen, self.buffer.back().expect("store buffer cannot be empty")) {
-            (chosen, LoadRecency::Latest)
+            Ok((chosen, LoadRecency::Latest))
         } else {
-            (chosen, LoadRecency::Outdated)
+            Ok((chosen, LoadRecency::Outdated))
         }
     }
 
@@ -517,6 +535,7 @@ fn buffered_atomic_read(
                     &this.machine.threads,
                     atomic == AtomicReadOrd::SeqCst,
                     &mut *rng,
+                    this.machine.schedule_log.as_ref(),
                     validate,
                 )?;
                 if global.track_outdated_loads && recency == LoadRecency::Outdated {
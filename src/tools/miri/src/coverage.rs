@@ -0,0 +1,155 @@
+//! Recording which basic blocks of the local crates were executed, for `-Zmiri-coverage`.
+//!
+//! Every time a terminator of a function in a local crate is about to be executed, the counter of
+//! its basic block is incremented. All monomorphizations of a function share their counters, so
+//! the counts refer to the polymorphic MIR of the function. When the program is done, the counts
+//! are mapped to source lines and written in the lcov tracefile format, which `genhtml`,
+//! `grcov`, and most CI coverage services understand. Every line gets the largest count of all
+//! basic blocks that have a statement or terminator starting on that line. Functions of the crate
+//! that is being interpreted that were never called show up with a count of 0; for functions of
+//! other local crates, we only know the ones that were called.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::{FileName, Span};
+
+/// Execution counts of the basic blocks of the local crates.
+#[derive(Debug)]
+pub struct Coverage {
+    /// The file the lcov report is written to, or `None` to print it to stdout.
+    path: Option<PathBuf>,
+    /// How often the terminator of each basic block was executed, per function.
+    counts: FxHashMap<DefId, FxHashMap<mir::BasicBlock, u64>>,
+}
+
+/// The coverage of a single function, as it ends up in the report.
+struct FunctionRecord {
+    name: String,
+    line: usize,
+    count: u64,
+}
+
+/// The coverage of a single source file, as it ends up in the report.
+#[derive(Default)]
+struct FileRecord {
+    functions: Vec<FunctionRecord>,
+    /// The count of each line that has code on it.
+    lines: BTreeMap<usize, u64>,
+}
+
+impl Coverage {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Coverage { path, counts: FxHashMap::default() }
+    }
+
+    /// Record that the terminator of `block` of the function `def_id` is about to be executed.
+    pub fn record(&mut self, def_id: DefId, block: mir::BasicBlock) {
+        let count = self.counts.entry(def_id).or_default().entry(block).or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// Write the report to the file given on creation, creating its directory if necessary, or
+    /// print it to stdout if no file was given.
+    pub fn write(&self, tcx: TyCtxt<'_>) -> Result<(), String> {
+        let report = self.lcov(tcx);
+        let Some(path) = &self.path else {
+            print!("{report}");
+            return Ok(());
+        };
+        let error =
+            |err: io::Error| format!("failed to write coverage report `{}`: {err}", path.display());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(error)?;
+        }
+        fs::write(path, report).map_err(error)
+    }
+
+    /// Render the report in the lcov tracefile format.
+    fn lcov(&self, tcx: TyCtxt<'_>) -> String {
+        // All functions of the crate that is being interpreted, and all other functions that ran.
+        let functions: BTreeSet<DefId> = tcx
+            .hir()
+            .body_owners()
+            .map(|def_id| def_id.to_def_id())
+            .filter(|&def_id| tcx.def_kind(def_id).is_fn_like() && tcx.is_mir_available(def_id))
+            .chain(self.counts.keys().copied())
+            .collect();
+
+        let mut files: BTreeMap<String, FileRecord> = BTreeMap::new();
+        for def_id in functions {
+            let body = tcx.optimized_mir(def_id);
+            let counts = self.counts.get(&def_id);
+            let count_of =
+                |block| counts.and_then(|counts| counts.get(&block)).copied().unwrap_or(0);
+            let Some((file, line)) = source_line(tcx, body.span) else {
+                continue;
+            };
+            let record = files.entry(file.clone()).or_default();
+            record.functions.push(FunctionRecord {
+                name: tcx.def_path_str(def_id),
+                line,
+                count: count_of(mir::START_BLOCK),
+            });
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                let count = count_of(block);
+                let spans = data.statements.iter().map(|statement| statement.source_info.span);
+                for span in spans.chain([data.terminator().source_info.span]) {
+                    let Some((span_file, line)) = source_line(tcx, span) else {
+                        continue;
+                    };
+                    // Code that was inlined from other files does not belong to this function.
+                    if span_file == file {
+                        let line_count = record.lines.entry(line).or_default();
+                        *line_count = (*line_count).max(count);
+                    }
+                }
+            }
+        }
+
+        let mut out = String::new();
+        for (file, record) in files {
+            writeln!(out, "TN:").unwrap();
+            writeln!(out, "SF:{file}").unwrap();
+            for function in &record.functions {
+                writeln!(out, "FN:{},{}", function.line, function.name).unwrap();
+            }
+            for function in &record.functions {
+                writeln!(out, "FNDA:{},{}", function.count, function.name).unwrap();
+            }
+            let functions_hit = record.functions.iter().filter(|function| function.count > 0);
+            writeln!(out, "FNF:{}", record.functions.len()).unwrap();
+            writeln!(out, "FNH:{}", functions_hit.count()).unwrap();
+            for (line, count) in &record.lines {
+                writeln!(out, "DA:{line},{count}").unwrap();
+            }
+            let lines_hit = record.lines.values().filter(|&&count| count > 0);
+            writeln!(out, "LF:{}", record.lines.len()).unwrap();
+            writeln!(out, "LH:{}", lines_hit.count()).unwrap();
+            writeln!(out, "end_of_record").unwrap();
+        }
+        out
+    }
+}
+
+/// The source file and line at which `span` starts, looking through macro expansions. Returns
+/// `None` for spans that do not point into a real source file.
+fn source_line(tcx: TyCtxt<'_>, span: Span) -> Option<(String, usize)> {
+    let span = span.source_callsite();
+    if span.is_dummy() {
+        return None;
+    }
+    let loc = tcx.sess.source_map().lookup_char_pos(span.lo());
+    match &loc.file.name {
+        FileName::Real(name) =>
+            Some((name.local_path_if_available().display().to_string(), loc.line)),
+        _ => None,
+    }
+}
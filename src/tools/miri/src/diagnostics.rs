@@ -1,6 +1,8 @@
 use std::fmt::{self, Write};
 use std::num::NonZero;
+use std::path::Path;
 
+use rustc_data_structures::fx::FxHashMap;
 use rustc_errors::{Diag, DiagMessage, Level};
 use rustc_span::{SpanData, Symbol, DUMMY_SP};
 use rustc_target::abi::{Align, Size};
@@ -29,6 +31,8 @@ pub enum TerminationInfo {
     },
     Int2PtrWithStrictProvenance,
     Deadlock,
+    /// The program made a scheduling decision that does not match the replayed schedule.
+    ScheduleDiverged(String),
     MultipleSymbolDefinitions {
         link_name: Symbol,
         first: SpanData,
@@ -70,6 +74,8 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             StackedBorrowsUb { msg, .. } => write!(f, "{msg}"),
             TreeBorrowsUb { title, .. } => write!(f, "{title}"),
             Deadlock => write!(f, "the evaluated program deadlocked"),
+            ScheduleDiverged(msg) =>
+                write!(f, "the evaluated program diverged from the recorded schedule: {msg}"),
             MultipleSymbolDefinitions { link_name, .. } =>
                 write!(f, "multiple definitions of symbol `{link_name}`"),
             SymbolShimClashing { link_name, .. } =>
@@ -216,6 +222,7 @@ pub fn report_error<'tcx, 'mir>(
             StackedBorrowsUb { .. } | TreeBorrowsUb { .. } | DataRace { .. } =>
                 Some("Undefined Behavior"),
             Deadlock => Some("deadlock"),
+            ScheduleDiverged(_) => Some("schedule replay failed"),
             MultipleSymbolDefinitions { .. } | SymbolShimClashing { .. } => None,
         };
         #[rustfmt::skip]
@@ -261,6 +268,11 @@ pub fn report_error<'tcx, 'mir>(
                 ],
             SymbolShimClashing { link_name, span } =>
                 vec![(Some(*span), format!("the `{link_name}` symbol is defined here"))],
+            ScheduleDiverged(_) =>
+                vec![
+                    (None, format!("this happens when the program, its inputs, or the Miri flags changed since the schedule was recorded")),
+                    (None, format!("record a new schedule with `-Zmiri-record-schedule` to capture the current behavior")),
+                ],
             Int2PtrWithStrictProvenance =>
                 vec![(None, format!("use Strict Provenance APIs (https://doc.rust-lang.org/nightly/std/ptr/index.html#strict-provenance, https://crates.io/crates/sptr) instead"))],
             DataRace { op1, extra, .. } => {
@@ -406,31 +418,104 @@ pub fn report_error<'tcx, 'mir>(
     None
 }
 
+/// Leaked allocations that have the same kind and were allocated by the same backtrace.
+struct LeakGroup<'tcx> {
+    kind: String,
+    backtrace: Vec<FrameInfo<'tcx>>,
+    count: u64,
+    bytes: u64,
+}
+
+/// Report the leaked allocations, either one by one or, if `summary` is set, grouped by allocation
+/// backtrace. If `json_path` is given, the grouped report is also written to that file, or to
+/// stdout if it is `-`.
 pub fn report_leaks<'mir, 'tcx>(
     ecx: &InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
     leaks: Vec<(AllocId, MemoryKind<MiriMemoryKind>, Allocation<Provenance, AllocExtra<'tcx>>)>,
+    summary: bool,
+    json_path: Option<&Path>,
 ) {
     let mut any_pruned = false;
+    let mut groups: Vec<LeakGroup<'tcx>> = Vec::new();
+    let mut group_index = FxHashMap::default();
     for (id, kind, mut alloc) in leaks {
-        let Some(backtrace) = alloc.extra.backtrace.take() else {
-            continue;
+        let (backtrace, pruned) = match alloc.extra.backtrace.take() {
+            Some(backtrace) => prune_stacktrace(backtrace, &ecx.machine),
+            None => (Vec::new(), false),
         };
-        let (backtrace, pruned) = prune_stacktrace(backtrace, &ecx.machine);
         any_pruned |= pruned;
-        report_msg(
-            DiagLevel::Error,
-            format!(
-                "memory leaked: {id:?} ({}, size: {:?}, align: {:?}), allocated here:",
-                kind,
-                alloc.size().bytes(),
-                alloc.align.bytes()
-            ),
-            vec![],
-            vec![],
-            vec![],
-            &backtrace,
-            &ecx.machine,
-        );
+        if !summary && !backtrace.is_empty() {
+            report_msg(
+                DiagLevel::Error,
+                format!(
+                    "memory leaked: {id:?} ({}, size: {:?}, align: {:?}), allocated here:",
+                    kind,
+                    alloc.size().bytes(),
+                    alloc.align.bytes()
+                ),
+                vec![],
+                vec![],
+                vec![],
+                &backtrace,
+                &ecx.machine,
+            );
+        }
+        if !summary && json_path.is_none() {
+            continue;
+        }
+        let kind = kind.to_string();
+        let key: Vec<_> = backtrace.iter().map(|frame| (frame.instance, frame.span)).collect();
+        let index = *group_index.entry((kind.clone(), key)).or_insert(groups.len());
+        if index == groups.len() {
+            groups.push(LeakGroup { kind, backtrace, count: 0, bytes: 0 });
+        }
+        let group = &mut groups[index];
+        group.count = group.count.checked_add(1).unwrap();
+        group.bytes = group.bytes.checked_add(alloc.size().bytes()).unwrap();
+    }
+    // Report the groups that leak the most memory first.
+    groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(b.count.cmp(&a.count)));
+
+    if summary {
+        for group in &groups {
+            let msg = format!(
+                "memory leaked: {} allocation{} ({}, total size: {})",
+                group.count,
+                if group.count == 1 { "" } else { "s" },
+                group.kind,
+                group.bytes
+            );
+            if group.backtrace.is_empty() {
+                // Without a backtrace, there is nothing to point at.
+                ecx.tcx.dcx().note(msg);
+            } else {
+                report_msg(
+                    DiagLevel::Error,
+                    format!("{msg}, allocated here:"),
+                    vec![],
+                    vec![],
+                    vec![],
+                    &group.backtrace,
+                    &ecx.machine,
+                );
+            }
+        }
+        if !groups.is_empty() {
+            let (count, bytes) = leak_totals(&groups);
+            ecx.tcx.dcx().note(format!(
+                "{count} leaked allocations with a total size of {bytes} bytes, \
+                allocated at {} different places",
+                groups.len()
+            ));
+        }
+    }
+    if let Some(path) = json_path {
+        let report = leak_report_json(ecx, &groups);
+        if path == Path::new("-") {
+            print!("{report}");
+        } else if let Err(err) = std::fs::write(path, report) {
+            ecx.tcx.dcx().err(format!("failed to write leak report `{}`: {err}", path.display()));
+        }
     }
     if any_pruned {
         ecx.tcx.dcx().note(
@@ -439,6 +524,71 @@ pub fn report_leaks<'mir, 'tcx>(
     }
 }
 
+/// The total number and size of the leaked allocations.
+fn leak_totals(groups: &[LeakGroup<'_>]) -> (u64, u64) {
+    groups.iter().fold((0, 0), |(count, bytes), group| {
+        (count.checked_add(group.count).unwrap(), bytes.checked_add(group.bytes).unwrap())
+    })
+}
+
+/// Render the grouped leak report as JSON.
+fn leak_report_json<'tcx>(
+    ecx: &InterpCx<'_, 'tcx, MiriMachine<'_, 'tcx>>,
+    groups: &[LeakGroup<'tcx>],
+) -> String {
+    let source_map = ecx.tcx.sess.source_map();
+    let (count, bytes) = leak_totals(groups);
+    let mut out = String::new();
+    writeln!(out, "{{").unwrap();
+    writeln!(out, "  \"allocations\": {count},").unwrap();
+    writeln!(out, "  \"bytes\": {bytes},").unwrap();
+    write!(out, "  \"groups\": [").unwrap();
+    for (i, group) in groups.iter().enumerate() {
+        out.push_str(if i == 0 { "\n" } else { ",\n" });
+        writeln!(out, "    {{").unwrap();
+        writeln!(out, "      \"kind\": {},", json_string(&group.kind)).unwrap();
+        writeln!(out, "      \"allocations\": {},", group.count).unwrap();
+        writeln!(out, "      \"bytes\": {},", group.bytes).unwrap();
+        write!(out, "      \"backtrace\": [").unwrap();
+        for (j, frame) in group.backtrace.iter().enumerate() {
+            out.push_str(if j == 0 { "\n" } else { ",\n" });
+            write!(
+                out,
+                "        {{ \"function\": {}, \"location\": {} }}",
+                json_string(&frame.instance.to_string()),
+                json_string(&source_map.span_to_embeddable_string(frame.span))
+            )
+            .unwrap();
+        }
+        if !group.backtrace.is_empty() {
+            out.push_str("\n      ");
+        }
+        write!(out, "]\n    }}").unwrap();
+    }
+    if !groups.is_empty() {
+        out.push_str("\n  ");
+    }
+    writeln!(out, "]\n}}").unwrap();
+    out
+}
+
+/// Quote and escape a string for use in JSON.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len().checked_add(2).unwrap());
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => write!(out, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 /// Report an error or note (depending on the `error` argument) with the given stacktrace.
 /// Also emits a full stacktrace of the interpreter stack.
 /// We want to present a multi-line span message for some errors. Diagnostics do not support this
@@ -653,3 +803,16 @@ fn handle_ice(&self) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string("main"), r#""main""#);
+        assert_eq!(json_string(r#"leak::<'"'>"#), r#""leak::<'\"'>""#);
+        assert_eq!(json_string(r"C:\src\main.rs"), r#""C:\\src\\main.rs""#);
+        assert_eq!(json_string("a\nb\tc\u{7f}"), r#""a\nb\u0009c\u007f""#);
+    }
+}
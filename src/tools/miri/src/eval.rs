@@ -126,6 +126,14 @@ pub struct MiriConfig {
     /// If `Some`, enable the `measureme` profiler, writing results to a file
     /// with the specified prefix.
     pub measureme_out: Option<String>,
+    /// If `Some`, write an lcov coverage report of the local crates to a file in this directory.
+    pub coverage_out: Option<String>,
+    /// If `Some`, write the number of executed MIR instructions per function to a file in this
+    /// directory.
+    pub instruction_profile_out: Option<String>,
+    /// Whether the instruction profile attributes instructions to call stacks instead of single
+    /// functions.
+    pub instruction_profile_stacks: bool,
     /// Panic when unsupported functionality is encountered.
     pub panic_on_unsupported: bool,
     /// Which style to use for printing backtraces.
@@ -137,6 +145,10 @@ pub struct MiriConfig {
     pub mute_stdout_stderr: bool,
     /// The probability of the active thread being preempted at the end of each basic block.
     pub preemption_rate: f64,
+    /// Whether to record the scheduling decisions to a file, or replay them from a file.
+    pub schedule_log: Option<ScheduleLogMode>,
+    /// Run the program once for every schedule with at most this many preemptions.
+    pub explore_schedules: Option<u32>,
     /// Report the current instruction being executed every N basic blocks.
     pub report_progress: Option<u32>,
     /// Whether Stacked Borrows and Tree Borrows retagging should recurse into fields of datatypes.
@@ -152,6 +164,10 @@ pub struct MiriConfig {
     pub page_size: Option<u64>,
     /// Whether to collect a backtrace when each allocation is created, just in case it leaks.
     pub collect_leak_backtraces: bool,
+    /// Whether to report leaks grouped by allocation backtrace instead of one by one.
+    pub leak_summary: bool,
+    /// Where to write a JSON report of the leaks, grouped by allocation backtrace.
+    pub leak_report_json: Option<PathBuf>,
 }
 
 impl Default for MiriConfig {
@@ -177,11 +193,16 @@ fn default() -> MiriConfig {
             track_outdated_loads: false,
             cmpxchg_weak_failure_rate: 0.8, // 80%
             measureme_out: None,
+            coverage_out: None,
+            instruction_profile_out: None,
+            instruction_profile_stacks: false,
             panic_on_unsupported: false,
             backtrace_style: BacktraceStyle::Short,
             provenance_mode: ProvenanceMode::Default,
             mute_stdout_stderr: false,
             preemption_rate: 0.01, // 1%
+            schedule_log: None,
+            explore_schedules: None,
             report_progress: None,
             retag_fields: RetagFields::Yes,
             external_so_file: None,
@@ -189,6 +210,8 @@ fn default() -> MiriConfig {
             num_cpus: 1,
             page_size: None,
             collect_leak_backtraces: true,
+            leak_summary: false,
+            leak_report_json: None,
         }
     }
 }
@@ -422,10 +445,130 @@ pub fn eval_entry<'tcx>(
     entry_type: EntryFnType,
     config: MiriConfig,
 ) -> Option<i64> {
-    // Copy setting before we move `config`.
+    if let Some(preemption_bound) = config.explore_schedules {
+        return explore_schedules(tcx, entry_id, entry_type, config, preemption_bound);
+    }
+    let mut outputs = RunOutputs::default();
+    let return_code = run_entry(tcx, entry_id, entry_type, &config, &mut outputs);
+    outputs.write(tcx);
+    return_code
+}
+
+/// What an execution of the entry function leaves behind. When exploring schedules, the coverage
+/// and instruction counts are carried over from one execution to the next, so that they are only
+/// written once, for all executions together.
+#[derive(Default)]
+struct RunOutputs<'tcx> {
+    /// The schedule log of the execution, if any.
+    schedule_log: Option<ScheduleLog>,
+    coverage: Option<Coverage>,
+    instruction_profile: Option<InstructionProfile<'tcx>>,
+}
+
+impl<'tcx> RunOutputs<'tcx> {
+    /// Write the coverage report and the instruction profile, if they were collected.
+    fn write(&self, tcx: TyCtxt<'tcx>) {
+        if let Some(coverage) = &self.coverage {
+            if let Err(err) = coverage.write(tcx) {
+                tcx.dcx().err(err);
+            }
+        }
+        if let Some(instruction_profile) = &self.instruction_profile {
+            if let Err(err) = instruction_profile.write() {
+                tcx.dcx().err(err);
+            }
+        }
+    }
+}
+
+/// Evaluates the entry function once for every schedule that needs at most `preemption_bound`
+/// preemptions, until an execution fails or all schedules have been explored.
+/// Returns the result of the last execution, like `eval_entry`.
+///
+/// Only the first execution shows the output of the program and writes the leak report, so that
+/// they are not repeated for every schedule.
+#[allow(clippy::needless_lifetimes)]
+fn explore_schedules<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    mut config: MiriConfig,
+    preemption_bound: u32,
+) -> Option<i64> {
+    // When a schedule file is given, it receives the schedule of the failing execution.
+    let save_path = match config.schedule_log.take() {
+        Some(ScheduleLogMode::Record(path)) => Some(path),
+        _ => None,
+    };
+    let mut first_config = config.clone();
+    config.mute_stdout_stderr = true;
+    config.leak_report_json = None;
+    let mut outputs = RunOutputs::default();
+    let mut prefix = SchedulePrefix::default();
+    let mut count = 0u64;
+    loop {
+        count = count.checked_add(1).unwrap();
+        let run_config = if count == 1 { &mut first_config } else { &mut config };
+        run_config.schedule_log = Some(ScheduleLogMode::Explore { prefix, preemption_bound });
+        let return_code = run_entry(tcx, entry_id, entry_type, run_config, &mut outputs);
+        let schedule_log =
+            outputs.schedule_log.take().expect("exploring schedules without a schedule log");
+        if return_code != Some(0) {
+            tcx.dcx().note(format!("found a failing schedule after exploring {count} schedules"));
+            // Write what was collected up to and including the failing execution.
+            outputs.write(tcx);
+            if count > 1 {
+                tcx.dcx().note(
+                    "the output of the program is only shown for the first schedule; \
+                    replay the failing schedule to see its output",
+                );
+            }
+            match &save_path {
+                Some(path) =>
+                    match schedule_log.save(path) {
+                        Ok(()) =>
+                            tcx.dcx().note(format!(
+                                "the failing schedule was written to `{path}`; \
+                                pass `-Zmiri-replay-schedule={path}` to reproduce it",
+                                path = path.display()
+                            )),
+                        Err(err) => tcx.dcx().err(err),
+                    },
+                None =>
+                    tcx.dcx().note(
+                        "pass `-Zmiri-record-schedule=<file>` to write the failing schedule to a \
+                        file that can be replayed",
+                    ),
+            }
+            return return_code;
+        }
+        match schedule_log.next_schedule() {
+            Some(next) => prefix = next,
+            None => {
+                tcx.dcx().note(format!(
+                    "explored all {count} schedules with at most {preemption_bound} preemptions"
+                ));
+                outputs.write(tcx);
+                return return_code;
+            }
+        }
+    }
+}
+
+/// Evaluates the entry function specified by `entry_id` once, like `eval_entry`.
+/// The coverage and instruction counts in `outputs` are added to, and what the execution leaves
+/// behind is moved to `outputs`. Writing them is up to the caller.
+#[allow(clippy::needless_lifetimes)]
+fn run_entry<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+    outputs: &mut RunOutputs<'tcx>,
+) -> Option<i64> {
     let ignore_leaks = config.ignore_leaks;
 
-    let mut ecx = match create_ecx(tcx, entry_id, entry_type, &config) {
+    let mut ecx = match create_ecx(tcx, entry_id, entry_type, config) {
         Ok(v) => v,
         Err(err) => {
             let (kind, backtrace) = err.into_parts();
@@ -433,6 +576,13 @@ pub fn eval_entry<'tcx>(
             panic!("Miri initialization error: {kind:?}")
         }
     };
+    // Continue counting where the previous execution stopped.
+    if let Some(coverage) = outputs.coverage.take() {
+        ecx.machine.coverage = Some(coverage);
+    }
+    if let Some(instruction_profile) = outputs.instruction_profile.take() {
+        ecx.machine.instruction_profile = Some(instruction_profile);
+    }
 
     // Perform the main execution.
     let res: thread::Result<InterpResult<'_, !>> =
@@ -447,6 +597,28 @@ pub fn eval_entry<'tcx>(
         Ok(never) => match never {},
     };
 
+    // Make sure the recorded schedule ends up on disk, in particular if the program failed.
+    if let Some(schedule_log) = ecx.machine.schedule_log.take() {
+        let mut schedule_log = schedule_log.into_inner();
+        if let Err(err) = schedule_log.flush() {
+            tcx.dcx().err(err);
+        }
+        let remaining = schedule_log.remaining();
+        if remaining > 0 {
+            let path = schedule_log.path().expect("only a replayed schedule can have entries left");
+            tcx.dcx().warn(format!(
+                "the evaluated program stopped before replaying the entire schedule; \
+                {remaining} entries of `{}` were not used",
+                path.display()
+            ));
+        }
+        outputs.schedule_log = Some(schedule_log);
+    }
+
+    // Hand out the coverage and instruction counts, in particular if the program failed.
+    outputs.coverage = ecx.machine.coverage.take();
+    outputs.instruction_profile = ecx.machine.instruction_profile.take();
+
     // Machine cleanup. Only do this if all threads have terminated; threads that are still running
     // might cause Stacked Borrows errors (https://github.com/rust-lang/miri/issues/2396).
     if ecx.have_all_terminated() {
@@ -469,8 +641,10 @@ pub fn eval_entry<'tcx>(
         // Check for memory leaks.
         info!("Additional static roots: {:?}", ecx.machine.static_roots);
         let leaks = ecx.find_leaked_allocations(&ecx.machine.static_roots);
-        if !leaks.is_empty() {
-            report_leaks(&ecx, leaks);
+        let leaked = !leaks.is_empty();
+        // This also writes the JSON report if there are no leaks, so that it is never stale.
+        report_leaks(&ecx, leaks, config.leak_summary, config.leak_report_json.as_deref());
+        if leaked {
             let leak_message = "the evaluated program leaked memory, pass `-Zmiri-ignore-leaks` to disable this check";
             if ecx.machine.collect_leak_backtraces {
                 // If we are collecting leak backtraces, each leak is a distinct error diagnostic.
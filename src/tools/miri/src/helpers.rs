@@ -77,6 +77,41 @@ pub enum AccessKind {
     ]
 };
 
+/// The Windows counterpart of `UNIX_IO_ERROR_TABLE`, following `decode_error_kind` in std.
+const WINDOWS_IO_ERROR_TABLE: &[(&str, std::io::ErrorKind)] = {
+    use std::io::ErrorKind::*;
+    &[
+        ("ERROR_ACCESS_DENIED", PermissionDenied),
+        ("ERROR_ALREADY_EXISTS", AlreadyExists),
+        ("ERROR_BROKEN_PIPE", BrokenPipe),
+        ("ERROR_BUSY", ResourceBusy),
+        ("ERROR_CALL_NOT_IMPLEMENTED", Unsupported),
+        ("ERROR_DIR_NOT_EMPTY", DirectoryNotEmpty),
+        ("ERROR_DIRECTORY", NotADirectory),
+        ("ERROR_DIRECTORY_NOT_SUPPORTED", IsADirectory),
+        ("ERROR_DISK_FULL", StorageFull),
+        ("ERROR_DISK_QUOTA_EXCEEDED", FilesystemQuotaExceeded),
+        ("ERROR_FILE_NOT_FOUND", NotFound),
+        ("ERROR_FILE_TOO_LARGE", FileTooLarge),
+        ("ERROR_HOST_UNREACHABLE", HostUnreachable),
+        ("ERROR_INVALID_NAME", InvalidFilename),
+        ("ERROR_INVALID_PARAMETER", InvalidInput),
+        ("ERROR_NETWORK_UNREACHABLE", NetworkUnreachable),
+        ("ERROR_NOT_ENOUGH_MEMORY", OutOfMemory),
+        ("ERROR_NOT_SAME_DEVICE", CrossesDevices),
+        ("ERROR_POSSIBLE_DEADLOCK", Deadlock),
+        ("ERROR_SEEK_ON_DEVICE", NotSeekable),
+        ("ERROR_TIMEOUT", TimedOut),
+        ("ERROR_TOO_MANY_LINKS", TooManyLinks),
+        ("ERROR_WRITE_PROTECT", ReadOnlyFilesystem),
+        // The following have several valid options. We have all of them for the backwards mapping;
+        // only the first one will be used for the forwards mapping.
+        ("ERROR_FILE_EXISTS", AlreadyExists),
+        ("ERROR_PATH_NOT_FOUND", NotFound),
+        ("ERROR_FILENAME_EXCED_RANGE", InvalidFilename),
+    ]
+};
+
 /// Gets an instance for a path.
 ///
 /// A `None` namespace indicates we are looking for a module.
@@ -684,20 +719,12 @@ fn io_error_to_errnum(
             }
             throw_unsup_format!("io error {:?} cannot be translated into a raw os error", err_kind)
         } else if target.families.iter().any(|f| f == "windows") {
-            // FIXME: we have to finish implementing the Windows equivalent of this.
-            use std::io::ErrorKind::*;
-            Ok(this.eval_windows(
-                "c",
-                match err_kind {
-                    NotFound => "ERROR_FILE_NOT_FOUND",
-                    PermissionDenied => "ERROR_ACCESS_DENIED",
-                    _ =>
-                        throw_unsup_format!(
-                            "io error {:?} cannot be translated into a raw os error",
-                            err_kind
-                        ),
-                },
-            ))
+            for &(name, kind) in WINDOWS_IO_ERROR_TABLE {
+                if err_kind == kind {
+                    return Ok(this.eval_windows("c", name));
+                }
+            }
+            throw_unsup_format!("io error {:?} cannot be translated into a raw os error", err_kind)
         } else {
             throw_unsup_format!(
                 "converting io::Error into errnum is unsupported for OS {}",
@@ -724,6 +751,14 @@ fn try_errnum_to_io_error(
             // Our table is as complete as the mapping in std, so we are okay with saying "that's a
             // strange one" here.
             return Ok(None);
+        } else if target.families.iter().any(|f| f == "windows") {
+            let errnum = errnum.to_u32()?;
+            for &(name, kind) in WINDOWS_IO_ERROR_TABLE {
+                if errnum == this.eval_windows_u32("c", name) {
+                    return Ok(Some(kind));
+                }
+            }
+            return Ok(None);
         } else {
             throw_unsup_format!(
                 "converting errnum into io::Error is unsupported for OS {}",
@@ -0,0 +1,105 @@
+//! Counting the MIR statements and terminators executed per function, for
+//! `-Zmiri-instruction-profile`.
+//!
+//! Whenever a terminator is about to be executed, all statements of its basic block have been
+//! executed as well, so we attribute the length of the block plus one to the function (or, with
+//! `-Zmiri-instruction-profile-stacks`, to the entire call stack of the active thread) that the
+//! block belongs to. When the program is done, the counts are written in the "folded stacks"
+//! format that `flamegraph.pl` and `inferno-flamegraph` read: one line per stack, with the frames
+//! from the outermost to the innermost separated by `;`, followed by a space and the count.
+//! Without call stacks, every line consists of a single frame, so the flamegraph degenerates into a
+//! list of functions sorted by their own instruction count.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::Instance;
+
+/// Executed instructions per function or per call stack.
+#[derive(Debug)]
+pub struct InstructionProfile<'tcx> {
+    /// The file the folded stacks are written to, or `None` to print them to stdout.
+    path: Option<PathBuf>,
+    /// Whether to attribute the instructions to entire call stacks rather than single functions.
+    stacks: bool,
+    /// How many instructions each function executed itself, if `stacks` is not set.
+    functions: FxHashMap<Instance<'tcx>, u64>,
+    /// How many instructions were executed in each call stack (outermost frame first), if `stacks`
+    /// is set.
+    call_stacks: FxHashMap<Vec<Instance<'tcx>>, u64>,
+}
+
+impl<'tcx> InstructionProfile<'tcx> {
+    pub fn new(path: Option<PathBuf>, stacks: bool) -> Self {
+        InstructionProfile {
+            path,
+            stacks,
+            functions: FxHashMap::default(),
+            call_stacks: FxHashMap::default(),
+        }
+    }
+
+    /// Whether the caller has to provide entire call stacks, using `record_stack`.
+    pub fn records_stacks(&self) -> bool {
+        self.stacks
+    }
+
+    /// Record that `instance` executed `count` more instructions.
+    pub fn record_function(&mut self, instance: Instance<'tcx>, count: u64) {
+        let total = self.functions.entry(instance).or_default();
+        *total = total.saturating_add(count);
+    }
+
+    /// Record that the innermost frame of `stack` executed `count` more instructions.
+    pub fn record_stack(&mut self, stack: Vec<Instance<'tcx>>, count: u64) {
+        let total = self.call_stacks.entry(stack).or_default();
+        *total = total.saturating_add(count);
+    }
+
+    /// Write the folded stacks to the file given on creation, creating its directory if necessary,
+    /// or print them to stdout if no file was given.
+    pub fn write(&self) -> Result<(), String> {
+        let folded = self.folded();
+        let Some(path) = &self.path else {
+            print!("{folded}");
+            return Ok(());
+        };
+        let error = |err: io::Error| {
+            format!("failed to write instruction profile `{}`: {err}", path.display())
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(error)?;
+        }
+        fs::write(path, folded).map_err(error)
+    }
+
+    /// Render the counts in the folded stacks format.
+    fn folded(&self) -> String {
+        // Different instances can have the same name (e.g. shims of the same function), so we
+        // merge them here. This also sorts the stacks, which keeps the output deterministic.
+        let mut lines: BTreeMap<String, u64> = BTreeMap::new();
+        let functions = self.functions.iter().map(|(&instance, &count)| (vec![instance], count));
+        let call_stacks = self.call_stacks.iter().map(|(stack, &count)| (stack.clone(), count));
+        for (stack, count) in functions.chain(call_stacks) {
+            let stack: Vec<String> = stack.into_iter().map(frame_name).collect();
+            let total = lines.entry(stack.join(";")).or_default();
+            *total = total.saturating_add(count);
+        }
+
+        let mut out = String::new();
+        for (stack, count) in lines {
+            writeln!(out, "{stack} {count}").unwrap();
+        }
+        out
+    }
+}
+
+/// The name of `instance` as a frame of a folded stack, which must not contain the frame
+/// separator. Semicolons only show up in array types like `[u8; 4]`, so we turn them into colons.
+fn frame_name(instance: Instance<'_>) -> String {
+    instance.to_string().replace(';', ":")
+}
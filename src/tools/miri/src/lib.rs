@@ -74,9 +74,11 @@
 mod borrow_tracker;
 mod clock;
 mod concurrency;
+mod coverage;
 mod diagnostics;
 mod eval;
 mod helpers;
+mod instruction_profile;
 mod intptrcast;
 mod machine;
 mod mono_hash_map;
@@ -111,9 +113,11 @@
 pub use crate::concurrency::{
     data_race::{AtomicFenceOrd, AtomicReadOrd, AtomicRwOrd, AtomicWriteOrd, EvalContextExt as _},
     init_once::{EvalContextExt as _, InitOnceId},
+    schedule_log::{ScheduleLog, ScheduleLogMode, SchedulePrefix},
     sync::{CondvarId, EvalContextExt as _, MutexId, RwLockId, SyncId},
     thread::{EvalContextExt as _, StackEmptyCallback, ThreadId, ThreadManager, Time},
 };
+pub use crate::coverage::Coverage;
 pub use crate::diagnostics::{
     report_error, EvalContextExt as _, NonHaltingDiagnostic, TerminationInfo,
 };
@@ -121,6 +125,7 @@
     create_ecx, eval_entry, AlignmentCheck, BacktraceStyle, IsolatedOp, MiriConfig, RejectOpWith,
 };
 pub use crate::helpers::{AccessKind, EvalContextExt as _};
+pub use crate::instruction_profile::InstructionProfile;
 pub use crate::intptrcast::{EvalContextExt as _, ProvenanceMode};
 pub use crate::machine::{
     AllocExtra, FrameExtra, MiriInterpCx, MiriInterpCxExt, MiriMachine, MiriMemoryKind,
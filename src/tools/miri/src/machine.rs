@@ -470,6 +470,12 @@ pub struct MiriMachine<'mir, 'tcx> {
     pub(crate) file_handler: shims::unix::FileHandler,
     /// The table of directory descriptors.
     pub(crate) dir_handler: shims::unix::DirHandler,
+    /// The threads blocked in `epoll_wait`.
+    pub(crate) epoll_waiters: Vec<shims::unix::EpollWaiter<'tcx>>,
+    /// The loopback network that sockets are attached to.
+    pub(crate) network: shims::unix::VirtualNetwork,
+    /// The threads blocked in socket operations.
+    pub(crate) socket_waiters: Vec<shims::unix::SocketWaiter<'tcx>>,
 
     /// This machine's monotone clock.
     pub(crate) clock: Clock,
@@ -490,6 +496,12 @@ pub struct MiriMachine<'mir, 'tcx> {
     /// used with `measureme`.
     string_cache: FxHashMap<String, measureme::StringId>,
 
+    /// If `-Zmiri-coverage` is set, how often the basic blocks of the local crates were executed.
+    pub(crate) coverage: Option<Coverage>,
+
+    /// If `-Zmiri-instruction-profile` is set, how many MIR instructions each function executed.
+    pub(crate) instruction_profile: Option<InstructionProfile<'tcx>>,
+
     /// Cache of `Instance` exported under the given `Symbol` name.
     /// `None` means no `Instance` exported under the given name is found.
     pub(crate) exported_symbols_cache: FxHashMap<Symbol, Option<Instance<'tcx>>>,
@@ -533,6 +545,10 @@ pub struct MiriMachine<'mir, 'tcx> {
     /// The probability of the active thread being preempted at the end of each basic block.
     pub(crate) preemption_rate: f64,
 
+    /// The schedule that is being recorded or replayed, if any.
+    /// Needs to be queried by weak memory loads, hence needs interior mutability.
+    pub(crate) schedule_log: Option<RefCell<ScheduleLog>>,
+
     /// If `Some`, we will report the current stack every N basic blocks.
     pub(crate) report_progress: Option<u32>,
     // The total number of blocks that have been executed.
@@ -584,14 +600,17 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
         let local_crates = helpers::get_local_crates(tcx);
         let layouts =
             PrimitiveLayouts::new(layout_cx).expect("Couldn't get layouts of primitive types");
-        let profiler = config.measureme_out.as_ref().map(|out| {
-            let crate_name = layout_cx
+        let crate_name = || {
+            layout_cx
                 .tcx
                 .sess
                 .opts
                 .crate_name
                 .clone()
-                .unwrap_or_else(|| "unknown-crate".to_string());
+                .unwrap_or_else(|| "unknown-crate".to_string())
+        };
+        let profiler = config.measureme_out.as_ref().map(|out| {
+            let crate_name = crate_name();
             let pid = process::id();
             // We adopt the same naming scheme for the profiler output that rustc uses. In rustc,
             // the PID is padded so that the nondeterministic value of the PID does not spread
@@ -601,7 +620,34 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             let path = Path::new(out).join(filename);
             measureme::Profiler::new(path).expect("Couldn't create `measureme` profiler")
         });
-        let rng = StdRng::seed_from_u64(config.seed.unwrap_or(0));
+        let coverage = config.coverage_out.as_ref().map(|out| {
+            // `-` prints the report instead of writing it to a file.
+            let path = (out != "-").then(|| Path::new(out).join(format!("{}.info", crate_name())));
+            Coverage::new(path)
+        });
+        let instruction_profile = config.instruction_profile_out.as_ref().map(|out| {
+            // `-` prints the profile instead of writing it to a file.
+            let path =
+                (out != "-").then(|| Path::new(out).join(format!("{}.folded", crate_name())));
+            InstructionProfile::new(path, config.instruction_profile_stacks)
+        });
+        let schedule_log = config.schedule_log.as_ref().map(|mode| {
+            let log = match mode {
+                ScheduleLogMode::Record(path) =>
+                    ScheduleLog::create(path, config.seed.unwrap_or(0)),
+                ScheduleLogMode::Replay(path) => ScheduleLog::open(path),
+                ScheduleLogMode::Explore { prefix, preemption_bound } =>
+                    Ok(ScheduleLog::explore(
+                        prefix.clone(),
+                        *preemption_bound,
+                        config.seed.unwrap_or(0),
+                    )),
+            };
+            log.unwrap_or_else(|err| tcx.dcx().fatal(err))
+        });
+        // When replaying a schedule, default to the seed it was recorded with.
+        let seed = config.seed.or_else(|| schedule_log.as_ref().and_then(ScheduleLog::seed));
+        let rng = StdRng::seed_from_u64(seed.unwrap_or(0));
         let borrow_tracker = config.borrow_tracker.map(|bt| bt.instantiate_global_state(config));
         let data_race = config.data_race_detector.then(|| data_race::GlobalState::new(config));
         // Determine page size, stack address, and stack size.
@@ -647,10 +693,15 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             enforce_abi: config.check_abi,
             file_handler: FileHandler::new(config.mute_stdout_stderr),
             dir_handler: Default::default(),
+            epoll_waiters: Vec::new(),
+            network: Default::default(),
+            socket_waiters: Vec::new(),
             layouts,
             threads: ThreadManager::default(),
             static_roots: Vec::new(),
             profiler,
+            coverage,
+            instruction_profile,
             string_cache: Default::default(),
             exported_symbols_cache: FxHashMap::default(),
             panic_on_unsupported: config.panic_on_unsupported,
@@ -665,6 +716,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             mute_stdout_stderr: config.mute_stdout_stderr,
             weak_memory: config.weak_memory_emulation,
             preemption_rate: config.preemption_rate,
+            schedule_log: schedule_log.map(RefCell::new),
             report_progress: config.report_progress,
             basic_block_count: 0,
             clock: Clock::new(config.isolated_op == IsolatedOp::Allow),
@@ -745,6 +797,18 @@ pub(crate) fn handle_abnormal_termination(&mut self) {
         // the profiler, it is not possible to interpret the profile data and all measureme tools
         // will panic when given the file.
         drop(self.profiler.take());
+        // Write out the coverage and the instruction counts collected so far. There is no way to
+        // report errors here.
+        if let Some(coverage) = &self.coverage {
+            let _ = coverage.write(self.tcx);
+        }
+        if let Some(instruction_profile) = &self.instruction_profile {
+            let _ = instruction_profile.write();
+        }
+        // Write out what we have recorded of the schedule. There is no way to report errors here.
+        if let Some(schedule_log) = &self.schedule_log {
+            let _ = schedule_log.borrow_mut().flush();
+        }
     }
 
     pub(crate) fn page_align(&self) -> Align {
@@ -780,6 +844,8 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             cmd_line,
             extern_statics,
             dir_handler,
+            epoll_waiters,
+            socket_waiters,
             borrow_tracker,
             data_race,
             intptrcast,
@@ -788,11 +854,14 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             isolated_op: _,
             validate: _,
             enforce_abi: _,
+            network: _,
             clock: _,
             layouts: _,
             static_roots: _,
             profiler: _,
             string_cache: _,
+            coverage: _,
+            instruction_profile: _,
             exported_symbols_cache: _,
             panic_on_unsupported: _,
             backtrace_style: _,
@@ -805,6 +874,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             mute_stdout_stderr: _,
             weak_memory: _,
             preemption_rate: _,
+            schedule_log: _,
             report_progress: _,
             basic_block_count: _,
             external_so_lib: _,
@@ -825,6 +895,12 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         env_vars.visit_provenance(visit);
         dir_handler.visit_provenance(visit);
         file_handler.visit_provenance(visit);
+        for waiter in epoll_waiters {
+            waiter.visit_provenance(visit);
+        }
+        for waiter in socket_waiters {
+            waiter.visit_provenance(visit);
+        }
         data_race.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
         intptrcast.visit_provenance(visit);
@@ -1416,6 +1492,40 @@ fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx>
             }
         }
 
+        // Count the execution of this block for `-Zmiri-coverage`.
+        if ecx.machine.coverage.is_some() {
+            let frame = ecx.frame();
+            if let (ty::InstanceDef::Item(def_id), Either::Left(loc)) =
+                (frame.instance.def, frame.current_loc())
+            {
+                if def_id.is_local() || ecx.machine.local_crates.contains(&def_id.krate) {
+                    ecx.machine.coverage.as_mut().unwrap().record(def_id, loc.block);
+                }
+            }
+        }
+
+        // Count the instructions of this block for `-Zmiri-instruction-profile`.
+        if let Some(instruction_profile) = &ecx.machine.instruction_profile {
+            let frame = ecx.frame();
+            if let Either::Left(loc) = frame.current_loc() {
+                // All statements of the block have run by the time we reach its terminator.
+                let statements = frame.body.basic_blocks[loc.block].statements.len();
+                let count = u64::try_from(statements).unwrap() + 1;
+                if instruction_profile.records_stacks() {
+                    let stack =
+                        ecx.active_thread_stack().iter().map(|frame| frame.instance).collect();
+                    ecx.machine.instruction_profile.as_mut().unwrap().record_stack(stack, count);
+                } else {
+                    let instance = frame.instance;
+                    ecx.machine
+                        .instruction_profile
+                        .as_mut()
+                        .unwrap()
+                        .record_function(instance, count);
+                }
+            }
+        }
+
         // Search for BorTags to find all live pointers, then remove all other tags from borrow
         // stacks.
         // When debug assertions are enabled, run the GC as often as possible so that any cases
@@ -1426,7 +1536,7 @@ fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx>
         }
 
         // These are our preemption points.
-        ecx.maybe_preempt_active_thread();
+        ecx.maybe_preempt_active_thread()?;
 
         // Make sure some time passes.
         ecx.machine.clock.tick();
@@ -107,7 +107,7 @@ fn gettimeofday(
         Ok(0)
     }
 
-    #[allow(non_snake_case, clippy::arithmetic_side_effects)]
+    #[allow(non_snake_case)]
     fn GetSystemTimeAsFileTime(
         &mut self,
         LPFILETIME_op: &OpTy<'tcx, Provenance>,
@@ -118,6 +118,20 @@ fn GetSystemTimeAsFileTime(
         this.check_no_isolation("`GetSystemTimeAsFileTime`")?;
 
         let filetime = this.deref_pointer_as(LPFILETIME_op, this.windows_ty_layout("FILETIME"))?;
+        this.write_filetime(SystemTime::now(), &filetime)?;
+
+        Ok(())
+    }
+
+    /// Stores `time` in a Windows `FILETIME`, i.e., as the number of 100ns intervals since the
+    /// Windows epoch.
+    #[allow(non_snake_case, clippy::arithmetic_side_effects)]
+    fn write_filetime(
+        &mut self,
+        time: SystemTime,
+        filetime: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
 
         let NANOS_PER_SEC = this.eval_windows_u64("time", "NANOS_PER_SEC");
         let INTERVALS_PER_SEC = this.eval_windows_u64("time", "INTERVALS_PER_SEC");
@@ -125,14 +139,14 @@ fn GetSystemTimeAsFileTime(
         let NANOS_PER_INTERVAL = NANOS_PER_SEC / INTERVALS_PER_SEC;
         let SECONDS_TO_UNIX_EPOCH = INTERVALS_TO_UNIX_EPOCH / INTERVALS_PER_SEC;
 
-        let duration = system_time_to_duration(&SystemTime::now())?
-            + Duration::from_secs(SECONDS_TO_UNIX_EPOCH);
+        let duration =
+            system_time_to_duration(&time)? + Duration::from_secs(SECONDS_TO_UNIX_EPOCH);
         let duration_ticks = u64::try_from(duration.as_nanos() / u128::from(NANOS_PER_INTERVAL))
             .map_err(|_| err_unsup_format!("programs running more than 2^64 Windows ticks after the Windows epoch are not supported"))?;
 
         let dwLowDateTime = u32::try_from(duration_ticks & 0x00000000FFFFFFFF).unwrap();
         let dwHighDateTime = u32::try_from((duration_ticks & 0xFFFFFFFF00000000) >> 32).unwrap();
-        this.write_int_fields(&[dwLowDateTime.into(), dwHighDateTime.into()], &filetime)?;
+        this.write_int_fields(&[dwLowDateTime.into(), dwHighDateTime.into()], filetime)?;
 
         Ok(())
     }
@@ -9,7 +9,9 @@
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
 use shims::unix::fs::EvalContextExt as _;
+use shims::unix::linux::fd::EvalContextExt as _;
 use shims::unix::mem::EvalContextExt as _;
+use shims::unix::socket::EvalContextExt as _;
 use shims::unix::sync::EvalContextExt as _;
 use shims::unix::thread::EvalContextExt as _;
 
@@ -103,8 +105,11 @@ fn emulate_foreign_item_inner(
                 let fd = this.read_scalar(fd)?.to_i32()?;
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(count)?;
-                let result = this.read(fd, buf, count)?;
-                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                // A blocking `read` on a timer completes once the timer expires.
+                if !this.timerfd_read_or_block(fd, buf, count, dest)? {
+                    let result = this.read(fd, buf, count)?;
+                    this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                }
             }
             "write" => {
                 let [fd, buf, n] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -116,6 +121,13 @@ fn emulate_foreign_item_inner(
                 // Now, `result` is the value we return back to the program.
                 this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
             }
+            "ioctl" => {
+                // `ioctl` is variadic. The argument count is checked based on the second argument
+                // in `this.ioctl()`, so we do not use `check_shim` here.
+                this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
+                let result = this.ioctl(args)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
             "unlink" => {
                 let [path] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 let result = this.unlink(path)?;
@@ -219,6 +231,73 @@ fn emulate_foreign_item_inner(
                 this.write_scalar(Scalar::from_i32(result), dest)?;
             }
 
+            // Socket related shims
+            "socket" => {
+                let [domain, type_, protocol] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.socket(domain, type_, protocol)?;
+                this.write_scalar(result, dest)?;
+            }
+            "bind" => {
+                let [socket, address, address_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.bind(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "listen" => {
+                let [socket, backlog] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.listen(socket, backlog)?;
+                this.write_scalar(result, dest)?;
+            }
+            "connect" => {
+                let [socket, address, address_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.connect(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "accept" => {
+                let [socket, address, address_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.accept(socket, address, address_len, None, dest)?;
+            }
+            "send" => {
+                let [socket, buf, len, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.send(socket, buf, len, flags, None, dest)?;
+            }
+            "sendto" => {
+                let [socket, buf, len, flags, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.send(socket, buf, len, flags, Some((addr, addrlen)), dest)?;
+            }
+            "recv" => {
+                let [socket, buf, len, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.recv(socket, buf, len, flags, None, dest)?;
+            }
+            "recvfrom" => {
+                let [socket, buf, len, flags, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.recv(socket, buf, len, flags, Some((addr, addrlen)), dest)?;
+            }
+            "shutdown" => {
+                let [socket, how] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.shutdown(socket, how)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getsockname" => {
+                let [socket, address, address_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getsockname(socket, address, address_len, /* peer */ false)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getpeername" => {
+                let [socket, address, address_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getsockname(socket, address, address_len, /* peer */ true)?;
+                this.write_scalar(result, dest)?;
+            }
+            "setsockopt" => {
+                let [socket, level, option_name, option_value, option_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.setsockopt(socket, level, option_name, option_value, option_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getsockopt" => {
+                let [socket, level, option_name, option_value, option_len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getsockopt(socket, level, option_name, option_value, option_len)?;
+                this.write_scalar(result, dest)?;
+            }
+
             // Time related shims
             "gettimeofday" => {
                 let [tv, tz] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -13,13 +13,18 @@
 use rustc_target::abi::Size;
 
 use crate::shims::os_str::bytes_to_os_str;
+use crate::shims::unix::linux::fd::epoll::{Epoll, EpollReadyEvents};
 use crate::*;
 use shims::time::system_time_to_duration;
+use shims::unix::linux::fd::EvalContextExt as _;
+use shims::unix::socket::EvalContextExt as _;
 
 #[derive(Debug)]
 pub struct FileHandle {
-    file: File,
-    writable: bool,
+    pub file: File,
+    pub writable: bool,
+    /// The path the file was opened with, which inotify events about the file refer to.
+    pub path: PathBuf,
 }
 
 pub trait FileDescriptor: std::fmt::Debug + Any {
@@ -63,6 +68,12 @@ fn close<'tcx>(
     fn is_tty(&self, _communicate_allowed: bool) -> bool {
         false
     }
+
+    /// Returns the current readiness of this file descriptor for `epoll`, or `None` if it
+    /// cannot be watched by `epoll` (in which case `epoll_ctl` fails with `EPERM`).
+    fn get_epoll_ready_events(&self) -> Option<EpollReadyEvents> {
+        None
+    }
 }
 
 impl dyn FileDescriptor {
@@ -139,7 +150,11 @@ fn close<'tcx>(
 
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
         let duplicated = self.file.try_clone()?;
-        Ok(Box::new(FileHandle { file: duplicated, writable: self.writable }))
+        Ok(Box::new(FileHandle {
+            file: duplicated,
+            writable: self.writable,
+            path: self.path.clone(),
+        }))
     }
 
     fn is_tty(&self, communicate_allowed: bool) -> bool {
@@ -260,8 +275,14 @@ pub struct FileHandler {
 }
 
 impl VisitProvenance for FileHandler {
-    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
-        // All our FileDescriptor do not have any tags.
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        // The only FileDescriptor that holds on to tags is `Epoll`, in the user data of its
+        // registrations.
+        for file_descriptor in self.handles.values() {
+            if let Some(epoll) = file_descriptor.downcast_ref::<Epoll>() {
+                epoll.visit_provenance(visit);
+            }
+        }
     }
 }
 
@@ -614,12 +635,25 @@ fn open(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
             return Ok(-1);
         }
 
-        let fd = options.open(path).map(|file| {
-            let fh = &mut this.machine.file_handler;
-            fh.insert_fd(Box::new(FileHandle { file, writable }))
-        });
+        // Whether the file is created, which inotify reports.
+        let created = flag & o_creat == o_creat && !path.exists();
+        let file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(-1);
+            }
+        };
+        let path = path.into_owned();
+        let fd = this.machine.file_handler.insert_fd(Box::new(FileHandle {
+            file,
+            writable,
+            path: path.clone(),
+        }));
+        let events: &[&str] = if created { &["IN_CREATE", "IN_OPEN"] } else { &["IN_OPEN"] };
+        this.inotify_notify(&path, events, false)?;
 
-        this.try_unwrap_io_result(fd)
+        Ok(fd)
     }
 
     fn fcntl(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
@@ -685,7 +719,7 @@ fn fcntl(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32>
 
             if let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) {
                 // FIXME: Support fullfsync for all FDs
-                let FileHandle { file, writable } =
+                let FileHandle { file, writable, .. } =
                     file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
                         err_unsup_format!(
                             "`F_FULLFSYNC` is only supported on file-backed file descriptors"
@@ -706,14 +740,31 @@ fn close(&mut self, fd_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, Scalar
 
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
-        Ok(Scalar::from_i32(
-            if let Some(file_descriptor) = this.machine.file_handler.handles.remove(&fd) {
-                let result = file_descriptor.close(this.machine.communicate())?;
-                this.try_unwrap_io_result(result)?
-            } else {
-                this.handle_not_found()?
-            },
-        ))
+        let result = if let Some(file_descriptor) = this.machine.file_handler.handles.remove(&fd) {
+            let closed_file = file_descriptor
+                .downcast_ref::<FileHandle>()
+                .map(|file_handle| (file_handle.path.clone(), file_handle.writable));
+            let result = file_descriptor.close(this.machine.communicate())?;
+            let result = this.try_unwrap_io_result(result)?;
+            // Epoll interests are keyed by file descriptor number, so they are dropped with the
+            // file descriptor, or a new file descriptor with the same number would inherit them.
+            for handle in this.machine.file_handler.handles.values_mut() {
+                if let Some(epoll) = handle.downcast_mut::<Epoll>() {
+                    epoll.file_descriptors.remove(&fd);
+                }
+            }
+            if let Some((path, writable)) = closed_file {
+                let event = if writable { "IN_CLOSE_WRITE" } else { "IN_CLOSE_NOWRITE" };
+                this.inotify_notify(&path, &[event], false)?;
+            }
+            result
+        } else {
+            this.handle_not_found()?
+        };
+        // Closing a file descriptor can make its peer ready (e.g. for socketpairs), and fails the
+        // operations of threads blocked on it.
+        this.wake_ready_waiters()?;
+        Ok(Scalar::from_i32(result))
     }
 
     /// Function used when a handle is not found inside `FileHandler`. It returns `Ok(-1)`and sets
@@ -765,6 +816,11 @@ fn read(
                 Ok(read_bytes) => {
                     // If reading to `bytes` did not fail, we write those bytes to the buffer.
                     this.write_bytes_ptr(buf, bytes)?;
+                    if read_bytes > 0 {
+                        this.inotify_notify_fd(fd, "IN_ACCESS")?;
+                    }
+                    // Reading can make room for a blocked writer.
+                    this.wake_ready_waiters()?;
                     Ok(read_bytes)
                 }
                 Err(e) => {
@@ -803,7 +859,13 @@ fn write(
             let result = file_descriptor
                 .write(communicate, bytes, *this.tcx)?
                 .map(|c| i64::try_from(c).unwrap());
-            this.try_unwrap_io_result(result)
+            let result = this.try_unwrap_io_result(result)?;
+            if result > 0 {
+                this.inotify_notify_fd(fd, "IN_MODIFY")?;
+            }
+            // Writing can make a blocked reader ready.
+            this.wake_ready_waiters()?;
+            Ok(result)
         } else {
             this.handle_not_found()
         }
@@ -856,8 +918,12 @@ fn unlink(&mut self, path_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, i32
             return Ok(-1);
         }
 
-        let result = remove_file(path).map(|_| 0);
-        this.try_unwrap_io_result(result)
+        let result = remove_file(&path).map(|_| 0);
+        let result = this.try_unwrap_io_result(result)?;
+        if result == 0 {
+            this.inotify_notify(&path, &["IN_DELETE", "IN_DELETE_SELF"], false)?;
+        }
+        Ok(result)
     }
 
     fn symlink(
@@ -1188,9 +1254,14 @@ fn rename(
             return Ok(-1);
         }
 
-        let result = rename(oldpath, newpath).map(|_| 0);
+        let result = rename(&oldpath, &newpath).map(|_| 0);
 
-        this.try_unwrap_io_result(result)
+        let result = this.try_unwrap_io_result(result)?;
+        if result == 0 {
+            let is_dir = newpath.symlink_metadata().is_ok_and(|metadata| metadata.is_dir());
+            this.inotify_notify_rename(&oldpath, &newpath, is_dir)?;
+        }
+        Ok(result)
     }
 
     fn mkdir(
@@ -1227,9 +1298,13 @@ fn mkdir(
             builder.mode(mode);
         }
 
-        let result = builder.create(path).map(|_| 0i32);
+        let result = builder.create(&path).map(|_| 0i32);
 
-        this.try_unwrap_io_result(result)
+        let result = this.try_unwrap_io_result(result)?;
+        if result == 0 {
+            this.inotify_notify(&path, &["IN_CREATE"], true)?;
+        }
+        Ok(result)
     }
 
     fn rmdir(&mut self, path_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, i32> {
@@ -1244,9 +1319,13 @@ fn rmdir(&mut self, path_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, i32>
             return Ok(-1);
         }
 
-        let result = remove_dir(path).map(|_| 0i32);
+        let result = remove_dir(&path).map(|_| 0i32);
 
-        this.try_unwrap_io_result(result)
+        let result = this.try_unwrap_io_result(result)?;
+        if result == 0 {
+            this.inotify_notify(&path, &["IN_DELETE", "IN_DELETE_SELF"], true)?;
+        }
+        Ok(result)
     }
 
     fn opendir(
@@ -1532,7 +1611,7 @@ fn ftruncate64(&mut self, fd: i32, length: i128) -> InterpResult<'tcx, Scalar<Pr
         Ok(Scalar::from_i32(
             if let Some(file_descriptor) = this.machine.file_handler.handles.get_mut(&fd) {
                 // FIXME: Support ftruncate64 for all FDs
-                let FileHandle { file, writable } =
+                let FileHandle { file, writable, .. } =
                     file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
                         err_unsup_format!(
                             "`ftruncate64` is only supported on file-backed file descriptors"
@@ -1541,7 +1620,11 @@ fn ftruncate64(&mut self, fd: i32, length: i128) -> InterpResult<'tcx, Scalar<Pr
                 if *writable {
                     if let Ok(length) = length.try_into() {
                         let result = file.set_len(length);
-                        this.try_unwrap_io_result(result.map(|_| 0i32))?
+                        let result = this.try_unwrap_io_result(result.map(|_| 0i32))?;
+                        if result == 0 {
+                            this.inotify_notify_fd(fd, "IN_MODIFY")?;
+                        }
+                        result
                     } else {
                         let einval = this.eval_libc("EINVAL");
                         this.set_last_error(einval)?;
@@ -1578,7 +1661,7 @@ fn fsync(&mut self, fd_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, i32> {
 
         if let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) {
             // FIXME: Support fsync for all FDs
-            let FileHandle { file, writable } =
+            let FileHandle { file, writable, .. } =
                 file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
                     err_unsup_format!("`fsync` is only supported on file-backed file descriptors")
                 })?;
@@ -1603,7 +1686,7 @@ fn fdatasync(&mut self, fd_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, i3
 
         if let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) {
             // FIXME: Support fdatasync for all FDs
-            let FileHandle { file, writable } =
+            let FileHandle { file, writable, .. } =
                 file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
                     err_unsup_format!(
                         "`fdatasync` is only supported on file-backed file descriptors"
@@ -1653,7 +1736,7 @@ fn sync_file_range(
 
         if let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) {
             // FIXME: Support sync_data_range for all FDs
-            let FileHandle { file, writable } =
+            let FileHandle { file, writable, .. } =
                 file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
                     err_unsup_format!(
                         "`sync_data_range` is only supported on file-backed file descriptors"
@@ -1893,12 +1976,17 @@ fn mkstemp(&mut self, template_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx
 
             let possibly_unique = std::env::temp_dir().join::<PathBuf>(p.into());
 
-            let file = fopts.open(possibly_unique);
+            let file = fopts.open(&possibly_unique);
 
             match file {
                 Ok(f) => {
                     let fh = &mut this.machine.file_handler;
-                    let fd = fh.insert_fd(Box::new(FileHandle { file: f, writable: true }));
+                    let fd = fh.insert_fd(Box::new(FileHandle {
+                        file: f,
+                        writable: true,
+                        path: possibly_unique.clone(),
+                    }));
+                    this.inotify_notify(&possibly_unique, &["IN_CREATE", "IN_OPEN"], false)?;
                     return Ok(fd);
                 }
                 Err(e) =>
@@ -1,17 +1,28 @@
-use std::cell::Cell;
+use std::cell::RefCell;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
 
-use rustc_middle::ty::ScalarInt;
+use rustc_middle::ty::{ScalarInt, Ty};
 
+use crate::concurrency::thread::MachineCallback;
+use crate::shims::unix::FileHandle;
 use crate::*;
-use epoll::{Epoll, EpollEvent};
+use epoll::{Epoll, EpollEvent, EpollWaiter};
 use event::Event;
+use inotify::{canonicalize_parent, Inotify, InotifyEvent, InotifyState, InotifyWatch};
 use socketpair::SocketPair;
+use timer::Timer;
 
 use shims::unix::fs::EvalContextExt as _;
 
 pub mod epoll;
 pub mod event;
+pub mod inotify;
 pub mod socketpair;
+pub mod timer;
 
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
 pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
@@ -70,41 +81,93 @@ fn epoll_ctl(
         let epoll_ctl_mod = this.eval_libc_i32("EPOLL_CTL_MOD");
         let epoll_ctl_del = this.eval_libc_i32("EPOLL_CTL_DEL");
 
-        if op == epoll_ctl_add || op == epoll_ctl_mod {
+        if op != epoll_ctl_add && op != epoll_ctl_mod && op != epoll_ctl_del {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        // Both file descriptors have to exist, and the target has to support epoll.
+        if !this.machine.file_handler.handles.contains_key(&epfd) {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        }
+        let Some(target) = this.machine.file_handler.handles.get(&fd) else {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        };
+        // Like Linux, check this before whether the target supports epoll, which an epoll
+        // instance doesn't in Miri.
+        if fd == epfd {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        if target.get_epoll_ready_events().is_none() {
+            let eperm = this.eval_libc("EPERM");
+            this.set_last_error(eperm)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let new_event = if op == epoll_ctl_add || op == epoll_ctl_mod {
             let event = this.deref_pointer_as(event, this.libc_ty_layout("epoll_event"))?;
 
             let events = this.project_field(&event, 0)?;
             let events = this.read_scalar(&events)?.to_u32()?;
             let data = this.project_field(&event, 1)?;
             let data = this.read_scalar(&data)?;
-            let event = EpollEvent { events, data };
 
-            if let Some(epfd) = this.machine.file_handler.handles.get_mut(&epfd) {
-                let epfd = epfd
-                    .downcast_mut::<Epoll>()
-                    .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
-
-                epfd.file_descriptors.insert(fd, event);
-                Ok(Scalar::from_i32(0))
-            } else {
-                Ok(Scalar::from_i32(this.handle_not_found()?))
+            let supported = this.eval_libc_u32("EPOLLIN")
+                | this.eval_libc_u32("EPOLLOUT")
+                | this.eval_libc_u32("EPOLLRDHUP")
+                | this.eval_libc_u32("EPOLLHUP")
+                | this.eval_libc_u32("EPOLLERR")
+                | this.eval_libc_u32("EPOLLET")
+                | this.eval_libc_u32("EPOLLONESHOT");
+            if events & !supported != 0 {
+                throw_unsup_format!(
+                    "epoll_ctl: events {:#x} are not supported",
+                    events & !supported
+                );
             }
-        } else if op == epoll_ctl_del {
-            if let Some(epfd) = this.machine.file_handler.handles.get_mut(&epfd) {
-                let epfd = epfd
-                    .downcast_mut::<Epoll>()
-                    .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
 
-                epfd.file_descriptors.remove(&fd);
-                Ok(Scalar::from_i32(0))
-            } else {
-                Ok(Scalar::from_i32(this.handle_not_found()?))
-            }
+            Some(EpollEvent { events, data, last_reported: None, disabled: false })
         } else {
-            let einval = this.eval_libc("EINVAL");
-            this.set_last_error(einval)?;
-            Ok(Scalar::from_i32(-1))
+            None
+        };
+
+        let epoll = this
+            .machine
+            .file_handler
+            .handles
+            .get_mut(&epfd)
+            .unwrap()
+            .downcast_mut::<Epoll>()
+            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
+
+        let registered = epoll.file_descriptors.contains_key(&fd);
+        let error = if op == epoll_ctl_add && registered {
+            Some("EEXIST")
+        } else if op != epoll_ctl_add && !registered {
+            Some("ENOENT")
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            let error = this.eval_libc(error);
+            this.set_last_error(error)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        if let Some(new_event) = new_event {
+            // This also re-arms `EPOLLONESHOT` interests, and makes sure the current readiness
+            // is reported again for edge-triggered interests.
+            epoll.file_descriptors.insert(fd, new_event);
+            // The file descriptor might already be ready, so a thread blocked on this epoll
+            // instance might be able to make progress now.
+            this.epoll_wake_ready_waiters()?;
+        } else {
+            epoll.file_descriptors.remove(&fd);
         }
+        Ok(Scalar::from_i32(0))
     }
 
     /// The `epoll_wait()` system call waits for events on the `Epoll`
@@ -135,6 +198,10 @@ fn epoll_ctl(
     /// ready during the requested timeout milliseconds. On failure,
     /// `epoll_wait()` returns -1 and errno is set to indicate the error.
     ///
+    /// In Miri, a blocked thread is woken up by the interpreted program itself performing an
+    /// operation that makes one of the watched file descriptors ready (see
+    /// `epoll_wake_ready_waiters`), or by the timeout callback.
+    ///
     /// <https://man7.org/linux/man-pages/man2/epoll_wait.2.html>
     fn epoll_wait(
         &mut self,
@@ -142,24 +209,101 @@ fn epoll_wait(
         events: &OpTy<'tcx, Provenance>,
         maxevents: &OpTy<'tcx, Provenance>,
         timeout: &OpTy<'tcx, Provenance>,
-    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         let epfd = this.read_scalar(epfd)?.to_i32()?;
-        let _events = this.read_scalar(events)?.to_pointer(this)?;
-        let _maxevents = this.read_scalar(maxevents)?.to_i32()?;
-        let _timeout = this.read_scalar(timeout)?.to_i32()?;
+        let maxevents = this.read_scalar(maxevents)?.to_i32()?;
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
 
-        if let Some(epfd) = this.machine.file_handler.handles.get_mut(&epfd) {
-            let _epfd = epfd
-                .downcast_mut::<Epoll>()
-                .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
+        let maxevents = match u64::try_from(maxevents) {
+            Ok(maxevents) if maxevents > 0 => maxevents,
+            _ => {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                this.write_scalar(Scalar::from_i32(-1), dest)?;
+                return Ok(());
+            }
+        };
+        let event_layout = this.libc_ty_layout("epoll_event");
+        let events_layout = this.layout_of(Ty::new_array(this.tcx.tcx, event_layout.ty, maxevents))?;
+        let events = this.deref_pointer_as(events, events_layout)?;
 
-            // FIXME return number of events ready when scheme for marking events ready exists
-            throw_unsup_format!("returning ready events from epoll_wait is not yet implemented");
-        } else {
-            Ok(Scalar::from_i32(this.handle_not_found()?))
+        let Some(ready) = this.epoll_collect_ready_events(epfd, maxevents)? else {
+            let result = this.handle_not_found()?;
+            this.write_scalar(Scalar::from_i32(result), dest)?;
+            return Ok(());
+        };
+        if !ready.is_empty() || timeout == 0 {
+            let result = this.epoll_write_ready_events(ready, &events)?;
+            this.write_scalar(Scalar::from_i32(result), dest)?;
+            return Ok(());
         }
+
+        // Nothing is ready yet, so we block the thread until a watched file descriptor becomes
+        // ready, or the timeout expires. A negative timeout means there is no timeout.
+        let timeout = u64::try_from(timeout).ok().map(|timeout| {
+            this.machine.clock.now().checked_add(Duration::from_millis(timeout)).unwrap()
+        });
+        let thread = this.get_active_thread();
+        this.block_thread(thread);
+        this.machine.epoll_waiters.push(EpollWaiter {
+            thread,
+            epfd,
+            events,
+            maxevents,
+            timeout,
+            dest: dest.clone(),
+        });
+        this.epoll_schedule_wakeup(thread);
+
+        Ok(())
+    }
+
+    /// Wakes up the threads blocked in `epoll_wait` whose epoll instance now has ready events,
+    /// and writes those events to their buffers.
+    ///
+    /// This has to be called after every operation that can change the readiness of a file
+    /// descriptor.
+    fn epoll_wake_ready_waiters(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if this.machine.epoll_waiters.is_empty() {
+            return Ok(());
+        }
+
+        let waiters = std::mem::take(&mut this.machine.epoll_waiters);
+        let mut still_waiting = Vec::new();
+        for waiter in waiters {
+            // The epoll instance might have been closed in the mean time. The waiter then keeps
+            // waiting for its timeout, like on Linux.
+            let ready =
+                this.epoll_collect_ready_events(waiter.epfd, waiter.maxevents)?.unwrap_or_default();
+            if ready.is_empty() {
+                still_waiting.push(waiter);
+                continue;
+            }
+            this.unblock_thread(waiter.thread);
+            this.unregister_timeout_callback_if_exists(waiter.thread);
+            // Write the results as the woken-up thread, since it is that thread's `epoll_wait`
+            // that returns them.
+            let old_thread = this.set_active_thread(waiter.thread);
+            let result = this.epoll_write_ready_events(ready, &waiter.events)?;
+            this.write_scalar(Scalar::from_i32(result), &waiter.dest)?;
+            this.set_active_thread(old_thread);
+        }
+        // Nothing above can block a thread, so no new waiters were registered in the mean time.
+        assert!(this.machine.epoll_waiters.is_empty());
+        this.machine.epoll_waiters = still_waiting;
+        // The timers watched by the remaining waiters might have been changed or (un)registered.
+        let threads: Vec<ThreadId> =
+            this.machine.epoll_waiters.iter().map(|waiter| waiter.thread).collect();
+        for thread in threads {
+            this.epoll_schedule_wakeup(thread);
+        }
+
+        Ok(())
     }
 
     /// This function creates an `Event` that is used as an event wait/notify mechanism by
@@ -171,14 +315,12 @@ fn epoll_wait(
     /// `select`, and `close` operations can be performed on the file descriptor. For more
     /// information on these operations, see the man page linked below.
     ///
-    /// The `flags` are not currently implemented for eventfd.
     /// The `flags` may be bitwise ORed to change the behavior of `eventfd`:
     /// `EFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
     /// `EFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
     /// `EFD_SEMAPHORE` - miri does not support semaphore-like semantics.
     ///
     /// <https://linux.die.net/man/2/eventfd>
-    #[expect(clippy::needless_if)]
     fn eventfd(
         &mut self,
         val: &OpTy<'tcx, Provenance>,
@@ -193,23 +335,456 @@ fn eventfd(
         let efd_nonblock = this.eval_libc_i32("EFD_NONBLOCK");
         let efd_semaphore = this.eval_libc_i32("EFD_SEMAPHORE");
 
-        if flags & (efd_cloexec | efd_nonblock | efd_semaphore) == 0 {
-            throw_unsup_format!("{flags} is unsupported");
+        if flags & !(efd_cloexec | efd_nonblock | efd_semaphore) != 0 {
+            throw_unsup_format!("eventfd flags {flags:#x} are unsupported");
         }
-        // FIXME handle the cloexec and nonblock flags
-        if flags & efd_cloexec == efd_cloexec {}
-        if flags & efd_nonblock == efd_nonblock {}
         if flags & efd_semaphore == efd_semaphore {
             throw_unsup_format!("EFD_SEMAPHORE is unsupported");
         }
+        // Miri does not support exec, so `EFD_CLOEXEC` has no effect.
+        let is_nonblock = flags & efd_nonblock == efd_nonblock;
 
         let fh = &mut this.machine.file_handler;
-        let fd = fh.insert_fd(Box::new(Event { val: Cell::new(val.into()) }));
+        let fd = fh.insert_fd(Box::new(Event::new(val.into(), is_nonblock)));
         Ok(Scalar::from_i32(fd))
     }
 
-    /// Currently this function creates new `SocketPair`s without specifying the domain, type, or
-    /// protocol of the new socket and these are stored in the socket values `sv` argument.
+    /// This function creates a `Timer` that delivers timer expiration notifications via a file
+    /// descriptor. The timer runs on Miri's monotone clock, so with isolation enabled the
+    /// expirations happen after a deterministic amount of (virtual) time. Only `CLOCK_MONOTONIC`
+    /// is supported as `clockid`.
+    ///
+    /// The `flags` may be bitwise ORed to change the behavior of `timerfd_create`:
+    /// `TFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
+    /// `TFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+    fn timerfd_create(
+        &mut self,
+        clockid: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let clockid = this.read_scalar(clockid)?.to_i32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+
+        if clockid != this.eval_libc_i32("CLOCK_MONOTONIC") {
+            throw_unsup_format!(
+                "timerfd_create: clock {clockid} is unsupported, only CLOCK_MONOTONIC is"
+            );
+        }
+
+        let tfd_cloexec = this.eval_libc_i32("TFD_CLOEXEC");
+        let tfd_nonblock = this.eval_libc_i32("TFD_NONBLOCK");
+        if flags & !(tfd_cloexec | tfd_nonblock) != 0 {
+            throw_unsup_format!("timerfd_create flags {flags:#x} are unsupported");
+        }
+        // Miri does not support exec, so `TFD_CLOEXEC` has no effect.
+        let is_nonblock = flags & tfd_nonblock == tfd_nonblock;
+
+        let timer = Timer::new(this.machine.clock.clone(), is_nonblock);
+        let fd = this.machine.file_handler.insert_fd(Box::new(timer));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// This function arms or disarms the timer referred to by `fd`. The timer first expires after
+    /// the time given by `it_value` of `new_value` (or at that time of the monotone clock, if
+    /// `flags` contains `TFD_TIMER_ABSTIME`), and then every `it_interval`. An `it_value` of zero
+    /// disarms the timer. If `old_value` is not null, the previous setting is written to it.
+    ///
+    /// `TFD_TIMER_CANCEL_ON_SET` is not supported, since it is only meaningful for realtime clocks.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_settime.2.html>
+    fn timerfd_settime(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        new_value: &OpTy<'tcx, Provenance>,
+        old_value: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+        let new_value = this.deref_pointer_as(new_value, this.libc_ty_layout("itimerspec"))?;
+        let old_value_ptr = this.read_pointer(old_value)?;
+
+        let tfd_timer_abstime = this.eval_libc_i32("TFD_TIMER_ABSTIME");
+        if flags & this.eval_libc_i32("TFD_TIMER_CANCEL_ON_SET") != 0 {
+            throw_unsup_format!("timerfd_settime: TFD_TIMER_CANCEL_ON_SET is unsupported");
+        }
+
+        let Some(timer) = this.machine.file_handler.handles.get(&fd) else {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        };
+        if timer.downcast_ref::<Timer>().is_none() || flags & !tfd_timer_abstime != 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let interval = this.project_field_named(&new_value, "it_interval")?;
+        let value = this.project_field_named(&new_value, "it_value")?;
+        let (Some(interval), Some(value)) =
+            (this.read_timespec(&interval)?, this.read_timespec(&value)?)
+        else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let deadline = if value.is_zero() {
+            None
+        } else {
+            let start = if flags & tfd_timer_abstime != 0 {
+                this.machine.clock.anchor()
+            } else {
+                this.machine.clock.now()
+            };
+            let Some(deadline) = start.checked_add(value) else {
+                throw_unsup_format!(
+                    "timerfd_settime: the expiration time is too far in the future"
+                );
+            };
+            Some(deadline)
+        };
+
+        let timer = this.machine.file_handler.handles[&fd].downcast_ref::<Timer>().unwrap();
+        let (old_remaining, old_interval) = timer.get();
+        timer.set(deadline, interval);
+
+        if !this.ptr_is_null(old_value_ptr)? {
+            let old_value = this.deref_pointer_as(old_value, this.libc_ty_layout("itimerspec"))?;
+            this.write_itimerspec(old_remaining, old_interval, &old_value)?;
+        }
+        // An absolute expiration time might already have passed, and threads waiting for the
+        // timer have to wait for the new expiration time instead.
+        this.epoll_wake_ready_waiters()?;
+
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// This function writes the time until the next expiration of the timer referred to by `fd`,
+    /// and its interval, to `curr_value`. A disarmed timer has an `it_value` of zero.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_gettime.2.html>
+    fn timerfd_gettime(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        curr_value: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let curr_value = this.deref_pointer_as(curr_value, this.libc_ty_layout("itimerspec"))?;
+
+        let Some(timer) = this.machine.file_handler.handles.get(&fd) else {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        };
+        let Some(timer) = timer.downcast_ref::<Timer>() else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        let (remaining, interval) = timer.get();
+        this.write_itimerspec(remaining, interval, &curr_value)?;
+
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// A `read` on a timer blocks until the timer expires if it did not expire since it was last
+    /// read. If `fd` refers to such a timer, this blocks the active thread until the next
+    /// expiration, performs the `read` of `count` bytes into `buf` once it is woken up, and writes
+    /// the result to `dest`. Returns whether the thread was blocked; if not, the caller has to
+    /// perform the `read` itself.
+    fn timerfd_read_or_block(
+        &mut self,
+        fd: i32,
+        buf: Pointer<Option<Provenance>>,
+        count: u64,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_mut();
+
+        let Some(deadline) = this
+            .machine
+            .file_handler
+            .handles
+            .get(&fd)
+            .and_then(|timer| timer.downcast_ref::<Timer>())
+            .and_then(|timer| timer.blocking_read_deadline())
+        else {
+            return Ok(false);
+        };
+
+        struct Callback<'tcx> {
+            thread: ThreadId,
+            fd: i32,
+            buf: Pointer<Option<Provenance>>,
+            count: u64,
+            dest: MPlaceTy<'tcx, Provenance>,
+        }
+
+        impl<'tcx> VisitProvenance for Callback<'tcx> {
+            fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+                let Callback { thread: _, fd: _, buf, count: _, dest } = self;
+                buf.visit_provenance(visit);
+                dest.visit_provenance(visit);
+            }
+        }
+
+        impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+            fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                this.unblock_thread(self.thread);
+                // The timer might have been set to a later time in the mean time.
+                if !this.timerfd_read_or_block(self.fd, self.buf, self.count, &self.dest)? {
+                    let result = this.read(self.fd, self.buf, self.count)?;
+                    this.write_scalar(Scalar::from_target_isize(result, this), &self.dest)?;
+                }
+                Ok(())
+            }
+        }
+
+        let thread = this.get_active_thread();
+        this.block_thread(thread);
+        this.register_timeout_callback(
+            thread,
+            Time::Monotonic(deadline),
+            Box::new(Callback { thread, fd, buf, count, dest: dest.clone() }),
+        );
+
+        Ok(true)
+    }
+
+    /// This function creates an `Inotify` instance, which reports file system events about the
+    /// files and directories it watches. Miri only reports the events caused by the file system
+    /// operations of the interpreted program itself, see `inotify_notify`.
+    ///
+    /// The `flags` may be bitwise ORed to change the behavior of `inotify_init1`:
+    /// `IN_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
+    /// `IN_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
+    /// If `flags` is 0, this function is the same as `inotify_init()`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/inotify_init.2.html>
+    fn inotify_init1(&mut self, flags: i32) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let in_cloexec = this.eval_libc_i32("IN_CLOEXEC");
+        let in_nonblock = this.eval_libc_i32("IN_NONBLOCK");
+        if flags & !(in_cloexec | in_nonblock) != 0 {
+            throw_unsup_format!("inotify_init1 flags {flags:#x} are unsupported");
+        }
+        // Miri does not support exec, so `IN_CLOEXEC` has no effect.
+        let is_nonblock = flags & in_nonblock == in_nonblock;
+
+        let fd = this.machine.file_handler.insert_fd(Box::new(Inotify::new(is_nonblock)));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// This function adds a watch for the file or directory at `pathname` to the `Inotify`
+    /// instance referred to by `fd`, or modifies the watch if there already is one, and returns
+    /// its watch descriptor. The `mask` selects the events to report, and may contain these flags:
+    /// `IN_DONT_FOLLOW` - Do not dereference `pathname` if it is a symbolic link.
+    /// `IN_EXCL_UNLINK` - Has no effect, since Miri never reports events about unlinked files.
+    /// `IN_MASK_ADD` - Add the events to the ones an existing watch reports.
+    /// `IN_MASK_CREATE` - Fail with `EEXIST` if there already is a watch for `pathname`.
+    /// `IN_ONESHOT` - Remove the watch after its first event.
+    /// `IN_ONLYDIR` - Fail with `ENOTDIR` if `pathname` is not a directory.
+    ///
+    /// Watches refer to the canonical path of `pathname` rather than to its inode.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/inotify_add_watch.2.html>
+    fn inotify_add_watch(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        pathname: &OpTy<'tcx, Provenance>,
+        mask: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let path = this.read_path_from_c_str(this.read_pointer(pathname)?)?;
+        let mask = this.read_scalar(mask)?.to_u32()?;
+
+        let in_all_events = this.eval_libc_u32("IN_ALL_EVENTS");
+        let in_dont_follow = this.eval_libc_u32("IN_DONT_FOLLOW");
+        let in_excl_unlink = this.eval_libc_u32("IN_EXCL_UNLINK");
+        let in_mask_add = this.eval_libc_u32("IN_MASK_ADD");
+        let in_mask_create = this.eval_libc_u32("IN_MASK_CREATE");
+        let in_oneshot = this.eval_libc_u32("IN_ONESHOT");
+        let in_onlydir = this.eval_libc_u32("IN_ONLYDIR");
+        let supported = in_all_events
+            | in_dont_follow
+            | in_excl_unlink
+            | in_mask_add
+            | in_mask_create
+            | in_oneshot
+            | in_onlydir;
+        if mask & !supported != 0 {
+            throw_unsup_format!("inotify_add_watch: mask {:#x} is unsupported", mask & !supported);
+        }
+
+        let Some(inotify) = this.machine.file_handler.handles.get(&fd) else {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        };
+        let state = inotify.downcast_ref::<Inotify>().map(|inotify| Rc::clone(&inotify.state));
+        let Some(state) = state.filter(|_| {
+            mask & in_all_events != 0 && (mask & in_mask_add == 0 || mask & in_mask_create == 0)
+        }) else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`inotify_add_watch`", reject_with)?;
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let follow_symlinks = mask & in_dont_follow == 0;
+        let metadata = if follow_symlinks { path.metadata() } else { path.symlink_metadata() };
+        let path = metadata.and_then(|metadata| {
+            if mask & in_onlydir != 0 && !metadata.is_dir() {
+                return Err(ErrorKind::NotADirectory.into());
+            }
+            if follow_symlinks {
+                fs::canonicalize(&path)
+            } else {
+                canonicalize_parent(&path)
+            }
+        });
+        let path = match path {
+            Ok(path) => path,
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(Scalar::from_i32(-1));
+            }
+        };
+
+        let mut state = state.borrow_mut();
+        let existing = state.watches.iter().find(|(_, watch)| watch.path == path);
+        let wd = if let Some(wd) = existing.map(|(&wd, _)| wd) {
+            if mask & in_mask_create != 0 {
+                let eexist = this.eval_libc("EEXIST");
+                this.set_last_error(eexist)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+            let watch = state.watches.get_mut(&wd).unwrap();
+            if mask & in_mask_add != 0 {
+                watch.mask |= mask;
+            } else {
+                watch.mask = mask;
+            }
+            wd
+        } else {
+            state.last_wd = state.last_wd.checked_add(1).unwrap();
+            let wd = state.last_wd;
+            state.watches.insert(wd, InotifyWatch { path, mask });
+            wd
+        };
+
+        Ok(Scalar::from_i32(wd))
+    }
+
+    /// This function removes the watch `wd` from the `Inotify` instance referred to by `fd`,
+    /// which queues an `IN_IGNORED` event for it.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/inotify_rm_watch.2.html>
+    fn inotify_rm_watch(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        wd: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let wd = this.read_scalar(wd)?.to_i32()?;
+
+        let Some(inotify) = this.machine.file_handler.handles.get(&fd) else {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        };
+        let state = inotify.downcast_ref::<Inotify>().map(|inotify| Rc::clone(&inotify.state));
+        let Some(state) = state.filter(|state| state.borrow().watches.contains_key(&wd)) else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let in_ignored = this.eval_libc_u32("IN_IGNORED");
+        let mut state = state.borrow_mut();
+        state.watches.remove(&wd);
+        state.push(InotifyEvent { wd, mask: in_ignored, cookie: 0, name: None });
+        drop(state);
+        this.epoll_wake_ready_waiters()?;
+
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Reports the `events` (given by their names, e.g. `IN_CREATE`) that happened to the file or
+    /// directory at `path` to all inotify watches for `path` itself and for its parent directory.
+    /// Events like `IN_CREATE` are only reported to watches for the parent directory, and events
+    /// like `IN_DELETE_SELF` only to watches for `path` itself. `is_dir` says whether `path` is
+    /// a directory.
+    ///
+    /// This has to be called after every operation of the program that inotify reports. Changes
+    /// to the host file system that other processes make are not reported.
+    fn inotify_notify(&mut self, path: &Path, events: &[&str], is_dir: bool) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let events: Vec<(&Path, &str)> = events.iter().map(|&event| (path, event)).collect();
+        this.inotify_report(&events, is_dir, false)
+    }
+
+    /// Like `inotify_notify`, for the file that `fd` refers to. Does nothing if `fd` does not
+    /// refer to a file.
+    fn inotify_notify_fd(&mut self, fd: i32, event: &str) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let Some(file_handle) = this
+            .machine
+            .file_handler
+            .handles
+            .get(&fd)
+            .and_then(|file_descriptor| file_descriptor.downcast_ref::<FileHandle>())
+        else {
+            return Ok(());
+        };
+        let path = file_handle.path.clone();
+        this.inotify_notify(&path, &[event], false)
+    }
+
+    /// Reports the rename of `from` to `to` to all inotify watches, like `inotify_notify`. The
+    /// `IN_MOVED_FROM` and `IN_MOVED_TO` events share a cookie, and the watches for `from` and the
+    /// files and directories inside of it move along.
+    fn inotify_notify_rename(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        is_dir: bool,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        this.inotify_report(&[(from, "IN_MOVED_FROM"), (to, "IN_MOVED_TO")], is_dir, true)?;
+        this.inotify_report(&[(from, "IN_MOVE_SELF")], is_dir, false)?;
+
+        let (Ok(from), Ok(to)) = (canonicalize_parent(from), canonicalize_parent(to)) else {
+            return Ok(());
+        };
+        for state in this.inotify_instances() {
+            for watch in state.borrow_mut().watches.values_mut() {
+                if let Ok(rest) = watch.path.strip_prefix(&from) {
+                    watch.path =
+                        if rest.as_os_str().is_empty() { to.clone() } else { to.join(rest) };
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Currently this function only supports `AF_UNIX` stream sockets. The data written to one
+    /// end of the pair is buffered in the interpreter until it is read from the other end.
     ///
     /// This function creates an unnamed pair of connected sockets in the specified domain, of the
     /// specified type, and using the optionally specified protocol.
@@ -234,15 +809,32 @@ fn socketpair(
     ) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
-        let _domain = this.read_scalar(domain)?.to_i32()?;
-        let _type_ = this.read_scalar(type_)?.to_i32()?;
-        let _protocol = this.read_scalar(protocol)?.to_i32()?;
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let mut type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
         let sv = this.deref_pointer(sv)?;
 
+        let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+        let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+        let is_nonblock = type_ & sock_nonblock == sock_nonblock;
+        // Miri does not support exec, so `SOCK_CLOEXEC` has no effect.
+        type_ &= !(sock_nonblock | sock_cloexec);
+
+        if domain != this.eval_libc_i32("AF_UNIX") {
+            throw_unsup_format!("socketpair: domain {domain:#x} is unsupported, only AF_UNIX is");
+        }
+        if type_ != this.eval_libc_i32("SOCK_STREAM") {
+            throw_unsup_format!("socketpair: type {type_:#x} is unsupported, only SOCK_STREAM is");
+        }
+        if protocol != 0 {
+            throw_unsup_format!("socketpair: socket protocol {protocol} is unsupported");
+        }
+
+        let (socket0, socket1) = SocketPair::new_pair(is_nonblock);
         let fh = &mut this.machine.file_handler;
-        let sv0 = fh.insert_fd(Box::new(SocketPair));
+        let sv0 = fh.insert_fd(Box::new(socket0));
         let sv0 = ScalarInt::try_from_int(sv0, sv.layout.size).unwrap();
-        let sv1 = fh.insert_fd(Box::new(SocketPair));
+        let sv1 = fh.insert_fd(Box::new(socket1));
         let sv1 = ScalarInt::try_from_int(sv1, sv.layout.size).unwrap();
 
         this.write_scalar(sv0, &sv)?;
@@ -251,3 +843,306 @@ fn socketpair(
         Ok(Scalar::from_i32(0))
     }
 }
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Collects up to `maxevents` events that are ready on the epoll instance `epfd`, as pairs of
+    /// the event bitmask and the user data. Returns `None` if `epfd` is not an open file
+    /// descriptor.
+    ///
+    /// The returned events count as reported: edge-triggered interests will not return them again
+    /// until something new happens on their file descriptor, and `EPOLLONESHOT` interests are
+    /// disabled.
+    fn epoll_collect_ready_events(
+        &mut self,
+        epfd: i32,
+        maxevents: u64,
+    ) -> InterpResult<'tcx, Option<Vec<(u32, Scalar<Provenance>)>>> {
+        let this = self.eval_context_mut();
+
+        let epollet = this.eval_libc_u32("EPOLLET");
+        let epolloneshot = this.eval_libc_u32("EPOLLONESHOT");
+        // `EPOLLHUP` and `EPOLLERR` are always reported, whether they were asked for or not.
+        let always_reported = this.eval_libc_u32("EPOLLHUP") | this.eval_libc_u32("EPOLLERR");
+
+        let handles = &this.machine.file_handler.handles;
+        let Some(epoll) = handles.get(&epfd) else {
+            return Ok(None);
+        };
+        let epoll = epoll
+            .downcast_ref::<Epoll>()
+            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
+
+        let mut ready = Vec::new();
+        for (&fd, event) in &epoll.file_descriptors {
+            if u64::try_from(ready.len()).unwrap() >= maxevents {
+                break;
+            }
+            if event.disabled {
+                continue;
+            }
+            // Closed file descriptors are not reported anymore.
+            let Some(ready_events) =
+                handles.get(&fd).and_then(|target| target.get_epoll_ready_events())
+            else {
+                continue;
+            };
+            let bitmask = ready_events.get_event_bitmask(this) & (event.events | always_reported);
+            if bitmask == 0 {
+                continue;
+            }
+            // An edge-triggered interest is only reported again if something happened on the
+            // file descriptor, or it became ready for something new.
+            if event.events & epollet != 0
+                && let Some((generation, last_bitmask)) = event.last_reported
+                && generation == ready_events.generation
+                && bitmask & !last_bitmask == 0
+            {
+                continue;
+            }
+            ready.push((fd, bitmask, ready_events.generation, event.data));
+        }
+
+        let epoll = this
+            .machine
+            .file_handler
+            .handles
+            .get_mut(&epfd)
+            .unwrap()
+            .downcast_mut::<Epoll>()
+            .unwrap();
+        for &(fd, bitmask, generation, _) in &ready {
+            let event = epoll.file_descriptors.get_mut(&fd).unwrap();
+            event.last_reported = Some((generation, bitmask));
+            if event.events & epolloneshot != 0 {
+                event.disabled = true;
+            }
+        }
+
+        Ok(Some(ready.into_iter().map(|(_, bitmask, _, data)| (bitmask, data)).collect()))
+    }
+
+    /// (Re-)registers the timeout callback of `thread`, which is blocked in `epoll_wait`, for the
+    /// earliest of its timeout and the next expiration of a timer it watches. Timers become ready
+    /// because time passes rather than because of something the program does, so
+    /// `epoll_wake_ready_waiters` would not notice them.
+    fn epoll_schedule_wakeup(&mut self, thread: ThreadId) {
+        let this = self.eval_context_mut();
+
+        this.unregister_timeout_callback_if_exists(thread);
+
+        let waiter = this.machine.epoll_waiters.iter().find(|waiter| waiter.thread == thread);
+        let waiter = waiter.expect("only threads blocked in `epoll_wait` have a wakeup");
+        let handles = &this.machine.file_handler.handles;
+        let watched = handles
+            .get(&waiter.epfd)
+            .and_then(|epoll| epoll.downcast_ref::<Epoll>())
+            .map(|epoll| &epoll.file_descriptors);
+        let expirations = watched
+            .into_iter()
+            .flatten()
+            .filter(|(_, event)| !event.disabled)
+            .filter_map(|(fd, _)| handles.get(fd)?.downcast_ref::<Timer>()?.next_expiration());
+        let Some(wakeup) =
+            waiter.timeout.clone().into_iter().chain(expirations).reduce(|earliest, instant| {
+                if instant.duration_since(earliest.clone()).is_zero() {
+                    instant
+                } else {
+                    earliest
+                }
+            })
+        else {
+            // Only an operation of the program can wake up this thread.
+            return;
+        };
+
+        struct Callback {
+            thread: ThreadId,
+        }
+
+        impl VisitProvenance for Callback {
+            fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {}
+        }
+
+        impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback {
+            fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                let index = this
+                    .machine
+                    .epoll_waiters
+                    .iter()
+                    .position(|waiter| waiter.thread == self.thread)
+                    .unwrap();
+                let waiter = &this.machine.epoll_waiters[index];
+                let (epfd, maxevents) = (waiter.epfd, waiter.maxevents);
+                let timed_out = waiter.timeout.as_ref().is_some_and(|timeout| {
+                    timeout.duration_since(this.machine.clock.now()).is_zero()
+                });
+                let ready = this.epoll_collect_ready_events(epfd, maxevents)?.unwrap_or_default();
+                if ready.is_empty() && !timed_out {
+                    // A timer expired, but the program is not interested in that (anymore).
+                    this.epoll_schedule_wakeup(self.thread);
+                    return Ok(());
+                }
+                let waiter = this.machine.epoll_waiters.remove(index);
+                this.unblock_thread(self.thread);
+                // If nothing became ready in time, this returns 0.
+                let result = this.epoll_write_ready_events(ready, &waiter.events)?;
+                this.write_scalar(Scalar::from_i32(result), &waiter.dest)?;
+                Ok(())
+            }
+        }
+
+        this.register_timeout_callback(
+            thread,
+            Time::Monotonic(wakeup),
+            Box::new(Callback { thread }),
+        );
+    }
+
+    /// Returns the states of all open inotify instances.
+    fn inotify_instances(&self) -> Vec<Rc<RefCell<InotifyState>>> {
+        let this = self.eval_context_ref();
+
+        let mut instances: Vec<Rc<RefCell<InotifyState>>> = Vec::new();
+        for file_descriptor in this.machine.file_handler.handles.values() {
+            let Some(inotify) = file_descriptor.downcast_ref::<Inotify>() else {
+                continue;
+            };
+            // Duplicated file descriptors share their state.
+            if !instances.iter().any(|state| Rc::ptr_eq(state, &inotify.state)) {
+                instances.push(Rc::clone(&inotify.state));
+            }
+        }
+        instances
+    }
+
+    /// Queues the `events` for the given paths to all inotify watches, as described in
+    /// `inotify_notify`. If `with_cookie` is set, each inotify instance gives all of these events
+    /// the same new cookie.
+    fn inotify_report(
+        &mut self,
+        events: &[(&Path, &str)],
+        is_dir: bool,
+        with_cookie: bool,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let instances = this.inotify_instances();
+        // This is the common case, which has to be fast since we get here for every `read` and
+        // `write`. (On targets other than Linux, there are no inotify constants either.)
+        if instances.iter().all(|state| state.borrow().watches.is_empty()) {
+            return Ok(());
+        }
+
+        let in_all_events = this.eval_libc_u32("IN_ALL_EVENTS");
+        let in_ignored = this.eval_libc_u32("IN_IGNORED");
+        let in_isdir = this.eval_libc_u32("IN_ISDIR");
+        let in_oneshot = this.eval_libc_u32("IN_ONESHOT");
+        let in_delete_self = this.eval_libc_u32("IN_DELETE_SELF");
+        let entry_events = in_all_events & !(in_delete_self | this.eval_libc_u32("IN_MOVE_SELF"));
+        let self_events = in_all_events
+            & !(this.eval_libc_u32("IN_CREATE")
+                | this.eval_libc_u32("IN_DELETE")
+                | this.eval_libc_u32("IN_MOVED_FROM")
+                | this.eval_libc_u32("IN_MOVED_TO"));
+        let is_dir = if is_dir { in_isdir } else { 0 };
+
+        let mut resolved = Vec::new();
+        for &(path, event) in events {
+            // The operation that caused the event succeeded, so this can only fail if the parent
+            // directory was removed concurrently. Then there is nothing we could report.
+            let Ok(path) = canonicalize_parent(path) else {
+                continue;
+            };
+            let name = path.file_name().map(|name| name.as_encoded_bytes().to_vec());
+            resolved.push((path, name, this.eval_libc_u32(event)));
+        }
+
+        for state in instances {
+            let mut state = state.borrow_mut();
+            let cookie = if with_cookie {
+                state.last_cookie = state.last_cookie.wrapping_add(1);
+                state.last_cookie
+            } else {
+                0
+            };
+            for (path, name, event) in &resolved {
+                let mut queued = Vec::new();
+                let mut removed = Vec::new();
+                for (&wd, watch) in &state.watches {
+                    let name = if watch.path == *path && event & self_events != 0 {
+                        None
+                    } else if path.parent() == Some(watch.path.as_path())
+                        && event & entry_events != 0
+                    {
+                        name.clone()
+                    } else {
+                        continue;
+                    };
+                    if watch.mask & event != 0 {
+                        queued.push(InotifyEvent { wd, mask: event | is_dir, cookie, name });
+                        if watch.mask & in_oneshot != 0 {
+                            removed.push(wd);
+                        }
+                    }
+                    // A deleted file or directory cannot be watched anymore.
+                    if *event == in_delete_self {
+                        removed.push(wd);
+                    }
+                }
+                for event in queued {
+                    state.push(event);
+                }
+                for wd in removed {
+                    if state.watches.remove(&wd).is_some() {
+                        state.push(InotifyEvent { wd, mask: in_ignored, cookie: 0, name: None });
+                    }
+                }
+            }
+        }
+        // The new events make the inotify instances ready for reading.
+        this.epoll_wake_ready_waiters()
+    }
+
+    /// Writes a timer setting to the `itimerspec` `dest`. A `remaining` time of `None` means the
+    /// timer is disarmed.
+    fn write_itimerspec(
+        &mut self,
+        remaining: Option<Duration>,
+        interval: Duration,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let remaining = remaining.unwrap_or_default();
+        for (field, duration) in [("it_interval", interval), ("it_value", remaining)] {
+            let timespec = this.project_field_named(dest, field)?;
+            this.write_int_fields(
+                &[duration.as_secs().into(), duration.subsec_nanos().into()],
+                &timespec,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the events returned by `epoll_collect_ready_events` to the `epoll_event` array
+    /// `events`, and returns how many there are.
+    fn epoll_write_ready_events(
+        &mut self,
+        ready: Vec<(u32, Scalar<Provenance>)>,
+        events: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        for (i, &(bitmask, data)) in ready.iter().enumerate() {
+            let event = this.project_index(events, u64::try_from(i).unwrap())?;
+            let events_field = this.project_field(&event, 0)?;
+            this.write_scalar(Scalar::from_u32(bitmask), &events_field)?;
+            let data_field = this.project_field(&event, 1)?;
+            this.write_scalar(data, &data_field)?;
+        }
+
+        Ok(i32::try_from(ready.len()).unwrap())
+    }
+}
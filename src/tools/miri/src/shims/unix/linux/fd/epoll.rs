@@ -2,18 +2,18 @@
 
 use crate::shims::unix::fs::FileDescriptor;
 
-use rustc_data_structures::fx::FxHashMap;
+use std::collections::BTreeMap;
 use std::io;
 
 /// An `Epoll` file descriptor connects file handles and epoll events
 #[derive(Clone, Debug, Default)]
 pub struct Epoll {
     /// The file descriptors we are watching, and what we are watching for.
-    pub file_descriptors: FxHashMap<i32, EpollEvent>,
+    /// This is a `BTreeMap` so that `epoll_wait` reports ready events in a deterministic order.
+    pub file_descriptors: BTreeMap<i32, EpollEvent>,
 }
 
 /// Epoll Events associate events with data.
-/// These fields are currently unused by miri.
 /// This matches the `epoll_event` struct defined
 /// by the epoll_ctl man page. For more information
 /// see the man page:
@@ -21,10 +21,93 @@ pub struct Epoll {
 /// <https://man7.org/linux/man-pages/man2/epoll_ctl.2.html>
 #[derive(Clone, Debug)]
 pub struct EpollEvent {
+    /// The events the program is interested in, including flags like `EPOLLET`.
     pub events: u32,
     /// `Scalar<Provenance>` is used to represent the
     /// `epoll_data` type union.
     pub data: Scalar<Provenance>,
+    /// The generation and event bitmask that were last reported for this file descriptor, if
+    /// any. This is used to implement edge-triggered (`EPOLLET`) notifications.
+    pub last_reported: Option<(u64, u32)>,
+    /// Set once an `EPOLLONESHOT` interest has been reported. Such an interest is not reported
+    /// again until it is re-armed with `EPOLL_CTL_MOD`.
+    pub disabled: bool,
+}
+
+/// The readiness of a file description, as reported by [`FileDescriptor::get_epoll_ready_events`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EpollReadyEvents {
+    /// The associated file is available for `read` operations.
+    pub epollin: bool,
+    /// The associated file is available for `write` operations.
+    pub epollout: bool,
+    /// The peer closed its end of the connection, or shut down writing half of connection.
+    pub epollrdhup: bool,
+    /// Hang up happened on the associated file descriptor.
+    pub epollhup: bool,
+    /// An error condition happened on the associated file descriptor.
+    pub epollerr: bool,
+    /// A counter that is incremented whenever something happens on the file description that
+    /// would wake up an edge-triggered waiter, e.g. new data arriving while there already was
+    /// unread data.
+    pub generation: u64,
+}
+
+impl EpollReadyEvents {
+    /// Converts the readiness to the `EPOLL*` bitmask of the target.
+    pub fn get_event_bitmask<'mir, 'tcx>(&self, ecx: &MiriInterpCx<'mir, 'tcx>) -> u32 {
+        let mut bitmask = 0;
+        if self.epollin {
+            bitmask |= ecx.eval_libc_u32("EPOLLIN");
+        }
+        if self.epollout {
+            bitmask |= ecx.eval_libc_u32("EPOLLOUT");
+        }
+        if self.epollrdhup {
+            bitmask |= ecx.eval_libc_u32("EPOLLRDHUP");
+        }
+        if self.epollhup {
+            bitmask |= ecx.eval_libc_u32("EPOLLHUP");
+        }
+        if self.epollerr {
+            bitmask |= ecx.eval_libc_u32("EPOLLERR");
+        }
+        bitmask
+    }
+}
+
+/// A thread that is blocked in `epoll_wait` until one of the file descriptors watched by `epfd`
+/// becomes ready, or its timeout expires.
+#[derive(Debug)]
+pub struct EpollWaiter<'tcx> {
+    /// The blocked thread.
+    pub thread: ThreadId,
+    /// The epoll instance the thread is waiting on.
+    pub epfd: i32,
+    /// The buffer of `epoll_event`s the ready events are written to.
+    pub events: MPlaceTy<'tcx, Provenance>,
+    /// The length of `events`.
+    pub maxevents: u64,
+    /// The time at which `epoll_wait` gives up, if any.
+    pub timeout: Option<Instant>,
+    /// The place the return value of `epoll_wait` is written to.
+    pub dest: MPlaceTy<'tcx, Provenance>,
+}
+
+impl VisitProvenance for EpollWaiter<'_> {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        let EpollWaiter { thread: _, epfd: _, events, maxevents: _, timeout: _, dest } = self;
+        events.visit_provenance(visit);
+        dest.visit_provenance(visit);
+    }
+}
+
+impl VisitProvenance for Epoll {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        for event in self.file_descriptors.values() {
+            event.data.visit_provenance(visit);
+        }
+    }
 }
 
 impl FileDescriptor for Epoll {
@@ -1,4 +1,5 @@
 use crate::shims::unix::fs::FileDescriptor;
+use crate::shims::unix::linux::fd::epoll::EpollReadyEvents;
 
 use rustc_const_eval::interpret::InterpResult;
 use rustc_middle::ty::TyCtxt;
@@ -6,9 +7,12 @@
 
 use std::cell::Cell;
 use std::io;
+use std::rc::Rc;
+
+/// The largest value the counter of an `Event` can hold.
+const MAX_COUNTER: u64 = u64::MAX - 1;
 
 /// A kind of file descriptor created by `eventfd`.
-/// The `Event` type isn't currently written to by `eventfd`.
 /// The interface is meant to keep track of objects associated
 /// with a file descriptor. For more information see the man
 /// page below:
@@ -18,7 +22,23 @@
 pub struct Event {
     /// The object contains an unsigned 64-bit integer (uint64_t) counter that is maintained by the
     /// kernel. This counter is initialized with the value specified in the argument initval.
-    pub val: Cell<u64>,
+    /// The counter is shared between all duplicates of this file descriptor.
+    val: Rc<Cell<u64>>,
+    /// Incremented on every successful read and write, so that edge-triggered epoll interests
+    /// see a new event even if the readiness did not change.
+    generation: Rc<Cell<u64>>,
+    /// Whether reads and writes that would block fail with `EAGAIN` instead.
+    is_nonblock: bool,
+}
+
+impl Event {
+    pub fn new(val: u64, is_nonblock: bool) -> Self {
+        Event { val: Rc::new(Cell::new(val)), generation: Rc::new(Cell::new(0)), is_nonblock }
+    }
+
+    fn bump_generation(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
 }
 
 impl FileDescriptor for Event {
@@ -27,7 +47,11 @@ fn name(&self) -> &'static str {
     }
 
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
-        Ok(Box::new(Event { val: self.val.clone() }))
+        Ok(Box::new(Event {
+            val: Rc::clone(&self.val),
+            generation: Rc::clone(&self.generation),
+            is_nonblock: self.is_nonblock,
+        }))
     }
 
     fn close<'tcx>(
@@ -37,6 +61,39 @@ fn close<'tcx>(
         Ok(Ok(0))
     }
 
+    /// A read call returns the current value of the counter as an 8-byte integer (in native
+    /// endianess) and resets the counter to zero. If the counter is zero at the time of the call,
+    /// the read either blocks until the counter becomes nonzero, or fails with the error
+    /// EAGAIN if the file descriptor has been made nonblocking.
+    ///
+    /// A read fails with the error EINVAL if the size of the supplied buffer is less than 8
+    /// bytes.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(bytes) = bytes.first_chunk_mut::<8>() else {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        let counter = self.val.get();
+        if counter == 0 {
+            if self.is_nonblock {
+                return Ok(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("blocking `read` on an eventfd with a zero counter is not supported");
+        }
+        // Convert from host endianess to target endianess.
+        *bytes = match tcx.sess.target.endian {
+            Endian::Little => counter.to_le_bytes(),
+            Endian::Big => counter.to_be_bytes(),
+        };
+        self.val.set(0);
+        self.bump_generation();
+        Ok(Ok(8))
+    }
+
     /// A write call adds the 8-byte integer value supplied in
     /// its buffer (in native endianess) to the counter.  The maximum value that may be
     /// stored in the counter is the largest unsigned 64-bit value
@@ -45,7 +102,7 @@ fn close<'tcx>(
     /// write either blocks until a read is performed on the
     /// file descriptor, or fails with the error EAGAIN if the
     /// file descriptor has been made nonblocking.
-
+    ///
     /// A write fails with the error EINVAL if the size of the
     /// supplied buffer is less than 8 bytes, or if an attempt is
     /// made to write the value 0xffffffffffffffff.
@@ -55,18 +112,41 @@ fn write<'tcx>(
         bytes: &[u8],
         tcx: TyCtxt<'tcx>,
     ) -> InterpResult<'tcx, io::Result<usize>> {
-        let v1 = self.val.get();
-        let bytes: [u8; 8] = bytes.try_into().unwrap(); // FIXME fail gracefully when this has the wrong size
+        let Some(bytes) = bytes.first_chunk::<8>() else {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        };
         // Convert from target endianess to host endianess.
         let num = match tcx.sess.target.endian {
-            Endian::Little => u64::from_le_bytes(bytes),
-            Endian::Big => u64::from_be_bytes(bytes),
+            Endian::Little => u64::from_le_bytes(*bytes),
+            Endian::Big => u64::from_be_bytes(*bytes),
         };
-        // FIXME handle blocking when addition results in exceeding the max u64 value
-        // or fail with EAGAIN if the file descriptor is nonblocking.
-        let v2 = v1.checked_add(num).unwrap();
-        self.val.set(v2);
-        assert_eq!(8, bytes.len());
-        Ok(Ok(8))
+        if num == u64::MAX {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        }
+        match self.val.get().checked_add(num) {
+            Some(new_val) if new_val <= MAX_COUNTER => {
+                self.val.set(new_val);
+                self.bump_generation();
+                Ok(Ok(8))
+            }
+            _ => {
+                if self.is_nonblock {
+                    return Ok(Err(io::ErrorKind::WouldBlock.into()));
+                }
+                throw_unsup_format!(
+                    "blocking `write` to an eventfd whose counter would overflow is not supported"
+                );
+            }
+        }
+    }
+
+    fn get_epoll_ready_events(&self) -> Option<EpollReadyEvents> {
+        let counter = self.val.get();
+        Some(EpollReadyEvents {
+            epollin: counter != 0,
+            epollout: counter != MAX_COUNTER,
+            generation: self.generation.get(),
+            ..EpollReadyEvents::default()
+        })
     }
 }
@@ -0,0 +1,197 @@
+use crate::shims::unix::fs::FileDescriptor;
+use crate::shims::unix::linux::fd::epoll::EpollReadyEvents;
+
+use rustc_const_eval::interpret::InterpResult;
+use rustc_middle::ty::TyCtxt;
+use rustc_target::abi::Endian;
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The size of a `struct inotify_event` without the name, which is the same on all targets.
+const EVENT_HEADER_SIZE: usize = 16;
+
+/// A kind of file descriptor created by `inotify_init`. Miri does not watch the host file system;
+/// the events are generated by the file system operations of the interpreted program itself (see
+/// `inotify_notify`). Watches refer to paths rather than inodes, so e.g. a file that is reachable
+/// via several hard links is only reported under the path it was watched by.
+///
+/// <https://man7.org/linux/man-pages/man7/inotify.7.html>
+#[derive(Debug)]
+pub struct Inotify {
+    /// The watches and pending events, which are shared between all duplicates of this file
+    /// descriptor.
+    pub state: Rc<RefCell<InotifyState>>,
+    /// Whether reads that would block fail with `EAGAIN` instead.
+    is_nonblock: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct InotifyState {
+    /// The watches, by watch descriptor. This is a `BTreeMap` so that events are generated in a
+    /// deterministic order.
+    pub watches: BTreeMap<i32, InotifyWatch>,
+    /// The watch descriptor that was handed out last.
+    pub last_wd: i32,
+    /// The cookie that was handed out last, used to connect the two events of a rename.
+    pub last_cookie: u32,
+    /// The events that were not read yet.
+    pub events: VecDeque<InotifyEvent>,
+    /// Incremented whenever an event is queued or read, so that edge-triggered epoll interests
+    /// see a new event even if the readiness did not change.
+    pub generation: u64,
+}
+
+/// What an inotify instance watches.
+#[derive(Debug)]
+pub struct InotifyWatch {
+    /// The canonical path of the watched file or directory.
+    pub path: PathBuf,
+    /// The events to report, together with flags like `IN_ONESHOT`.
+    pub mask: u32,
+}
+
+/// An event that was not read yet. This matches the `inotify_event` struct, except that the name
+/// is not padded.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InotifyEvent {
+    pub wd: i32,
+    pub mask: u32,
+    pub cookie: u32,
+    /// The name of the file in a watched directory the event is about, if any.
+    pub name: Option<Vec<u8>>,
+}
+
+impl InotifyEvent {
+    /// Encodes the event like `read` returns it: the fields of `struct inotify_event` followed by
+    /// the null-terminated name, padded with zeros so the next event is aligned again.
+    fn encode(&self, endian: Endian) -> Vec<u8> {
+        let name_len = self.name.as_ref().map_or(0, |name| {
+            // The name is terminated by at least one null byte.
+            name.len().checked_add(1).unwrap().next_multiple_of(EVENT_HEADER_SIZE)
+        });
+        let len = u32::try_from(name_len).unwrap();
+        let mut bytes = match endian {
+            Endian::Little =>
+                [
+                    self.wd.to_le_bytes(),
+                    self.mask.to_le_bytes(),
+                    self.cookie.to_le_bytes(),
+                    len.to_le_bytes(),
+                ]
+                .concat(),
+            Endian::Big =>
+                [
+                    self.wd.to_be_bytes(),
+                    self.mask.to_be_bytes(),
+                    self.cookie.to_be_bytes(),
+                    len.to_be_bytes(),
+                ]
+                .concat(),
+        };
+        if let Some(name) = &self.name {
+            bytes.extend_from_slice(name);
+        }
+        bytes.resize(EVENT_HEADER_SIZE.checked_add(name_len).unwrap(), 0);
+        bytes
+    }
+}
+
+impl InotifyState {
+    /// Queues `event`, unless it is identical to the last queued event, which Linux merges too.
+    pub fn push(&mut self, event: InotifyEvent) {
+        if self.events.back() != Some(&event) {
+            self.events.push_back(event);
+        }
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
+
+impl Inotify {
+    pub fn new(is_nonblock: bool) -> Self {
+        Inotify { state: Rc::new(RefCell::new(InotifyState::default())), is_nonblock }
+    }
+}
+
+/// Canonicalizes `path`, except for its last component, which is kept as is even if it is a
+/// symbolic link or does not exist (anymore). Events about a directory entry refer to this path.
+pub fn canonicalize_parent(path: &Path) -> io::Result<PathBuf> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            Ok(fs::canonicalize(parent)?.join(name))
+        }
+        // The root directory, or a path ending in `..`.
+        _ => fs::canonicalize(path),
+    }
+}
+
+impl FileDescriptor for Inotify {
+    fn name(&self) -> &'static str {
+        "inotify"
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(Inotify { state: Rc::clone(&self.state), is_nonblock: self.is_nonblock }))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    /// A read call returns as many whole pending events as fit into the buffer. If there are no
+    /// pending events, the read either blocks until there are, or fails with the error EAGAIN if
+    /// the file descriptor has been made nonblocking. Since the events are only generated by the
+    /// program itself, blocking reads are not supported.
+    ///
+    /// A read fails with the error EINVAL if the buffer is too small for the first pending event.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let mut state = self.state.borrow_mut();
+        if state.events.is_empty() {
+            if self.is_nonblock {
+                return Ok(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!(
+                "blocking `read` on an inotify instance without events is not supported"
+            );
+        }
+
+        let endian = tcx.sess.target.endian;
+        let mut read = Vec::new();
+        while let Some(event) = state.events.front() {
+            let event = event.encode(endian);
+            if bytes.len().saturating_sub(read.len()) < event.len() {
+                break;
+            }
+            read.extend_from_slice(&event);
+            state.events.pop_front();
+        }
+        if read.is_empty() {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        }
+        bytes[..read.len()].copy_from_slice(&read);
+        state.generation = state.generation.wrapping_add(1);
+        Ok(Ok(read.len()))
+    }
+
+    fn get_epoll_ready_events(&self) -> Option<EpollReadyEvents> {
+        let state = self.state.borrow();
+        Some(EpollReadyEvents {
+            epollin: !state.events.is_empty(),
+            generation: state.generation,
+            ..EpollReadyEvents::default()
+        })
+    }
+}
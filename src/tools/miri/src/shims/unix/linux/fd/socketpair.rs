@@ -1,14 +1,73 @@
 use crate::*;
 
 use crate::shims::unix::fs::FileDescriptor;
+use crate::shims::unix::linux::fd::epoll::EpollReadyEvents;
 
+use rustc_middle::ty::TyCtxt;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::io;
+use std::rc::{Rc, Weak};
+
+/// The maximum number of bytes that can be buffered in one direction of a socketpair.
+/// This matches the default socket buffer size on Linux.
+const MAX_SOCKETPAIR_BUFFER_CAPACITY: usize = 212992;
+
+/// The data travelling in one direction of a socketpair.
+#[derive(Debug, Default)]
+struct Buffer {
+    buf: VecDeque<u8>,
+    /// Incremented whenever data is added to or removed from `buf`, so that edge-triggered epoll
+    /// interests of both ends see a new event.
+    generation: u64,
+    /// Set once the writing end shut down its writing half of the connection. No more data will
+    /// arrive in `buf`.
+    closed: bool,
+}
 
-/// Pair of connected sockets.
+impl Buffer {
+    fn bump_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
+
+/// One end of a pair of connected sockets.
 ///
-/// We currently don't allow sending any data through this pair, so this can be just a dummy.
+/// Each end owns the buffer it reads from, and has a weak reference to the buffer of its peer,
+/// which it writes to. Once all duplicates of the peer are closed, that reference dangles, which
+/// is how we detect that the connection was closed.
 #[derive(Debug)]
-pub struct SocketPair;
+pub struct SocketPair {
+    readbuf: Rc<RefCell<Buffer>>,
+    writebuf: Weak<RefCell<Buffer>>,
+    /// Whether reads and writes that would block fail with `EAGAIN` instead.
+    is_nonblock: bool,
+}
+
+impl SocketPair {
+    /// Creates two connected ends.
+    pub fn new_pair(is_nonblock: bool) -> (SocketPair, SocketPair) {
+        let buf0 = Rc::new(RefCell::new(Buffer::default()));
+        let buf1 = Rc::new(RefCell::new(Buffer::default()));
+        let writebuf0 = Rc::downgrade(&buf1);
+        let writebuf1 = Rc::downgrade(&buf0);
+        (
+            SocketPair { readbuf: buf0, writebuf: writebuf0, is_nonblock },
+            SocketPair { readbuf: buf1, writebuf: writebuf1, is_nonblock },
+        )
+    }
+
+    /// Shuts down the writing half of this end. Further writes fail with `EPIPE`, and the peer
+    /// reads EOF once it has consumed the data that is already buffered.
+    pub fn shutdown_write(&self) {
+        if let Some(writebuf) = self.writebuf.upgrade() {
+            let mut writebuf = writebuf.borrow_mut();
+            writebuf.closed = true;
+            writebuf.bump_generation();
+        }
+    }
+}
 
 impl FileDescriptor for SocketPair {
     fn name(&self) -> &'static str {
@@ -16,7 +75,11 @@ fn name(&self) -> &'static str {
     }
 
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
-        Ok(Box::new(SocketPair))
+        Ok(Box::new(SocketPair {
+            readbuf: Rc::clone(&self.readbuf),
+            writebuf: Weak::clone(&self.writebuf),
+            is_nonblock: self.is_nonblock,
+        }))
     }
 
     fn close<'tcx>(
@@ -25,4 +88,87 @@ fn close<'tcx>(
     ) -> InterpResult<'tcx, io::Result<i32>> {
         Ok(Ok(0))
     }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let mut readbuf = self.readbuf.borrow_mut();
+        if bytes.is_empty() {
+            return Ok(Ok(0));
+        }
+        if readbuf.buf.is_empty() {
+            if readbuf.closed || self.writebuf.strong_count() == 0 {
+                // The peer is gone or shut down writing, so it will never write anything again:
+                // this is EOF.
+                return Ok(Ok(0));
+            }
+            if self.is_nonblock {
+                return Ok(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("blocking `read` on an empty socketpair is not supported");
+        }
+        let len = bytes.len().min(readbuf.buf.len());
+        for (dest, byte) in bytes.iter_mut().zip(readbuf.buf.drain(..len)) {
+            *dest = byte;
+        }
+        readbuf.bump_generation();
+        Ok(Ok(len))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(writebuf) = self.writebuf.upgrade() else {
+            // The peer is gone.
+            return Ok(Err(io::ErrorKind::BrokenPipe.into()));
+        };
+        let mut writebuf = writebuf.borrow_mut();
+        if writebuf.closed {
+            // We shut down writing.
+            return Ok(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if bytes.is_empty() {
+            return Ok(Ok(0));
+        }
+        let available = MAX_SOCKETPAIR_BUFFER_CAPACITY.saturating_sub(writebuf.buf.len());
+        if available == 0 {
+            if self.is_nonblock {
+                return Ok(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("blocking `write` to a full socketpair is not supported");
+        }
+        let len = bytes.len().min(available);
+        writebuf.buf.extend(&bytes[..len]);
+        writebuf.bump_generation();
+        Ok(Ok(len))
+    }
+
+    fn get_epoll_ready_events(&self) -> Option<EpollReadyEvents> {
+        let readbuf = self.readbuf.borrow();
+        let mut ready = EpollReadyEvents {
+            epollin: !readbuf.buf.is_empty() || readbuf.closed,
+            epollrdhup: readbuf.closed,
+            generation: readbuf.generation,
+            ..EpollReadyEvents::default()
+        };
+        if let Some(writebuf) = self.writebuf.upgrade() {
+            let writebuf = writebuf.borrow();
+            // After shutting down writing, writes fail immediately instead of blocking.
+            ready.epollout = writebuf.closed || writebuf.buf.len() < MAX_SOCKETPAIR_BUFFER_CAPACITY;
+            ready.epollhup = readbuf.closed && writebuf.closed;
+            ready.generation = ready.generation.wrapping_add(writebuf.generation);
+        } else {
+            // The peer is gone: reading will return EOF, and the connection is hung up.
+            ready.epollin = true;
+            ready.epollrdhup = true;
+            ready.epollhup = true;
+        }
+        Some(ready)
+    }
 }
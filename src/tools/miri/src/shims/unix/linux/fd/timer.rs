@@ -0,0 +1,180 @@
+use crate::shims::unix::fs::FileDescriptor;
+use crate::shims::unix::linux::fd::epoll::EpollReadyEvents;
+use crate::{Clock, Instant};
+
+use rustc_const_eval::interpret::InterpResult;
+use rustc_middle::ty::TyCtxt;
+use rustc_target::abi::Endian;
+
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A kind of file descriptor created by `timerfd_create`. The timer runs on Miri's monotone
+/// clock, so with isolation enabled it expires after a deterministic amount of (virtual) time.
+/// The interface is described in the man page below:
+///
+/// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+#[derive(Debug)]
+pub struct Timer {
+    /// The state of the timer, which is shared between all duplicates of this file descriptor.
+    state: Rc<RefCell<TimerState>>,
+    /// The clock the timer runs on.
+    clock: Clock,
+    /// Whether reads that would block fail with `EAGAIN` instead.
+    is_nonblock: bool,
+}
+
+#[derive(Debug, Default)]
+struct TimerState {
+    /// The next time the timer expires, or `None` if it is disarmed.
+    deadline: Option<Instant>,
+    /// The period of the timer after its first expiration, or zero for a one-shot timer.
+    interval: Duration,
+    /// How often the timer expired since it was last read or set.
+    expirations: u64,
+    /// Incremented whenever the timer expires or is read, so that edge-triggered epoll interests
+    /// see a new event even if the readiness did not change.
+    generation: u64,
+}
+
+impl Timer {
+    pub fn new(clock: Clock, is_nonblock: bool) -> Self {
+        Timer { state: Rc::new(RefCell::new(TimerState::default())), clock, is_nonblock }
+    }
+
+    /// Accounts for all expirations that happened up to now.
+    fn update(&self) {
+        let mut state = self.state.borrow_mut();
+        let Some(deadline) = state.deadline.clone() else {
+            return;
+        };
+        let now = self.clock.now();
+        // `duration_since` saturates, so this is zero iff the deadline has passed.
+        if !deadline.duration_since(now.clone()).is_zero() {
+            return;
+        }
+        let overdue = now.duration_since(deadline.clone()).as_nanos();
+        let interval = state.interval.as_nanos();
+        if interval == 0 {
+            state.expirations = state.expirations.saturating_add(1);
+            state.deadline = None;
+        } else {
+            // The timer expired at the deadline and then once more for every full period since.
+            let periods = overdue.checked_div(interval).unwrap();
+            let expired = u64::try_from(periods).unwrap_or(u64::MAX).saturating_add(1);
+            state.expirations = state.expirations.saturating_add(expired);
+            // If the next expiration is too far in the future to represent, it never happens.
+            state.deadline = periods
+                .checked_add(1)
+                .and_then(|periods| periods.checked_mul(interval))
+                .and_then(|nanos| u64::try_from(nanos).ok())
+                .and_then(|nanos| deadline.checked_add(Duration::from_nanos(nanos)));
+        }
+        state.generation = state.generation.wrapping_add(1);
+    }
+
+    /// Arms the timer to expire at `deadline` and then every `interval`, or disarms it if
+    /// `deadline` is `None`. This discards all expirations that were not read yet.
+    pub fn set(&self, deadline: Option<Instant>, interval: Duration) {
+        let mut state = self.state.borrow_mut();
+        state.deadline = deadline;
+        state.interval = interval;
+        state.expirations = 0;
+    }
+
+    /// Returns the time until the next expiration (or `None` if the timer is disarmed), and the
+    /// interval of the timer.
+    pub fn get(&self) -> (Option<Duration>, Duration) {
+        self.update();
+        let state = self.state.borrow();
+        let remaining =
+            state.deadline.as_ref().map(|deadline| deadline.duration_since(self.clock.now()));
+        (remaining, state.interval)
+    }
+
+    /// Returns the next time the timer expires, if it is armed.
+    pub fn next_expiration(&self) -> Option<Instant> {
+        self.update();
+        self.state.borrow().deadline.clone()
+    }
+
+    /// Returns the time a blocking `read` has to wait until, or `None` if a `read` would not
+    /// block: the timer already expired, it is nonblocking, or it is disarmed (in which case a
+    /// `read` would wait forever).
+    pub fn blocking_read_deadline(&self) -> Option<Instant> {
+        if self.is_nonblock {
+            return None;
+        }
+        self.update();
+        let state = self.state.borrow();
+        if state.expirations > 0 { None } else { state.deadline.clone() }
+    }
+}
+
+impl FileDescriptor for Timer {
+    fn name(&self) -> &'static str {
+        "timer"
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(Timer {
+            state: Rc::clone(&self.state),
+            clock: self.clock.clone(),
+            is_nonblock: self.is_nonblock,
+        }))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    /// A read call returns the number of expirations since the timer was last read or set as an
+    /// 8-byte integer (in native endianess). If the timer did not expire since, the read either
+    /// blocks until the next expiration, or fails with the error EAGAIN if the file descriptor
+    /// has been made nonblocking. Blocking reads are handled by `timerfd_read_or_block`, so here
+    /// we only have to handle disarmed timers, which would block forever.
+    ///
+    /// A read fails with the error EINVAL if the size of the supplied buffer is less than 8
+    /// bytes.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(bytes) = bytes.first_chunk_mut::<8>() else {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        self.update();
+        let mut state = self.state.borrow_mut();
+        if state.expirations == 0 {
+            if self.is_nonblock {
+                return Ok(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("blocking `read` on a disarmed timerfd is not supported");
+        }
+        // Convert from host endianess to target endianess.
+        *bytes = match tcx.sess.target.endian {
+            Endian::Little => state.expirations.to_le_bytes(),
+            Endian::Big => state.expirations.to_be_bytes(),
+        };
+        state.expirations = 0;
+        state.generation = state.generation.wrapping_add(1);
+        Ok(Ok(8))
+    }
+
+    fn get_epoll_ready_events(&self) -> Option<EpollReadyEvents> {
+        self.update();
+        let state = self.state.borrow();
+        Some(EpollReadyEvents {
+            epollin: state.expirations > 0,
+            generation: state.generation,
+            ..EpollReadyEvents::default()
+        })
+    }
+}
@@ -2,6 +2,7 @@
 
 mod fs;
 mod mem;
+mod socket;
 mod sync;
 mod thread;
 
@@ -9,7 +10,9 @@
 mod linux;
 mod macos;
 
-pub use fs::{DirHandler, FileHandler};
+pub use fs::{DirHandler, FileDescriptor, FileHandle, FileHandler};
+pub use linux::fd::epoll::EpollWaiter;
+pub use socket::{SocketWaiter, VirtualNetwork};
 
 // Make up some constants.
 const UID: u32 = 1000;
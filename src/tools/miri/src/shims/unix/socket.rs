@@ -0,0 +1,1212 @@
+//! An in-process emulation of loopback TCP and UDP sockets.
+//!
+//! Miri never talks to the network of the host. Instead, all sockets are attached to a virtual
+//! network that only consists of the loopback interface: `127.0.0.0/8` and `0.0.0.0` are the only
+//! accepted addresses, and they all refer to the interpreted program itself. This is enough for
+//! programs that talk to themselves, like tests that spawn a server and a client thread.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::rc::{Rc, Weak};
+
+use rustc_middle::ty::TyCtxt;
+use rustc_target::abi::Size;
+
+use crate::shims::unix::fs::FileDescriptor;
+use crate::shims::unix::linux::fd::epoll::EpollReadyEvents;
+use crate::shims::unix::linux::fd::socketpair::SocketPair;
+use crate::*;
+use shims::unix::fs::EvalContextExt as _;
+use shims::unix::linux::fd::EvalContextExt as _;
+
+/// The first port that is handed out when a socket is bound implicitly, or to port 0. This is the
+/// start of the dynamic port range recommended by IANA.
+const FIRST_EPHEMERAL_PORT: u16 = 49152;
+
+/// The maximum number of payload bytes that can be queued on a UDP socket. Datagrams that do not
+/// fit are dropped, like on a real network.
+const MAX_DATAGRAM_QUEUE_CAPACITY: usize = 212992;
+
+/// The largest payload of a single UDP datagram.
+const MAX_DATAGRAM_SIZE: usize = 65507;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Protocol {
+    Tcp,
+    Udp,
+}
+
+/// The virtual loopback network all sockets of the interpreted program are attached to.
+#[derive(Debug, Default)]
+pub struct VirtualNetwork {
+    /// The sockets that own a port, because they were bound to it explicitly or implicitly.
+    /// A port becomes free again once all file descriptors of its socket are closed, i.e. once
+    /// the weak reference dangles.
+    ports: BTreeMap<(Protocol, u16), Weak<RefCell<SocketState>>>,
+}
+
+impl VirtualNetwork {
+    fn lookup(&self, protocol: Protocol, port: u16) -> Option<Rc<RefCell<SocketState>>> {
+        self.ports.get(&(protocol, port)).and_then(Weak::upgrade)
+    }
+
+    /// Makes `socket` the owner of `port`, or of a free ephemeral port if `port` is 0. Returns the
+    /// port, or `None` if it is already in use.
+    fn bind(
+        &mut self,
+        protocol: Protocol,
+        port: u16,
+        socket: &Rc<RefCell<SocketState>>,
+    ) -> Option<u16> {
+        let port = if port == 0 {
+            (FIRST_EPHEMERAL_PORT..=u16::MAX).find(|&port| self.lookup(protocol, port).is_none())?
+        } else if self.lookup(protocol, port).is_some() {
+            return None;
+        } else {
+            port
+        };
+        self.ports.insert((protocol, port), Rc::downgrade(socket));
+        Some(port)
+    }
+}
+
+/// A datagram waiting to be received by a UDP socket.
+#[derive(Debug)]
+struct Datagram {
+    from: SocketAddrV4,
+    payload: Vec<u8>,
+}
+
+#[derive(Debug)]
+enum SocketKind {
+    /// A TCP socket that is neither listening nor connected.
+    Unconnected,
+    /// A TCP socket that accepts connections.
+    Listening {
+        /// The connections that were established but not yet accepted, with the address of the
+        /// connecting socket and the server end of the connection.
+        pending: VecDeque<(SocketAddrV4, SocketPair)>,
+        /// Incremented whenever a connection arrives, for edge-triggered epoll interests.
+        generation: u64,
+    },
+    /// A connected TCP socket. The data is transferred through a socketpair, whose other end is
+    /// owned by the peer.
+    Connected { peer: SocketAddrV4, stream: SocketPair },
+    /// A UDP socket.
+    Datagram {
+        /// The address and socket set with `connect`, if any. The socket is resolved when
+        /// connecting, so that `write` can be used on connected UDP sockets.
+        peer: Option<(SocketAddrV4, Weak<RefCell<SocketState>>)>,
+        queue: VecDeque<Datagram>,
+        /// The sum of the payload sizes of the datagrams in `queue`.
+        queued_bytes: usize,
+        /// Incremented whenever a datagram arrives, for edge-triggered epoll interests.
+        generation: u64,
+    },
+}
+
+/// The state of a socket, shared by all its file descriptors.
+#[derive(Debug)]
+struct SocketState {
+    protocol: Protocol,
+    /// Whether operations that would block fail with `EAGAIN` instead.
+    is_nonblock: bool,
+    /// The address the socket is bound to, if any.
+    local: Option<SocketAddrV4>,
+    /// Set by `shutdown(SHUT_RD)`. Reading returns EOF from then on.
+    read_shutdown: bool,
+    kind: SocketKind,
+}
+
+impl SocketState {
+    fn new(protocol: Protocol, is_nonblock: bool, kind: SocketKind) -> Self {
+        SocketState { protocol, is_nonblock, local: None, read_shutdown: false, kind }
+    }
+
+    fn readiness(&self) -> EpollReadyEvents {
+        let mut ready = match &self.kind {
+            // Linux reports unconnected stream sockets as hung up.
+            SocketKind::Unconnected =>
+                EpollReadyEvents { epollout: true, epollhup: true, ..EpollReadyEvents::default() },
+            SocketKind::Listening { pending, generation } =>
+                EpollReadyEvents {
+                    epollin: !pending.is_empty(),
+                    generation: *generation,
+                    ..EpollReadyEvents::default()
+                },
+            SocketKind::Connected { stream, .. } => stream.get_epoll_ready_events().unwrap(),
+            SocketKind::Datagram { queue, generation, .. } =>
+                EpollReadyEvents {
+                    epollin: !queue.is_empty(),
+                    epollout: true,
+                    generation: *generation,
+                    ..EpollReadyEvents::default()
+                },
+        };
+        if self.read_shutdown {
+            ready.epollin = true;
+            ready.epollrdhup = true;
+        }
+        ready
+    }
+
+    /// Whether receiving (or accepting) would block.
+    fn would_block_read(&self) -> bool {
+        let ready = self.readiness();
+        !(ready.epollin || ready.epollhup || ready.epollerr)
+    }
+
+    /// Whether sending would block.
+    fn would_block_write(&self) -> bool {
+        let ready = self.readiness();
+        !(ready.epollout || ready.epollhup || ready.epollerr)
+    }
+
+    /// Receives data, and returns the number of bytes received and the address of the sender.
+    /// Must only be called if `would_block_read` returned `false`.
+    fn recv<'tcx>(
+        &mut self,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<(usize, Option<SocketAddrV4>)>> {
+        if self.read_shutdown {
+            return Ok(Ok((0, None)));
+        }
+        match &mut self.kind {
+            SocketKind::Unconnected | SocketKind::Listening { .. } =>
+                Ok(Err(io::ErrorKind::NotConnected.into())),
+            SocketKind::Connected { peer, stream } => {
+                let peer = *peer;
+                Ok(stream.read(false, bytes, tcx)?.map(|len| (len, Some(peer))))
+            }
+            SocketKind::Datagram { queue, queued_bytes, .. } => {
+                let datagram = queue.pop_front().unwrap();
+                *queued_bytes = queued_bytes.checked_sub(datagram.payload.len()).unwrap();
+                // The part of the datagram that does not fit is discarded.
+                let len = bytes.len().min(datagram.payload.len());
+                bytes[..len].copy_from_slice(&datagram.payload[..len]);
+                Ok(Ok((len, Some(datagram.from))))
+            }
+        }
+    }
+
+    /// Queues a datagram that was sent to this socket. It is silently dropped if it does not
+    /// fit, or if this is not a UDP socket anymore.
+    fn deliver(&mut self, from: SocketAddrV4, payload: &[u8]) {
+        if let SocketKind::Datagram { queue, queued_bytes, generation, .. } = &mut self.kind {
+            let new_queued_bytes = queued_bytes.saturating_add(payload.len());
+            if new_queued_bytes <= MAX_DATAGRAM_QUEUE_CAPACITY {
+                queue.push_back(Datagram { from, payload: payload.to_vec() });
+                *queued_bytes = new_queued_bytes;
+                *generation = generation.wrapping_add(1);
+            }
+        }
+    }
+}
+
+/// Sends `bytes` from the socket `state` to the UDP socket `to`, if it still exists.
+fn send_datagram(
+    state: &RefCell<SocketState>,
+    to: &Weak<RefCell<SocketState>>,
+    bytes: &[u8],
+) -> io::Result<usize> {
+    if bytes.len() > MAX_DATAGRAM_SIZE {
+        // Linux fails with `EMSGSIZE`, which has no `io::ErrorKind`. The `send` shims check this
+        // themselves, so this is only reachable through `write`.
+        return Err(io::ErrorKind::InvalidInput.into());
+    }
+    // The socket was bound by the caller.
+    let from = loopback_address(state.borrow().local.unwrap());
+    // Sending to a port nobody listens on is not an error for UDP, the datagram is just lost.
+    if let Some(to) = to.upgrade() {
+        to.borrow_mut().deliver(from, bytes);
+    }
+    Ok(bytes.len())
+}
+
+/// Returns the address a socket bound to `addr` actually uses to talk to its peers: sockets
+/// bound to the unspecified address use `127.0.0.1`.
+fn loopback_address(addr: SocketAddrV4) -> SocketAddrV4 {
+    if addr.ip().is_unspecified() {
+        SocketAddrV4::new(Ipv4Addr::LOCALHOST, addr.port())
+    } else {
+        addr
+    }
+}
+
+/// A file descriptor referring to a socket created by `socket` or `accept`.
+#[derive(Debug)]
+pub struct Socket {
+    state: Rc<RefCell<SocketState>>,
+}
+
+impl FileDescriptor for Socket {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(Socket { state: Rc::clone(&self.state) }))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let mut state = self.state.borrow_mut();
+        if state.would_block_read() {
+            if state.is_nonblock {
+                return Ok(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!("blocking `read` on a socket is not supported, use `recv` instead");
+        }
+        Ok(state.recv(bytes, tcx)?.map(|(len, _from)| len))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let state = self.state.borrow();
+        if state.would_block_write() {
+            if state.is_nonblock {
+                return Ok(Err(io::ErrorKind::WouldBlock.into()));
+            }
+            throw_unsup_format!(
+                "blocking `write` to a socket is not supported, use `send` instead"
+            );
+        }
+        match &state.kind {
+            SocketKind::Connected { stream, .. } => stream.write(false, bytes, tcx),
+            SocketKind::Datagram { peer: Some((_, peer)), .. } => {
+                let peer = Weak::clone(peer);
+                drop(state);
+                Ok(send_datagram(&self.state, &peer, bytes))
+            }
+            _ => Ok(Err(io::ErrorKind::NotConnected.into())),
+        }
+    }
+
+    fn get_epoll_ready_events(&self) -> Option<EpollReadyEvents> {
+        Some(self.state.borrow().readiness())
+    }
+}
+
+/// A socket operation that could not complete immediately, and that is retried whenever the state
+/// of a file descriptor changed.
+#[derive(Debug)]
+pub enum BlockedSocketOp {
+    Accept {
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Pointer<Option<Provenance>>,
+        /// Whether the accepted socket is nonblocking (`SOCK_NONBLOCK` passed to `accept4`).
+        is_nonblock: bool,
+    },
+    Recv {
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        /// Where to store the address of the sender, for `recvfrom`.
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Pointer<Option<Provenance>>,
+    },
+    Send {
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        /// The destination passed to `sendto`, if any.
+        to: Option<SocketAddrV4>,
+    },
+}
+
+/// A thread that is blocked in a socket operation.
+#[derive(Debug)]
+pub struct SocketWaiter<'tcx> {
+    /// The blocked thread.
+    pub thread: ThreadId,
+    /// The socket the operation is performed on.
+    pub fd: i32,
+    pub op: BlockedSocketOp,
+    /// The place the return value of the operation is written to.
+    pub dest: MPlaceTy<'tcx, Provenance>,
+}
+
+impl VisitProvenance for SocketWaiter<'_> {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        let SocketWaiter { thread: _, fd: _, op, dest } = self;
+        match op {
+            BlockedSocketOp::Accept { addr, addrlen, is_nonblock: _ } => {
+                addr.visit_provenance(visit);
+                addrlen.visit_provenance(visit);
+            }
+            BlockedSocketOp::Recv { buf, len: _, addr, addrlen } => {
+                buf.visit_provenance(visit);
+                addr.visit_provenance(visit);
+                addrlen.visit_provenance(visit);
+            }
+            BlockedSocketOp::Send { buf, len: _, to: _ } => {
+                buf.visit_provenance(visit);
+            }
+        }
+        dest.visit_provenance(visit);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Returns the state of the socket `fd` refers to. If there is no such socket, sets the last
+    /// error and returns `None`.
+    fn get_socket(&mut self, fd: i32) -> InterpResult<'tcx, Option<Rc<RefCell<SocketState>>>> {
+        let this = self.eval_context_mut();
+
+        let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) else {
+            this.handle_not_found::<i32>()?;
+            return Ok(None);
+        };
+        let Some(socket) = file_descriptor.downcast_ref::<Socket>() else {
+            let enotsock = this.eval_libc("ENOTSOCK");
+            this.set_last_error(enotsock)?;
+            return Ok(None);
+        };
+        Ok(Some(Rc::clone(&socket.state)))
+    }
+
+    /// Sets the last error to `name` and returns -1.
+    fn socket_error(&mut self, name: &str) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let errno = this.eval_libc(name);
+        this.set_last_error(errno)?;
+        Ok(-1)
+    }
+
+    /// Reads the `sockaddr_in` at `addr`. Sets the last error and returns `None` if the address is
+    /// invalid.
+    fn read_socket_address(
+        &mut self,
+        addr: Pointer<Option<Provenance>>,
+        addrlen: u32,
+    ) -> InterpResult<'tcx, Option<SocketAddrV4>> {
+        let this = self.eval_context_mut();
+
+        let layout = this.libc_ty_layout("sockaddr_in");
+        if u64::from(addrlen) < layout.size.bytes() {
+            this.socket_error("EINVAL")?;
+            return Ok(None);
+        }
+        let place = this.ptr_to_mplace(addr, layout);
+
+        let family = this.project_field_named(&place, "sin_family")?;
+        let family = this.read_scalar(&family)?.to_bits(family.layout.size)?;
+        if family != u128::try_from(this.eval_libc_i32("AF_INET")).unwrap() {
+            throw_unsup_format!(
+                "only IPv4 addresses are supported by the socket emulation, got family {family}"
+            );
+        }
+        // The port and the address are stored in network byte order.
+        let port = this.project_field_named(&place, "sin_port")?;
+        let port = this.read_bytes_ptr_strip_provenance(port.ptr(), Size::from_bytes(2))?;
+        let port = u16::from_be_bytes(port.try_into().unwrap());
+        let ip = this.project_field_named(&place, "sin_addr")?;
+        let ip = this.read_bytes_ptr_strip_provenance(ip.ptr(), Size::from_bytes(4))?;
+        let ip = Ipv4Addr::from(<[u8; 4]>::try_from(ip).unwrap());
+
+        if !ip.is_loopback() && !ip.is_unspecified() {
+            throw_unsup_format!(
+                "the socket emulation only supports the loopback interface, but {ip} was requested"
+            );
+        }
+        Ok(Some(SocketAddrV4::new(ip, port)))
+    }
+
+    /// Writes `sock_addr` to the `sockaddr_in` at `addr`, and its size to `addrlen`. Does nothing
+    /// if `addr` is null.
+    fn write_socket_address(
+        &mut self,
+        sock_addr: SocketAddrV4,
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Pointer<Option<Provenance>>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if this.ptr_is_null(addr)? {
+            return Ok(());
+        }
+        let layout = this.libc_ty_layout("sockaddr_in");
+        let addrlen = this.ptr_to_mplace(addrlen, this.libc_ty_layout("socklen_t"));
+        if u64::from(this.read_scalar(&addrlen)?.to_u32()?) < layout.size.bytes() {
+            throw_unsup_format!("truncating socket addresses is not supported");
+        }
+        let place = this.ptr_to_mplace(addr, layout);
+
+        this.write_bytes_ptr(place.ptr(), std::iter::repeat(0).take(layout.size.bytes_usize()))?;
+        if this.tcx.sess.target.os == "macos" || this.tcx.sess.target.os == "freebsd" {
+            let sin_len = this.project_field_named(&place, "sin_len")?;
+            this.write_int(layout.size.bytes(), &sin_len)?;
+        }
+        let family = this.project_field_named(&place, "sin_family")?;
+        this.write_int(this.eval_libc_i32("AF_INET"), &family)?;
+        let port = this.project_field_named(&place, "sin_port")?;
+        this.write_bytes_ptr(port.ptr(), sock_addr.port().to_be_bytes())?;
+        let ip = this.project_field_named(&place, "sin_addr")?;
+        this.write_bytes_ptr(ip.ptr(), sock_addr.ip().octets())?;
+
+        this.write_int(layout.size.bytes(), &addrlen)?;
+        Ok(())
+    }
+
+    /// Binds `state` to a free ephemeral port if it is not bound yet.
+    fn socket_bind_implicitly(&mut self, state: &Rc<RefCell<SocketState>>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if state.borrow().local.is_some() {
+            return Ok(());
+        }
+        let protocol = state.borrow().protocol;
+        let Some(port) = this.machine.network.bind(protocol, 0, state) else {
+            throw_unsup_format!("the socket emulation ran out of ephemeral ports");
+        };
+        state.borrow_mut().local = Some(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+        Ok(())
+    }
+
+    /// Tries to perform `op` on the socket `fd`. Returns `None` if the operation would block, and
+    /// otherwise the return value of the operation, after setting the last error if it failed.
+    ///
+    /// This does not wake up other threads, the caller is responsible for that.
+    fn socket_try_op(
+        &mut self,
+        fd: i32,
+        op: &BlockedSocketOp,
+    ) -> InterpResult<'tcx, Option<Scalar<Provenance>>> {
+        let this = self.eval_context_mut();
+
+        let Some(state) = this.get_socket(fd)? else {
+            return Ok(Some(match op {
+                BlockedSocketOp::Accept { .. } => Scalar::from_i32(-1),
+                _ => Scalar::from_target_isize(-1, this),
+            }));
+        };
+
+        match *op {
+            BlockedSocketOp::Accept { addr, addrlen, is_nonblock } => {
+                let (peer, stream) = match &mut state.borrow_mut().kind {
+                    SocketKind::Listening { pending, .. } =>
+                        match pending.pop_front() {
+                            Some(connection) => connection,
+                            None => return Ok(None),
+                        },
+                    _ => return Ok(Some(Scalar::from_i32(this.socket_error("EINVAL")?))),
+                };
+                let local = loopback_address(state.borrow().local.unwrap());
+                let mut accepted = SocketState::new(
+                    Protocol::Tcp,
+                    is_nonblock,
+                    SocketKind::Connected { peer, stream },
+                );
+                accepted.local = Some(local);
+                this.write_socket_address(peer, addr, addrlen)?;
+                let fd = this
+                    .machine
+                    .file_handler
+                    .insert_fd(Box::new(Socket { state: Rc::new(RefCell::new(accepted)) }));
+                Ok(Some(Scalar::from_i32(fd)))
+            }
+            BlockedSocketOp::Recv { buf, len, addr, addrlen } => {
+                if state.borrow().would_block_read() {
+                    return Ok(None);
+                }
+                let mut bytes = vec![0; usize::try_from(len).unwrap()];
+                let result = state.borrow_mut().recv(&mut bytes, *this.tcx)?;
+                let result = match result {
+                    Ok((read, from)) => {
+                        this.write_bytes_ptr(buf, bytes[..read].iter().copied())?;
+                        if let Some(from) = from {
+                            this.write_socket_address(from, addr, addrlen)?;
+                        }
+                        i64::try_from(read).unwrap()
+                    }
+                    Err(e) => {
+                        this.set_last_error_from_io_error(e.kind())?;
+                        -1
+                    }
+                };
+                Ok(Some(Scalar::from_target_isize(result, this)))
+            }
+            BlockedSocketOp::Send { buf, len, to } => {
+                if state.borrow().would_block_write() {
+                    return Ok(None);
+                }
+                let protocol = state.borrow().protocol;
+                if protocol == Protocol::Udp && len > u64::try_from(MAX_DATAGRAM_SIZE).unwrap() {
+                    let result = this.socket_error("EMSGSIZE")?;
+                    return Ok(Some(Scalar::from_target_isize(result.into(), this)));
+                }
+                let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(len))?;
+                let result = match (protocol, to) {
+                    (Protocol::Udp, Some(to)) => {
+                        this.socket_bind_implicitly(&state)?;
+                        let to = this
+                            .machine
+                            .network
+                            .lookup(Protocol::Udp, to.port())
+                            .map(|to| Rc::downgrade(&to))
+                            .unwrap_or_default();
+                        send_datagram(&state, &to, bytes)
+                    }
+                    // Like on Linux, the destination is ignored for connected stream sockets.
+                    _ => Socket { state: Rc::clone(&state) }.write(false, bytes, *this.tcx)?,
+                };
+                let result =
+                    this.try_unwrap_io_result(result.map(|len| i64::try_from(len).unwrap()))?;
+                Ok(Some(Scalar::from_target_isize(result, this)))
+            }
+        }
+    }
+
+    /// Performs `op` on the socket `fd` and writes the result to `dest`. If the operation would
+    /// block, either fails with `EAGAIN` if the socket is nonblocking or `dont_wait` is set, or
+    /// blocks the active thread until the operation can complete.
+    fn socket_op_or_block(
+        &mut self,
+        fd: i32,
+        op: BlockedSocketOp,
+        dont_wait: bool,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if let Some(result) = this.socket_try_op(fd, &op)? {
+            this.write_scalar(result, dest)?;
+            return this.wake_ready_waiters();
+        }
+
+        // `socket_try_op` only returns `None` for existing sockets.
+        let is_nonblock = this.get_socket(fd)?.unwrap().borrow().is_nonblock;
+        if is_nonblock || dont_wait {
+            let result = this.socket_error("EAGAIN")?;
+            let result = match op {
+                BlockedSocketOp::Accept { .. } => Scalar::from_i32(result),
+                _ => Scalar::from_target_isize(result.into(), this),
+            };
+            return this.write_scalar(result, dest);
+        }
+
+        let thread = this.get_active_thread();
+        this.block_thread(thread);
+        this.machine.socket_waiters.push(SocketWaiter { thread, fd, op, dest: dest.clone() });
+        Ok(())
+    }
+
+    /// Reads the `flags` argument of `send`, `recv` and their variants, and returns whether
+    /// `MSG_DONTWAIT` is set.
+    fn read_socket_msg_flags(
+        &mut self,
+        flags: &OpTy<'tcx, Provenance>,
+        name: &str,
+    ) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_mut();
+
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+        let mut dont_wait = false;
+        if this.tcx.sess.target.os == "linux" {
+            // We never raise `SIGPIPE`, so `MSG_NOSIGNAL` has no effect.
+            flags &= !this.eval_libc_i32("MSG_NOSIGNAL");
+        }
+        let msg_dontwait = this.eval_libc_i32("MSG_DONTWAIT");
+        if flags & msg_dontwait == msg_dontwait {
+            dont_wait = true;
+            flags &= !msg_dontwait;
+        }
+        if flags != 0 {
+            throw_unsup_format!("{name}: flags {flags:#x} are not supported");
+        }
+        Ok(dont_wait)
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Creates a TCP (`SOCK_STREAM`) or UDP (`SOCK_DGRAM`) socket in the `AF_INET` domain.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/socket.2.html>
+    fn socket(
+        &mut self,
+        domain: &OpTy<'tcx, Provenance>,
+        type_: &OpTy<'tcx, Provenance>,
+        protocol: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let mut type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
+
+        let mut is_nonblock = false;
+        if this.tcx.sess.target.os == "linux" {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            is_nonblock = type_ & sock_nonblock == sock_nonblock;
+            // Miri does not support exec, so `SOCK_CLOEXEC` has no effect.
+            type_ &= !(sock_nonblock | sock_cloexec);
+        }
+
+        if domain != this.eval_libc_i32("AF_INET") {
+            throw_unsup_format!("socket: domain {domain:#x} is unsupported, only AF_INET is");
+        }
+        let (kind, default_protocol) = if type_ == this.eval_libc_i32("SOCK_STREAM") {
+            (Protocol::Tcp, this.eval_libc_i32("IPPROTO_TCP"))
+        } else if type_ == this.eval_libc_i32("SOCK_DGRAM") {
+            (Protocol::Udp, this.eval_libc_i32("IPPROTO_UDP"))
+        } else {
+            throw_unsup_format!(
+                "socket: type {type_:#x} is unsupported, only SOCK_STREAM and SOCK_DGRAM are"
+            );
+        };
+        if protocol != 0 && protocol != default_protocol {
+            throw_unsup_format!("socket: socket protocol {protocol} is unsupported");
+        }
+
+        let state = match kind {
+            Protocol::Tcp => SocketState::new(kind, is_nonblock, SocketKind::Unconnected),
+            Protocol::Udp =>
+                SocketState::new(
+                    kind,
+                    is_nonblock,
+                    SocketKind::Datagram {
+                        peer: None,
+                        queue: VecDeque::new(),
+                        queued_bytes: 0,
+                        generation: 0,
+                    },
+                ),
+        };
+        let fd = this
+            .machine
+            .file_handler
+            .insert_fd(Box::new(Socket { state: Rc::new(RefCell::new(state)) }));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// <https://man7.org/linux/man-pages/man2/bind.2.html>
+    fn bind(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+        let addrlen = this.read_scalar(addrlen)?.to_u32()?;
+
+        let Some(state) = this.get_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let Some(addr) = this.read_socket_address(addr, addrlen)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        if state.borrow().local.is_some() {
+            return Ok(Scalar::from_i32(this.socket_error("EINVAL")?));
+        }
+        let protocol = state.borrow().protocol;
+        let Some(port) = this.machine.network.bind(protocol, addr.port(), &state) else {
+            return Ok(Scalar::from_i32(this.socket_error("EADDRINUSE")?));
+        };
+        state.borrow_mut().local = Some(SocketAddrV4::new(*addr.ip(), port));
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Marks a TCP socket as accepting connections. The `backlog` is ignored: connections are
+    /// always established immediately, and queued until they are accepted.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/listen.2.html>
+    fn listen(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        backlog: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let _backlog = this.read_scalar(backlog)?.to_i32()?;
+
+        let Some(state) = this.get_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        match state.borrow().kind {
+            SocketKind::Unconnected => {}
+            SocketKind::Listening { .. } => return Ok(Scalar::from_i32(0)),
+            SocketKind::Connected { .. } =>
+                return Ok(Scalar::from_i32(this.socket_error("EINVAL")?)),
+            SocketKind::Datagram { .. } =>
+                return Ok(Scalar::from_i32(this.socket_error("EOPNOTSUPP")?)),
+        }
+        this.socket_bind_implicitly(&state)?;
+        state.borrow_mut().kind = SocketKind::Listening { pending: VecDeque::new(), generation: 0 };
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Connects a socket to the given address. For TCP sockets, the connection is established
+    /// immediately if there is a socket listening on that address, and refused otherwise.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/connect.2.html>
+    fn connect(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+        let addrlen = this.read_scalar(addrlen)?.to_u32()?;
+
+        let Some(state) = this.get_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let Some(addr) = this.read_socket_address(addr, addrlen)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        // Connecting to the unspecified address connects to the local host.
+        let addr = loopback_address(addr);
+
+        let protocol = state.borrow().protocol;
+        match (protocol, &state.borrow().kind) {
+            (Protocol::Tcp, SocketKind::Unconnected) | (Protocol::Udp, _) => {}
+            (_, SocketKind::Connected { .. }) =>
+                return Ok(Scalar::from_i32(this.socket_error("EISCONN")?)),
+            _ => return Ok(Scalar::from_i32(this.socket_error("EINVAL")?)),
+        }
+
+        let target = this.machine.network.lookup(protocol, addr.port());
+        this.socket_bind_implicitly(&state)?;
+        // Once connected, a socket bound to the unspecified address reports the address it
+        // actually uses.
+        let local = loopback_address(state.borrow().local.unwrap());
+        state.borrow_mut().local = Some(local);
+
+        if protocol == Protocol::Udp {
+            let peer = target.map(|target| Rc::downgrade(&target)).unwrap_or_default();
+            if let SocketKind::Datagram { peer: old_peer, .. } = &mut state.borrow_mut().kind {
+                *old_peer = Some((addr, peer));
+            }
+            return Ok(Scalar::from_i32(0));
+        }
+
+        let Some(listener) = target else {
+            return Ok(Scalar::from_i32(this.socket_error("ECONNREFUSED")?));
+        };
+        let mut listener = listener.borrow_mut();
+        let SocketKind::Listening { pending, generation } = &mut listener.kind else {
+            // The port is bound, but nobody listens on it.
+            return Ok(Scalar::from_i32(this.socket_error("ECONNREFUSED")?));
+        };
+        let (client, server) = SocketPair::new_pair(/* is_nonblock */ true);
+        pending.push_back((local, server));
+        *generation = generation.wrapping_add(1);
+        drop(listener);
+        state.borrow_mut().kind = SocketKind::Connected { peer: addr, stream: client };
+
+        // The listener became ready.
+        this.wake_ready_waiters()?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Accepts a connection on a listening TCP socket, blocking until there is one. This also
+    /// implements `accept4`, whose `flags` are passed as `flags` (0 for `accept`).
+    ///
+    /// <https://man7.org/linux/man-pages/man2/accept.2.html>
+    fn accept(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+        flags: Option<&OpTy<'tcx, Provenance>>,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+        let addrlen = this.read_pointer(addrlen)?;
+        let mut flags = match flags {
+            Some(flags) => this.read_scalar(flags)?.to_i32()?,
+            None => 0,
+        };
+
+        let mut is_nonblock = false;
+        if flags != 0 {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            is_nonblock = flags & sock_nonblock == sock_nonblock;
+            // Miri does not support exec, so `SOCK_CLOEXEC` has no effect.
+            flags &= !(sock_nonblock | sock_cloexec);
+            if flags != 0 {
+                throw_unsup_format!("accept4: flags {flags:#x} are not supported");
+            }
+        }
+
+        this.socket_op_or_block(
+            fd,
+            BlockedSocketOp::Accept { addr, addrlen, is_nonblock },
+            /* dont_wait */ false,
+            dest,
+        )
+    }
+
+    /// Implements `send` and, if `to` is given, `sendto`. Sending on a TCP socket blocks while the
+    /// buffer of the connection is full.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/send.2.html>
+    fn send(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        buf: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        to: Option<(&OpTy<'tcx, Provenance>, &OpTy<'tcx, Provenance>)>,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        let len = this.read_target_usize(len)?;
+        let dont_wait = this.read_socket_msg_flags(flags, "send")?;
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+
+        let to = match to {
+            Some((addr, addrlen)) => {
+                let addr = this.read_pointer(addr)?;
+                let addrlen = this.read_scalar(addrlen)?.to_u32()?;
+                if this.ptr_is_null(addr)? {
+                    None
+                } else {
+                    let Some(addr) = this.read_socket_address(addr, addrlen)? else {
+                        return this.write_scalar(Scalar::from_target_isize(-1, this), dest);
+                    };
+                    Some(loopback_address(addr))
+                }
+            }
+            None => None,
+        };
+        let is_socket = this.machine.file_handler.handles.get(&fd).map_or(
+            // Let `get_socket` report the error.
+            true,
+            |file_descriptor| file_descriptor.downcast_ref::<Socket>().is_some(),
+        );
+        if to.is_none() && !is_socket {
+            // `send` on a socketpair behaves like `write`.
+            let result = this.write(fd, buf, len)?;
+            return this.write_scalar(Scalar::from_target_isize(result, this), dest);
+        }
+
+        this.socket_op_or_block(fd, BlockedSocketOp::Send { buf, len, to }, dont_wait, dest)
+    }
+
+    /// Implements `recv` and, if `from` is given, `recvfrom`. Receiving blocks until there is
+    /// data.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/recv.2.html>
+    fn recv(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        buf: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        from: Option<(&OpTy<'tcx, Provenance>, &OpTy<'tcx, Provenance>)>,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        let len = this.read_target_usize(len)?;
+        let dont_wait = this.read_socket_msg_flags(flags, "recv")?;
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+        // We cap the number of received bytes like `read` does.
+        let len = len
+            .min(u64::try_from(this.target_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+
+        let (addr, addrlen) = match from {
+            Some((addr, addrlen)) => (this.read_pointer(addr)?, this.read_pointer(addrlen)?),
+            None => (Pointer::null(), Pointer::null()),
+        };
+        let is_socket = this.machine.file_handler.handles.get(&fd).map_or(
+            // Let `get_socket` report the error.
+            true,
+            |file_descriptor| file_descriptor.downcast_ref::<Socket>().is_some(),
+        );
+        if from.is_none() && !is_socket {
+            // `recv` on a socketpair behaves like `read`.
+            let result = this.read(fd, buf, len)?;
+            return this.write_scalar(Scalar::from_target_isize(result, this), dest);
+        }
+
+        this.socket_op_or_block(
+            fd,
+            BlockedSocketOp::Recv { buf, len, addr, addrlen },
+            dont_wait,
+            dest,
+        )
+    }
+
+    /// <https://man7.org/linux/man-pages/man2/shutdown.2.html>
+    fn shutdown(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        how: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let how = this.read_scalar(how)?.to_i32()?;
+
+        let Some(state) = this.get_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let (read, write) = if how == this.eval_libc_i32("SHUT_RD") {
+            (true, false)
+        } else if how == this.eval_libc_i32("SHUT_WR") {
+            (false, true)
+        } else if how == this.eval_libc_i32("SHUT_RDWR") {
+            (true, true)
+        } else {
+            return Ok(Scalar::from_i32(this.socket_error("EINVAL")?));
+        };
+
+        let mut state = state.borrow_mut();
+        let SocketKind::Connected { stream, .. } = &state.kind else {
+            if let SocketKind::Datagram { .. } = state.kind {
+                throw_unsup_format!("shutdown: shutting down UDP sockets is not supported");
+            }
+            drop(state);
+            return Ok(Scalar::from_i32(this.socket_error("ENOTCONN")?));
+        };
+        if write {
+            stream.shutdown_write();
+        }
+        if read {
+            state.read_shutdown = true;
+        }
+        drop(state);
+
+        // The peer sees EOF now.
+        this.wake_ready_waiters()?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Implements `getsockname` and, if `peer` is set, `getpeername`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/getsockname.2.html>
+    fn getsockname(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+        peer: bool,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let addr = this.read_pointer(addr)?;
+        let addrlen = this.read_pointer(addrlen)?;
+
+        let Some(state) = this.get_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let sock_addr = if peer {
+            match &state.borrow().kind {
+                SocketKind::Connected { peer, .. } => Some(*peer),
+                SocketKind::Datagram { peer, .. } => peer.as_ref().map(|(peer, _)| *peer),
+                _ => None,
+            }
+        } else {
+            // Unbound sockets report the unspecified address.
+            Some(state.borrow().local.unwrap_or(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)))
+        };
+        let Some(sock_addr) = sock_addr else {
+            return Ok(Scalar::from_i32(this.socket_error("ENOTCONN")?));
+        };
+        this.write_socket_address(sock_addr, addr, addrlen)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Only options that do not make a difference for the socket emulation are supported; their
+    /// values are ignored.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/setsockopt.2.html>
+    fn setsockopt(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        level: &OpTy<'tcx, Provenance>,
+        option_name: &OpTy<'tcx, Provenance>,
+        option_value: &OpTy<'tcx, Provenance>,
+        option_len: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let option_name = this.read_scalar(option_name)?.to_i32()?;
+        let option_value = this.read_pointer(option_value)?;
+        let option_len = this.read_scalar(option_len)?.to_u32()?;
+
+        if this.get_socket(fd)?.is_none() {
+            return Ok(Scalar::from_i32(-1));
+        }
+        // The value is ignored, but it still has to be readable.
+        this.check_ptr_access(
+            option_value,
+            Size::from_bytes(option_len),
+            CheckInAllocMsg::MemoryAccessTest,
+        )?;
+
+        let ignored = if level == this.eval_libc_i32("SOL_SOCKET") {
+            // There are no lingering connections that could block reusing an address.
+            option_name == this.eval_libc_i32("SO_REUSEADDR")
+                // We never raise `SIGPIPE`.
+                || (this.tcx.sess.target.os == "macos"
+                    && option_name == this.eval_libc_i32("SO_NOSIGPIPE"))
+        } else if level == this.eval_libc_i32("IPPROTO_TCP") {
+            // Data is always delivered immediately.
+            option_name == this.eval_libc_i32("TCP_NODELAY")
+        } else {
+            false
+        };
+        if !ignored {
+            throw_unsup_format!(
+                "setsockopt: option {option_name:#x} at level {level:#x} is not supported"
+            );
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Only `SO_ERROR` is supported. There are no asynchronous errors in the socket emulation, so
+    /// it is always 0.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/getsockopt.2.html>
+    fn getsockopt(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        level: &OpTy<'tcx, Provenance>,
+        option_name: &OpTy<'tcx, Provenance>,
+        option_value: &OpTy<'tcx, Provenance>,
+        option_len: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let option_name = this.read_scalar(option_name)?.to_i32()?;
+
+        if this.get_socket(fd)?.is_none() {
+            return Ok(Scalar::from_i32(-1));
+        }
+        if level != this.eval_libc_i32("SOL_SOCKET")
+            || option_name != this.eval_libc_i32("SO_ERROR")
+        {
+            throw_unsup_format!(
+                "getsockopt: option {option_name:#x} at level {level:#x} is not supported"
+            );
+        }
+        let option_len = this.deref_pointer_as(option_len, this.libc_ty_layout("socklen_t"))?;
+        if this.read_scalar(&option_len)?.to_u32()? < 4 {
+            return Ok(Scalar::from_i32(this.socket_error("EINVAL")?));
+        }
+        let option_value = this.deref_pointer_as(option_value, this.machine.layouts.i32)?;
+        this.write_null(&option_value)?;
+        this.write_int(4, &option_len)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Only `FIONBIO` on sockets is supported, which is what `set_nonblocking` in `std` uses.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/ioctl.2.html>
+    fn ioctl(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        if args.len() < 2 {
+            throw_ub_format!(
+                "incorrect number of arguments for ioctl: got {}, expected at least 2",
+                args.len()
+            );
+        }
+        let fd = this.read_scalar(&args[0])?.to_i32()?;
+        let request = this.read_scalar(&args[1])?.to_bits(args[1].layout.size)?;
+
+        let fionbio = this.eval_libc("FIONBIO");
+        if request != fionbio.to_bits(fionbio.size())? {
+            throw_unsup_format!("ioctl: request {request:#x} is not supported");
+        }
+        if args.len() < 3 {
+            throw_ub_format!(
+                "incorrect number of arguments for ioctl with request=`FIONBIO`: got {}, expected at least 3",
+                args.len()
+            );
+        }
+        let Some(state) = this.get_socket(fd)? else {
+            return Ok(-1);
+        };
+        let value = this.deref_pointer_as(&args[2], this.machine.layouts.i32)?;
+        let is_nonblock = this.read_scalar(&value)?.to_i32()? != 0;
+        state.borrow_mut().is_nonblock = is_nonblock;
+        Ok(0)
+    }
+
+    /// Wakes up the threads blocked in socket operations that can complete now, and then the
+    /// threads blocked in `epoll_wait` that have ready events.
+    ///
+    /// This has to be called after every operation that can change the readiness of a file
+    /// descriptor.
+    fn wake_ready_waiters(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        // Completing an operation can make another one ready (e.g. receiving makes room for a
+        // sender), so we keep retrying until nothing changes anymore.
+        let mut progress = !this.machine.socket_waiters.is_empty();
+        while progress {
+            progress = false;
+            let waiters = std::mem::take(&mut this.machine.socket_waiters);
+            let mut still_waiting = Vec::new();
+            for waiter in waiters {
+                // Perform the operation as the blocked thread, since it is that thread's call
+                // that returns the result (and possibly sets `errno`).
+                let old_thread = this.set_active_thread(waiter.thread);
+                let result = this.socket_try_op(waiter.fd, &waiter.op)?;
+                if let Some(result) = result {
+                    this.unblock_thread(waiter.thread);
+                    this.write_scalar(result, &waiter.dest)?;
+                    progress = true;
+                } else {
+                    still_waiting.push(waiter);
+                }
+                this.set_active_thread(old_thread);
+            }
+            // Nothing above can block a thread, so no new waiters were registered in the mean
+            // time.
+            assert!(this.machine.socket_waiters.is_empty());
+            this.machine.socket_waiters = still_waiting;
+        }
+
+        this.epoll_wake_ready_waiters()
+    }
+}
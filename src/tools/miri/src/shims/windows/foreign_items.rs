@@ -2,12 +2,12 @@
 use std::str;
 
 use rustc_span::Symbol;
-use rustc_target::abi::Size;
 use rustc_target::spec::abi::Abi;
 
 use crate::shims::os_str::bytes_to_os_str;
 use crate::*;
 use shims::foreign_items::EmulateForeignItemResult;
+use shims::windows::fs::EvalContextExt as _;
 use shims::windows::handle::{EvalContextExt as _, Handle, PseudoHandle};
 use shims::windows::sync::EvalContextExt as _;
 use shims::windows::thread::EvalContextExt as _;
@@ -78,72 +78,168 @@ fn emulate_foreign_item_inner(
             }
 
             // File related shims
+            "CreateFileW" => {
+                let [
+                    file_name,
+                    desired_access,
+                    share_mode,
+                    security_attributes,
+                    creation_disposition,
+                    flags_and_attributes,
+                    template_file,
+                ] = this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.CreateFileW(
+                    file_name,
+                    desired_access,
+                    share_mode,
+                    security_attributes,
+                    creation_disposition,
+                    flags_and_attributes,
+                    template_file,
+                )?;
+                this.write_scalar(result, dest)?;
+            }
+            "NtReadFile" => {
+                let [
+                    handle,
+                    event,
+                    apc_routine,
+                    apc_context,
+                    io_status_block,
+                    buf,
+                    n,
+                    byte_offset,
+                    key,
+                ] = this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.NtReadFile(
+                    handle,
+                    event,
+                    apc_routine,
+                    apc_context,
+                    io_status_block,
+                    buf,
+                    n,
+                    byte_offset,
+                    key,
+                )?;
+                this.write_scalar(result, dest)?;
+            }
             "NtWriteFile" => {
-                if !this.frame_in_std() {
-                    throw_unsup_format!(
-                        "`NtWriteFile` support is crude and just enough for stdout to work"
-                    );
-                }
-
                 let [
                     handle,
-                    _event,
-                    _apc_routine,
-                    _apc_context,
+                    event,
+                    apc_routine,
+                    apc_context,
                     io_status_block,
                     buf,
                     n,
                     byte_offset,
-                    _key,
+                    key,
                 ] = this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
-                let handle = this.read_target_isize(handle)?;
-                let buf = this.read_pointer(buf)?;
-                let n = this.read_scalar(n)?.to_u32()?;
-                let byte_offset = this.read_target_usize(byte_offset)?; // is actually a pointer
-                let io_status_block = this
-                    .deref_pointer_as(io_status_block, this.windows_ty_layout("IO_STATUS_BLOCK"))?;
-
-                if byte_offset != 0 {
-                    throw_unsup_format!(
-                        "`NtWriteFile` `ByteOffset` parameter is non-null, which is unsupported"
-                    );
-                }
-
-                let written = if handle == -11 || handle == -12 {
-                    // stdout/stderr
-                    use std::io::{self, Write};
-
-                    let buf_cont =
-                        this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(u64::from(n)))?;
-                    let res = if this.machine.mute_stdout_stderr {
-                        Ok(buf_cont.len())
-                    } else if handle == -11 {
-                        io::stdout().write(buf_cont)
-                    } else {
-                        io::stderr().write(buf_cont)
-                    };
-                    // We write at most `n` bytes, which is a `u32`, so we cannot have written more than that.
-                    res.ok().map(|n| u32::try_from(n).unwrap())
-                } else {
-                    throw_unsup_format!(
-                        "on Windows, writing to anything except stdout/stderr is not supported"
-                    )
-                };
-                // We have to put the result into io_status_block.
-                if let Some(n) = written {
-                    let io_status_information =
-                        this.project_field_named(&io_status_block, "Information")?;
-                    this.write_scalar(
-                        Scalar::from_target_usize(n.into(), this),
-                        &io_status_information,
-                    )?;
-                }
-                // Return whether this was a success. >= 0 is success.
-                // For the error code we arbitrarily pick 0xC0000185, STATUS_IO_DEVICE_ERROR.
-                this.write_scalar(
-                    Scalar::from_u32(if written.is_some() { 0 } else { 0xC0000185u32 }),
-                    dest,
+                let result = this.NtWriteFile(
+                    handle,
+                    event,
+                    apc_routine,
+                    apc_context,
+                    io_status_block,
+                    buf,
+                    n,
+                    byte_offset,
+                    key,
                 )?;
+                this.write_scalar(result, dest)?;
+            }
+            "RtlNtStatusToDosError" => {
+                let [status] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.RtlNtStatusToDosError(status)?;
+                this.write_scalar(result, dest)?;
+            }
+            "ReadFile" => {
+                let [file, buf, n, number_of_bytes_read, overlapped] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.ReadFile(file, buf, n, number_of_bytes_read, overlapped)?;
+                this.write_scalar(result, dest)?;
+            }
+            "WriteFile" => {
+                let [file, buf, n, number_of_bytes_written, overlapped] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.WriteFile(file, buf, n, number_of_bytes_written, overlapped)?;
+                this.write_scalar(result, dest)?;
+            }
+            "SetFilePointerEx" => {
+                let [file, distance_to_move, new_file_pointer, move_method] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result =
+                    this.SetFilePointerEx(file, distance_to_move, new_file_pointer, move_method)?;
+                this.write_scalar(result, dest)?;
+            }
+            "SetFileInformationByHandle" => {
+                let [file, info_class, info, buffer_size] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.SetFileInformationByHandle(file, info_class, info, buffer_size)?;
+                this.write_scalar(result, dest)?;
+            }
+            "FlushFileBuffers" => {
+                let [file] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.FlushFileBuffers(file)?;
+                this.write_scalar(result, dest)?;
+            }
+            "GetFileInformationByHandle" => {
+                let [file, file_information] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.GetFileInformationByHandle(file, file_information)?;
+                this.write_scalar(result, dest)?;
+            }
+            "GetFileInformationByHandleEx" => {
+                let [file, info_class, info, buffer_size] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result =
+                    this.GetFileInformationByHandleEx(file, info_class, info, buffer_size)?;
+                this.write_scalar(result, dest)?;
+            }
+            "FindFirstFileW" => {
+                let [file_name, find_file_data] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.FindFirstFileW(file_name, find_file_data)?;
+                this.write_scalar(result, dest)?;
+            }
+            "FindNextFileW" => {
+                let [find_file, find_file_data] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.FindNextFileW(find_file, find_file_data)?;
+                this.write_scalar(result, dest)?;
+            }
+            "FindClose" => {
+                let [find_file] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.FindClose(find_file)?;
+                this.write_scalar(result, dest)?;
+            }
+            "DeleteFileW" => {
+                let [file_name] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.DeleteFileW(file_name)?;
+                this.write_scalar(result, dest)?;
+            }
+            "CreateDirectoryW" => {
+                let [path_name, security_attributes] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.CreateDirectoryW(path_name, security_attributes)?;
+                this.write_scalar(result, dest)?;
+            }
+            "RemoveDirectoryW" => {
+                let [path_name] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.RemoveDirectoryW(path_name)?;
+                this.write_scalar(result, dest)?;
+            }
+            "MoveFileExW" => {
+                let [existing_file_name, new_file_name, flags] =
+                    this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
+                let result = this.MoveFileExW(existing_file_name, new_file_name, flags)?;
+                this.write_scalar(result, dest)?;
             }
 
             // Allocation
@@ -523,20 +619,27 @@ fn emulate_foreign_item_inner(
             "GetStdHandle" => {
                 let [which] =
                     this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
-                let which = this.read_scalar(which)?.to_i32()?;
-                // We just make this the identity function, so we know later in `NtWriteFile` which
-                // one it is. This is very fake, but libtest needs it so we cannot make it a
-                // std-only shim.
-                // FIXME: this should return real HANDLEs when io support is added
-                this.write_scalar(Scalar::from_target_isize(which.into(), this), dest)?;
+                let which = this.read_scalar(which)?.to_u32()?;
+                // The standard streams are the first three entries of the file handler, just like
+                // on Unix.
+                let fd = if which == this.eval_windows_u32("c", "STD_INPUT_HANDLE") {
+                    0
+                } else if which == this.eval_windows_u32("c", "STD_OUTPUT_HANDLE") {
+                    1
+                } else if which == this.eval_windows_u32("c", "STD_ERROR_HANDLE") {
+                    2
+                } else {
+                    throw_unsup_format!("`GetStdHandle` does not support the handle {which:#x}");
+                };
+                this.write_scalar(Handle::File(fd).to_scalar(this), dest)?;
             }
             "CloseHandle" => {
                 let [handle] =
                     this.check_shim(abi, Abi::System { unwind: false }, link_name, args)?;
 
-                this.CloseHandle(handle)?;
+                let ret = this.CloseHandle(handle)?;
 
-                this.write_scalar(Scalar::from_u32(1), dest)?;
+                this.write_scalar(ret, dest)?;
             }
             "GetModuleFileNameW" => {
                 let [handle, filename, size] =
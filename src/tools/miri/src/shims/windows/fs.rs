@@ -0,0 +1,1070 @@
+//! File system shims for Windows targets, mapped onto the host file system.
+//!
+//! Open files live in the same `FileHandler` that Unix targets use for their file descriptors;
+//! the `HANDLE` of a file is a `Handle::File` carrying its file descriptor. Handles that are opened
+//! without access to the file contents (which is how std queries metadata) and handles to
+//! directories are not backed by a host file, they only remember their path.
+
+use std::ffi::OsStr;
+use std::fs::{self, Metadata, OpenOptions, ReadDir};
+use std::io::{self, ErrorKind, SeekFrom};
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use rustc_target::abi::Size;
+
+use crate::shims::unix::{FileDescriptor, FileHandle};
+use crate::*;
+use shims::windows::handle::{EvalContextExt as _, Handle};
+
+/// `ERROR_SEVERITY_ERROR | FACILITY_NTWIN32 << 16`: the `NTSTATUS` space that wraps Win32 error
+/// codes in its lower 16 bits.
+const NTSTATUS_WIN32_ERROR: u32 = 0xC007_0000;
+
+/// A handle that only remembers the path it was opened with: either a directory, or a file that
+/// was opened without read or write access.
+#[derive(Debug, Clone)]
+struct PathHandle {
+    path: PathBuf,
+    /// Whether the handle refers to the target of a symbolic link, rather than the link itself.
+    follow_symlinks: bool,
+}
+
+impl PathHandle {
+    fn metadata(&self) -> io::Result<Metadata> {
+        if self.follow_symlinks {
+            fs::metadata(&self.path)
+        } else {
+            fs::symlink_metadata(&self.path)
+        }
+    }
+}
+
+impl FileDescriptor for PathHandle {
+    fn name(&self) -> &'static str {
+        "path handle"
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+/// A directory listing started by `FindFirstFileW`.
+#[derive(Debug)]
+struct FindHandle {
+    read_dir: ReadDir,
+}
+
+impl FileDescriptor for FindHandle {
+    fn name(&self) -> &'static str {
+        "directory search handle"
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Err(ErrorKind::Unsupported.into())
+    }
+}
+
+/// The host metadata of the file or directory behind `file_descriptor`, or `None` if it is not
+/// backed by the host file system (like the standard streams).
+fn host_metadata(file_descriptor: &dyn FileDescriptor) -> Option<io::Result<Metadata>> {
+    if let Some(file_handle) = file_descriptor.downcast_ref::<FileHandle>() {
+        Some(file_handle.file.metadata())
+    } else if let Some(path_handle) = file_descriptor.downcast_ref::<PathHandle>() {
+        Some(path_handle.metadata())
+    } else {
+        None
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Reads a `HANDLE` and returns the file descriptor of the open file it refers to. Aborts the
+    /// program for anything else.
+    fn read_file_handle(
+        &mut self,
+        handle_op: &OpTy<'tcx, Provenance>,
+        function_name: &str,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let handle = this.read_scalar(handle_op)?;
+        match Handle::from_scalar(handle, this)? {
+            Some(Handle::File(fd)) if this.machine.file_handler.handles.contains_key(&fd) => Ok(fd),
+            _ => this.invalid_handle(function_name)?,
+        }
+    }
+
+    /// Turns the result of a host operation into a `BOOL`, setting the last error on failure.
+    fn io_result_to_bool(
+        &mut self,
+        result: io::Result<()>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        match result {
+            Ok(()) => Ok(this.eval_windows("c", "TRUE")),
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                Ok(this.eval_windows("c", "FALSE"))
+            }
+        }
+    }
+
+    /// The `NTSTATUS` for an I/O error. We wrap the corresponding Win32 error code, which is what
+    /// `RtlNtStatusToDosError` unwraps again.
+    fn io_error_to_ntstatus(&self, err_kind: ErrorKind) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_ref();
+        let error = this.io_error_to_errnum(err_kind)?.to_u32()?;
+        Ok(Scalar::from_u32(NTSTATUS_WIN32_ERROR | error))
+    }
+
+    /// Rejects the event and APC routine arguments of `NtReadFile` and `NtWriteFile`; we only
+    /// support synchronous I/O.
+    fn check_synchronous_io(
+        &self,
+        function_name: &str,
+        event_op: &OpTy<'tcx, Provenance>,
+        apc_routine_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_ref();
+        let event = this.read_target_isize(event_op)?;
+        let apc_routine = this.read_pointer(apc_routine_op)?;
+        if event != 0 || !this.ptr_is_null(apc_routine)? {
+            throw_unsup_format!(
+                "`{function_name}` is only supported without an event and without an APC routine"
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads the optional `ByteOffset` argument of `NtReadFile` and `NtWriteFile`.
+    fn read_byte_offset(
+        &self,
+        byte_offset_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Option<u64>> {
+        let this = self.eval_context_ref();
+        let byte_offset = this.read_pointer(byte_offset_op)?;
+        if this.ptr_is_null(byte_offset)? {
+            return Ok(None);
+        }
+        let byte_offset = this
+            .read_scalar(&this.ptr_to_mplace(byte_offset, this.machine.layouts.i64))?
+            .to_i64()?;
+        // Negative offsets select special positions like `FILE_WRITE_TO_END_OF_FILE`.
+        let Ok(byte_offset) = u64::try_from(byte_offset) else {
+            throw_unsup_format!("negative `ByteOffset` {byte_offset} is not supported");
+        };
+        Ok(Some(byte_offset))
+    }
+
+    /// Reads up to `len` bytes from the file behind `fd` into `buf`, starting at `offset` if it
+    /// is given and at the current file position otherwise.
+    fn read_from_file(
+        &mut self,
+        fd: i32,
+        buf: Pointer<Option<Provenance>>,
+        len: u32,
+        offset: Option<u64>,
+    ) -> InterpResult<'tcx, io::Result<u32>> {
+        let this = self.eval_context_mut();
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access(buf, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+
+        let communicate = this.machine.communicate();
+        let file_descriptor = this.machine.file_handler.handles.get_mut(&fd).unwrap();
+        if let Some(offset) = offset {
+            if let Err(e) = file_descriptor.seek(communicate, SeekFrom::Start(offset))? {
+                return Ok(Err(e));
+            }
+        }
+        let mut bytes = vec![0; usize::try_from(len).unwrap()];
+        match file_descriptor.read(communicate, &mut bytes, *this.tcx)? {
+            Ok(read) => {
+                bytes.truncate(read);
+                this.write_bytes_ptr(buf, bytes)?;
+                // We read at most `len` bytes, which is a `u32`.
+                Ok(Ok(u32::try_from(read).unwrap()))
+            }
+            Err(e) => Ok(Err(e)),
+        }
+    }
+
+    /// Writes `len` bytes from `buf` to the file behind `fd`, starting at `offset` if it is given
+    /// and at the current file position otherwise.
+    fn write_to_file(
+        &mut self,
+        fd: i32,
+        buf: Pointer<Option<Provenance>>,
+        len: u32,
+        offset: Option<u64>,
+    ) -> InterpResult<'tcx, io::Result<u32>> {
+        let this = self.eval_context_mut();
+
+        let communicate = this.machine.communicate();
+        if let Some(offset) = offset {
+            let file_descriptor = this.machine.file_handler.handles.get_mut(&fd).unwrap();
+            if let Err(e) = file_descriptor.seek(communicate, SeekFrom::Start(offset))? {
+                return Ok(Err(e));
+            }
+        }
+        let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(len))?;
+        let file_descriptor = this.machine.file_handler.handles.get(&fd).unwrap();
+        let result = file_descriptor.write(communicate, bytes, *this.tcx)?;
+        // We write at most `len` bytes, which is a `u32`.
+        Ok(result.map(|written| u32::try_from(written).unwrap()))
+    }
+
+    /// Stores the number of bytes that `ReadFile` or `WriteFile` transferred, and turns the
+    /// result into a `BOOL`.
+    fn finish_read_write(
+        &mut self,
+        result: io::Result<u32>,
+        number_of_bytes: Pointer<Option<Provenance>>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let transferred = *result.as_ref().unwrap_or(&0);
+        if !this.ptr_is_null(number_of_bytes)? {
+            let number_of_bytes = this.ptr_to_mplace(number_of_bytes, this.machine.layouts.u32);
+            this.write_scalar(Scalar::from_u32(transferred), &number_of_bytes)?;
+        }
+        this.io_result_to_bool(result.map(|_| ()))
+    }
+
+    /// The Windows file attributes of a host file.
+    fn file_attributes(&self, metadata: &Metadata) -> u32 {
+        let this = self.eval_context_ref();
+
+        let mut attributes = 0;
+        if metadata.is_dir() {
+            attributes |= this.eval_windows_u32("c", "FILE_ATTRIBUTE_DIRECTORY");
+        }
+        if metadata.is_symlink() {
+            attributes |= this.eval_windows_u32("c", "FILE_ATTRIBUTE_REPARSE_POINT");
+        }
+        if metadata.permissions().readonly() {
+            attributes |= this.eval_windows_u32("c", "FILE_ATTRIBUTE_READONLY");
+        }
+        if attributes == 0 {
+            // This attribute is only valid on its own.
+            attributes = this.eval_windows_u32("c", "FILE_ATTRIBUTE_NORMAL");
+        }
+        attributes
+    }
+
+    /// Fills in the fields that `BY_HANDLE_FILE_INFORMATION` and `WIN32_FIND_DATAW` have in
+    /// common: the attributes, the times, and the size.
+    #[allow(clippy::arithmetic_side_effects)] // the shifts cannot overflow
+    fn write_file_metadata(
+        &mut self,
+        metadata: &Metadata,
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let size = metadata.len();
+        this.write_int_fields_named(
+            &[
+                ("dwFileAttributes", this.file_attributes(metadata).into()),
+                ("nFileSizeHigh", (size >> 32).into()),
+                ("nFileSizeLow", (size & 0xFFFF_FFFF).into()),
+            ],
+            dest,
+        )?;
+        let times = [
+            ("ftCreationTime", metadata.created()),
+            ("ftLastAccessTime", metadata.accessed()),
+            ("ftLastWriteTime", metadata.modified()),
+        ];
+        for (field, time) in times {
+            let filetime = this.project_field_named(dest, field)?;
+            match time {
+                Ok(time) => this.write_filetime(time, &filetime)?,
+                // Not every host records every time; Windows uses 0 for missing times.
+                Err(_) => this.write_int_fields(&[0, 0], &filetime)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills in a `WIN32_FIND_DATAW` for a directory entry.
+    fn write_find_data(
+        &mut self,
+        entry: &fs::DirEntry,
+        find_data: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        let this = self.eval_context_mut();
+
+        // Like `FindFirstFileW`, this does not follow symbolic links.
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(e) => return Ok(Err(e)),
+        };
+        // Initialize with `0`; this leaves the short 8.3 file name empty.
+        this.write_bytes_ptr(
+            find_data.ptr(),
+            iter::repeat(0u8).take(find_data.layout.size.bytes_usize()),
+        )?;
+        this.write_file_metadata(&metadata, find_data)?;
+        if metadata.is_symlink() {
+            // For reparse points, this field holds the reparse tag.
+            let symlink_tag = this.eval_windows_u32("c", "IO_REPARSE_TAG_SYMLINK");
+            this.write_int_fields_named(&[("dwReserved0", symlink_tag.into())], find_data)?;
+        }
+        let file_name = entry.file_name();
+        let file_name_field = this.project_field_named(find_data, "cFileName")?;
+        let size = file_name_field.len(this)?;
+        let (written, _) =
+            this.write_os_str_to_wide_str(&file_name, file_name_field.ptr(), size, false)?;
+        if !written {
+            throw_unsup_format!("file name {file_name:?} is too long for `WIN32_FIND_DATAW`");
+        }
+        Ok(Ok(()))
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+
+#[allow(non_snake_case)]
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn CreateFileW(
+        &mut self,
+        file_name_op: &OpTy<'tcx, Provenance>,      // LPCWSTR
+        desired_access_op: &OpTy<'tcx, Provenance>, // DWORD
+        share_mode_op: &OpTy<'tcx, Provenance>,     // DWORD
+        security_attributes_op: &OpTy<'tcx, Provenance>, // LPSECURITY_ATTRIBUTES
+        creation_disposition_op: &OpTy<'tcx, Provenance>, // DWORD
+        flags_and_attributes_op: &OpTy<'tcx, Provenance>, // DWORD
+        template_file_op: &OpTy<'tcx, Provenance>,  // HANDLE
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns HANDLE
+        let this = self.eval_context_mut();
+        this.assert_target_os("windows", "CreateFileW");
+
+        let path = this.read_path_from_wide_str(this.read_pointer(file_name_op)?)?;
+        let desired_access = this.read_scalar(desired_access_op)?.to_u32()?;
+        // Sharing restrictions are up to the host.
+        this.read_scalar(share_mode_op)?.to_u32()?;
+        // Security attributes only matter for child processes, which we do not support.
+        this.read_pointer(security_attributes_op)?;
+        let creation_disposition = this.read_scalar(creation_disposition_op)?.to_u32()?;
+        let flags_and_attributes = this.read_scalar(flags_and_attributes_op)?.to_u32()?;
+        let template_file = this.read_target_isize(template_file_op)?;
+
+        // `INVALID_HANDLE_VALUE`
+        let invalid_handle = Scalar::from_target_isize(-1, this);
+
+        if template_file != 0 {
+            throw_unsup_format!("`CreateFileW` with a template file is not supported");
+        }
+
+        // The attributes of newly created files (the lower 16 bits) have no portable counterpart
+        // on the host, and the security quality of service flags only apply to named pipes, so we
+        // ignore both.
+        let ignored_flags = 0xFFFF
+            | this.eval_windows_u32("c", "SECURITY_SQOS_PRESENT")
+            | this.eval_windows_u32("c", "SECURITY_VALID_SQOS_FLAGS");
+        let backup_semantics = this.eval_windows_u32("c", "FILE_FLAG_BACKUP_SEMANTICS");
+        let open_reparse_point = this.eval_windows_u32("c", "FILE_FLAG_OPEN_REPARSE_POINT");
+        let flags = flags_and_attributes & !ignored_flags;
+        if flags & !(backup_semantics | open_reparse_point) != 0 {
+            throw_unsup_format!(
+                "`CreateFileW` does not support the flags {:#x}",
+                flags & !(backup_semantics | open_reparse_point)
+            );
+        }
+
+        // Every access right that lets us look at the file contents. The remaining access rights
+        // (like those for attributes or deletion) do not need a host file.
+        let generic_all = this.eval_windows_u32("c", "GENERIC_ALL");
+        let read = desired_access
+            & (generic_all
+                | this.eval_windows_u32("c", "GENERIC_READ")
+                | this.eval_windows_u32("c", "FILE_READ_DATA"))
+            != 0;
+        let write = desired_access
+            & (generic_all
+                | this.eval_windows_u32("c", "GENERIC_WRITE")
+                | this.eval_windows_u32("c", "FILE_WRITE_DATA"))
+            != 0;
+        let append = !write && desired_access & this.eval_windows_u32("c", "FILE_APPEND_DATA") != 0;
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`CreateFileW`", reject_with)?;
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(invalid_handle);
+        }
+
+        let follow_symlinks = flags & open_reparse_point == 0;
+        let existing =
+            if follow_symlinks { fs::metadata(&path) } else { fs::symlink_metadata(&path) };
+        let exists = existing.is_ok();
+        let is_dir = existing.as_ref().is_ok_and(|metadata| metadata.is_dir());
+
+        let create_new = this.eval_windows_u32("c", "CREATE_NEW");
+        let create_always = this.eval_windows_u32("c", "CREATE_ALWAYS");
+        let open_always = this.eval_windows_u32("c", "OPEN_ALWAYS");
+        let open_existing = this.eval_windows_u32("c", "OPEN_EXISTING");
+        let truncate_existing = this.eval_windows_u32("c", "TRUNCATE_EXISTING");
+
+        let file_descriptor: Box<dyn FileDescriptor> = if is_dir || !(read || write || append) {
+            if is_dir && flags & backup_semantics == 0 {
+                // Directories can only be opened with `FILE_FLAG_BACKUP_SEMANTICS`.
+                this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+                return Ok(invalid_handle);
+            }
+            if creation_disposition != open_existing {
+                throw_unsup_format!(
+                    "`CreateFileW` only supports `OPEN_EXISTING` for directories and for files \
+                    opened without read or write access"
+                );
+            }
+            if let Err(e) = existing {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(invalid_handle);
+            }
+            Box::new(PathHandle { path, follow_symlinks })
+        } else {
+            // `FILE_FLAG_OPEN_REPARSE_POINT` is ignored here: the host always follows symbolic
+            // links when opening a file for reading or writing.
+            let mut options = OpenOptions::new();
+            options.read(read).write(write).append(append);
+            if creation_disposition == create_new {
+                options.create_new(true);
+            } else if creation_disposition == create_always {
+                options.create(true).truncate(true);
+            } else if creation_disposition == open_always {
+                options.create(true);
+            } else if creation_disposition == truncate_existing {
+                options.truncate(true);
+            } else if creation_disposition != open_existing {
+                let invalid_parameter = this.eval_windows("c", "ERROR_INVALID_PARAMETER");
+                this.set_last_error(invalid_parameter)?;
+                return Ok(invalid_handle);
+            }
+            match options.open(&path) {
+                Ok(file) => Box::new(FileHandle { file, writable: write || append, path }),
+                Err(e) => {
+                    if e.kind() == ErrorKind::AlreadyExists {
+                        // That is what `CREATE_NEW` reports, rather than `ERROR_ALREADY_EXISTS`.
+                        let file_exists = this.eval_windows("c", "ERROR_FILE_EXISTS");
+                        this.set_last_error(file_exists)?;
+                    } else {
+                        this.set_last_error_from_io_error(e.kind())?;
+                    }
+                    return Ok(invalid_handle);
+                }
+            }
+        };
+
+        // On success, these two report whether the file already existed.
+        if creation_disposition == create_always || creation_disposition == open_always {
+            let last_error = if exists {
+                this.eval_windows("c", "ERROR_ALREADY_EXISTS")
+            } else {
+                Scalar::from_u32(0)
+            };
+            this.set_last_error(last_error)?;
+        }
+
+        let fd = this.machine.file_handler.insert_fd(file_descriptor);
+        Ok(Handle::File(fd).to_scalar(this))
+    }
+
+    fn NtReadFile(
+        &mut self,
+        handle_op: &OpTy<'tcx, Provenance>,          // HANDLE
+        event_op: &OpTy<'tcx, Provenance>,           // HANDLE
+        apc_routine_op: &OpTy<'tcx, Provenance>,     // PIO_APC_ROUTINE
+        apc_context_op: &OpTy<'tcx, Provenance>,     // PVOID
+        io_status_block_op: &OpTy<'tcx, Provenance>, // PIO_STATUS_BLOCK
+        buf_op: &OpTy<'tcx, Provenance>,             // PVOID
+        n_op: &OpTy<'tcx, Provenance>,               // ULONG
+        byte_offset_op: &OpTy<'tcx, Provenance>,     // PLARGE_INTEGER
+        key_op: &OpTy<'tcx, Provenance>,             // PULONG
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns NTSTATUS
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(handle_op, "NtReadFile")?;
+        this.check_synchronous_io("NtReadFile", event_op, apc_routine_op)?;
+        this.read_pointer(apc_context_op)?;
+        let io_status_block =
+            this.deref_pointer_as(io_status_block_op, this.windows_ty_layout("IO_STATUS_BLOCK"))?;
+        let buf = this.read_pointer(buf_op)?;
+        let n = this.read_scalar(n_op)?.to_u32()?;
+        let byte_offset = this.read_byte_offset(byte_offset_op)?;
+        // The key only matters for byte range locks, which we do not support.
+        this.read_pointer(key_op)?;
+
+        let result = this.read_from_file(fd, buf, n, byte_offset)?;
+        let (status, information) = match result {
+            Ok(0) if n > 0 => (this.eval_windows("c", "STATUS_END_OF_FILE"), 0),
+            Ok(read) => (this.eval_windows("c", "STATUS_SUCCESS"), read),
+            Err(e) => (this.io_error_to_ntstatus(e.kind())?, 0),
+        };
+        let io_status_information = this.project_field_named(&io_status_block, "Information")?;
+        this.write_scalar(
+            Scalar::from_target_usize(information.into(), this),
+            &io_status_information,
+        )?;
+        Ok(status)
+    }
+
+    fn NtWriteFile(
+        &mut self,
+        handle_op: &OpTy<'tcx, Provenance>,          // HANDLE
+        event_op: &OpTy<'tcx, Provenance>,           // HANDLE
+        apc_routine_op: &OpTy<'tcx, Provenance>,     // PIO_APC_ROUTINE
+        apc_context_op: &OpTy<'tcx, Provenance>,     // PVOID
+        io_status_block_op: &OpTy<'tcx, Provenance>, // PIO_STATUS_BLOCK
+        buf_op: &OpTy<'tcx, Provenance>,             // PVOID
+        n_op: &OpTy<'tcx, Provenance>,               // ULONG
+        byte_offset_op: &OpTy<'tcx, Provenance>,     // PLARGE_INTEGER
+        key_op: &OpTy<'tcx, Provenance>,             // PULONG
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns NTSTATUS
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(handle_op, "NtWriteFile")?;
+        this.check_synchronous_io("NtWriteFile", event_op, apc_routine_op)?;
+        this.read_pointer(apc_context_op)?;
+        let io_status_block =
+            this.deref_pointer_as(io_status_block_op, this.windows_ty_layout("IO_STATUS_BLOCK"))?;
+        let buf = this.read_pointer(buf_op)?;
+        let n = this.read_scalar(n_op)?.to_u32()?;
+        let byte_offset = this.read_byte_offset(byte_offset_op)?;
+        // The key only matters for byte range locks, which we do not support.
+        this.read_pointer(key_op)?;
+
+        let result = this.write_to_file(fd, buf, n, byte_offset)?;
+        let (status, information) = match result {
+            Ok(written) => (this.eval_windows("c", "STATUS_SUCCESS"), written),
+            Err(e) => (this.io_error_to_ntstatus(e.kind())?, 0),
+        };
+        let io_status_information = this.project_field_named(&io_status_block, "Information")?;
+        this.write_scalar(
+            Scalar::from_target_usize(information.into(), this),
+            &io_status_information,
+        )?;
+        Ok(status)
+    }
+
+    fn RtlNtStatusToDosError(
+        &mut self,
+        status_op: &OpTy<'tcx, Provenance>, // NTSTATUS
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns ULONG
+        let this = self.eval_context_mut();
+
+        let status = this.read_scalar(status_op)?.to_u32()?;
+        if status & 0xFFFF_0000 == NTSTATUS_WIN32_ERROR {
+            Ok(Scalar::from_u32(status & 0xFFFF))
+        } else if status == this.eval_windows("c", "STATUS_SUCCESS").to_u32()? {
+            // `ERROR_SUCCESS`
+            Ok(Scalar::from_u32(0))
+        } else if status == this.eval_windows("c", "STATUS_END_OF_FILE").to_u32()? {
+            Ok(this.eval_windows("c", "ERROR_HANDLE_EOF"))
+        } else {
+            throw_unsup_format!("`RtlNtStatusToDosError` does not support the status {status:#x}")
+        }
+    }
+
+    fn ReadFile(
+        &mut self,
+        file_op: &OpTy<'tcx, Provenance>, // HANDLE
+        buf_op: &OpTy<'tcx, Provenance>,  // LPVOID
+        number_of_bytes_to_read_op: &OpTy<'tcx, Provenance>, // DWORD
+        number_of_bytes_read_op: &OpTy<'tcx, Provenance>, // LPDWORD
+        overlapped_op: &OpTy<'tcx, Provenance>, // LPOVERLAPPED
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(file_op, "ReadFile")?;
+        let buf = this.read_pointer(buf_op)?;
+        let len = this.read_scalar(number_of_bytes_to_read_op)?.to_u32()?;
+        let number_of_bytes_read = this.read_pointer(number_of_bytes_read_op)?;
+        if !this.ptr_is_null(this.read_pointer(overlapped_op)?)? {
+            throw_unsup_format!("`ReadFile` with an `OVERLAPPED` structure is not supported");
+        }
+
+        let result = this.read_from_file(fd, buf, len, None)?;
+        this.finish_read_write(result, number_of_bytes_read)
+    }
+
+    fn WriteFile(
+        &mut self,
+        file_op: &OpTy<'tcx, Provenance>, // HANDLE
+        buf_op: &OpTy<'tcx, Provenance>,  // LPCVOID
+        number_of_bytes_to_write_op: &OpTy<'tcx, Provenance>, // DWORD
+        number_of_bytes_written_op: &OpTy<'tcx, Provenance>, // LPDWORD
+        overlapped_op: &OpTy<'tcx, Provenance>, // LPOVERLAPPED
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(file_op, "WriteFile")?;
+        let buf = this.read_pointer(buf_op)?;
+        let len = this.read_scalar(number_of_bytes_to_write_op)?.to_u32()?;
+        let number_of_bytes_written = this.read_pointer(number_of_bytes_written_op)?;
+        if !this.ptr_is_null(this.read_pointer(overlapped_op)?)? {
+            throw_unsup_format!("`WriteFile` with an `OVERLAPPED` structure is not supported");
+        }
+
+        let result = this.write_to_file(fd, buf, len, None)?;
+        this.finish_read_write(result, number_of_bytes_written)
+    }
+
+    fn SetFilePointerEx(
+        &mut self,
+        file_op: &OpTy<'tcx, Provenance>,             // HANDLE
+        distance_to_move_op: &OpTy<'tcx, Provenance>, // LARGE_INTEGER
+        new_file_pointer_op: &OpTy<'tcx, Provenance>, // PLARGE_INTEGER
+        move_method_op: &OpTy<'tcx, Provenance>,      // DWORD
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(file_op, "SetFilePointerEx")?;
+        let distance = this.read_scalar(distance_to_move_op)?.to_i64()?;
+        let new_file_pointer = this.read_pointer(new_file_pointer_op)?;
+        let move_method = this.read_scalar(move_method_op)?.to_u32()?;
+
+        let seek_from = if move_method == this.eval_windows_u32("c", "FILE_BEGIN") {
+            let Ok(distance) = u64::try_from(distance) else {
+                let negative_seek = this.eval_windows("c", "ERROR_NEGATIVE_SEEK");
+                this.set_last_error(negative_seek)?;
+                return Ok(this.eval_windows("c", "FALSE"));
+            };
+            SeekFrom::Start(distance)
+        } else if move_method == this.eval_windows_u32("c", "FILE_CURRENT") {
+            SeekFrom::Current(distance)
+        } else if move_method == this.eval_windows_u32("c", "FILE_END") {
+            SeekFrom::End(distance)
+        } else {
+            let invalid_parameter = this.eval_windows("c", "ERROR_INVALID_PARAMETER");
+            this.set_last_error(invalid_parameter)?;
+            return Ok(this.eval_windows("c", "FALSE"));
+        };
+
+        let communicate = this.machine.communicate();
+        let file_descriptor = this.machine.file_handler.handles.get_mut(&fd).unwrap();
+        let result = file_descriptor.seek(communicate, seek_from)?;
+        if let Ok(position) = result {
+            if !this.ptr_is_null(new_file_pointer)? {
+                let new_file_pointer =
+                    this.ptr_to_mplace(new_file_pointer, this.machine.layouts.i64);
+                this.write_scalar(
+                    Scalar::from_i64(i64::try_from(position).unwrap()),
+                    &new_file_pointer,
+                )?;
+            }
+        }
+        this.io_result_to_bool(result.map(|_| ()))
+    }
+
+    fn SetFileInformationByHandle(
+        &mut self,
+        file_op: &OpTy<'tcx, Provenance>,        // HANDLE
+        info_class_op: &OpTy<'tcx, Provenance>,  // FILE_INFO_BY_HANDLE_CLASS
+        info_op: &OpTy<'tcx, Provenance>,        // LPVOID
+        buffer_size_op: &OpTy<'tcx, Provenance>, // DWORD
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(file_op, "SetFileInformationByHandle")?;
+        let info_class = this.read_scalar(info_class_op)?.to_i32()?;
+        // The size is implied by the information class.
+        this.read_scalar(buffer_size_op)?.to_u32()?;
+
+        if info_class != this.eval_windows("c", "FileEndOfFileInfo").to_i32()? {
+            throw_unsup_format!(
+                "`SetFileInformationByHandle` only supports the `FileEndOfFileInfo` class"
+            );
+        }
+        let info =
+            this.deref_pointer_as(info_op, this.windows_ty_layout("FILE_END_OF_FILE_INFO"))?;
+        let end_of_file = this.project_field_named(&info, "EndOfFile")?;
+        let end_of_file = this.read_scalar(&end_of_file)?.to_i64()?;
+
+        let file_descriptor = this.machine.file_handler.handles.get(&fd).unwrap();
+        let result = if let Some(file_handle) = file_descriptor.downcast_ref::<FileHandle>() {
+            if !file_handle.writable {
+                Err(ErrorKind::PermissionDenied.into())
+            } else if let Ok(len) = u64::try_from(end_of_file) {
+                file_handle.file.set_len(len)
+            } else {
+                Err(ErrorKind::InvalidInput.into())
+            }
+        } else if file_descriptor.downcast_ref::<PathHandle>().is_some() {
+            // The handle was opened without write access.
+            Err(ErrorKind::PermissionDenied.into())
+        } else {
+            throw_unsup_format!(
+                "`SetFileInformationByHandle` is not supported on {}",
+                file_descriptor.name()
+            );
+        };
+        this.io_result_to_bool(result)
+    }
+
+    fn FlushFileBuffers(
+        &mut self,
+        file_op: &OpTy<'tcx, Provenance>, // HANDLE
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(file_op, "FlushFileBuffers")?;
+
+        let file_descriptor = this.machine.file_handler.handles.get(&fd).unwrap();
+        let result = if let Some(file_handle) = file_descriptor.downcast_ref::<FileHandle>() {
+            // Windows requires write access to flush a file.
+            if file_handle.writable {
+                file_handle.file.sync_all()
+            } else {
+                Err(ErrorKind::PermissionDenied.into())
+            }
+        } else if file_descriptor.downcast_ref::<PathHandle>().is_some() {
+            Err(ErrorKind::PermissionDenied.into())
+        } else {
+            throw_unsup_format!(
+                "`FlushFileBuffers` is not supported on {}",
+                file_descriptor.name()
+            );
+        };
+        this.io_result_to_bool(result)
+    }
+
+    fn GetFileInformationByHandle(
+        &mut self,
+        file_op: &OpTy<'tcx, Provenance>,             // HANDLE
+        file_information_op: &OpTy<'tcx, Provenance>, // LPBY_HANDLE_FILE_INFORMATION
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(file_op, "GetFileInformationByHandle")?;
+        let file_information = this.deref_pointer_as(
+            file_information_op,
+            this.windows_ty_layout("BY_HANDLE_FILE_INFORMATION"),
+        )?;
+
+        let file_descriptor = &**this.machine.file_handler.handles.get(&fd).unwrap();
+        let Some(metadata) = host_metadata(file_descriptor) else {
+            throw_unsup_format!(
+                "`GetFileInformationByHandle` is not supported on {}",
+                file_descriptor.name()
+            );
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => return this.io_result_to_bool(Err(e)),
+        };
+
+        // The volume serial number and the file index together identify a file, which is what
+        // `dev` and `ino` do on Unix hosts.
+        #[cfg(unix)]
+        let (volume, index, links) = {
+            use std::os::unix::fs::MetadataExt;
+            (metadata.dev(), metadata.ino(), metadata.nlink())
+        };
+        #[cfg(not(unix))]
+        let (volume, index, links) = (0u64, 0u64, 1u64);
+
+        this.write_file_metadata(&metadata, &file_information)?;
+        #[allow(clippy::arithmetic_side_effects)] // the shift cannot overflow
+        this.write_int_fields_named(
+            &[
+                ("dwVolumeSerialNumber", (volume & 0xFFFF_FFFF).into()),
+                ("nNumberOfLinks", links.min(u32::MAX.into()).into()),
+                ("nFileIndexHigh", (index >> 32).into()),
+                ("nFileIndexLow", (index & 0xFFFF_FFFF).into()),
+            ],
+            &file_information,
+        )?;
+        Ok(this.eval_windows("c", "TRUE"))
+    }
+
+    fn GetFileInformationByHandleEx(
+        &mut self,
+        file_op: &OpTy<'tcx, Provenance>,        // HANDLE
+        info_class_op: &OpTy<'tcx, Provenance>,  // FILE_INFO_BY_HANDLE_CLASS
+        info_op: &OpTy<'tcx, Provenance>,        // LPVOID
+        buffer_size_op: &OpTy<'tcx, Provenance>, // DWORD
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(file_op, "GetFileInformationByHandleEx")?;
+        let info_class = this.read_scalar(info_class_op)?.to_i32()?;
+        // The size is implied by the information class.
+        this.read_scalar(buffer_size_op)?.to_u32()?;
+
+        // std uses this to find out whether a reparse point is a symbolic link.
+        if info_class != this.eval_windows("c", "FileAttributeTagInfo").to_i32()? {
+            throw_unsup_format!(
+                "`GetFileInformationByHandleEx` only supports the `FileAttributeTagInfo` class"
+            );
+        }
+        let info =
+            this.deref_pointer_as(info_op, this.windows_ty_layout("FILE_ATTRIBUTE_TAG_INFO"))?;
+
+        let file_descriptor = &**this.machine.file_handler.handles.get(&fd).unwrap();
+        let Some(metadata) = host_metadata(file_descriptor) else {
+            throw_unsup_format!(
+                "`GetFileInformationByHandleEx` is not supported on {}",
+                file_descriptor.name()
+            );
+        };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => return this.io_result_to_bool(Err(e)),
+        };
+
+        let reparse_tag = if metadata.is_symlink() {
+            this.eval_windows_u32("c", "IO_REPARSE_TAG_SYMLINK")
+        } else {
+            0
+        };
+        this.write_int_fields_named(
+            &[
+                ("FileAttributes", this.file_attributes(&metadata).into()),
+                ("ReparseTag", reparse_tag.into()),
+            ],
+            &info,
+        )?;
+        Ok(this.eval_windows("c", "TRUE"))
+    }
+
+    fn FindFirstFileW(
+        &mut self,
+        file_name_op: &OpTy<'tcx, Provenance>,      // LPCWSTR
+        find_file_data_op: &OpTy<'tcx, Provenance>, // LPWIN32_FIND_DATAW
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns HANDLE
+        let this = self.eval_context_mut();
+        this.assert_target_os("windows", "FindFirstFileW");
+
+        let pattern = this.read_path_from_wide_str(this.read_pointer(file_name_op)?)?;
+        let find_data =
+            this.deref_pointer_as(find_file_data_op, this.windows_ty_layout("WIN32_FIND_DATAW"))?;
+
+        // `INVALID_HANDLE_VALUE`
+        let invalid_handle = Scalar::from_target_isize(-1, this);
+
+        // Wildcards are up to the host shell, so we only support listing an entire directory,
+        // which is what `std::fs::read_dir` does.
+        if pattern.file_name() != Some(OsStr::new("*")) {
+            throw_unsup_format!("`FindFirstFileW` only supports patterns of the form `dir\\*`");
+        }
+        let dir = pattern.parent().filter(|dir| !dir.as_os_str().is_empty());
+        let dir = dir.unwrap_or(Path::new("."));
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`FindFirstFileW`", reject_with)?;
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(invalid_handle);
+        }
+
+        let mut read_dir = match fs::read_dir(dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                if e.kind() == ErrorKind::NotFound {
+                    // `ERROR_FILE_NOT_FOUND` would mean that the directory exists, but that
+                    // nothing in it matches the pattern.
+                    let path_not_found = this.eval_windows("c", "ERROR_PATH_NOT_FOUND");
+                    this.set_last_error(path_not_found)?;
+                } else {
+                    this.set_last_error_from_io_error(e.kind())?;
+                }
+                return Ok(invalid_handle);
+            }
+        };
+
+        // Unlike Windows, the host does not list `.` and `..`, so an empty directory has no
+        // matches at all.
+        let result = match read_dir.next() {
+            Some(Ok(entry)) => this.write_find_data(&entry, &find_data)?,
+            Some(Err(e)) => Err(e),
+            None => Err(ErrorKind::NotFound.into()),
+        };
+        if let Err(e) = result {
+            this.set_last_error_from_io_error(e.kind())?;
+            return Ok(invalid_handle);
+        }
+
+        let fd = this.machine.file_handler.insert_fd(Box::new(FindHandle { read_dir }));
+        Ok(Handle::File(fd).to_scalar(this))
+    }
+
+    fn FindNextFileW(
+        &mut self,
+        find_file_op: &OpTy<'tcx, Provenance>,      // HANDLE
+        find_file_data_op: &OpTy<'tcx, Provenance>, // LPWIN32_FIND_DATAW
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(find_file_op, "FindNextFileW")?;
+        let find_data =
+            this.deref_pointer_as(find_file_data_op, this.windows_ty_layout("WIN32_FIND_DATAW"))?;
+
+        let file_descriptor = this.machine.file_handler.handles.get_mut(&fd).unwrap();
+        let Some(find_handle) = file_descriptor.downcast_mut::<FindHandle>() else {
+            this.invalid_handle("FindNextFileW")?
+        };
+        let result = match find_handle.read_dir.next() {
+            Some(Ok(entry)) => this.write_find_data(&entry, &find_data)?,
+            Some(Err(e)) => Err(e),
+            None => {
+                let no_more_files = this.eval_windows("c", "ERROR_NO_MORE_FILES");
+                this.set_last_error(no_more_files)?;
+                return Ok(this.eval_windows("c", "FALSE"));
+            }
+        };
+        this.io_result_to_bool(result)
+    }
+
+    fn FindClose(
+        &mut self,
+        find_file_op: &OpTy<'tcx, Provenance>, // HANDLE
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+
+        let fd = this.read_file_handle(find_file_op, "FindClose")?;
+        let file_descriptor = this.machine.file_handler.handles.get(&fd).unwrap();
+        if file_descriptor.downcast_ref::<FindHandle>().is_none() {
+            this.invalid_handle("FindClose")?;
+        }
+        // Dropping the search cannot fail.
+        this.machine.file_handler.handles.remove(&fd);
+        Ok(this.eval_windows("c", "TRUE"))
+    }
+
+    fn DeleteFileW(
+        &mut self,
+        file_name_op: &OpTy<'tcx, Provenance>, // LPCWSTR
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+        this.assert_target_os("windows", "DeleteFileW");
+
+        let path = this.read_path_from_wide_str(this.read_pointer(file_name_op)?)?;
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`DeleteFileW`", reject_with)?;
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(this.eval_windows("c", "FALSE"));
+        }
+
+        let result = fs::remove_file(path);
+        this.io_result_to_bool(result)
+    }
+
+    fn CreateDirectoryW(
+        &mut self,
+        path_name_op: &OpTy<'tcx, Provenance>, // LPCWSTR
+        security_attributes_op: &OpTy<'tcx, Provenance>, // LPSECURITY_ATTRIBUTES
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+        this.assert_target_os("windows", "CreateDirectoryW");
+
+        let path = this.read_path_from_wide_str(this.read_pointer(path_name_op)?)?;
+        if !this.ptr_is_null(this.read_pointer(security_attributes_op)?)? {
+            throw_unsup_format!("`CreateDirectoryW` with security attributes is not supported");
+        }
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`CreateDirectoryW`", reject_with)?;
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(this.eval_windows("c", "FALSE"));
+        }
+
+        let result = fs::create_dir(path);
+        this.io_result_to_bool(result)
+    }
+
+    fn RemoveDirectoryW(
+        &mut self,
+        path_name_op: &OpTy<'tcx, Provenance>, // LPCWSTR
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+        this.assert_target_os("windows", "RemoveDirectoryW");
+
+        let path = this.read_path_from_wide_str(this.read_pointer(path_name_op)?)?;
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`RemoveDirectoryW`", reject_with)?;
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(this.eval_windows("c", "FALSE"));
+        }
+
+        let result = fs::remove_dir(path);
+        this.io_result_to_bool(result)
+    }
+
+    fn MoveFileExW(
+        &mut self,
+        existing_file_name_op: &OpTy<'tcx, Provenance>, // LPCWSTR
+        new_file_name_op: &OpTy<'tcx, Provenance>,      // LPCWSTR
+        flags_op: &OpTy<'tcx, Provenance>,              // DWORD
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        // ^ Returns BOOL
+        let this = self.eval_context_mut();
+        this.assert_target_os("windows", "MoveFileExW");
+
+        let existing = this.read_path_from_wide_str(this.read_pointer(existing_file_name_op)?)?;
+        let new = this.read_path_from_wide_str(this.read_pointer(new_file_name_op)?)?;
+        let flags = this.read_scalar(flags_op)?.to_u32()?;
+
+        let replace_existing = this.eval_windows_u32("c", "MOVEFILE_REPLACE_EXISTING");
+        if flags & !replace_existing != 0 {
+            throw_unsup_format!(
+                "`MoveFileExW` does not support the flags {:#x}",
+                flags & !replace_existing
+            );
+        }
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`MoveFileExW`", reject_with)?;
+            this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
+            return Ok(this.eval_windows("c", "FALSE"));
+        }
+
+        // The host replaces existing files, so we have to check for them ourselves.
+        if flags & replace_existing == 0 && fs::symlink_metadata(&new).is_ok() {
+            this.set_last_error_from_io_error(ErrorKind::AlreadyExists)?;
+            return Ok(this.eval_windows("c", "FALSE"));
+        }
+        let result = fs::rename(existing, new);
+        this.io_result_to_bool(result)
+    }
+}
@@ -14,6 +14,8 @@ pub enum Handle {
     Null,
     Pseudo(PseudoHandle),
     Thread(ThreadId),
+    /// A file, directory or directory search, stored in the machine's `FileHandler`.
+    File(i32),
 }
 
 impl PseudoHandle {
@@ -37,12 +39,14 @@ impl Handle {
     const NULL_DISCRIMINANT: u32 = 0;
     const PSEUDO_DISCRIMINANT: u32 = 1;
     const THREAD_DISCRIMINANT: u32 = 2;
+    const FILE_DISCRIMINANT: u32 = 3;
 
     fn discriminant(self) -> u32 {
         match self {
             Self::Null => Self::NULL_DISCRIMINANT,
             Self::Pseudo(_) => Self::PSEUDO_DISCRIMINANT,
             Self::Thread(_) => Self::THREAD_DISCRIMINANT,
+            Self::File(_) => Self::FILE_DISCRIMINANT,
         }
     }
 
@@ -51,6 +55,7 @@ fn data(self) -> u32 {
             Self::Null => 0,
             Self::Pseudo(pseudo_handle) => pseudo_handle.value(),
             Self::Thread(thread) => thread.to_u32(),
+            Self::File(fd) => u32::try_from(fd).unwrap(),
         }
     }
 
@@ -96,6 +101,7 @@ fn new(discriminant: u32, data: u32) -> Option<Self> {
             Self::NULL_DISCRIMINANT if data == 0 => Some(Self::Null),
             Self::PSEUDO_DISCRIMINANT => Some(Self::Pseudo(PseudoHandle::from_value(data)?)),
             Self::THREAD_DISCRIMINANT => Some(Self::Thread(data.into())),
+            Self::FILE_DISCRIMINANT => Some(Self::File(i32::try_from(data).ok()?)),
             _ => None,
         }
     }
@@ -155,7 +161,10 @@ fn invalid_handle(&mut self, function_name: &str) -> InterpResult<'tcx, !> {
         )))
     }
 
-    fn CloseHandle(&mut self, handle_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx> {
+    fn CloseHandle(
+        &mut self,
+        handle_op: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
         let handle = this.read_scalar(handle_op)?;
@@ -163,9 +172,18 @@ fn CloseHandle(&mut self, handle_op: &OpTy<'tcx, Provenance>) -> InterpResult<'t
         match Handle::from_scalar(handle, this)? {
             Some(Handle::Thread(thread)) =>
                 this.detach_thread(thread, /*allow_terminated_joined*/ true)?,
+            Some(Handle::File(fd)) => {
+                let Some(file_descriptor) = this.machine.file_handler.handles.remove(&fd) else {
+                    this.invalid_handle("CloseHandle")?
+                };
+                if let Err(e) = file_descriptor.close(this.machine.communicate())? {
+                    this.set_last_error_from_io_error(e.kind())?;
+                    return Ok(this.eval_windows("c", "FALSE"));
+                }
+            }
             _ => this.invalid_handle("CloseHandle")?,
         }
 
-        Ok(())
+        Ok(this.eval_windows("c", "TRUE"))
     }
 }
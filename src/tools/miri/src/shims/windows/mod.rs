@@ -1,5 +1,6 @@
 pub mod foreign_items;
 
+mod fs;
 mod handle;
 mod sync;
 mod thread;
@@ -0,0 +1,121 @@
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use crate::*;
+use shims::foreign_items::EmulateForeignItemResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
+    crate::MiriInterpCxExt<'mir, 'tcx>
+{
+    fn emulate_x86_bmi_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateForeignItemResult> {
+        let this = self.eval_context_mut();
+
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.bmi.").unwrap();
+
+        // The intrinsics are suffixed with the bit size of their operands.
+        let Some((unprefixed_name, size)) = unprefixed_name.rsplit_once('.') else {
+            return Ok(EmulateForeignItemResult::NotSupported);
+        };
+        let is_64_bit = match size {
+            "32" => false,
+            "64" => true,
+            _ => return Ok(EmulateForeignItemResult::NotSupported),
+        };
+
+        // All intrinsics of the "bmi" namespace belong to the "bmi2" ISA extension.
+        // The exception is "bextr", which belongs to "bmi1".
+        let target_feature = if unprefixed_name == "bextr" { "bmi1" } else { "bmi2" };
+        this.expect_target_feature_for_intrinsic(link_name, target_feature)?;
+
+        if is_64_bit && this.tcx.sess.target.arch != "x86_64" {
+            return Ok(EmulateForeignItemResult::NotSupported);
+        }
+
+        let [source, control] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+        let source = this.read_scalar(source)?;
+        let control = this.read_scalar(control)?;
+        let (source, control, bits) = if is_64_bit {
+            (source.to_u64()?, control.to_u64()?, 64)
+        } else {
+            (u64::from(source.to_u32()?), u64::from(control.to_u32()?), 32)
+        };
+
+        let result = match unprefixed_name {
+            // Used to implement the `_bextr_u32` and `_bextr_u64` functions.
+            // Extracts a contiguous range of bits from `source`. Bits 0..=7 of
+            // `control` specify the index of the first bit, and bits 8..=15 the
+            // number of bits to extract.
+            // https://www.felixcloutier.com/x86/bextr
+            "bextr" => {
+                let start = u32::try_from(control & 0xff).unwrap();
+                let len = u32::try_from((control >> 8) & 0xff).unwrap();
+                let shifted = source.checked_shr(start).unwrap_or(0);
+                // Keep only the lowest `len` bits. When `len` is at least as large as
+                // the operand size, all the bits are kept.
+                if len >= bits { shifted } else { shifted & (1u64 << len).wrapping_sub(1) }
+            }
+            // Used to implement the `_bzhi_u32` and `_bzhi_u64` functions.
+            // Clears the bits of `source` starting at the index specified by bits 0..=7
+            // of `control`.
+            // https://www.felixcloutier.com/x86/bzhi
+            "bzhi" => {
+                let index = u32::try_from(control & 0xff).unwrap();
+                if index >= bits { source } else { source & (1u64 << index).wrapping_sub(1) }
+            }
+            // Used to implement the `_pdep_u32` and `_pdep_u64` functions.
+            // Scatters the low bits of `source` to the positions of the set bits of
+            // `control`, from the lowest to the highest one.
+            // https://www.felixcloutier.com/x86/pdep
+            "pdep" => {
+                let mut mask = control;
+                let mut result = 0u64;
+                let mut bit = 1u64;
+                while mask != 0 {
+                    let lowest_set = mask & mask.wrapping_neg();
+                    if source & bit != 0 {
+                        result |= lowest_set;
+                    }
+                    mask &= !lowest_set;
+                    bit = bit.wrapping_shl(1);
+                }
+                result
+            }
+            // Used to implement the `_pext_u32` and `_pext_u64` functions.
+            // Gathers the bits of `source` at the positions of the set bits of
+            // `control` into the low bits of the result.
+            // https://www.felixcloutier.com/x86/pext
+            "pext" => {
+                let mut mask = control;
+                let mut result = 0u64;
+                let mut bit = 1u64;
+                while mask != 0 {
+                    let lowest_set = mask & mask.wrapping_neg();
+                    if source & lowest_set != 0 {
+                        result |= bit;
+                    }
+                    mask &= !lowest_set;
+                    bit = bit.wrapping_shl(1);
+                }
+                result
+            }
+            _ => return Ok(EmulateForeignItemResult::NotSupported),
+        };
+
+        let result = if is_64_bit {
+            Scalar::from_u64(result)
+        } else {
+            Scalar::from_u32(u32::try_from(result).unwrap())
+        };
+        this.write_scalar(result, dest)?;
+
+        Ok(EmulateForeignItemResult::NeedsJumping)
+    }
+}
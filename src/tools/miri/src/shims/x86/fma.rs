@@ -0,0 +1,92 @@
+use rustc_middle::ty;
+use rustc_middle::ty::FloatTy;
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use crate::helpers::{ToHost, ToSoft};
+use crate::*;
+use shims::foreign_items::EmulateForeignItemResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
+    crate::MiriInterpCxExt<'mir, 'tcx>
+{
+    fn emulate_x86_fma_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateForeignItemResult> {
+        let this = self.eval_context_mut();
+        this.expect_target_feature_for_intrinsic(link_name, "fma")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.fma.").unwrap();
+
+        // Most FMA intrinsics (e.g. `_mm_fmadd_ps`) are implemented with the portable
+        // `simd_fma` intrinsic. Only the ones that alternate between addition and
+        // subtraction need a dedicated shim.
+        match unprefixed_name {
+            // Used to implement the _mm{,256}_fmaddsub_{ps,pd} and
+            // _mm{,256}_fmsubadd_{ps,pd} functions.
+            // Computes `a * b - c` for even elements and `a * b + c` for odd
+            // elements ("fmaddsub"), or `a * b + c` for even elements and
+            // `a * b - c` for odd elements ("fmsubadd"). The multiplication and
+            // the addition or subtraction are fused, i.e., rounded only once.
+            "vfmaddsub.ps" | "vfmaddsub.pd" | "vfmaddsub.ps.256" | "vfmaddsub.pd.256"
+            | "vfmsubadd.ps" | "vfmsubadd.pd" | "vfmsubadd.ps.256" | "vfmsubadd.pd.256" => {
+                let [a, b, c] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+                let (a, a_len) = this.operand_to_simd(a)?;
+                let (b, b_len) = this.operand_to_simd(b)?;
+                let (c, c_len) = this.operand_to_simd(c)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, a_len);
+                assert_eq!(dest_len, b_len);
+                assert_eq!(dest_len, c_len);
+
+                let sub_even = unprefixed_name.starts_with("vfmaddsub");
+
+                for i in 0..dest_len {
+                    let a = this.read_scalar(&this.project_index(&a, i)?)?;
+                    let b = this.read_scalar(&this.project_index(&b, i)?)?;
+                    let c = this.read_scalar(&this.project_index(&c, i)?)?;
+                    let dest = this.project_index(&dest, i)?;
+
+                    let sub = (i % 2 == 0) == sub_even;
+
+                    // FIXME: using host floats to work around https://github.com/rust-lang/miri/issues/2468.
+                    let ty::Float(float_ty) = dest.layout.ty.kind() else {
+                        span_bug!(this.cur_span(), "{} operand is not a float", link_name)
+                    };
+                    let res = match float_ty {
+                        FloatTy::F32 => {
+                            let a = a.to_f32()?;
+                            let b = b.to_f32()?;
+                            let c = c.to_f32()?;
+                            let addend = if sub { -c.to_host() } else { c.to_host() };
+                            let res = a.to_host().mul_add(b.to_host(), addend).to_soft();
+                            let res = this.adjust_nan(res, &[a, b, c]);
+                            Scalar::from(res)
+                        }
+                        FloatTy::F64 => {
+                            let a = a.to_f64()?;
+                            let b = b.to_f64()?;
+                            let c = c.to_f64()?;
+                            let addend = if sub { -c.to_host() } else { c.to_host() };
+                            let res = a.to_host().mul_add(b.to_host(), addend).to_soft();
+                            let res = this.adjust_nan(res, &[a, b, c]);
+                            Scalar::from(res)
+                        }
+                        FloatTy::F16 | FloatTy::F128 => unreachable!(),
+                    };
+
+                    this.write_scalar(res, &dest)?;
+                }
+            }
+            _ => return Ok(EmulateForeignItemResult::NotSupported),
+        }
+        Ok(EmulateForeignItemResult::NeedsJumping)
+    }
+}
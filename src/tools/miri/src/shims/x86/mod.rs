@@ -14,6 +14,10 @@
 
 mod aesni;
 mod avx;
+mod avx2;
+mod bmi;
+mod fma;
+mod pclmulqdq;
 mod sse;
 mod sse2;
 mod sse3;
@@ -123,6 +127,26 @@ fn emulate_x86_intrinsic(
                     this, link_name, abi, args, dest,
                 );
             }
+            name if name.starts_with("avx2.") => {
+                return avx2::EvalContextExt::emulate_x86_avx2_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
+            name if name.starts_with("fma.") => {
+                return fma::EvalContextExt::emulate_x86_fma_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
+            name if name.starts_with("bmi.") => {
+                return bmi::EvalContextExt::emulate_x86_bmi_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
+            name if name.starts_with("pclmulqdq") => {
+                return pclmulqdq::EvalContextExt::emulate_x86_pclmulqdq_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
 
             _ => return Ok(EmulateForeignItemResult::NotSupported),
         }
@@ -792,3 +816,624 @@ fn test_high_bits_masked<'tcx>(
 
     Ok((direct, negated))
 }
+
+/// Conditionally loads from `ptr` according the high bit of each
+/// element of `mask`. `ptr` does not need to be aligned.
+fn mask_load<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    ptr: &OpTy<'tcx, Provenance>,
+    mask: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (mask, mask_len) = this.operand_to_simd(mask)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, mask_len);
+
+    let mask_item_size = mask.layout.field(this, 0).size;
+    let high_bit_offset = mask_item_size.bits().checked_sub(1).unwrap();
+
+    let ptr = this.read_pointer(ptr)?;
+    for i in 0..dest_len {
+        let mask = this.project_index(&mask, i)?;
+        let dest = this.project_index(&dest, i)?;
+
+        if this.read_scalar(&mask)?.to_uint(mask_item_size)? >> high_bit_offset != 0 {
+            // Size * u64 is implemented as always checked
+            #[allow(clippy::arithmetic_side_effects)]
+            let ptr = ptr.wrapping_offset(dest.layout.size * i, &this.tcx);
+            // Unaligned copy, which is what we want.
+            this.mem_copy(ptr, dest.ptr(), dest.layout.size, /*nonoverlapping*/ true)?;
+        } else {
+            this.write_scalar(Scalar::from_int(0, dest.layout.size), &dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Conditionally stores into `ptr` according the high bit of each
+/// element of `mask`. `ptr` does not need to be aligned.
+fn mask_store<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    ptr: &OpTy<'tcx, Provenance>,
+    mask: &OpTy<'tcx, Provenance>,
+    value: &OpTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (mask, mask_len) = this.operand_to_simd(mask)?;
+    let (value, value_len) = this.operand_to_simd(value)?;
+
+    assert_eq!(value_len, mask_len);
+
+    let mask_item_size = mask.layout.field(this, 0).size;
+    let high_bit_offset = mask_item_size.bits().checked_sub(1).unwrap();
+
+    let ptr = this.read_pointer(ptr)?;
+    for i in 0..value_len {
+        let mask = this.project_index(&mask, i)?;
+        let value = this.project_index(&value, i)?;
+
+        if this.read_scalar(&mask)?.to_uint(mask_item_size)? >> high_bit_offset != 0 {
+            // Size * u64 is implemented as always checked
+            #[allow(clippy::arithmetic_side_effects)]
+            let ptr = ptr.wrapping_offset(value.layout.size * i, &this.tcx);
+            // Unaligned copy, which is what we want.
+            this.mem_copy(value.ptr(), ptr, value.layout.size, /*nonoverlapping*/ true)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Calculates the absolute value of each element of `op` and stores
+/// the result in `dest`.
+fn int_abs<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (op, op_len) = this.operand_to_simd(op)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(op_len, dest_len);
+
+    for i in 0..dest_len {
+        let op = this.read_scalar(&this.project_index(&op, i)?)?;
+        let dest = this.project_index(&dest, i)?;
+
+        // Converting to a host "i128" works since the input is always signed.
+        let res = op.to_int(dest.layout.size)?.unsigned_abs();
+
+        this.write_scalar(Scalar::from_uint(res, dest.layout.size), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Takes a 128-bit vector, transmutes it to `[u64; 2]` and extracts
+/// the first value.
+fn extract_first_u64<'tcx>(
+    this: &crate::MiriInterpCx<'_, 'tcx>,
+    op: &OpTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, u64> {
+    // Transmute vector to `[u64; 2]`
+    let u64_array_layout = this.layout_of(Ty::new_array(this.tcx.tcx, this.tcx.types.u64, 2))?;
+    let op = op.transmute(u64_array_layout, this)?;
+
+    // Get the first u64 from the array
+    this.read_scalar(&this.project_index(&op, 0)?)?.to_u64()
+}
+
+#[derive(Copy, Clone)]
+enum ShiftOp {
+    /// Shift left, shifting in zeros.
+    Left,
+    /// Shift right, shifting in zeros.
+    RightLogic,
+    /// Shift right, shifting in copies of the sign bit.
+    RightArith,
+}
+
+/// Shifts a single element of `op` by `shift` bits according to `which`.
+///
+/// For logic shifts, when `shift` is larger than BITS - 1, zero is produced.
+/// For arithmetic right-shifts, when `shift` is larger than BITS - 1, the
+/// sign bit is copied to remaining bits.
+fn shift_element<'tcx>(
+    which: ShiftOp,
+    op: Scalar<Provenance>,
+    shift: u32,
+    size: Size,
+) -> InterpResult<'tcx, Scalar<Provenance>> {
+    let res = match which {
+        ShiftOp::Left => {
+            let op = op.to_uint(size)?;
+            let res = op.checked_shl(shift).unwrap_or(0);
+            // `truncate` is needed as left-shift can make the absolute value larger.
+            Scalar::from_uint(size.truncate(res), size)
+        }
+        ShiftOp::RightLogic => {
+            let op = op.to_uint(size)?;
+            let res = op.checked_shr(shift).unwrap_or(0);
+            // No `truncate` needed as right-shift can only make the absolute value smaller.
+            Scalar::from_uint(res, size)
+        }
+        ShiftOp::RightArith => {
+            let op = op.to_int(size)?;
+            // On overflow, copy the sign bit to the remaining bits
+            let res = op.checked_shr(shift).unwrap_or(op >> 127);
+            // No `truncate` needed as right-shift can only make the absolute value smaller.
+            Scalar::from_int(res, size)
+        }
+    };
+    Ok(res)
+}
+
+/// Shifts each element of `left` by a scalar amount. The shift amount
+/// is determined by the lowest 64 bits of `right` (which is a 128-bit vector).
+fn shift_simd_by_scalar<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    which: ShiftOp,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    // `right` may have a different length, and we only care about its
+    // lowest 64 bits anyway.
+
+    // Get the 64-bit shift operand and convert it to the type expected
+    // by checked_{shl,shr} (u32).
+    // It is ok to saturate the value to u32::MAX because any value
+    // above BITS - 1 will produce the same result.
+    let shift = u32::try_from(extract_first_u64(this, right)?).unwrap_or(u32::MAX);
+
+    for i in 0..dest_len {
+        let left = this.read_scalar(&this.project_index(&left, i)?)?;
+        let dest = this.project_index(&dest, i)?;
+
+        let res = shift_element(which, left, shift, dest.layout.size)?;
+        this.write_scalar(res, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Shifts each element of `left` by the corresponding element of `right`.
+fn shift_simd_by_simd<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    which: ShiftOp,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let left = this.read_scalar(&this.project_index(&left, i)?)?;
+        let right = this.read_scalar(&this.project_index(&right, i)?)?;
+        let dest = this.project_index(&dest, i)?;
+
+        // Convert the shift amount to the type expected by checked_{shl,shr} (u32).
+        // It is ok to saturate the value to u32::MAX because any value
+        // above BITS - 1 will produce the same result.
+        let shift = u32::try_from(right.to_uint(dest.layout.size)?).unwrap_or(u32::MAX);
+
+        let res = shift_element(which, left, shift, dest.layout.size)?;
+        this.write_scalar(res, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Converts two vectors of `N`-bit integers into a single vector of
+/// `N/2`-bit integers, applying `f` to each element.
+///
+/// Each 128-bit chunk is treated independently: the first half of the
+/// i-th 128-bit chunk of `dest` comes from the i-th 128-bit chunk of
+/// `left` and the second half from the i-th 128-bit chunk of `right`.
+fn pack_generic<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+    f: impl Fn(Scalar<Provenance>) -> InterpResult<'tcx, Scalar<Provenance>>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(left.layout, right.layout);
+    assert_eq!(left.layout.size, dest.layout.size);
+
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(dest_len, left_len.checked_mul(2).unwrap());
+
+    assert_eq!(dest.layout.size.bits() % 128, 0);
+    let num_chunks = dest.layout.size.bits() / 128;
+    let op_items_per_chunk = left_len.checked_div(num_chunks).unwrap();
+
+    for i in 0..num_chunks {
+        for j in 0..op_items_per_chunk {
+            let src_i = i.checked_mul(op_items_per_chunk).unwrap().checked_add(j).unwrap();
+            let left = this.read_scalar(&this.project_index(&left, src_i)?)?;
+            let right = this.read_scalar(&this.project_index(&right, src_i)?)?;
+
+            let left_dest_i = i
+                .checked_mul(op_items_per_chunk.checked_mul(2).unwrap())
+                .unwrap()
+                .checked_add(j)
+                .unwrap();
+            let right_dest_i = left_dest_i.checked_add(op_items_per_chunk).unwrap();
+
+            this.write_scalar(f(left)?, &this.project_index(&dest, left_dest_i)?)?;
+            this.write_scalar(f(right)?, &this.project_index(&dest, right_dest_i)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts two 16-bit integer vectors to a single 8-bit integer
+/// vector with signed saturation.
+///
+/// Each 128-bit chunk is treated independently.
+fn packsswb<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    pack_generic(this, left, right, dest, |op| {
+        let op = op.to_i16()?;
+        let res = i8::try_from(op).unwrap_or(if op < 0 { i8::MIN } else { i8::MAX });
+        Ok(Scalar::from_i8(res))
+    })
+}
+
+/// Converts two 16-bit signed integer vectors to a single 8-bit
+/// unsigned integer vector with saturation.
+///
+/// Each 128-bit chunk is treated independently.
+fn packuswb<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    pack_generic(this, left, right, dest, |op| {
+        let op = op.to_i16()?;
+        let res = u8::try_from(op).unwrap_or(if op < 0 { 0 } else { u8::MAX });
+        Ok(Scalar::from_u8(res))
+    })
+}
+
+/// Converts two 32-bit integer vectors to a single 16-bit integer
+/// vector with signed saturation.
+///
+/// Each 128-bit chunk is treated independently.
+fn packssdw<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    pack_generic(this, left, right, dest, |op| {
+        let op = op.to_i32()?;
+        let res = i16::try_from(op).unwrap_or(if op < 0 { i16::MIN } else { i16::MAX });
+        Ok(Scalar::from_i16(res))
+    })
+}
+
+/// Converts two 32-bit signed integer vectors to a single 16-bit
+/// unsigned integer vector with saturation.
+///
+/// Each 128-bit chunk is treated independently.
+fn packusdw<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    pack_generic(this, left, right, dest, |op| {
+        let op = op.to_i32()?;
+        let res = u16::try_from(op).unwrap_or(if op < 0 { 0 } else { u16::MAX });
+        Ok(Scalar::from_u16(res))
+    })
+}
+
+/// Multiplies packed signed 16-bit integers in `left` and `right`, producing
+/// intermediate signed 32-bit integers. Horizontally add adjacent pairs of
+/// intermediate 32-bit integers, and pack the results in `dest`.
+fn pmaddwd<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(dest_len.checked_mul(2).unwrap(), left_len);
+
+    for i in 0..dest_len {
+        let j1 = i.checked_mul(2).unwrap();
+        let left1 = this.read_scalar(&this.project_index(&left, j1)?)?.to_i16()?;
+        let right1 = this.read_scalar(&this.project_index(&right, j1)?)?.to_i16()?;
+
+        let j2 = j1.checked_add(1).unwrap();
+        let left2 = this.read_scalar(&this.project_index(&left, j2)?)?.to_i16()?;
+        let right2 = this.read_scalar(&this.project_index(&right, j2)?)?.to_i16()?;
+
+        let dest = this.project_index(&dest, i)?;
+
+        // Multiplications are i16*i16->i32, which will not overflow.
+        let mul1 = i32::from(left1).checked_mul(right1.into()).unwrap();
+        let mul2 = i32::from(left2).checked_mul(right2.into()).unwrap();
+        // However, this addition can overflow in the most extreme case
+        // (-0x8000)*(-0x8000)+(-0x8000)*(-0x8000) = 0x80000000
+        let res = mul1.wrapping_add(mul2);
+
+        this.write_scalar(Scalar::from_i32(res), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Multiplies packed 8-bit unsigned integers from `left` and packed
+/// signed 8-bit integers from `right` into 16-bit signed integers. Then,
+/// the saturating sum of the products with indices `2*i` and `2*i+1`
+/// produces the output at index `i`.
+fn pmaddubsw<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(dest_len.checked_mul(2).unwrap(), left_len);
+
+    for i in 0..dest_len {
+        let j1 = i.checked_mul(2).unwrap();
+        let left1 = this.read_scalar(&this.project_index(&left, j1)?)?.to_u8()?;
+        let right1 = this.read_scalar(&this.project_index(&right, j1)?)?.to_i8()?;
+
+        let j2 = j1.checked_add(1).unwrap();
+        let left2 = this.read_scalar(&this.project_index(&left, j2)?)?.to_u8()?;
+        let right2 = this.read_scalar(&this.project_index(&right, j2)?)?.to_i8()?;
+
+        let dest = this.project_index(&dest, i)?;
+
+        // Multiplication of a u8 and an i8 into an i16 cannot overflow.
+        let mul1 = i16::from(left1).checked_mul(right1.into()).unwrap();
+        let mul2 = i16::from(left2).checked_mul(right2.into()).unwrap();
+        let res = mul1.saturating_add(mul2);
+
+        this.write_scalar(Scalar::from_i16(res), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Computes the absolute differences of packed unsigned 8-bit integers in
+/// `left` and `right`, then horizontally sum each consecutive 8 differences
+/// to produce unsigned 16-bit integers, and pack these unsigned 16-bit
+/// integers in the low 16 bits of the 64-bit elements of `dest`.
+fn psadbw<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    // left and right are u8xN, dest is u64x(N/8)
+    assert_eq!(left_len, right_len);
+    assert_eq!(left_len, dest_len.checked_mul(8).unwrap());
+
+    for i in 0..dest_len {
+        let dest = this.project_index(&dest, i)?;
+
+        let mut res: u16 = 0;
+        for j in 0..8 {
+            let op_i = j.checked_add(i.checked_mul(8).unwrap()).unwrap();
+            let left = this.read_scalar(&this.project_index(&left, op_i)?)?.to_u8()?;
+            let right = this.read_scalar(&this.project_index(&right, op_i)?)?.to_u8()?;
+
+            res = res.checked_add(left.abs_diff(right).into()).unwrap();
+        }
+
+        this.write_scalar(Scalar::from_u64(res.into()), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Compute the sum of absolute differences of quadruplets of unsigned
+/// 8-bit integers in `left` and `right`, and store the 16-bit results
+/// in `right`. Quadruplets are selected from `left` and `right` with
+/// offsets specified in `imm`.
+///
+/// Each 128-bit chunk is treated independently (i.e., the value for
+/// the is i-th 128-bit chunk of `dest` is calculated with the i-th
+/// 128-bit chunks of `left` and `right`, using the bits `3*i..3*i+3`
+/// of `imm`).
+fn mpsadbw<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    imm: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    assert_eq!(left.layout, right.layout);
+    assert_eq!(left.layout.size, dest.layout.size);
+
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(left_len, right_len);
+    assert_eq!(left_len, dest_len.checked_mul(2).unwrap());
+
+    assert_eq!(dest.layout.size.bits() % 128, 0);
+    let num_chunks = dest.layout.size.bits() / 128;
+    let op_items_per_chunk = left_len.checked_div(num_chunks).unwrap();
+    let dest_items_per_chunk = dest_len.checked_div(num_chunks).unwrap();
+
+    let imm = this.read_scalar(imm)?.to_uint(imm.layout.size)?;
+
+    for i in 0..num_chunks {
+        // Each 128-bit chunk uses three bits of `imm`.
+        let imm = imm >> i.checked_mul(3).unwrap();
+        let op_base = i.checked_mul(op_items_per_chunk).unwrap();
+        // Bit 2 of `imm` specifies the offset for indices of `left`.
+        // The offset is 0 when the bit is 0 or 4 when the bit is 1.
+        let left_offset = u64::try_from((imm >> 2) & 1).unwrap().checked_mul(4).unwrap();
+        let left_offset = op_base.checked_add(left_offset).unwrap();
+        // Bits 0..=1 of `imm` specify the offset for indices of
+        // `right` in blocks of 4 elements.
+        let right_offset = u64::try_from(imm & 0b11).unwrap().checked_mul(4).unwrap();
+        let right_offset = op_base.checked_add(right_offset).unwrap();
+
+        for j in 0..dest_items_per_chunk {
+            let left_offset = left_offset.checked_add(j).unwrap();
+            let mut res: u16 = 0;
+            for k in 0..4 {
+                let left = this
+                    .read_scalar(&this.project_index(&left, left_offset.checked_add(k).unwrap())?)?
+                    .to_u8()?;
+                let right = this
+                    .read_scalar(
+                        &this.project_index(&right, right_offset.checked_add(k).unwrap())?,
+                    )?
+                    .to_u8()?;
+                res = res.checked_add(left.abs_diff(right).into()).unwrap();
+            }
+            let dest_i = i.checked_mul(dest_items_per_chunk).unwrap().checked_add(j).unwrap();
+            this.write_scalar(Scalar::from_u16(res), &this.project_index(&dest, dest_i)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Multiplies packed 16-bit signed integer values, truncates the 32-bit
+/// product to the 18 most significant bits by right-shifting, and then
+/// divides the 18-bit value by 2 (rounding to nearest) by first adding
+/// 1 and then taking the bits `1..=16`.
+fn pmulhrsw<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let left = this.read_scalar(&this.project_index(&left, i)?)?.to_i16()?;
+        let right = this.read_scalar(&this.project_index(&right, i)?)?.to_i16()?;
+        let dest = this.project_index(&dest, i)?;
+
+        let res =
+            (i32::from(left).checked_mul(right.into()).unwrap() >> 14).checked_add(1).unwrap() >> 1;
+
+        // The result of this operation can overflow a signed 16-bit integer.
+        // When `left` and `right` are -0x8000, the result is 0x8000.
+        #[allow(clippy::cast_possible_truncation)]
+        let res = res as i16;
+
+        this.write_scalar(Scalar::from_i16(res), &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Shuffles bytes from `left` using `right` as pattern. Each 128-bit
+/// chunk is shuffled independently: the indices in `right` can only
+/// select bytes from the corresponding 128-bit chunk of `left`.
+fn pshufb<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let right = this.read_scalar(&this.project_index(&right, i)?)?.to_u8()?;
+        let dest = this.project_index(&dest, i)?;
+
+        let res = if right & 0x80 == 0 {
+            // The index wraps around within the current 16-byte chunk.
+            let j = u64::from(right % 16).checked_add(i & !15).unwrap();
+            this.read_scalar(&this.project_index(&left, j)?)?
+        } else {
+            // If the highest bit in `right` is 1, write zero.
+            Scalar::from_u8(0)
+        };
+
+        this.write_scalar(res, &dest)?;
+    }
+
+    Ok(())
+}
+
+/// Negates elements from `left` when the corresponding element in
+/// `right` is negative. If an element from `right` is zero, zero
+/// is writen to the corresponding output element.
+/// Basically, we multiply `left` with `right.signum()`.
+fn psign<'tcx>(
+    this: &mut crate::MiriInterpCx<'_, 'tcx>,
+    left: &OpTy<'tcx, Provenance>,
+    right: &OpTy<'tcx, Provenance>,
+    dest: &MPlaceTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, ()> {
+    let (left, left_len) = this.operand_to_simd(left)?;
+    let (right, right_len) = this.operand_to_simd(right)?;
+    let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+    assert_eq!(dest_len, left_len);
+    assert_eq!(dest_len, right_len);
+
+    for i in 0..dest_len {
+        let dest = this.project_index(&dest, i)?;
+        let left = this.read_immediate(&this.project_index(&left, i)?)?;
+        let right = this.read_scalar(&this.project_index(&right, i)?)?.to_int(dest.layout.size)?;
+
+        let res = this.wrapping_binary_op(
+            mir::BinOp::Mul,
+            &left,
+            &ImmTy::from_int(right.signum(), dest.layout),
+        )?;
+
+        this.write_immediate(*res, &dest)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use rustc_middle::ty::layout::LayoutOf as _;
+use rustc_middle::ty::Ty;
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use crate::*;
+use shims::foreign_items::EmulateForeignItemResult;
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub(super) trait EvalContextExt<'mir, 'tcx: 'mir>:
+    crate::MiriInterpCxExt<'mir, 'tcx>
+{
+    fn emulate_x86_pclmulqdq_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx, Provenance>],
+        dest: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, EmulateForeignItemResult> {
+        let this = self.eval_context_mut();
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.").unwrap();
+
+        let (len, target_feature) = match unprefixed_name {
+            "pclmulqdq" => (2, "pclmulqdq"),
+            "pclmulqdq.256" => (4, "vpclmulqdq"),
+            "pclmulqdq.512" => (8, "vpclmulqdq"),
+            _ => return Ok(EmulateForeignItemResult::NotSupported),
+        };
+        this.expect_target_feature_for_intrinsic(link_name, target_feature)?;
+
+        // Used to implement the _mm_clmulepi64_si128, _mm256_clmulepi64_epi128
+        // and _mm512_clmulepi64_epi128 functions.
+        // Performs a carry-less multiplication of two 64-bit integers, selected
+        // from each 128-bit chunk of `left` and `right` according to `imm`, and
+        // stores the 128-bit product in the corresponding chunk of `dest`.
+        // Bit 0 of `imm` selects the element of `left`, and bit 4 the element
+        // of `right`.
+        // https://www.felixcloutier.com/x86/pclmulqdq
+        let [left, right, imm] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+
+        assert_eq!(left.layout, right.layout);
+        assert_eq!(left.layout.size, dest.layout.size);
+
+        // Transmute the input into arrays of `[u64; len]`.
+        // Transmute the output into an array of `[u128; len / 2]`.
+        let src_layout = this.layout_of(Ty::new_array(this.tcx.tcx, this.tcx.types.u64, len))?;
+        let dest_layout =
+            this.layout_of(Ty::new_array(this.tcx.tcx, this.tcx.types.u128, len / 2))?;
+
+        let left = left.transmute(src_layout, this)?;
+        let right = right.transmute(src_layout, this)?;
+        let dest = dest.transmute(dest_layout, this)?;
+
+        let imm = this.read_scalar(imm)?.to_u8()?;
+
+        for i in 0..len / 2 {
+            let lo = i.checked_mul(2).unwrap();
+            let hi = lo.checked_add(1).unwrap();
+
+            let index_left = if imm & 0x01 == 0 { lo } else { hi };
+            let index_right = if imm & 0x10 == 0 { lo } else { hi };
+
+            let left = this.read_scalar(&this.project_index(&left, index_left)?)?.to_u64()?;
+            let right = this.read_scalar(&this.project_index(&right, index_right)?)?.to_u64()?;
+
+            // Carry-less multiplication: like a regular long multiplication,
+            // but the partial products are combined with XOR instead of addition.
+            let left = u128::from(left);
+            let mut result = 0u128;
+            for bit in 0..64 {
+                if right & (1 << bit) != 0 {
+                    result ^= left << bit;
+                }
+            }
+
+            let dest = this.project_index(&dest, i)?;
+            this.write_scalar(Scalar::from_u128(result), &dest)?;
+        }
+
+        Ok(EmulateForeignItemResult::NeedsJumping)
+    }
+}
@@ -0,0 +1,17 @@
+//@compile-flags: -Zmiri-leak-summary -Zmiri-leak-report-json=-
+//@error-in-other-file: memory leaked
+//@normalize-stderr-test: ".*│.*" -> "$$stripped$$"
+//@normalize-stdout-test: "[^ \n\x22]*/(rust[^/]*|checkout)/library/" -> "RUSTLIB/"
+//@normalize-stdout-test: "\.rs:[0-9]+:[0-9]+" -> ".rs:LL:CC"
+
+// The `"` in the name of this function has to be escaped in the report. (The test suite's
+// normalization of Windows paths turns the escaping `\` into a `/` in the checked output.)
+fn leak<const C: char>() {
+    std::mem::forget(Box::new(C));
+}
+
+fn main() {
+    for _ in 0..2 {
+        leak::<'"'>();
+    }
+}
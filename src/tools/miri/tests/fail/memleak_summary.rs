@@ -0,0 +1,10 @@
+//@compile-flags: -Zmiri-leak-summary
+//@error-in-other-file: memory leaked
+//@normalize-stderr-test: ".*│.*" -> "$$stripped$$"
+
+fn main() {
+    for _ in 0..3 {
+        std::mem::forget(Box::new(42i32));
+    }
+    std::mem::forget(Box::new(42u64));
+}
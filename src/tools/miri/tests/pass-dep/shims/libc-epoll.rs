@@ -0,0 +1,317 @@
+//@only-target-linux
+// test_epoll_block_then_unblock depends on a deterministic schedule.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::convert::TryInto;
+use std::mem::MaybeUninit;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn main() {
+    test_eventfd();
+    test_socketpair();
+    test_socketpair_peer_closed();
+    test_epoll_socketpair();
+    test_epoll_eventfd_edge_triggered();
+    test_epoll_oneshot();
+    test_epoll_ctl_errors();
+    test_epoll_timeout();
+    test_epoll_block_then_unblock();
+}
+
+fn eventfd_write(fd: i32, val: u64) -> isize {
+    let buf = val.to_ne_bytes();
+    unsafe { libc::write(fd, buf.as_ptr().cast(), 8) }
+}
+
+fn eventfd_read(fd: i32) -> Result<u64, i32> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) };
+    if res < 0 {
+        return Err(std::io::Error::last_os_error().raw_os_error().unwrap());
+    }
+    assert_eq!(res, 8);
+    Ok(u64::from_ne_bytes(buf))
+}
+
+fn epoll_add(epfd: i32, fd: i32, events: i32, data: u64) {
+    let mut ev = libc::epoll_event { events: events as u32, u64: data };
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut ev) };
+    assert_eq!(res, 0);
+}
+
+/// Calls `epoll_wait` and returns the `(events, data)` pairs that were reported.
+fn epoll_wait(epfd: i32, timeout: i32) -> Vec<(u32, u64)> {
+    let mut events = [MaybeUninit::<libc::epoll_event>::uninit(); 8];
+    let res = unsafe {
+        libc::epoll_wait(epfd, events.as_mut_ptr().cast(), events.len().try_into().unwrap(), timeout)
+    };
+    assert!(res >= 0);
+    events[..res as usize]
+        .iter()
+        .map(|ev| {
+            let ev = unsafe { ev.assume_init() };
+            (ev.events, ev.u64)
+        })
+        .collect()
+}
+
+fn test_eventfd() {
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+    assert_ne!(fd, -1);
+
+    // Reading a zero counter would block.
+    assert_eq!(eventfd_read(fd), Err(libc::EAGAIN));
+
+    // Writes are added up, and a read resets the counter.
+    assert_eq!(eventfd_write(fd, 1), 8);
+    assert_eq!(eventfd_write(fd, 41), 8);
+    assert_eq!(eventfd_read(fd), Ok(42));
+    assert_eq!(eventfd_read(fd), Err(libc::EAGAIN));
+
+    // `u64::MAX` cannot be written.
+    assert_eq!(eventfd_write(fd, u64::MAX), -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    // The counter cannot exceed `u64::MAX - 1`.
+    assert_eq!(eventfd_write(fd, u64::MAX - 1), 8);
+    assert_eq!(eventfd_write(fd, 1), -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+
+    // Buffers shorter than 8 bytes are rejected.
+    let buf = [0u8; 4];
+    let res = unsafe { libc::write(fd, buf.as_ptr().cast(), 4) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // A duplicated file descriptor shares the counter.
+    let fd2 = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+    assert_eq!(eventfd_read(fd2), Ok(u64::MAX - 1));
+    assert_eq!(eventfd_read(fd), Err(libc::EAGAIN));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(fd2) }, 0);
+}
+
+fn test_socketpair() {
+    let mut fds = [-1, -1];
+    let res = unsafe {
+        libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0, fds.as_mut_ptr())
+    };
+    assert_eq!(res, 0);
+
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+
+    // Data flows in both directions.
+    let data = b"abcde";
+    let res = unsafe { libc::write(fds[0], data.as_ptr().cast(), data.len()) };
+    assert_eq!(res, 5);
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), 3) };
+    assert_eq!(res, 3);
+    assert_eq!(&buf[..3], b"abc");
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, 2);
+    assert_eq!(&buf[..2], b"de");
+
+    let data = b"12";
+    let res = unsafe { libc::write(fds[1], data.as_ptr().cast(), data.len()) };
+    assert_eq!(res, 2);
+    let res = unsafe { libc::read(fds[0], buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, 2);
+    assert_eq!(&buf[..2], b"12");
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
+
+fn test_socketpair_peer_closed() {
+    let mut fds = [-1, -1];
+    let res = unsafe {
+        libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0, fds.as_mut_ptr())
+    };
+    assert_eq!(res, 0);
+
+    let data = b"xy";
+    let res = unsafe { libc::write(fds[0], data.as_ptr().cast(), data.len()) };
+    assert_eq!(res, 2);
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+
+    // Buffered data can still be read, after that we hit EOF.
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, 2);
+    let res = unsafe { libc::read(fds[1], buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, 0);
+
+    // Writing to a closed peer fails.
+    let res = unsafe { libc::write(fds[1], data.as_ptr().cast(), data.len()) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EPIPE));
+
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+}
+
+fn test_epoll_socketpair() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+
+    let mut fds = [-1, -1];
+    let res = unsafe {
+        libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM | libc::SOCK_NONBLOCK, 0, fds.as_mut_ptr())
+    };
+    assert_eq!(res, 0);
+
+    // Level-triggered: the socket is reported as writable every time.
+    epoll_add(epfd, fds[1], libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLRDHUP, 7);
+    let out = libc::EPOLLOUT as u32;
+    assert_eq!(epoll_wait(epfd, 0), vec![(out, 7)]);
+    assert_eq!(epoll_wait(epfd, 0), vec![(out, 7)]);
+
+    // Once there is data, it is also readable.
+    let data = b"abc";
+    let res = unsafe { libc::write(fds[0], data.as_ptr().cast(), data.len()) };
+    assert_eq!(res, 3);
+    let in_out = (libc::EPOLLIN | libc::EPOLLOUT) as u32;
+    assert_eq!(epoll_wait(epfd, 0), vec![(in_out, 7)]);
+
+    // When the peer is closed, the connection is reported as hung up.
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    let hup = (libc::EPOLLIN | libc::EPOLLRDHUP | libc::EPOLLHUP) as u32;
+    assert_eq!(epoll_wait(epfd, 0), vec![(hup, 7)]);
+
+    // Deregistered file descriptors are not reported anymore.
+    let res = unsafe {
+        libc::epoll_ctl(epfd, libc::EPOLL_CTL_DEL, fds[1], std::ptr::null_mut::<libc::epoll_event>())
+    };
+    assert_eq!(res, 0);
+    assert_eq!(epoll_wait(epfd, 0), vec![]);
+
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_eventfd_edge_triggered() {
+    let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+    assert_ne!(epfd, -1);
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+    assert_ne!(fd, -1);
+
+    epoll_add(epfd, fd, libc::EPOLLIN | libc::EPOLLET, 1);
+    assert_eq!(epoll_wait(epfd, 0), vec![]);
+
+    // A write is reported exactly once.
+    assert_eq!(eventfd_write(fd, 1), 8);
+    let epollin = libc::EPOLLIN as u32;
+    assert_eq!(epoll_wait(epfd, 0), vec![(epollin, 1)]);
+    assert_eq!(epoll_wait(epfd, 0), vec![]);
+
+    // Another write is a new event, even though the counter was not drained in between.
+    assert_eq!(eventfd_write(fd, 1), 8);
+    assert_eq!(epoll_wait(epfd, 0), vec![(epollin, 1)]);
+    assert_eq!(epoll_wait(epfd, 0), vec![]);
+
+    assert_eq!(eventfd_read(fd), Ok(2));
+    assert_eq!(epoll_wait(epfd, 0), vec![]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_oneshot() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+    let fd = unsafe { libc::eventfd(1, libc::EFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+
+    epoll_add(epfd, fd, libc::EPOLLIN | libc::EPOLLONESHOT, 2);
+    let epollin = libc::EPOLLIN as u32;
+    assert_eq!(epoll_wait(epfd, 0), vec![(epollin, 2)]);
+    // The interest is disabled until it is re-armed.
+    assert_eq!(epoll_wait(epfd, 0), vec![]);
+
+    let mut ev = libc::epoll_event { events: (libc::EPOLLIN | libc::EPOLLONESHOT) as u32, u64: 3 };
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut ev) };
+    assert_eq!(res, 0);
+    assert_eq!(epoll_wait(epfd, 0), vec![(epollin, 3)]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_ctl_errors() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+
+    let mut ev = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 0 };
+    // Modifying an unregistered file descriptor.
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut ev) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::ENOENT));
+    // Registering a file descriptor twice.
+    epoll_add(epfd, fd, libc::EPOLLIN, 0);
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut ev) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EEXIST));
+    // Registering a closed file descriptor.
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, 1234, &mut ev) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    // Registering the epoll instance in itself.
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, epfd, &mut ev) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    // Closing a file descriptor drops its registration, so a new file descriptor that reuses its
+    // number starts out unregistered.
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut ev) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::ENOENT));
+    epoll_add(epfd, fd, libc::EPOLLIN, 0);
+    // `maxevents` has to be positive.
+    let res = unsafe { libc::epoll_wait(epfd, &mut ev, 0, 0) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_timeout() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+    epoll_add(epfd, fd, libc::EPOLLIN, 0);
+
+    let start = Instant::now();
+    assert_eq!(epoll_wait(epfd, 100), vec![]);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_block_then_unblock() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+    epoll_add(epfd, fd, libc::EPOLLIN | libc::EPOLLET, 5);
+
+    // The main thread blocks in `epoll_wait` until the other thread writes to the eventfd.
+    let writer = thread::spawn(move || {
+        thread::yield_now();
+        assert_eq!(eventfd_write(fd, 1), 8);
+    });
+    assert_eq!(epoll_wait(epfd, -1), vec![(libc::EPOLLIN as u32, 5)]);
+    writer.join().unwrap();
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
@@ -0,0 +1,240 @@
+//@only-target-linux
+//@compile-flags: -Zmiri-disable-isolation
+
+use std::ffi::{CString, OsStr};
+use std::fs::{create_dir, remove_dir, remove_dir_all, remove_file, rename, File, OpenOptions};
+use std::io::{Read, Write};
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+#[path = "../../utils/mod.rs"]
+mod utils;
+
+fn main() {
+    test_inotify_directory();
+    test_inotify_file();
+    test_inotify_rename();
+    test_inotify_oneshot();
+    test_inotify_errors();
+    test_epoll_inotify();
+}
+
+/// Prepare directory: compute directory name and make sure it exists and is empty.
+fn prepare_dir(dirname: &str) -> PathBuf {
+    let path = utils::tmp().join(dirname);
+    remove_dir_all(&path).ok();
+    create_dir(&path).unwrap();
+    path
+}
+
+fn add_watch(fd: i32, path: &Path, mask: u32) -> i32 {
+    let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    unsafe { libc::inotify_add_watch(fd, path.as_ptr(), mask) }
+}
+
+/// Reads all pending events, as `(wd, mask, cookie, name)`.
+fn read_events(fd: i32) -> Vec<(i32, u32, u32, String)> {
+    let mut buf = [0u8; 1024];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    if res < 0 {
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+        return vec![];
+    }
+    let mut events = Vec::new();
+    let mut buf = &buf[..res as usize];
+    while !buf.is_empty() {
+        let field = |i: usize| u32::from_ne_bytes(buf[4 * i..4 * i + 4].try_into().unwrap());
+        let (wd, mask, cookie, len) = (field(0) as i32, field(1), field(2), field(3) as usize);
+        assert_eq!(len % 16, 0);
+        let name = &buf[16..16 + len];
+        let name = OsStr::from_bytes(name.split(|&b| b == 0).next().unwrap());
+        events.push((wd, mask, cookie, name.to_str().unwrap().to_owned()));
+        buf = &buf[16 + len..];
+    }
+    events
+}
+
+fn test_inotify_directory() {
+    let dir = prepare_dir("miri_test_inotify_directory");
+    let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+    assert_ne!(fd, -1);
+    let wd = add_watch(fd, &dir, libc::IN_ALL_EVENTS);
+    assert!(wd > 0);
+    assert_eq!(read_events(fd), vec![]);
+
+    let path = dir.join("file");
+    let mut file = File::create(&path).unwrap();
+    file.write_all(b"hello").unwrap();
+    drop(file);
+    let mut file = File::open(&path).unwrap();
+    file.read_to_end(&mut Vec::new()).unwrap();
+    drop(file);
+    create_dir(dir.join("subdir")).unwrap();
+    remove_dir(dir.join("subdir")).unwrap();
+    remove_file(&path).unwrap();
+
+    let name = || "file".to_owned();
+    let isdir = libc::IN_ISDIR;
+    assert_eq!(
+        read_events(fd),
+        vec![
+            (wd, libc::IN_CREATE, 0, name()),
+            (wd, libc::IN_OPEN, 0, name()),
+            (wd, libc::IN_MODIFY, 0, name()),
+            (wd, libc::IN_CLOSE_WRITE, 0, name()),
+            (wd, libc::IN_OPEN, 0, name()),
+            (wd, libc::IN_ACCESS, 0, name()),
+            (wd, libc::IN_CLOSE_NOWRITE, 0, name()),
+            (wd, libc::IN_CREATE | isdir, 0, "subdir".to_owned()),
+            (wd, libc::IN_DELETE | isdir, 0, "subdir".to_owned()),
+            (wd, libc::IN_DELETE, 0, name()),
+        ]
+    );
+
+    // Removing the watch reports `IN_IGNORED`.
+    assert_eq!(unsafe { libc::inotify_rm_watch(fd, wd) }, 0);
+    assert_eq!(read_events(fd), vec![(wd, libc::IN_IGNORED, 0, String::new())]);
+    File::create(&path).unwrap();
+    assert_eq!(read_events(fd), vec![]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    remove_dir_all(&dir).unwrap();
+}
+
+fn test_inotify_file() {
+    let dir = prepare_dir("miri_test_inotify_file");
+    let path = dir.join("file");
+    File::create(&path).unwrap();
+    let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK) };
+    assert_ne!(fd, -1);
+    let wd = add_watch(fd, &path, libc::IN_MODIFY | libc::IN_DELETE_SELF);
+
+    // Only the selected events are reported, without a name.
+    let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.write_all(b"hello").unwrap();
+    file.set_len(1).unwrap();
+    drop(file);
+    assert_eq!(read_events(fd), vec![(wd, libc::IN_MODIFY, 0, String::new())]);
+
+    // Deleting a watched file removes the watch.
+    remove_file(&path).unwrap();
+    assert_eq!(
+        read_events(fd),
+        vec![
+            (wd, libc::IN_DELETE_SELF, 0, String::new()),
+            (wd, libc::IN_IGNORED, 0, String::new()),
+        ]
+    );
+    assert_eq!(unsafe { libc::inotify_rm_watch(fd, wd) }, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    remove_dir_all(&dir).unwrap();
+}
+
+fn test_inotify_rename() {
+    let dir = prepare_dir("miri_test_inotify_rename");
+    let from = dir.join("from");
+    let to = dir.join("to");
+    File::create(&from).unwrap();
+    let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK) };
+    assert_ne!(fd, -1);
+    let dir_wd = add_watch(fd, &dir, libc::IN_MOVE);
+    let file_wd = add_watch(fd, &from, libc::IN_MOVE_SELF | libc::IN_OPEN);
+
+    rename(&from, &to).unwrap();
+    let events = read_events(fd);
+    assert_eq!(events.len(), 3);
+    let cookie = events[0].2;
+    assert_ne!(cookie, 0);
+    assert_eq!(
+        events,
+        vec![
+            (dir_wd, libc::IN_MOVED_FROM, cookie, "from".to_owned()),
+            (dir_wd, libc::IN_MOVED_TO, cookie, "to".to_owned()),
+            (file_wd, libc::IN_MOVE_SELF, 0, String::new()),
+        ]
+    );
+
+    // The watch moves along with the file.
+    File::open(&to).unwrap();
+    assert_eq!(read_events(fd), vec![(file_wd, libc::IN_OPEN, 0, String::new())]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    remove_dir_all(&dir).unwrap();
+}
+
+fn test_inotify_oneshot() {
+    let dir = prepare_dir("miri_test_inotify_oneshot");
+    let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK) };
+    assert_ne!(fd, -1);
+    let wd = add_watch(fd, &dir, libc::IN_CREATE | libc::IN_ONESHOT);
+
+    File::create(dir.join("a")).unwrap();
+    File::create(dir.join("b")).unwrap();
+    assert_eq!(
+        read_events(fd),
+        vec![(wd, libc::IN_CREATE, 0, "a".to_owned()), (wd, libc::IN_IGNORED, 0, String::new())]
+    );
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    remove_dir_all(&dir).unwrap();
+}
+
+fn test_inotify_errors() {
+    let dir = prepare_dir("miri_test_inotify_errors");
+    let path = dir.join("file");
+    File::create(&path).unwrap();
+    let fd = unsafe { libc::inotify_init() };
+    assert_ne!(fd, -1);
+
+    let check_error = |res: i32, errno: i32| {
+        assert_eq!(res, -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(errno));
+    };
+    check_error(add_watch(fd, &dir.join("missing"), libc::IN_ALL_EVENTS), libc::ENOENT);
+    check_error(add_watch(fd, &path, libc::IN_ALL_EVENTS | libc::IN_ONLYDIR), libc::ENOTDIR);
+    check_error(add_watch(fd, &path, libc::IN_ONESHOT), libc::EINVAL);
+
+    // Watching the same path again modifies the existing watch.
+    let wd = add_watch(fd, &path, libc::IN_OPEN);
+    assert_eq!(add_watch(fd, &path, libc::IN_ACCESS | libc::IN_MASK_ADD), wd);
+    check_error(add_watch(fd, &path, libc::IN_ACCESS | libc::IN_MASK_CREATE), libc::EEXIST);
+
+    // Reading into a buffer that is too small for the first event fails.
+    File::open(&path).unwrap();
+    let mut buf = [0u8; 8];
+    check_error(unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) } as i32, libc::EINVAL);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    remove_dir_all(&dir).unwrap();
+}
+
+fn test_epoll_inotify() {
+    let dir = prepare_dir("miri_test_epoll_inotify");
+    let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK) };
+    assert_ne!(fd, -1);
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+    let mut ev = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 3 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut ev) }, 0);
+    add_watch(fd, &dir, libc::IN_CREATE);
+
+    let mut events = [MaybeUninit::<libc::epoll_event>::uninit(); 1];
+    let epoll_wait = |events: &mut [MaybeUninit<libc::epoll_event>]| unsafe {
+        libc::epoll_wait(epfd, events.as_mut_ptr().cast(), 1, 0)
+    };
+    assert_eq!(epoll_wait(&mut events), 0);
+    File::create(dir.join("file")).unwrap();
+    assert_eq!(epoll_wait(&mut events), 1);
+    let ev = unsafe { events[0].assume_init() };
+    assert_eq!((ev.events, ev.u64), (libc::EPOLLIN as u32, 3));
+
+    assert_eq!(read_events(fd).len(), 1);
+    assert_eq!(epoll_wait(&mut events), 0);
+
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    remove_dir_all(&dir).unwrap();
+}
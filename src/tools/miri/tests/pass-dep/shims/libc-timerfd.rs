@@ -0,0 +1,209 @@
+//@only-target-linux
+// With isolation, timers run on the virtual clock, so these tests are deterministic.
+
+use std::convert::TryInto;
+use std::mem::MaybeUninit;
+use std::time::{Duration, Instant};
+
+fn main() {
+    test_timerfd_nonblocking();
+    test_timerfd_blocking_read();
+    test_timerfd_periodic();
+    test_timerfd_abstime();
+    test_timerfd_gettime();
+    test_timerfd_errors();
+    test_epoll_timerfd();
+}
+
+fn timespec(duration: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: duration.as_secs().try_into().unwrap(),
+        tv_nsec: duration.subsec_nanos().into(),
+    }
+}
+
+fn settime(fd: i32, flags: i32, value: Duration, interval: Duration) {
+    let new_value = libc::itimerspec { it_interval: timespec(interval), it_value: timespec(value) };
+    let res = unsafe { libc::timerfd_settime(fd, flags, &new_value, std::ptr::null_mut()) };
+    assert_eq!(res, 0);
+}
+
+fn timerfd_read(fd: i32) -> Result<u64, i32> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) };
+    if res < 0 {
+        return Err(std::io::Error::last_os_error().raw_os_error().unwrap());
+    }
+    assert_eq!(res, 8);
+    Ok(u64::from_ne_bytes(buf))
+}
+
+fn test_timerfd_nonblocking() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+
+    // A disarmed timer never expires.
+    assert_eq!(timerfd_read(fd), Err(libc::EAGAIN));
+
+    settime(fd, 0, Duration::from_millis(10), Duration::ZERO);
+    assert_eq!(timerfd_read(fd), Err(libc::EAGAIN));
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(timerfd_read(fd), Ok(1));
+    // A one-shot timer is disarmed after its expiration.
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(timerfd_read(fd), Err(libc::EAGAIN));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_timerfd_blocking_read() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC) };
+    assert_ne!(fd, -1);
+
+    let start = Instant::now();
+    settime(fd, 0, Duration::from_millis(50), Duration::ZERO);
+    assert_eq!(timerfd_read(fd), Ok(1));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_timerfd_periodic() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+
+    settime(fd, 0, Duration::from_millis(10), Duration::from_millis(10));
+    std::thread::sleep(Duration::from_millis(35));
+    // The expirations at 10, 20 and 30ms are counted.
+    assert_eq!(timerfd_read(fd), Ok(3));
+    assert_eq!(timerfd_read(fd), Err(libc::EAGAIN));
+
+    // Setting the timer again discards the expirations that were not read yet.
+    std::thread::sleep(Duration::from_millis(10));
+    settime(fd, 0, Duration::ZERO, Duration::ZERO);
+    assert_eq!(timerfd_read(fd), Err(libc::EAGAIN));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_timerfd_abstime() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+
+    let mut now = MaybeUninit::<libc::timespec>::uninit();
+    assert_eq!(unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, now.as_mut_ptr()) }, 0);
+    let now = unsafe { now.assume_init() };
+    let now = Duration::new(now.tv_sec.try_into().unwrap(), now.tv_nsec.try_into().unwrap());
+
+    // An absolute time in the past expires immediately.
+    settime(fd, libc::TFD_TIMER_ABSTIME, now, Duration::ZERO);
+    assert_eq!(timerfd_read(fd), Ok(1));
+
+    settime(fd, libc::TFD_TIMER_ABSTIME, now + Duration::from_millis(100), Duration::ZERO);
+    assert_eq!(timerfd_read(fd), Err(libc::EAGAIN));
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(timerfd_read(fd), Ok(1));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_timerfd_gettime() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, 0) };
+    assert_ne!(fd, -1);
+
+    let gettime = || {
+        let mut curr_value = MaybeUninit::<libc::itimerspec>::uninit();
+        assert_eq!(unsafe { libc::timerfd_gettime(fd, curr_value.as_mut_ptr()) }, 0);
+        let curr_value = unsafe { curr_value.assume_init() };
+        (curr_value.it_value.tv_sec, curr_value.it_value.tv_nsec, curr_value.it_interval.tv_sec)
+    };
+
+    // A disarmed timer reports zero.
+    assert_eq!(gettime(), (0, 0, 0));
+
+    settime(fd, 0, Duration::from_secs(10), Duration::from_secs(5));
+    let (secs, nsecs, interval) = gettime();
+    assert!(secs < 10 && (secs, nsecs) > (9, 0));
+    assert_eq!(interval, 5);
+
+    // Setting the timer reports the old setting.
+    let new_value = libc::itimerspec {
+        it_interval: timespec(Duration::ZERO),
+        it_value: timespec(Duration::ZERO),
+    };
+    let mut old_value = MaybeUninit::<libc::itimerspec>::uninit();
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &new_value, old_value.as_mut_ptr()) }, 0);
+    let old_value = unsafe { old_value.assume_init() };
+    assert_eq!(old_value.it_interval.tv_sec, 5);
+    assert_eq!(old_value.it_value.tv_sec, secs);
+    assert_eq!(gettime(), (0, 0, 0));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_timerfd_errors() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+
+    // Buffers shorter than 8 bytes are rejected.
+    let mut buf = [0u8; 4];
+    assert_eq!(unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 4) }, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // Nanoseconds must be less than a second.
+    let new_value = libc::itimerspec {
+        it_interval: timespec(Duration::ZERO),
+        it_value: libc::timespec { tv_sec: 0, tv_nsec: 1_000_000_000 },
+    };
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &new_value, std::ptr::null_mut()) }, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    // Only timers can be set.
+    let efd = unsafe { libc::eventfd(0, 0) };
+    assert_ne!(efd, -1);
+    let new_value = libc::itimerspec {
+        it_interval: timespec(Duration::ZERO),
+        it_value: timespec(Duration::from_secs(1)),
+    };
+    assert_eq!(unsafe { libc::timerfd_settime(efd, 0, &new_value, std::ptr::null_mut()) }, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    assert_eq!(unsafe { libc::close(efd) }, 0);
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_epoll_timerfd() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert_ne!(epfd, -1);
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert_ne!(fd, -1);
+    let mut ev = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 7 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut ev) }, 0);
+
+    let epoll_wait = |timeout| {
+        let mut events = [MaybeUninit::<libc::epoll_event>::uninit(); 8];
+        let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr().cast(), 8, timeout) };
+        assert!(res >= 0);
+        events[..res as usize]
+            .iter()
+            .map(|ev| {
+                let ev = unsafe { ev.assume_init() };
+                (ev.events, ev.u64)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // `epoll_wait` wakes up when the timer expires, long before its timeout.
+    let start = Instant::now();
+    settime(fd, 0, Duration::from_millis(20), Duration::ZERO);
+    assert_eq!(epoll_wait(1000), vec![(libc::EPOLLIN as u32, 7)]);
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(20) && elapsed < Duration::from_millis(1000));
+    assert_eq!(timerfd_read(fd), Ok(1));
+
+    // Without an expiration, the timeout still applies.
+    assert_eq!(epoll_wait(10), vec![]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
@@ -0,0 +1,19 @@
+//@compile-flags: -Zmiri-permissive-provenance
+//@only-target-x86_64-unknown-linux: support for tokio only on linux and x86
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+
+    let writer = tokio::spawn(async move {
+        a.write_all(b"hello").await.unwrap();
+    });
+
+    let mut buf = [0; 5];
+    b.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"hello");
+    writer.await.unwrap();
+}
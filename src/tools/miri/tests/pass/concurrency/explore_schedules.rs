@@ -0,0 +1,30 @@
+//@compile-flags: -Zmiri-explore-schedules=1
+//@normalize-stderr-test: "explored all \d+ schedules" -> "explored all $$N schedules"
+
+//! Explore all interleavings of two threads that increment a counter with a compare-exchange loop.
+//! Every interleaving must end up with both increments applied. The output of the program is only
+//! shown for the first schedule.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn increment() {
+    let mut current = COUNTER.load(Ordering::Relaxed);
+    loop {
+        match COUNTER.compare_exchange(current, current + 1, Ordering::AcqRel, Ordering::Relaxed) {
+            Ok(_) => break,
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+fn main() {
+    let handles: Vec<_> = (0..2).map(|_| thread::spawn(increment)).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(COUNTER.load(Ordering::Relaxed), 2);
+    println!("both increments applied");
+}
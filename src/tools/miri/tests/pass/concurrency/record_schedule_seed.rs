@@ -0,0 +1,27 @@
+//@revisions: plain record
+//@ignore-host-windows: records the schedule into `/dev/null`
+//@compile-flags: -Zmiri-seed=7 -Zmiri-preemption-rate=0 -Zmiri-ignore-leaks
+//@[record]compile-flags: -Zmiri-record-schedule=/dev/null
+
+//! Recording a schedule must not change the execution: with the same seed, the recorded run reads
+//! the same outdated values from the store buffer as the plain run, so both revisions print the
+//! same output.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread::spawn;
+
+fn main() {
+    for _ in 0..4 {
+        let x: &'static AtomicUsize = Box::leak(Box::new(AtomicUsize::new(0)));
+        let writer = spawn(move || {
+            x.store(1, Relaxed);
+            x.store(2, Relaxed);
+            x.store(3, Relaxed);
+        });
+        // The reader does not synchronize with the writer, so its loads can read outdated stores.
+        let reader = spawn(move || [x.load(Relaxed), x.load(Relaxed), x.load(Relaxed)]);
+        writer.join().unwrap();
+        println!("{:?}", reader.join().unwrap());
+    }
+}
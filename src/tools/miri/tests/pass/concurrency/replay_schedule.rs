@@ -0,0 +1,26 @@
+//@compile-flags: -Zmiri-replay-schedule=tests/pass/concurrency/replay_schedule.schedule
+
+//! Replay a schedule that runs the second thread before the first one. The default scheduler
+//! always picks the next enabled thread in order, so it would run the first thread first.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn main() {
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let first = {
+        let order = Arc::clone(&order);
+        thread::spawn(move || order.lock().unwrap().push(1))
+    };
+    let second = {
+        let order = Arc::clone(&order);
+        thread::spawn(move || order.lock().unwrap().push(2))
+    };
+
+    // Blocking here makes the scheduler pick the next thread, which the schedule says is `second`.
+    first.join().unwrap();
+    second.join().unwrap();
+
+    assert_eq!(*order.lock().unwrap(), [2, 1]);
+}
@@ -0,0 +1,16 @@
+//@compile-flags: -Zmiri-coverage=-
+
+fn answer(x: bool) -> &'static str {
+    if x {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn _unused() {}
+
+fn main() {
+    answer(true);
+    answer(false);
+}
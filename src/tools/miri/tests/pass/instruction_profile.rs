@@ -0,0 +1,20 @@
+//@compile-flags: -Zmiri-instruction-profile=- -Zmiri-instruction-profile-stacks
+// Only keep the stacks that go through `main`, starting at `main`.
+//@normalize-stdout-test: "(?m)^(.*;)?main\b" -> "@main"
+//@normalize-stdout-test: "(?m)^[^@\n].*\n" -> ""
+//@normalize-stdout-test: "(?m)^@" -> ""
+// The exact counts depend on how the MIR is built.
+//@normalize-stdout-test: " [0-9]+\n" -> " N\n"
+
+fn leaf(x: u32) -> u32 {
+    x + 1
+}
+
+fn twice(x: u32) -> u32 {
+    leaf(leaf(x))
+}
+
+fn main() {
+    let x = twice(1);
+    leaf(x);
+}
@@ -0,0 +1,597 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+avx2
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("avx2"));
+
+    unsafe {
+        test_avx2();
+    }
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn test_avx2() {
+    // Mostly copied from library/stdarch/crates/core_arch/src/x86/avx2.rs
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_abs_epi32() {
+        let a = _mm256_setr_epi32(0, 1, -1, i32::MAX, i32::MIN, 100, -100, -32);
+        let r = _mm256_abs_epi32(a);
+        let e = _mm256_setr_epi32(0, 1, 1, i32::MAX, i32::MIN, 100, 100, 32);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_abs_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_abs_epi16() {
+        #[rustfmt::skip]
+        let a = _mm256_setr_epi16(
+            0, 1, -1, 2, -2, 3, -3, 4, -4, 5, -5, i16::MAX, i16::MIN, 100, -100, -32,
+        );
+        let r = _mm256_abs_epi16(a);
+        let e =
+            _mm256_setr_epi16(0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, i16::MAX, i16::MIN, 100, 100, 32);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_abs_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_abs_epi8() {
+        #[rustfmt::skip]
+        let a = _mm256_setr_epi8(
+            0, 1, -1, 2, -2, 3, -3, 4, -4, 5, -5, i8::MAX, i8::MIN, 100, -100, -32,
+            0, 1, -1, 2, -2, 3, -3, 4, -4, 5, -5, i8::MAX, i8::MIN, 100, -100, -32,
+        );
+        let r = _mm256_abs_epi8(a);
+        #[rustfmt::skip]
+        let e = _mm256_setr_epi8(
+            0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, i8::MAX, i8::MIN, 100, 100, 32,
+            0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, i8::MAX, i8::MIN, 100, 100, 32,
+        );
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_abs_epi8();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_hadd_epi16() {
+        let a = _mm256_setr_epi16(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let b = _mm256_setr_epi16(16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31);
+        let r = _mm256_hadd_epi16(a, b);
+        // Each 128-bit chunk is processed independently.
+        let e = _mm256_setr_epi16(1, 5, 9, 13, 33, 37, 41, 45, 17, 21, 25, 29, 49, 53, 57, 61);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_hsub_epi16(a, b);
+        let e = _mm256_set1_epi16(-1);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_hadd_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_hadds_epi16() {
+        let a = _mm256_set1_epi16(2);
+        let a = _mm256_insert_epi16::<0>(a, 0x7fff);
+        let a = _mm256_insert_epi16::<1>(a, 1);
+        let b = _mm256_set1_epi16(4);
+        let r = _mm256_hadds_epi16(a, b);
+        let e = _mm256_setr_epi16(0x7FFF, 4, 4, 4, 8, 8, 8, 8, 4, 4, 4, 4, 8, 8, 8, 8);
+        assert_eq_m256i(r, e);
+
+        let a = _mm256_set1_epi16(2);
+        let a = _mm256_insert_epi16::<0>(a, i16::MIN);
+        let a = _mm256_insert_epi16::<1>(a, 1);
+        let b = _mm256_set1_epi16(4);
+        let r = _mm256_hsubs_epi16(a, b);
+        let e = _mm256_setr_epi16(i16::MIN, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_hadds_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_hadd_epi32() {
+        let a = _mm256_setr_epi32(1, 2, 3, 4, 5, 6, 7, 8);
+        let b = _mm256_setr_epi32(10, 20, 30, 40, 50, 60, 70, 80);
+        let r = _mm256_hadd_epi32(a, b);
+        let e = _mm256_setr_epi32(3, 7, 30, 70, 11, 15, 110, 150);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_hsub_epi32(a, b);
+        let e = _mm256_setr_epi32(-1, -1, -10, -10, -1, -1, -10, -10);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_hadd_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_i32gather_epi32() {
+        let arr: [i32; 128] = core::array::from_fn(|i| i as i32);
+        // A multiplier of 4 is word-addressing
+        let r = _mm_i32gather_epi32::<4>(arr.as_ptr(), _mm_setr_epi32(0, 16, 32, 48));
+        assert_eq_m128i(r, _mm_setr_epi32(0, 16, 32, 48));
+
+        let r = _mm_mask_i32gather_epi32::<4>(
+            _mm_set1_epi32(256),
+            arr.as_ptr(),
+            _mm_setr_epi32(0, 16, 64, 96),
+            _mm_setr_epi32(-1, -1, -1, 0),
+        );
+        assert_eq_m128i(r, _mm_setr_epi32(0, 16, 64, 256));
+
+        let r =
+            _mm256_i32gather_epi32::<4>(arr.as_ptr(), _mm256_setr_epi32(0, 16, 32, 48, 1, 2, 3, 4));
+        assert_eq_m256i(r, _mm256_setr_epi32(0, 16, 32, 48, 1, 2, 3, 4));
+
+        // Offsets can be negative.
+        let r = _mm_i32gather_epi32::<4>(arr.as_ptr().add(64), _mm_setr_epi32(-64, -1, 0, 63));
+        assert_eq_m128i(r, _mm_setr_epi32(0, 63, 64, 127));
+
+        // 64-bit offsets with 32-bit elements only fill the lower half.
+        let r = _mm_i64gather_epi32::<4>(arr.as_ptr(), _mm_setr_epi64x(0, 16));
+        assert_eq_m128i(r, _mm_setr_epi32(0, 16, 0, 0));
+    }
+    test_mm256_i32gather_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_i64gather_pd() {
+        let arr: [f64; 128] = core::array::from_fn(|i| i as f64);
+        // A multiplier of 8 is word-addressing for f64s
+        let r = _mm256_i64gather_pd::<8>(arr.as_ptr(), _mm256_setr_epi64x(0, 16, 32, 48));
+        assert_eq_m256d(r, _mm256_setr_pd(0.0, 16.0, 32.0, 48.0));
+
+        let r = _mm256_mask_i64gather_pd::<8>(
+            _mm256_set1_pd(256.0),
+            arr.as_ptr(),
+            _mm256_setr_epi64x(0, 16, 64, 96),
+            _mm256_setr_pd(-1.0, -1.0, -1.0, 0.0),
+        );
+        assert_eq_m256d(r, _mm256_setr_pd(0.0, 16.0, 64.0, 256.0));
+    }
+    test_mm256_i64gather_pd();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_madd_epi16() {
+        let a = _mm256_set1_epi16(2);
+        let b = _mm256_set1_epi16(4);
+        let r = _mm256_madd_epi16(a, b);
+        let e = _mm256_set1_epi32(16);
+        assert_eq_m256i(r, e);
+
+        // The sum of the two products overflows.
+        let a = _mm256_set1_epi16(i16::MIN);
+        let r = _mm256_madd_epi16(a, a);
+        let e = _mm256_set1_epi32(i32::MIN);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_madd_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_maddubs_epi16() {
+        let a = _mm256_set1_epi8(2);
+        let b = _mm256_set1_epi8(4);
+        let r = _mm256_maddubs_epi16(a, b);
+        let e = _mm256_set1_epi16(16);
+        assert_eq_m256i(r, e);
+
+        // `a` is unsigned and `b` is signed, the sum saturates.
+        let a = _mm256_set1_epi8(-1);
+        let r = _mm256_maddubs_epi16(a, _mm256_set1_epi8(i8::MAX));
+        assert_eq_m256i(r, _mm256_set1_epi16(i16::MAX));
+        let r = _mm256_maddubs_epi16(a, _mm256_set1_epi8(i8::MIN));
+        assert_eq_m256i(r, _mm256_set1_epi16(i16::MIN));
+    }
+    test_mm256_maddubs_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm_maskload_epi32() {
+        let nums = [1, 2, 3, 4];
+        let a = &nums as *const i32;
+        let mask = _mm_setr_epi32(-1, 0, 0, -1);
+        let r = _mm_maskload_epi32(a, mask);
+        let e = _mm_setr_epi32(1, 0, 0, 4);
+        assert_eq_m128i(r, e);
+
+        let nums = [1, 2, 3, 4, 5, 6, 7, 8];
+        let a = &nums as *const i32;
+        let mask = _mm256_setr_epi32(-1, 0, 0, -1, 0, -1, -1, 0);
+        let r = _mm256_maskload_epi32(a, mask);
+        let e = _mm256_setr_epi32(1, 0, 0, 4, 0, 6, 7, 0);
+        assert_eq_m256i(r, e);
+    }
+    test_mm_maskload_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm_maskload_epi64() {
+        let nums = [1_i64, 2_i64];
+        let a = &nums as *const i64;
+        let mask = _mm_setr_epi64x(0, -1);
+        let r = _mm_maskload_epi64(a, mask);
+        let e = _mm_setr_epi64x(0, 2);
+        assert_eq_m128i(r, e);
+
+        let nums = [1_i64, 2_i64, 3_i64, 4_i64];
+        let a = &nums as *const i64;
+        let mask = _mm256_setr_epi64x(0, -1, -1, 0);
+        let r = _mm256_maskload_epi64(a, mask);
+        let e = _mm256_setr_epi64x(0, 2, 3, 0);
+        assert_eq_m256i(r, e);
+    }
+    test_mm_maskload_epi64();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm_maskstore_epi32() {
+        let a = _mm_setr_epi32(1, 2, 3, 4);
+        let mut arr = [-1, -1, -1, -1];
+        let mask = _mm_setr_epi32(-1, 0, 0, -1);
+        _mm_maskstore_epi32(arr.as_mut_ptr(), mask, a);
+        let e = [1, -1, -1, 4];
+        assert_eq!(arr, e);
+
+        let a = _mm256_setr_epi32(1, 0x6d726f, 3, 42, 0x777161, 6, 7, 8);
+        let mut arr = [-1, -1, -1, 0x776173, -1, 0x68657265, -1, -1];
+        let mask = _mm256_setr_epi32(-1, 0, 0, -1, 0, -1, -1, 0);
+        _mm256_maskstore_epi32(arr.as_mut_ptr(), mask, a);
+        let e = [1, -1, -1, 42, -1, 6, 7, -1];
+        assert_eq!(arr, e);
+    }
+    test_mm_maskstore_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm_maskstore_epi64() {
+        let a = _mm_setr_epi64x(1_i64, 2_i64);
+        let mut arr = [-1_i64, -1_i64];
+        let mask = _mm_setr_epi64x(0, -1);
+        _mm_maskstore_epi64(arr.as_mut_ptr(), mask, a);
+        let e = [-1, 2];
+        assert_eq!(arr, e);
+
+        let a = _mm256_setr_epi64x(1_i64, 2_i64, 3_i64, 4_i64);
+        let mut arr = [-1_i64, -1_i64, -1_i64, -1_i64];
+        let mask = _mm256_setr_epi64x(0, -1, -1, 0);
+        _mm256_maskstore_epi64(arr.as_mut_ptr(), mask, a);
+        let e = [-1, 2, 3, -1];
+        assert_eq!(arr, e);
+    }
+    test_mm_maskstore_epi64();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_mpsadbw_epu8() {
+        let a = transmute::<[u8; 32], __m256i>(core::array::from_fn(|i| i as u8));
+        let b = transmute::<[u8; 32], __m256i>([
+            0, 7, 14, 21, 28, 3, 10, 17, 24, 31, 6, 13, 20, 27, 2, 9, 16, 23, 30, 5, 12, 19, 26, 1,
+            8, 15, 22, 29, 4, 11, 18, 25,
+        ]);
+        // Bits 0..=2 of `imm` are used for the low 128-bit chunk, bits 3..=5
+        // for the high one.
+        let r = _mm256_mpsadbw_epu8::<0b101_110>(a, b);
+        let e = transmute::<[u16; 16], __m256i>([
+            52, 50, 48, 46, 44, 42, 40, 40, 36, 38, 40, 42, 44, 48, 52, 56,
+        ]);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_mpsadbw_epu8();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_mulhrs_epi16() {
+        // 0.5 * 0.25 = 0.125 in Q15 fixed-point.
+        let a = _mm256_set1_epi16(0x4000);
+        let b = _mm256_set1_epi16(0x2000);
+        let r = _mm256_mulhrs_epi16(a, b);
+        let e = _mm256_set1_epi16(0x1000);
+        assert_eq_m256i(r, e);
+
+        // -1.0 * -1.0 overflows.
+        let a = _mm256_set1_epi16(i16::MIN);
+        let r = _mm256_mulhrs_epi16(a, a);
+        let e = _mm256_set1_epi16(i16::MIN);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_mulhrs_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_packs_epi16() {
+        let a = _mm256_setr_epi16(0, 1, -1, 127, 128, -128, -129, 300, -300, 2, 3, 4, 5, 6, 7, 8);
+        let b =
+            _mm256_setr_epi16(9, 10, 11, 12, 13, 14, 15, 16, 1000, -1000, 255, 256, -1, 0, 1, 2);
+
+        let r = _mm256_packs_epi16(a, b);
+        #[rustfmt::skip]
+        let e = transmute::<[i8; 32], __m256i>([
+            0, 1, -1, 127, 127, -128, -128, 127, 9, 10, 11, 12, 13, 14, 15, 16,
+            -128, 2, 3, 4, 5, 6, 7, 8, 127, -128, 127, 127, -1, 0, 1, 2,
+        ]);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_packus_epi16(a, b);
+        #[rustfmt::skip]
+        let e = transmute::<[u8; 32], __m256i>([
+            0, 1, 0, 127, 128, 0, 0, 255, 9, 10, 11, 12, 13, 14, 15, 16,
+            0, 2, 3, 4, 5, 6, 7, 8, 255, 0, 255, 255, 0, 0, 1, 2,
+        ]);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_packs_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_packs_epi32() {
+        let a = _mm256_setr_epi32(0, -1, 70000, -70000, 1, 2, 3, 4);
+        let b = _mm256_setr_epi32(5, 6, 65535, 65536, 32767, 32768, -32768, -32769);
+
+        let r = _mm256_packs_epi32(a, b);
+        let e = transmute::<[i16; 16], __m256i>([
+            0, -1, 32767, -32768, 5, 6, 32767, 32767, 1, 2, 3, 4, 32767, 32767, -32768, -32768,
+        ]);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_packus_epi32(a, b);
+        let e = transmute::<[u16; 16], __m256i>([
+            0, 0, 65535, 0, 5, 6, 65535, 65535, 1, 2, 3, 4, 32767, 32768, 0, 0,
+        ]);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_packs_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_permutevar8x32_epi32() {
+        let a = _mm256_setr_epi32(100, 200, 300, 400, 500, 600, 700, 800);
+        // Only the lowest 3 bits of each index are used.
+        let b = _mm256_setr_epi32(5, 0, 13, 1, 7, 6, 3, -4);
+        let r = _mm256_permutevar8x32_epi32(a, b);
+        let e = _mm256_setr_epi32(600, 100, 600, 200, 800, 700, 400, 500);
+        assert_eq_m256i(r, e);
+
+        let a = _mm256_setr_ps(1., 2., 3., 4., 5., 6., 7., 8.);
+        let r = _mm256_permutevar8x32_ps(a, b);
+        let e = _mm256_setr_ps(6., 1., 6., 2., 8., 7., 4., 5.);
+        assert_eq_m256(r, e);
+    }
+    test_mm256_permutevar8x32_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_permute2x128_si256() {
+        let a = _mm256_setr_epi64x(100, 200, 500, 600);
+        let b = _mm256_setr_epi64x(300, 400, 700, 800);
+        let r = _mm256_permute2x128_si256::<0b00_01_00_11>(a, b);
+        let e = _mm256_setr_epi64x(700, 800, 500, 600);
+        assert_eq_m256i(r, e);
+
+        // Bit 3 zeroes the low chunk, bit 7 zeroes the high chunk.
+        let r = _mm256_permute2x128_si256::<0b0001_1000>(a, b);
+        let e = _mm256_setr_epi64x(0, 0, 500, 600);
+        assert_eq_m256i(r, e);
+        let r = _mm256_permute2x128_si256::<0b1000_0010>(a, b);
+        let e = _mm256_setr_epi64x(300, 400, 0, 0);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_permute2x128_si256();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_sad_epu8() {
+        let a = transmute::<[u8; 32], __m256i>(core::array::from_fn(|i| i as u8));
+        let b = transmute::<[u8; 32], __m256i>(core::array::from_fn(|i| 31 - i as u8));
+        let r = _mm256_sad_epu8(a, b);
+        let e = _mm256_setr_epi64x(192, 64, 64, 192);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_sad_epu8();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_shuffle_epi8() {
+        let a = transmute::<[u8; 32], __m256i>(core::array::from_fn(|i| i as u8 + 1));
+        #[rustfmt::skip]
+        let b = transmute::<[u8; 32], __m256i>([
+            4, 128, 4, 3, 24, 12, 6, 19, 12, 5, 5, 10, 4, 1, 8, 0,
+            4, 128, 4, 3, 24, 12, 6, 19, 12, 5, 5, 10, 4, 1, 8, 0,
+        ]);
+        // Each 128-bit chunk is shuffled independently.
+        let r = _mm256_shuffle_epi8(a, b);
+        #[rustfmt::skip]
+        let e = transmute::<[u8; 32], __m256i>([
+            5, 0, 5, 4, 9, 13, 7, 4, 13, 6, 6, 11, 5, 2, 9, 1,
+            21, 0, 21, 20, 25, 29, 23, 20, 29, 22, 22, 27, 21, 18, 25, 17,
+        ]);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_shuffle_epi8();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_sign_epi16() {
+        let a = _mm256_set1_epi16(2);
+        let b = _mm256_setr_epi16(-1, 0, 1, -1, 0, 1, -1, 0, 1, -1, 0, 1, -1, 0, 1, i16::MIN);
+        let r = _mm256_sign_epi16(a, b);
+        let e = _mm256_setr_epi16(-2, 0, 2, -2, 0, 2, -2, 0, 2, -2, 0, 2, -2, 0, 2, -2);
+        assert_eq_m256i(r, e);
+
+        let a = _mm256_setr_epi32(1, 2, 3, 4, 5, 6, 7, i32::MIN);
+        let b = _mm256_setr_epi32(-1, 0, 1, -1, 0, 1, 100, -1);
+        let r = _mm256_sign_epi32(a, b);
+        let e = _mm256_setr_epi32(-1, 0, 3, -4, 0, 6, 7, i32::MIN);
+        assert_eq_m256i(r, e);
+
+        let a = _mm256_set1_epi8(5);
+        let b = _mm256_set1_epi8(-7);
+        let r = _mm256_sign_epi8(a, b);
+        let e = _mm256_set1_epi8(-5);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_sign_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_sll_epi16() {
+        #[rustfmt::skip]
+        let a = _mm256_setr_epi16(
+            0xCC, -0xCC, 0xFF, -0xFF, i16::MAX, i16::MIN, 1, -1,
+            0xCC, -0xCC, 0xFF, -0xFF, i16::MAX, i16::MIN, 1, -1,
+        );
+        let r = _mm256_sll_epi16(a, _mm_set_epi64x(0, 4));
+        #[rustfmt::skip]
+        let e = _mm256_setr_epi16(
+            0xCC0, -0xCC0, 0xFF0, -0xFF0, -0x10, 0, 0x10, -0x10,
+            0xCC0, -0xCC0, 0xFF0, -0xFF0, -0x10, 0, 0x10, -0x10,
+        );
+        assert_eq_m256i(r, e);
+        let r = _mm256_srl_epi16(a, _mm_set_epi64x(0, 4));
+        #[rustfmt::skip]
+        let e = _mm256_setr_epi16(
+            0xC, 0xFF3, 0xF, 0xFF0, 0x7FF, 0x800, 0, 0xFFF,
+            0xC, 0xFF3, 0xF, 0xFF0, 0x7FF, 0x800, 0, 0xFFF,
+        );
+        assert_eq_m256i(r, e);
+        let r = _mm256_sra_epi16(a, _mm_set_epi64x(0, 4));
+        #[rustfmt::skip]
+        let e = _mm256_setr_epi16(
+            0xC, -0xD, 0xF, -0x10, 0x7FF, -0x800, 0, -1,
+            0xC, -0xD, 0xF, -0x10, 0x7FF, -0x800, 0, -1,
+        );
+        assert_eq_m256i(r, e);
+
+        // Only the low 64 bits of the count are used, and counts larger
+        // than 15 shift out all bits.
+        let r = _mm256_sll_epi16(a, _mm_set_epi64x(4, 16));
+        assert_eq_m256i(r, _mm256_setzero_si256());
+        let r = _mm256_srl_epi16(a, _mm_set_epi64x(4, i64::MAX));
+        assert_eq_m256i(r, _mm256_setzero_si256());
+        let r = _mm256_sra_epi16(a, _mm_set_epi64x(4, 16));
+        let e = _mm256_setr_epi16(0, -1, 0, -1, 0, -1, 0, -1, 0, -1, 0, -1, 0, -1, 0, -1);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_sll_epi16();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_sll_epi32() {
+        let a = _mm256_setr_epi32(0xCC, -0xCC, 0xFF, -0xFF, i32::MAX, i32::MIN, 1, -1);
+        let r = _mm256_sll_epi32(a, _mm_set_epi64x(0, 4));
+        let e = _mm256_setr_epi32(0xCC0, -0xCC0, 0xFF0, -0xFF0, -0x10, 0, 0x10, -0x10);
+        assert_eq_m256i(r, e);
+        let r = _mm256_srl_epi32(a, _mm_set_epi64x(0, 4));
+        let e =
+            _mm256_setr_epi32(0xC, 0xFFFFFF3, 0xF, 0xFFFFFF0, 0x7FFFFFF, 0x8000000, 0, 0xFFFFFFF);
+        assert_eq_m256i(r, e);
+        let r = _mm256_sra_epi32(a, _mm_set_epi64x(0, 4));
+        let e = _mm256_setr_epi32(0xC, -0xD, 0xF, -0x10, 0x7FFFFFF, -0x8000000, 0, -1);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_sll_epi32(a, _mm_set_epi64x(0, 32));
+        assert_eq_m256i(r, _mm256_setzero_si256());
+        let r = _mm256_srl_epi32(a, _mm_set_epi64x(0, 32));
+        assert_eq_m256i(r, _mm256_setzero_si256());
+        let r = _mm256_sra_epi32(a, _mm_set_epi64x(0, 32));
+        let e = _mm256_setr_epi32(0, -1, 0, -1, 0, -1, 0, -1);
+        assert_eq_m256i(r, e);
+    }
+    test_mm256_sll_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_sll_epi64() {
+        let a = _mm256_setr_epi64x(0xCC, -0xCC, i64::MAX, i64::MIN);
+        let r = _mm256_sll_epi64(a, _mm_set_epi64x(0, 4));
+        let e = _mm256_setr_epi64x(0xCC0, -0xCC0, -0x10, 0);
+        assert_eq_m256i(r, e);
+        let r = _mm256_srl_epi64(a, _mm_set_epi64x(0, 4));
+        let e = _mm256_setr_epi64x(0xC, 0xFFFFFFFFFFFFFF3, 0x7FFFFFFFFFFFFFF, 0x800000000000000);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_sll_epi64(a, _mm_set_epi64x(0, 64));
+        assert_eq_m256i(r, _mm256_setzero_si256());
+        let r = _mm256_srl_epi64(a, _mm_set_epi64x(0, 64));
+        assert_eq_m256i(r, _mm256_setzero_si256());
+    }
+    test_mm256_sll_epi64();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_sllv_epi32() {
+        let a = _mm256_setr_epi32(1, -1, 0xCC, -0xCC, i32::MAX, i32::MIN, 2, -2);
+        let count = _mm256_setr_epi32(0, 1, 4, 8, 31, 32, 100, -1);
+
+        let r = _mm256_sllv_epi32(a, count);
+        let e = _mm256_setr_epi32(1, -2, 0xCC0, -0xCC00, i32::MIN, 0, 0, 0);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_srlv_epi32(a, count);
+        let e = _mm256_setr_epi32(1, i32::MAX, 0xC, 0xFFFFFF, 0, 0, 0, 0);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_srav_epi32(a, count);
+        let e = _mm256_setr_epi32(1, -1, 0xC, -1, 0, -1, 0, -1);
+        assert_eq_m256i(r, e);
+
+        let a = _mm_setr_epi32(1, -1, 0xCC, -0xCC);
+        let count = _mm_setr_epi32(0, 1, 4, 32);
+        let r = _mm_sllv_epi32(a, count);
+        let e = _mm_setr_epi32(1, -2, 0xCC0, 0);
+        assert_eq_m128i(r, e);
+        let r = _mm_srlv_epi32(a, count);
+        let e = _mm_setr_epi32(1, i32::MAX, 0xC, 0);
+        assert_eq_m128i(r, e);
+        let r = _mm_srav_epi32(a, count);
+        let e = _mm_setr_epi32(1, -1, 0xC, -1);
+        assert_eq_m128i(r, e);
+    }
+    test_mm256_sllv_epi32();
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn test_mm256_sllv_epi64() {
+        let a = _mm256_setr_epi64x(1, -1, 0xCC, i64::MIN);
+        let count = _mm256_setr_epi64x(0, 1, 4, 64);
+
+        let r = _mm256_sllv_epi64(a, count);
+        let e = _mm256_setr_epi64x(1, -2, 0xCC0, 0);
+        assert_eq_m256i(r, e);
+
+        let r = _mm256_srlv_epi64(a, count);
+        let e = _mm256_setr_epi64x(1, i64::MAX, 0xC, 0);
+        assert_eq_m256i(r, e);
+
+        let a = _mm_setr_epi64x(-1, 0xCC);
+        let count = _mm_setr_epi64x(63, -1);
+        let r = _mm_sllv_epi64(a, count);
+        let e = _mm_setr_epi64x(i64::MIN, 0);
+        assert_eq_m128i(r, e);
+        let r = _mm_srlv_epi64(a, count);
+        let e = _mm_setr_epi64x(1, 0);
+        assert_eq_m128i(r, e);
+    }
+    test_mm256_sllv_epi64();
+}
+
+#[track_caller]
+#[target_feature(enable = "sse2")]
+unsafe fn assert_eq_m128i(a: __m128i, b: __m128i) {
+    assert_eq!(transmute::<_, [u64; 2]>(a), transmute::<_, [u64; 2]>(b))
+}
+
+#[track_caller]
+#[target_feature(enable = "avx")]
+unsafe fn assert_eq_m256(a: __m256, b: __m256) {
+    let cmp = _mm256_cmp_ps::<_CMP_EQ_OQ>(a, b);
+    if _mm256_movemask_ps(cmp) != 0b11111111 {
+        panic!("{:?} != {:?}", a, b);
+    }
+}
+
+#[track_caller]
+#[target_feature(enable = "avx")]
+unsafe fn assert_eq_m256d(a: __m256d, b: __m256d) {
+    let cmp = _mm256_cmp_pd::<_CMP_EQ_OQ>(a, b);
+    if _mm256_movemask_pd(cmp) != 0b1111 {
+        panic!("{:?} != {:?}", a, b);
+    }
+}
+
+#[track_caller]
+#[target_feature(enable = "avx")]
+unsafe fn assert_eq_m256i(a: __m256i, b: __m256i) {
+    assert_eq!(transmute::<_, [u64; 4]>(a), transmute::<_, [u64; 4]>(b))
+}
@@ -0,0 +1,124 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+bmi1,+bmi2,+lzcnt,+popcnt
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+fn main() {
+    assert!(is_x86_feature_detected!("bmi1"));
+    assert!(is_x86_feature_detected!("bmi2"));
+    assert!(is_x86_feature_detected!("lzcnt"));
+    assert!(is_x86_feature_detected!("popcnt"));
+
+    unsafe {
+        test_bmi_32();
+        #[cfg(target_arch = "x86_64")]
+        test_bmi_64();
+        test_lzcnt_popcnt();
+    }
+}
+
+/// Test the 32-bit variants of the intrinsics.
+#[target_feature(enable = "bmi1,bmi2")]
+unsafe fn test_bmi_32() {
+    // Mostly copied from library/stdarch/crates/core_arch/src/x86/bmi1.rs
+    // and library/stdarch/crates/core_arch/src/x86/bmi2.rs
+
+    // bextr: extract `len` bits starting at bit `start`.
+    assert_eq!(_bextr_u32(0b0101_0000, 4, 4), 0b0000_0101);
+    assert_eq!(_bextr_u32(0b0101_0000, 4, 3), 0b0000_0101);
+    assert_eq!(_bextr_u32(0b0101_0000, 5, 3), 0b0000_0010);
+    // A length that is larger than the operand keeps all remaining bits.
+    assert_eq!(_bextr_u32(0xFFFF_FFFF, 4, 200), 0x0FFF_FFFF);
+    // A start that is larger than the operand produces zero.
+    assert_eq!(_bextr_u32(0xFFFF_FFFF, 32, 4), 0);
+    assert_eq!(_bextr2_u32(0xFFFF_FFFF, 0), 0);
+
+    // bzhi: zero the high bits starting at the index.
+    assert_eq!(_bzhi_u32(0b1111_0010, 5), 0b0001_0010);
+    assert_eq!(_bzhi_u32(0xFFFF_FFFF, 0), 0);
+    // An index that is larger than the operand keeps all the bits.
+    assert_eq!(_bzhi_u32(0xFFFF_FFFF, 32), 0xFFFF_FFFF);
+    assert_eq!(_bzhi_u32(0xFFFF_FFFF, 200), 0xFFFF_FFFF);
+    // Only the lowest 8 bits of the index are used.
+    assert_eq!(_bzhi_u32(0xFFFF_FFFF, 0x105), 0b1_1111);
+
+    // pdep: deposit the low bits of `a` at the positions of the set bits of the mask.
+    let n = 0b1011_1110_1001_0011u32;
+    let m0 = 0b0110_0011_1000_0101u32;
+    let s0 = 0b0000_0010_0000_0101u32;
+    let m1 = 0b1110_1011_1110_1111u32;
+    let s1 = 0b1110_1001_0010_0011u32;
+    assert_eq!(_pdep_u32(n, m0), s0);
+    assert_eq!(_pdep_u32(n, m1), s1);
+    assert_eq!(_pdep_u32(u32::MAX, u32::MAX), u32::MAX);
+    assert_eq!(_pdep_u32(n, 0), 0);
+
+    // pext: extract the bits of `a` at the positions of the set bits of the mask.
+    let m0 = 0b0110_0011_1000_0101u32;
+    let s0 = 0b0000_0000_0011_0101u32;
+    let m1 = 0b1110_1011_1110_1111u32;
+    let s1 = 0b0001_0111_0100_0011u32;
+    assert_eq!(_pext_u32(n, m0), s0);
+    assert_eq!(_pext_u32(n, m1), s1);
+    assert_eq!(_pext_u32(u32::MAX, u32::MAX), u32::MAX);
+    assert_eq!(_pext_u32(n, 0), 0);
+
+    // These are implemented with portable operations, but make sure they work too.
+    assert_eq!(_andn_u32(0b0000_1111, 0b0101_0101), 0b0101_0000);
+    assert_eq!(_blsi_u32(0b1101_0000), 0b0001_0000);
+    assert_eq!(_blsmsk_u32(0b0011_0000), 0b0001_1111);
+    assert_eq!(_blsr_u32(0b0011_0000), 0b0010_0000);
+    assert_eq!(_tzcnt_u32(0b0000_0001), 0);
+    assert_eq!(_tzcnt_u32(0b1000_0000), 7);
+    assert_eq!(_tzcnt_u32(0), 32);
+    let mut hi = 0;
+    let lo = _mulx_u32(u32::MAX, u32::MAX, &mut hi);
+    assert_eq!((lo, hi), (1, u32::MAX - 1));
+}
+
+/// Test the 64-bit variants of the intrinsics.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi1,bmi2")]
+unsafe fn test_bmi_64() {
+    assert_eq!(_bextr_u64(0b0101_0000, 4, 4), 0b0000_0101);
+    assert_eq!(_bextr_u64(u64::MAX, 60, 8), 0xF);
+    assert_eq!(_bextr_u64(u64::MAX, 4, 200), u64::MAX >> 4);
+    assert_eq!(_bextr_u64(u64::MAX, 64, 4), 0);
+
+    assert_eq!(_bzhi_u64(0b1111_0010, 5), 0b0001_0010);
+    assert_eq!(_bzhi_u64(u64::MAX, 40), (1 << 40) - 1);
+    assert_eq!(_bzhi_u64(u64::MAX, 64), u64::MAX);
+
+    let n = 0xDEAD_BEEF_1234_5678u64;
+    let m = 0xFF00_FF00_FF00_FF00u64;
+    assert_eq!(_pdep_u64(n, m), 0x1200_3400_5600_7800);
+    assert_eq!(_pext_u64(n, m), 0xDEBE_1256);
+    assert_eq!(_pdep_u64(u64::MAX, u64::MAX), u64::MAX);
+    assert_eq!(_pext_u64(u64::MAX, u64::MAX), u64::MAX);
+}
+
+#[target_feature(enable = "lzcnt,popcnt")]
+unsafe fn test_lzcnt_popcnt() {
+    assert_eq!(_lzcnt_u32(0b0101_1010), 25);
+    assert_eq!(_lzcnt_u32(0), 32);
+    assert_eq!(_popcnt32(0b0101_1010), 4);
+    assert_eq!(_popcnt32(-1), 32);
+    #[cfg(target_arch = "x86_64")]
+    {
+        assert_eq!(_lzcnt_u64(0b0101_1010), 57);
+        assert_eq!(_lzcnt_u64(0), 64);
+        assert_eq!(_popcnt64(0b0101_1010), 4);
+        assert_eq!(_popcnt64(-1), 64);
+    }
+}
@@ -0,0 +1,146 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+fma
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+fn main() {
+    assert!(is_x86_feature_detected!("fma"));
+
+    unsafe {
+        test_fma();
+    }
+}
+
+#[target_feature(enable = "fma")]
+unsafe fn test_fma() {
+    // Mostly copied from library/stdarch/crates/core_arch/src/x86/fma.rs
+
+    #[target_feature(enable = "fma")]
+    unsafe fn test_mm_fmadd_ps() {
+        let a = _mm_setr_ps(1., 2., 3., 4.);
+        let b = _mm_setr_ps(5., 3., 7., 2.);
+        let c = _mm_setr_ps(4., 9., 1., 7.);
+        let r = _mm_setr_ps(9., 15., 22., 15.);
+        assert_eq_m128(_mm_fmadd_ps(a, b, c), r);
+
+        let r = _mm_setr_ps(1., -3., 20., 1.);
+        assert_eq_m128(_mm_fmsub_ps(a, b, c), r);
+    }
+    test_mm_fmadd_ps();
+
+    #[target_feature(enable = "fma")]
+    unsafe fn test_mm_fmaddsub_ps() {
+        let a = _mm_setr_ps(1., 2., 3., 4.);
+        let b = _mm_setr_ps(5., 6., 7., 8.);
+        let c = _mm_setr_ps(4., 3., 2., 1.);
+        // Even elements are subtracted, odd elements are added.
+        let r = _mm_setr_ps(1., 15., 19., 33.);
+        assert_eq_m128(_mm_fmaddsub_ps(a, b, c), r);
+        // Even elements are added, odd elements are subtracted.
+        let r = _mm_setr_ps(9., 9., 23., 31.);
+        assert_eq_m128(_mm_fmsubadd_ps(a, b, c), r);
+    }
+    test_mm_fmaddsub_ps();
+
+    #[target_feature(enable = "fma")]
+    unsafe fn test_mm_fmaddsub_pd() {
+        let a = _mm_setr_pd(1., 2.);
+        let b = _mm_setr_pd(5., 6.);
+        let c = _mm_setr_pd(4., 3.);
+        let r = _mm_setr_pd(1., 15.);
+        assert_eq_m128d(_mm_fmaddsub_pd(a, b, c), r);
+        let r = _mm_setr_pd(9., 9.);
+        assert_eq_m128d(_mm_fmsubadd_pd(a, b, c), r);
+    }
+    test_mm_fmaddsub_pd();
+
+    #[target_feature(enable = "fma")]
+    unsafe fn test_mm256_fmaddsub_ps() {
+        let a = _mm256_setr_ps(1., 2., 3., 4., 5., 6., 7., 8.);
+        let b = _mm256_setr_ps(5., 6., 7., 8., 9., 10., 11., 12.);
+        let c = _mm256_setr_ps(4., 3., 2., 1., 4., 3., 2., 1.);
+        let r = _mm256_setr_ps(1., 15., 19., 33., 41., 63., 75., 97.);
+        assert_eq_m256(_mm256_fmaddsub_ps(a, b, c), r);
+        let r = _mm256_setr_ps(9., 9., 23., 31., 49., 57., 79., 95.);
+        assert_eq_m256(_mm256_fmsubadd_ps(a, b, c), r);
+    }
+    test_mm256_fmaddsub_ps();
+
+    #[target_feature(enable = "fma")]
+    unsafe fn test_mm256_fmaddsub_pd() {
+        let a = _mm256_setr_pd(1., 2., 3., 4.);
+        let b = _mm256_setr_pd(5., 6., 7., 8.);
+        let c = _mm256_setr_pd(4., 3., 2., 1.);
+        let r = _mm256_setr_pd(1., 15., 19., 33.);
+        assert_eq_m256d(_mm256_fmaddsub_pd(a, b, c), r);
+        let r = _mm256_setr_pd(9., 9., 23., 31.);
+        assert_eq_m256d(_mm256_fmsubadd_pd(a, b, c), r);
+    }
+    test_mm256_fmaddsub_pd();
+
+    #[target_feature(enable = "fma")]
+    unsafe fn test_fused() {
+        // `a * b` is `1 - EPSILON^2`, which is not representable and would be
+        // rounded to `1` before the addition if the operation was not fused.
+        let a = _mm_set1_ps(1. + f32::EPSILON);
+        let b = _mm_set1_ps(1. - f32::EPSILON);
+        let c = _mm_set1_ps(1.);
+        let tiny = f32::EPSILON * f32::EPSILON;
+        let r = _mm_setr_ps(-tiny, 2., -tiny, 2.);
+        assert_eq_m128(_mm_fmaddsub_ps(a, b, c), r);
+
+        let a = _mm_set1_pd(1. + f64::EPSILON);
+        let b = _mm_set1_pd(1. - f64::EPSILON);
+        let c = _mm_set1_pd(1.);
+        let tiny = f64::EPSILON * f64::EPSILON;
+        let r = _mm_setr_pd(2., -tiny);
+        assert_eq_m128d(_mm_fmsubadd_pd(a, b, c), r);
+    }
+    test_fused();
+}
+
+#[track_caller]
+#[target_feature(enable = "sse")]
+unsafe fn assert_eq_m128(a: __m128, b: __m128) {
+    let r = _mm_cmpeq_ps(a, b);
+    if _mm_movemask_ps(r) != 0b1111 {
+        panic!("{:?} != {:?}", a, b);
+    }
+}
+
+#[track_caller]
+#[target_feature(enable = "sse2")]
+unsafe fn assert_eq_m128d(a: __m128d, b: __m128d) {
+    if _mm_movemask_pd(_mm_cmpeq_pd(a, b)) != 0b11 {
+        panic!("{:?} != {:?}", a, b);
+    }
+}
+
+#[track_caller]
+#[target_feature(enable = "avx")]
+unsafe fn assert_eq_m256(a: __m256, b: __m256) {
+    let cmp = _mm256_cmp_ps::<_CMP_EQ_OQ>(a, b);
+    if _mm256_movemask_ps(cmp) != 0b11111111 {
+        panic!("{:?} != {:?}", a, b);
+    }
+}
+
+#[track_caller]
+#[target_feature(enable = "avx")]
+unsafe fn assert_eq_m256d(a: __m256d, b: __m256d) {
+    let cmp = _mm256_cmp_pd::<_CMP_EQ_OQ>(a, b);
+    if _mm256_movemask_pd(cmp) != 0b1111 {
+        panic!("{:?} != {:?}", a, b);
+    }
+}
@@ -0,0 +1,62 @@
+// Ignore everything except x86 and x86_64
+// Any new targets that are added to CI should be ignored here.
+// (We cannot use `cfg`-based tricks here since the `target-feature` flags below only work on x86.)
+//@ignore-target-aarch64
+//@ignore-target-arm
+//@ignore-target-avr
+//@ignore-target-s390x
+//@ignore-target-thumbv7em
+//@ignore-target-wasm32
+//@compile-flags: -C target-feature=+pclmulqdq
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("pclmulqdq"));
+
+    let a = (0x7fffffffffffffff, 0x4317e40ab4ddcf05);
+    let b = (0xdd358416f52ecd34, 0x633d11cc638ca16b);
+
+    unsafe {
+        // Bit 0 of `imm` selects the element of `a`, bit 4 the element of `b`.
+        let r = clmulepi64_si128::<0x00>(a, b);
+        assert_eq!(r, (13036940098130298092, 2704901987789626761));
+
+        let r = clmulepi64_si128::<0x01>(a, b);
+        assert_eq!(r, (6707488474444649956, 3901733953304450635));
+
+        let r = clmulepi64_si128::<0x10>(a, b);
+        assert_eq!(r, (11607166829323378905, 1191897396234301548));
+
+        let r = clmulepi64_si128::<0x11>(a, b);
+        assert_eq!(r, (7731954893213347271, 1760130762532070957));
+
+        // The other bits of `imm` are ignored.
+        let r = clmulepi64_si128::<0xee>(a, b);
+        assert_eq!(r, (13036940098130298092, 2704901987789626761));
+
+        // Squaring in GF(2) spreads out the bits.
+        let r = clmulepi64_si128::<0x00>((u64::MAX, 0), (u64::MAX, 0));
+        assert_eq!(r, (0x5555555555555555, 0x5555555555555555));
+    }
+}
+
+#[target_feature(enable = "pclmulqdq")]
+unsafe fn clmulepi64_si128<const IMM8: i32>(
+    (a1, a2): (u64, u64),
+    (b1, b2): (u64, u64),
+) -> (u64, u64) {
+    // SAFETY: There are no safety requirements for calling `_mm_clmulepi64_si128`.
+    // It's just unsafe for API consistency with other intrinsics.
+    let a = transmute::<[u64; 2], __m128i>([a1, a2]);
+    let b = transmute::<[u64; 2], __m128i>([b1, b2]);
+
+    let r = _mm_clmulepi64_si128::<IMM8>(a, b);
+
+    let [r1, r2] = transmute::<__m128i, [u64; 2]>(r);
+    (r1, r2)
+}
@@ -1,4 +1,4 @@
-//@ignore-target-windows: File handling is not implemented yet
+//@ignore-target-windows: uses file system APIs the Windows shims do not support, see windows-fs.rs
 //@compile-flags: -Zmiri-disable-isolation
 
 #![feature(io_error_more)]
@@ -0,0 +1,158 @@
+//@only-target-linux: the socket emulation is only tested on Linux
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::thread;
+
+fn main() {
+    test_tcp_echo();
+    test_tcp_many_clients();
+    test_tcp_shutdown();
+    test_tcp_nonblocking();
+    test_tcp_errors();
+    test_udp();
+    test_udp_connected();
+}
+
+fn localhost(port: u16) -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::LOCALHOST, port))
+}
+
+fn test_tcp_echo() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert!(addr.port() != 0);
+
+    let server = thread::spawn(move || {
+        let (mut stream, peer) = listener.accept().unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), peer);
+        assert_eq!(stream.local_addr().unwrap(), addr);
+        let mut buf = [0; 5];
+        stream.read_exact(&mut buf).unwrap();
+        stream.write_all(&buf).unwrap();
+    });
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.set_nodelay(true).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    stream.write_all(b"hello").unwrap();
+    let mut buf = Vec::new();
+    // The server closing the connection ends the stream.
+    stream.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"hello");
+    server.join().unwrap();
+}
+
+fn test_tcp_many_clients() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let clients: Vec<_> = (0..3u8)
+        .map(|i| {
+            thread::spawn(move || {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                stream.write_all(&[i]).unwrap();
+            })
+        })
+        .collect();
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).unwrap();
+        received.extend(buf);
+    }
+    received.sort();
+    assert_eq!(received, [0, 1, 2]);
+    for client in clients {
+        client.join().unwrap();
+    }
+}
+
+fn test_tcp_shutdown() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    client.write_all(b"request").unwrap();
+    client.shutdown(Shutdown::Write).unwrap();
+    assert_eq!(client.write(b"more").unwrap_err().kind(), ErrorKind::BrokenPipe);
+
+    // The server sees EOF after the request, but can still answer.
+    let mut request = String::new();
+    server.read_to_string(&mut request).unwrap();
+    assert_eq!(request, "request");
+    server.write_all(b"response").unwrap();
+    drop(server);
+
+    let mut response = String::new();
+    client.read_to_string(&mut response).unwrap();
+    assert_eq!(response, "response");
+}
+
+fn test_tcp_nonblocking() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    server.set_nonblocking(true).unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(server.read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+    client.write_all(b"ping").unwrap();
+    assert_eq!(server.read(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"ping");
+}
+
+fn test_tcp_errors() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    assert_eq!(TcpListener::bind(localhost(port)).unwrap_err().kind(), ErrorKind::AddrInUse);
+    drop(listener);
+    // Once the listener is gone, its port is free again, and nobody accepts connections on it.
+    assert_eq!(
+        TcpStream::connect(localhost(port)).unwrap_err().kind(),
+        ErrorKind::ConnectionRefused
+    );
+    TcpListener::bind(localhost(port)).unwrap();
+}
+
+fn test_udp() {
+    let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b_addr = b.local_addr().unwrap();
+
+    let sender = thread::spawn(move || {
+        a.send_to(b"first", b_addr).unwrap();
+        a.send_to(b"second", b_addr).unwrap();
+        a.local_addr().unwrap()
+    });
+
+    // Datagrams keep their boundaries, and the part that does not fit is discarded.
+    let mut buf = [0; 3];
+    let (len, from) = b.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"fir");
+    let mut buf = [0; 16];
+    let (len, from2) = b.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"second");
+    assert_eq!(from, from2);
+    assert_eq!(from, sender.join().unwrap());
+}
+
+fn test_udp_connected() {
+    let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+    a.connect(b.local_addr().unwrap()).unwrap();
+    b.connect(a.local_addr().unwrap()).unwrap();
+    assert_eq!(a.peer_addr().unwrap(), b.local_addr().unwrap());
+
+    a.send(b"ping").unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(b.recv(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"ping");
+
+    b.set_nonblocking(true).unwrap();
+    assert_eq!(b.recv(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
@@ -0,0 +1,274 @@
+//@only-target-windows: this tests the Windows file system shims
+//@compile-flags: -Zmiri-disable-isolation
+
+#![feature(io_error_more)]
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{create_dir, read_dir, remove_dir, remove_file, rename, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+#[path = "../../utils/mod.rs"]
+mod utils;
+
+fn main() {
+    test_file();
+    test_file_create_new();
+    test_seek();
+    test_metadata();
+    test_file_set_len();
+    test_file_sync();
+    test_errors();
+    test_rename();
+    test_directory();
+}
+
+/// Prepare: compute filename and make sure the file does not exist.
+fn prepare(filename: &str) -> PathBuf {
+    let path = utils::tmp().join(filename);
+    // Clean the paths for robustness.
+    remove_file(&path).ok();
+    path
+}
+
+/// Prepare like above, and also write some initial content to the file.
+fn prepare_with_content(filename: &str, content: &[u8]) -> PathBuf {
+    let path = prepare(filename);
+    let mut file = File::create(&path).unwrap();
+    file.write_all(content).unwrap();
+    path
+}
+
+fn test_file() {
+    let bytes = b"Hello, World!\n";
+    let path = prepare("miri_test_windows_fs_file.txt");
+
+    // Test creating, writing and closing a file (closing is tested when `file` is dropped).
+    let mut file = File::create(&path).unwrap();
+    // Writing 0 bytes should not change the file contents.
+    file.write(&mut []).unwrap();
+    assert_eq!(file.metadata().unwrap().len(), 0);
+
+    file.write_all(bytes).unwrap();
+    assert_eq!(file.metadata().unwrap().len(), bytes.len() as u64);
+    // Test opening, reading and closing a file.
+    let mut file = File::open(&path).unwrap();
+    let mut contents = Vec::new();
+    // Reading 0 bytes should not move the file pointer.
+    file.read(&mut []).unwrap();
+    // Reading until EOF should get the whole text.
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(bytes, contents.as_slice());
+    // Reading a file that was opened for writing only should fail.
+    let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+    assert!(file.read(&mut [0; 4]).is_err());
+
+    // Clean up.
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_file_create_new() {
+    let path = prepare("miri_test_windows_fs_file_create_new.txt");
+
+    // Creating a new file that doesn't yet exist should succeed.
+    OpenOptions::new().write(true).create_new(true).open(&path).unwrap();
+    // Creating a new file that already exists should fail.
+    assert_eq!(
+        ErrorKind::AlreadyExists,
+        OpenOptions::new().write(true).create_new(true).open(&path).unwrap_err().kind()
+    );
+    // Optionally creating a new file that already exists should succeed.
+    OpenOptions::new().write(true).create(true).open(&path).unwrap();
+    // Truncating an existing file should empty it.
+    let mut file = File::create(&path).unwrap();
+    file.write_all(b"abc").unwrap();
+    drop(file);
+    File::create(&path).unwrap();
+    assert_eq!(path.metadata().unwrap().len(), 0);
+
+    // Clean up
+    remove_file(&path).unwrap();
+}
+
+fn test_seek() {
+    let bytes = b"Hello, entire World!\n";
+    let path = prepare_with_content("miri_test_windows_fs_seek.txt", bytes);
+
+    let mut file = File::open(&path).unwrap();
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(bytes, contents.as_slice());
+    // Test that seeking to the beginning and reading until EOF gets the text again.
+    file.seek(SeekFrom::Start(0)).unwrap();
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(bytes, contents.as_slice());
+    // Test seeking relative to the end of the file.
+    file.seek(SeekFrom::End(-1)).unwrap();
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(&bytes[bytes.len() - 1..], contents.as_slice());
+    // Test seeking relative to the current position of the file.
+    file.seek(SeekFrom::Start(1)).unwrap();
+    file.seek(SeekFrom::Current(-1)).unwrap();
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(bytes, contents.as_slice());
+    // Seeking before the start of the file should fail.
+    assert!(file.seek(SeekFrom::Current(-100)).is_err());
+
+    // Clean up
+    remove_file(&path).unwrap();
+}
+
+fn test_metadata() {
+    let bytes = b"Hello, meta-World!\n";
+    let path = prepare_with_content("miri_test_windows_fs_metadata.txt", bytes);
+
+    // Test that the file metadata is correct.
+    let metadata = path.metadata().unwrap();
+    assert!(metadata.is_file());
+    assert_eq!(bytes.len() as u64, metadata.len());
+    assert!(metadata.modified().is_ok());
+    // Test that the metadata through the file handle agrees.
+    let metadata = File::open(&path).unwrap().metadata().unwrap();
+    assert!(metadata.is_file());
+    assert_eq!(bytes.len() as u64, metadata.len());
+    // The metadata of a file that is not a symbolic link is the same either way.
+    assert!(path.symlink_metadata().unwrap().is_file());
+
+    // Clean up
+    remove_file(&path).unwrap();
+}
+
+fn test_file_set_len() {
+    let bytes = b"Hello, World!\n";
+    let path = prepare_with_content("miri_test_windows_fs_set_len.txt", bytes);
+
+    // Test extending the file
+    let mut file = OpenOptions::new().read(true).write(true).open(&path).unwrap();
+    let bytes_extended = b"Hello, World!\n\x00\x00\x00\x00\x00\x00";
+    file.set_len(20).unwrap();
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(bytes_extended, contents.as_slice());
+
+    // Test truncating the file
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.set_len(10).unwrap();
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(&bytes[..10], contents.as_slice());
+
+    // Can't use set_len on a file not opened for writing
+    let file = OpenOptions::new().read(true).open(&path).unwrap();
+    assert_eq!(ErrorKind::PermissionDenied, file.set_len(14).unwrap_err().kind());
+
+    // Clean up
+    remove_file(&path).unwrap();
+}
+
+fn test_file_sync() {
+    let bytes = b"Hello, World!\n";
+    let path = prepare_with_content("miri_test_windows_fs_sync.txt", bytes);
+
+    // Test that we can flush a file that was opened for writing.
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.sync_all().unwrap();
+    file.sync_data().unwrap();
+    // Flushing requires write access on Windows.
+    let file = File::open(&path).unwrap();
+    assert_eq!(ErrorKind::PermissionDenied, file.sync_all().unwrap_err().kind());
+
+    // Clean up
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_errors() {
+    let path = prepare("miri_test_windows_fs_errors.txt");
+
+    // Opening a non-existing file should fail with a "not found" error.
+    assert_eq!(ErrorKind::NotFound, File::open(&path).unwrap_err().kind());
+    // Make sure we can also format this.
+    format!("{0:?}: {0}", File::open(&path).unwrap_err());
+    // Removing a non-existing file should fail with a "not found" error.
+    assert_eq!(ErrorKind::NotFound, remove_file(&path).unwrap_err().kind());
+    // Reading the metadata of a non-existing file should fail with a "not found" error.
+    assert_eq!(ErrorKind::NotFound, path.metadata().unwrap_err().kind());
+}
+
+fn test_rename() {
+    // Renaming a file should succeed.
+    let path1 = prepare("miri_test_windows_fs_rename_source.txt");
+    let path2 = prepare("miri_test_windows_fs_rename_destination.txt");
+
+    let file = File::create(&path1).unwrap();
+    drop(file);
+
+    // Renaming should succeed
+    rename(&path1, &path2).unwrap();
+    // Check that the old file path isn't present
+    assert_eq!(ErrorKind::NotFound, path1.metadata().unwrap_err().kind());
+    // Check that the file has moved successfully
+    assert!(path2.metadata().unwrap().is_file());
+
+    // Renaming onto an existing file should replace it.
+    drop(File::create(&path1).unwrap());
+    rename(&path2, &path1).unwrap();
+    assert_eq!(ErrorKind::NotFound, path2.metadata().unwrap_err().kind());
+
+    // Renaming a nonexistent file should fail
+    assert_eq!(ErrorKind::NotFound, rename(&path2, &path1).unwrap_err().kind());
+
+    remove_file(&path1).unwrap();
+}
+
+fn test_directory() {
+    let dir_path = utils::tmp().join("miri_test_windows_fs_dir");
+    // Creating a directory should succeed.
+    create_dir(&dir_path).unwrap();
+    // Test that the metadata of a directory is correct.
+    assert!(dir_path.metadata().unwrap().is_dir());
+    // Creating a directory when it already exists should fail.
+    assert_eq!(ErrorKind::AlreadyExists, create_dir(&dir_path).unwrap_err().kind());
+    // Listing an empty directory should succeed.
+    assert_eq!(0, read_dir(&dir_path).unwrap().count());
+
+    // Create some files and dirs inside the directory
+    let path_1 = dir_path.join("test_file_1");
+    drop(File::create(&path_1).unwrap());
+    let path_2 = dir_path.join("test_file_2");
+    drop(File::create(&path_2).unwrap());
+    let dir_1 = dir_path.join("test_dir_1");
+    create_dir(&dir_1).unwrap();
+    // Test that read_dir metadata calls succeed
+    assert_eq!(
+        HashMap::from([
+            (OsString::from("test_file_1"), true),
+            (OsString::from("test_file_2"), true),
+            (OsString::from("test_dir_1"), false)
+        ]),
+        read_dir(&dir_path)
+            .unwrap()
+            .map(|e| {
+                let e = e.unwrap();
+                (e.file_name(), e.metadata().unwrap().is_file())
+            })
+            .collect::<HashMap<_, _>>()
+    );
+    // Deleting the directory should fail, since it is not empty.
+    assert_eq!(ErrorKind::DirectoryNotEmpty, remove_dir(&dir_path).unwrap_err().kind());
+    // Clean up the files in the directory
+    remove_file(&path_1).unwrap();
+    remove_file(&path_2).unwrap();
+    remove_dir(&dir_1).unwrap();
+    // Now there should be nothing left in the directory.
+    assert_eq!(0, read_dir(&dir_path).unwrap().count());
+    // Deleting the directory should succeed.
+    remove_dir(&dir_path).unwrap();
+    // Reading the contents of a deleted directory should fail.
+    assert_eq!(ErrorKind::NotFound, read_dir(&dir_path).unwrap_err().kind());
+}
@@ -27,6 +27,8 @@ pub const m_test: test::TestDescAndFn =
             no_run: false,
             should_panic: test::ShouldPanic::No,
             test_type: test::TestType::Unknown,
+            timeout_secs: ::core::option::Option::None,
+            isolate: false,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
                 test::assert_test_result(m_test())),
@@ -51,6 +53,8 @@ pub const z_test: test::TestDescAndFn =
             no_run: false,
             should_panic: test::ShouldPanic::No,
             test_type: test::TestType::Unknown,
+            timeout_secs: ::core::option::Option::None,
+            isolate: false,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
                 test::assert_test_result(z_test())),
@@ -76,6 +80,8 @@ pub const a_test: test::TestDescAndFn =
             no_run: false,
             should_panic: test::ShouldPanic::No,
             test_type: test::TestType::Unknown,
+            timeout_secs: ::core::option::Option::None,
+            isolate: false,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
                 test::assert_test_result(a_test())),
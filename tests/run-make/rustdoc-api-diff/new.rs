@@ -0,0 +1,5 @@
+pub fn kept() {}
+
+pub fn added() {}
+
+pub fn changed<T: Clone>(t: T) {}
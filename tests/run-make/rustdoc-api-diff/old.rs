@@ -0,0 +1,5 @@
+pub fn kept() {}
+
+pub fn removed() {}
+
+pub fn changed<T>(t: T) {}
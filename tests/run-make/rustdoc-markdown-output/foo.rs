@@ -0,0 +1,28 @@
+//! The crate documentation, linking to [`Foo`] and [`inner::helper`].
+
+/// A struct.
+///
+/// # Examples
+///
+/// ```
+/// # use foo::Foo;
+/// let foo = Foo::new();
+/// ```
+pub struct Foo {
+    /// The value.
+    pub x: u32,
+}
+
+impl Foo {
+    /// Creates a [`Foo`].
+    pub fn new() -> Foo {
+        Foo { x: 0 }
+    }
+}
+
+pub mod inner {
+    /// Returns a [Foo](crate::Foo).
+    pub fn helper() -> crate::Foo {
+        crate::Foo::new()
+    }
+}
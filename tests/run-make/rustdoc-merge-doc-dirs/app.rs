@@ -0,0 +1,15 @@
+extern crate dep;
+
+// @has index.html
+// @has - '//ul[@class="all-items"]/li/a[@href="app/index.html"]' 'app'
+// @has - '//ul[@class="all-items"]/li/a[@href="dep/index.html"]' 'dep'
+// @has dep/trait.Greet.html
+// @has app/struct.Person.html
+// @has - '//a[@href="../dep/trait.Greet.html"]' 'Greet'
+pub struct Person;
+
+impl dep::Greet for Person {
+    fn greet(&self) -> String {
+        String::from("Hello")
+    }
+}
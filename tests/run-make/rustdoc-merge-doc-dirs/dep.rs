@@ -0,0 +1,3 @@
+pub trait Greet {
+    fn greet(&self) -> String;
+}
@@ -0,0 +1,29 @@
+// exact-check
+
+const EXPECTED = [
+    {
+        'query': 'exponential',
+        'others': [
+            { 'path': 'full_text_search', 'name': 'retry' },
+        ],
+    },
+    {
+        'query': 'backoff',
+        'others': [
+            { 'path': 'full_text_search', 'name': 'Connection' },
+            { 'path': 'full_text_search', 'name': 'retry' },
+        ],
+    },
+    {
+        'query': 'parsing address',
+        'others': [
+            { 'path': 'full_text_search::Connection', 'name': 'parse_address' },
+        ],
+    },
+    {
+        'query': 'retried without backoff',
+        'others': [
+            { 'path': 'full_text_search', 'name': 'Connection' },
+        ],
+    },
+];
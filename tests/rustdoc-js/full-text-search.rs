@@ -0,0 +1,16 @@
+//@ compile-flags: -Zunstable-options --full-text-search
+
+/// Runs a closure again until it succeeds, waiting longer after each failure
+/// (exponential backoff). Retries stop after `max_attempts`.
+pub fn retry<T>(max_attempts: u32, f: impl FnMut() -> Option<T>) -> Option<T> {
+    None
+}
+
+/// A connection to a server. Failed requests are retried with a fixed delay,
+/// without any backoff.
+pub struct Connection;
+
+impl Connection {
+    /// Parses the address of the server.
+    pub fn parse_address(&self) {}
+}
@@ -0,0 +1,16 @@
+//@ compile-flags:-Z unstable-options --show-coverage --coverage-items
+//@ check-pass
+
+//! Crate docs
+
+/// Documented, with an example.
+///
+/// ```
+/// let x = 12;
+/// ```
+pub fn documented() {}
+
+pub struct Undocumented {
+    /// A documented field.
+    pub field: u32,
+}
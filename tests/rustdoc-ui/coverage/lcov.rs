@@ -0,0 +1,11 @@
+//@ compile-flags:-Z unstable-options --output-format lcov --show-coverage
+//@ check-pass
+
+//! Crate docs
+
+/// Documented
+pub struct Foo {
+    pub undocumented: u32,
+}
+
+pub fn bar() {}
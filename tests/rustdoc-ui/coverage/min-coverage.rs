@@ -0,0 +1,9 @@
+//@ compile-flags:-Z unstable-options --show-coverage
+//@ compile-flags:--min-coverage 75 --min-example-coverage 50
+
+//! Crate docs
+
+/// Documented
+pub fn foo() {}
+
+pub fn bar() {}
@@ -0,0 +1,24 @@
+// Test that the output of doctests is checked against the `output` block following them with
+// `--check-doctest-output`.
+
+//@ compile-flags:-Z unstable-options --test --check-doctest-output --test-args=--test-threads=1
+//@ normalize-stdout-test: "tests/rustdoc-ui/doctest" -> "$$DIR"
+//@ normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ failure-status: 101
+
+/// ```
+/// println!("Hello, {}!", "world");
+/// ```
+///
+/// ```output
+/// Hello, world!
+/// ```
+pub struct Matching;
+
+/// ```
+/// println!("{}", 1 + 1);
+/// ```
+/// ```output
+/// 3
+/// ```
+pub struct Mismatching;
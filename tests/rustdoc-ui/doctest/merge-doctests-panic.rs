@@ -0,0 +1,18 @@
+//@ ignore-windows
+// The exit status is printed differently on Windows.
+
+// Test that the location of a panic in a merged doctest is the one in the documentation, rather
+// than the one in the harness it is compiled into.
+
+//@ compile-flags:-Z unstable-options --test --merge-doctests --test-args=--test-threads=1
+//@ rustc-env:RUST_BACKTRACE=0
+//@ normalize-stdout-test: "tests/rustdoc-ui/doctest" -> "$$DIR"
+//@ normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ normalize-stdout-test "_doctest_\w+" -> "_doctest_$$ID"
+//@ failure-status: 101
+
+/// ```
+/// let x = 1;
+/// assert_eq!(x, 2);
+/// ```
+pub struct Foo;
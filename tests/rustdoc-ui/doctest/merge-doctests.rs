@@ -0,0 +1,55 @@
+// Test that doctests are compiled together with `--merge-doctests`, and that the ones that
+// can't be merged still run on their own.
+
+//@ edition:2018
+//@ compile-flags:-Z unstable-options --test --merge-doctests --test-args=--test-threads=1
+//@ normalize-stdout-test: "tests/rustdoc-ui/doctest" -> "$$DIR"
+//@ normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ check-pass
+
+/// ```
+/// assert_eq!(merge_doctests::add(1, 2), 3);
+/// ```
+///
+/// ```
+/// let x: u32 = "3".parse()?;
+/// assert_eq!(merge_doctests::add(x, 2), 5);
+/// Ok::<(), std::num::ParseIntError>(())
+/// ```
+///
+/// ```should_panic
+/// merge_doctests::add(u32::MAX, 1);
+/// ```
+///
+/// ```no_run
+/// loop {}
+/// ```
+///
+/// Doctests that are compiled on their own:
+///
+/// ```
+/// #![allow(arithmetic_overflow)]
+/// assert_eq!(merge_doctests::add(1, 1), 2);
+/// ```
+///
+/// ```
+/// fn main() {
+///     assert_eq!(merge_doctests::add(2, 2), 4);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// merge_doctests::add(1);
+/// ```
+pub fn add(a: u32, b: u32) -> u32 {
+    a.checked_add(b).unwrap()
+}
+
+/// This doctest only passes when it's merged, as it then runs in a thread of the harness named
+/// after its test function, instead of the main thread of its own executable:
+///
+/// ```
+/// let thread = std::thread::current();
+/// assert!(thread.name().unwrap().starts_with("_doctest_"), "{:?}", thread.name());
+/// ```
+pub struct Merged;
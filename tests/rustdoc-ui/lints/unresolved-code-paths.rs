@@ -0,0 +1,42 @@
+#![deny(rustdoc::unresolved_code_paths)]
+
+pub struct Foo;
+
+impl Foo {
+    pub fn bar() {}
+}
+
+/// Calls `Foo::bar` and `Foo::baz`.
+//~^ ERROR `Foo::baz` looks like a path, but it does not resolve
+///
+/// Also see `Qux::new()`, `std::vec!`, `std::vec::Vec::new`, `u32::MAX` and `Vec<u8>`.
+//~^ ERROR `Qux::new()` looks like a path, but it does not resolve
+///
+/// Single words like `x` and links like [`Gone::item`](https://example.com) are ignored.
+pub fn f() {}
+
+/// Documentation without links, like this mention of `Foo::gone`, is checked too.
+//~^ ERROR `Foo::gone` looks like a path, but it does not resolve
+pub fn g() {}
+
+/// The paths in allowed documentation, like `Foo::gone`, are not resolved.
+#[allow(rustdoc::unresolved_code_paths)]
+pub fn h() {}
+
+#[allow(rustdoc::unresolved_code_paths)]
+pub mod allowed {
+    /// Denying the lint again checks `Foo::gone`.
+    //~^ ERROR `Foo::gone` looks like a path, but it does not resolve
+    #[deny(rustdoc::unresolved_code_paths)]
+    pub fn i() {}
+}
+
+#[allow(rustdoc::unresolved_code_paths)]
+pub enum E {
+    #[deny(rustdoc::unresolved_code_paths)]
+    V {
+        /// The level of a variant applies to its fields, so `Foo::gone` is checked.
+        //~^ ERROR `Foo::gone` looks like a path, but it does not resolve
+        x: u32,
+    },
+}
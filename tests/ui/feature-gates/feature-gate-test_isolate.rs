@@ -0,0 +1,4 @@
+#[test_isolate] //~ ERROR the `#[test_isolate]` attribute is an experimental feature
+fn f() {}
+
+fn main() {}
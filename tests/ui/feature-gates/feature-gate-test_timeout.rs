@@ -0,0 +1,4 @@
+#[test_timeout = "1"] //~ ERROR the `#[test_timeout]` attribute is an experimental feature
+fn f() {}
+
+fn main() {}
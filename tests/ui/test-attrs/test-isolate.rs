@@ -0,0 +1,27 @@
+//@ run-pass
+//@ compile-flags: --test
+//@ run-flags: --test-threads=1
+//@ check-run-results
+//@ normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ needs-unwind
+//@ ignore-wasm no subprocess support
+//@ ignore-emscripten no subprocess support
+//@ ignore-sgx no subprocess support
+
+#![feature(test_isolate)]
+
+use std::env;
+
+const VAR: &str = "TEST_ISOLATE_VAR";
+
+#[test]
+#[test_isolate]
+fn a_sets_env_var() {
+    env::set_var(VAR, "1");
+    assert!(std::panic::catch_unwind(|| panic!("caught by the test")).is_err());
+}
+
+#[test]
+fn b_does_not_see_env_var() {
+    assert!(env::var_os(VAR).is_none());
+}
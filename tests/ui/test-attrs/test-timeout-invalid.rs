@@ -0,0 +1,17 @@
+//@ compile-flags: --test
+
+#![feature(test_timeout)]
+
+#[test]
+#[test_timeout = "0"]
+//~^ ERROR `#[test_timeout]` expects a positive number of seconds
+fn zero() {}
+
+#[test]
+#[test_timeout = "1.5"]
+//~^ ERROR `#[test_timeout]` expects a positive number of seconds
+fn fractional() {}
+
+#[test]
+#[test_timeout = "30"]
+fn valid() {}